@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use vidmod_core::{
+    bench::bench_pipeline,
+    nodes::{CounterSource, Limit},
+};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, Node, NodeImpl, NodePorts};
+
+const TICKS: usize = 1000;
+const BUF_SIZES: &[usize] = &[16, 64, 256, 1024];
+
+/// Drops everything it receives
+#[node_decl]
+struct Discard {
+    buf_size: usize,
+}
+
+impl Discard {
+    #[node_new]
+    fn new(buf_size: usize) -> Self {
+        Self { buf_size }
+    }
+}
+
+impl NodeImpl for Discard {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U16, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = self.inbuf_avail("in");
+        if count > 0 {
+            self.inbuf_get("in", count);
+        }
+        count > 0
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+fn pipeline(buf_size: usize) -> Vec<Node> {
+    let mut params = BTreeMap::new();
+    params.insert("kind".to_owned(), "U16".to_owned());
+    params.insert("count".to_owned(), usize::MAX.to_string());
+    params.insert("buf_size".to_owned(), buf_size.to_string());
+    vec![
+        Node::new(CounterSource::new(params.clone())),
+        Node::new(Limit::new(params)),
+        Node::new(Discard::new(buf_size)),
+    ]
+}
+
+fn throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pipeline");
+    for &buf_size in BUF_SIZES {
+        let frames = bench_pipeline(pipeline(buf_size), TICKS);
+        group.throughput(Throughput::Elements(frames as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(buf_size),
+            &buf_size,
+            |b, &buf_size| b.iter(|| bench_pipeline(pipeline(buf_size), TICKS)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, throughput);
+criterion_main!(benches);
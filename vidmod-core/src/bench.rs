@@ -0,0 +1,49 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use vidmod_node::{frame::Frame, Node};
+
+use crate::{
+    spec::NodeGraph,
+    tap::{FrameTap, LinkId},
+};
+
+#[derive(Debug)]
+struct CountTap(Arc<AtomicUsize>);
+
+impl FrameTap for CountTap {
+    fn on_transfer(&mut self, _link: &LinkId, frame: &Frame) {
+        self.0.fetch_add(frame.size(), Ordering::Relaxed);
+    }
+}
+
+/// Chain `nodes` from each one's "out" port to the next one's "in" port, tick the graph `ticks`
+/// times, and return how many frames reached the last node
+pub fn bench_pipeline(nodes: Vec<Node>, ticks: usize) -> usize {
+    let mut graph = NodeGraph::new();
+    let count = nodes.len();
+    let mut ids = Vec::new();
+    for (idx, mut node) in nodes.into_iter().enumerate() {
+        node.init();
+        ids.push(graph.insert(node, idx.to_string()).unwrap());
+    }
+    for idx in 1..count {
+        let p1 = graph.get_pull_port(ids[idx - 1], "out").unwrap();
+        let p2 = graph.get_push_port(ids[idx], "in").unwrap();
+        graph.add_link(p1, p2).unwrap();
+    }
+
+    let frames = Arc::new(AtomicUsize::new(0));
+    if count > 1 {
+        let from = (count - 2).to_string();
+        let to = (count - 1).to_string();
+        let tap = Box::new(CountTap(frames.clone()));
+        graph.tap_link((&from, "out"), (&to, "in"), tap).unwrap();
+    }
+    for _ in 0..ticks {
+        graph.tick();
+    }
+    frames.load(Ordering::Relaxed)
+}
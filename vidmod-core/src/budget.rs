@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use vidmod_node::{
+    params::{Params, TICK_BUDGET_ARG, TICK_LIMIT_ARG},
+    VidmodError,
+};
+
+/// Number of ticks over the hard limit after which a node is marked failed
+pub const DEFAULT_STRIKES: usize = 3;
+
+/// Time allowed for a single call to a node's `tick()`
+///
+/// The scheduler cannot preempt a node, so this is detection after the fact: a tick is only
+/// measured once it returns. A tick over `warn` logs a warning; after `strikes` ticks over
+/// `limit` the node is marked failed and the run stops. A node that never returns from `tick()`
+/// still hangs the run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TickBudget {
+    pub warn:    Option<Duration>,
+    pub limit:   Option<Duration>,
+    pub strikes: usize,
+}
+
+impl TickBudget {
+    /// Read `vidmod.tick_budget_ms` and `vidmod.tick_limit_ms` from a node's arguments
+    pub fn from_params(params: &Params) -> Result<Option<Self>> {
+        let millis = |key| -> Result<Option<Duration>> {
+            Ok(match params.get(key) {
+                Some(v) => Some(Duration::from_millis(v.parse()?)),
+                None => None,
+            })
+        };
+        let warn = millis(TICK_BUDGET_ARG)?;
+        let limit = millis(TICK_LIMIT_ARG)?;
+        if warn.is_none() && limit.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            warn,
+            limit,
+            strikes: DEFAULT_STRIKES,
+        }))
+    }
+}
+
+/// Tracks one node's ticks against its budget
+#[derive(Debug)]
+pub(crate) struct BudgetState {
+    budget: TickBudget,
+    over:   usize,
+}
+
+impl BudgetState {
+    pub(crate) fn new(budget: TickBudget) -> Self {
+        Self { budget, over: 0 }
+    }
+
+    // Check a finished tick against the budget, failing once the limit has been hit too often
+    pub(crate) fn record(&mut self, node: &str, elapsed: Duration) -> Result<(), VidmodError> {
+        if let Some(limit) = self.budget.limit {
+            if elapsed > limit {
+                self.over += 1;
+                println!(
+                    "Warning: node {} tick took {}ms, over its limit of {}ms ({}/{})",
+                    node,
+                    elapsed.as_millis(),
+                    limit.as_millis(),
+                    self.over,
+                    self.budget.strikes
+                );
+                if self.over >= self.budget.strikes {
+                    return Err(VidmodError::TickLimitExceeded {
+                        node: node.to_owned(),
+                        elapsed,
+                        limit,
+                        count: self.over,
+                    });
+                }
+                return Ok(());
+            }
+        }
+        if let Some(warn) = self.budget.warn {
+            if elapsed > warn {
+                println!(
+                    "Warning: node {} tick took {}ms, over its budget of {}ms",
+                    node,
+                    elapsed.as_millis(),
+                    warn.as_millis()
+                );
+            }
+        }
+        Ok(())
+    }
+}
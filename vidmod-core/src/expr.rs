@@ -0,0 +1,331 @@
+use std::{iter::Peekable, str::CharIndices};
+
+use anyhow::{bail, Result};
+
+/// A built-in function callable from an expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Func {
+    Abs,
+    Sqrt,
+    Floor,
+    Ceil,
+    Round,
+    Sin,
+    Cos,
+    Exp,
+    Ln,
+    Pow,
+    Min,
+    Max,
+    Clamp,
+}
+
+impl Func {
+    fn lookup(name: &str) -> Option<Self> {
+        Some(match name {
+            "abs" => Func::Abs,
+            "sqrt" => Func::Sqrt,
+            "floor" => Func::Floor,
+            "ceil" => Func::Ceil,
+            "round" => Func::Round,
+            "sin" => Func::Sin,
+            "cos" => Func::Cos,
+            "exp" => Func::Exp,
+            "ln" => Func::Ln,
+            "pow" => Func::Pow,
+            "min" => Func::Min,
+            "max" => Func::Max,
+            "clamp" => Func::Clamp,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Func::Pow | Func::Min | Func::Max => 2,
+            Func::Clamp => 3,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> f64 {
+        match self {
+            Func::Abs => args[0].abs(),
+            Func::Sqrt => args[0].sqrt(),
+            Func::Floor => args[0].floor(),
+            Func::Ceil => args[0].ceil(),
+            Func::Round => args[0].round(),
+            Func::Sin => args[0].sin(),
+            Func::Cos => args[0].cos(),
+            Func::Exp => args[0].exp(),
+            Func::Ln => args[0].ln(),
+            Func::Pow => args[0].powf(args[1]),
+            Func::Min => args[0].min(args[1]),
+            Func::Max => args[0].max(args[1]),
+            Func::Clamp => args[0].max(args[1]).min(args[2]),
+        }
+    }
+}
+
+/// A binary operator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+}
+
+/// A parsed arithmetic expression over numbered variables
+///
+/// Evaluation uses f64 arithmetic, so division by zero gives an infinity or NaN.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Num(f64),
+    Var(usize),
+    Neg(Box<Expr>),
+    Bin(BinOp, Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+    Comma,
+    End,
+}
+
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    len:   usize,
+    vars:  &'a [&'a str],
+    // The current token and the byte offset it starts at
+    token: Token,
+    pos:   usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Result<()> {
+        while let Some((_, c)) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+        let (pos, c) = match self.chars.next() {
+            Some(next) => next,
+            None => {
+                self.token = Token::End;
+                self.pos = self.len;
+                return Ok(());
+            }
+        };
+        self.pos = pos;
+        self.token = match c {
+            '+' | '-' | '*' | '/' | '%' | '^' => Token::Op(c),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut text = c.to_string();
+                while let Some(&(_, c)) = self.chars.peek() {
+                    if !(c.is_ascii_digit() || c == '.') {
+                        break;
+                    }
+                    text.push(c);
+                    self.chars.next();
+                }
+                match text.parse() {
+                    Ok(v) => Token::Num(v),
+                    Err(_) => bail!("Invalid number {} at position {}", text, pos),
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut text = c.to_string();
+                while let Some(&(_, c)) = self.chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    text.push(c);
+                    self.chars.next();
+                }
+                Token::Ident(text)
+            }
+            c => bail!("Unexpected {:?} at position {}", c, pos),
+        };
+        Ok(())
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<()> {
+        if self.token != token {
+            bail!("Expected {} at position {}", what, self.pos);
+        }
+        self.next()
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        let mut lhs = self.product()?;
+        loop {
+            let op = match self.token {
+                Token::Op('+') => BinOp::Add,
+                Token::Op('-') => BinOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.next()?;
+            lhs = Expr::Bin(op, Box::new(lhs), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.token {
+                Token::Op('*') => BinOp::Mul,
+                Token::Op('/') => BinOp::Div,
+                Token::Op('%') => BinOp::Rem,
+                _ => return Ok(lhs),
+            };
+            self.next()?;
+            lhs = Expr::Bin(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        match self.token {
+            Token::Op('-') => {
+                self.next()?;
+                Ok(Expr::Neg(Box::new(self.unary()?)))
+            }
+            Token::Op('+') => {
+                self.next()?;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    // Exponentiation binds tighter than negation on its left and is right associative
+    fn power(&mut self) -> Result<Expr> {
+        let base = self.atom()?;
+        if self.token == Token::Op('^') {
+            self.next()?;
+            Ok(Expr::Bin(
+                BinOp::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expr> {
+        let pos = self.pos;
+        match self.token.clone() {
+            Token::Num(v) => {
+                self.next()?;
+                Ok(Expr::Num(v))
+            }
+            Token::Open => {
+                self.next()?;
+                let expr = self.sum()?;
+                self.expect(Token::Close, "')'")?;
+                Ok(expr)
+            }
+            Token::Ident(name) => {
+                self.next()?;
+                if self.token == Token::Open {
+                    self.call(&name, pos)
+                } else if let Some(idx) = self.vars.iter().position(|v| *v == name) {
+                    Ok(Expr::Var(idx))
+                } else {
+                    bail!("Unknown variable {} at position {}", name, pos)
+                }
+            }
+            Token::End => bail!("Unexpected end of expression at position {}", pos),
+            _ => bail!("Expected a value at position {}", pos),
+        }
+    }
+
+    fn call(&mut self, name: &str, pos: usize) -> Result<Expr> {
+        let func = match Func::lookup(name) {
+            Some(func) => func,
+            None => bail!("Unknown function {} at position {}", name, pos),
+        };
+        self.next()?;
+        let mut args = Vec::new();
+        if self.token != Token::Close {
+            args.push(self.sum()?);
+            while self.token == Token::Comma {
+                self.next()?;
+                args.push(self.sum()?);
+            }
+        }
+        self.expect(Token::Close, "')'")?;
+        if args.len() != func.arity() {
+            bail!(
+                "{} takes {} arguments, got {} at position {}",
+                name,
+                func.arity(),
+                args.len(),
+                pos
+            );
+        }
+        Ok(Expr::Call(func, args))
+    }
+}
+
+impl Expr {
+    /// Parse an expression, numbering variables by their index in `vars`
+    ///
+    /// Errors give the byte offset in `src` where parsing failed.
+    pub fn parse(src: &str, vars: &[&str]) -> Result<Self> {
+        let mut parser = Parser {
+            chars: src.char_indices().peekable(),
+            len: src.len(),
+            vars,
+            token: Token::End,
+            pos: 0,
+        };
+        parser.next()?;
+        let expr = parser.sum()?;
+        if parser.token != Token::End {
+            bail!("Unexpected trailing input at position {}", parser.pos);
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate the expression, taking each variable's value from `vars`
+    pub fn eval(&self, vars: &[f64]) -> f64 {
+        match self {
+            Expr::Num(v) => *v,
+            Expr::Var(idx) => vars[*idx],
+            Expr::Neg(e) => -e.eval(vars),
+            Expr::Bin(op, a, b) => {
+                let (a, b) = (a.eval(vars), b.eval(vars));
+                match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                    BinOp::Rem => a % b,
+                    BinOp::Pow => a.powf(b),
+                }
+            }
+            Expr::Call(func, args) => {
+                // No function takes more than three arguments
+                let mut vals = [0.0; 3];
+                for (v, e) in vals.iter_mut().zip(args) {
+                    *v = e.eval(vars);
+                }
+                func.apply(&vals)
+            }
+        }
+    }
+}
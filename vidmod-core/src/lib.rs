@@ -1 +1,12 @@
+pub mod bench;
+pub mod budget;
+pub mod cancel;
+pub mod expr;
+pub mod meter;
+pub mod nodes;
+pub mod record;
+pub mod report;
 pub mod spec;
+pub mod tap;
+pub mod timecode;
+pub mod watch;
@@ -1,19 +1,287 @@
-use std::{env::args, fs::File, path::PathBuf, process::exit, str::FromStr};
+use std::{
+    collections::BTreeMap, env::args, fs, fs::File, path::PathBuf, process::exit, str::FromStr,
+    time::Instant,
+};
 
-use vidmod_core::spec::Project;
+use vidmod_core::{
+    cancel::CancellationToken,
+    record::RecordTap,
+    report::{compare_reports, RunReport},
+    spec::{manifest, Project},
+    tap::{FileTap, FrameTap, SummaryTap},
+    watch,
+};
+
+fn usage(name: &str) -> ! {
+    println!(
+        "{} [run] [--dot] [--lint] [--dry-run] [--tap node.port[:file]]... [--record node.port=file]... [--start-frame N] [--max-frames M] \
+         [--max-frame-bytes N] [--var key=value]... [--report-json file [--hash-links]] [path] [overlay.yml]...\n\
+         {} run --watch [--tap node.port[:file]]... [--record node.port=file]... [--start-frame N] [--max-frames M] \
+         [--max-frame-bytes N] [--var key=value]... [path]\n\
+         {} clean [path]\n{} schema\n{} compare-reports a.json b.json",
+        name, name, name, name, name
+    );
+    exit(1);
+}
 
 fn main() {
-    if args().len() == 2 {
-        let proj_path = PathBuf::from_str(&args().next_back().unwrap()).unwrap();
-        if let Ok(proj_manifest) = File::open(proj_path.join("manifest.yml")) {
-            let mut project = Project::load(proj_manifest, proj_path);
+    let args: Vec<String> = args().collect();
+    if args.get(1).map(String::as_str) == Some("clean") {
+        match args.get(2..) {
+            Some([path]) => Project::clean(&PathBuf::from(path)).unwrap(),
+            _ => usage(&args[0]),
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("schema") {
+        if args.len() != 2 {
+            usage(&args[0]);
+        }
+        let schema = manifest::json_schema(&vidmod_core::nodes::registry());
+        println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("compare-reports") {
+        match args.get(2..) {
+            Some([a, b]) => compare(a, b),
+            _ => usage(&args[0]),
+        }
+        return;
+    }
+    let mut dot = false;
+    let mut lint = false;
+    let mut dry_run = false;
+    let mut watching = false;
+    let mut taps = Vec::new();
+    let mut records = Vec::new();
+    let mut start_frame = None;
+    let mut max_frames = None;
+    let mut max_frame_bytes = None;
+    let mut report = None;
+    let mut hash_links = false;
+    let mut vars = BTreeMap::new();
+    let mut path = None;
+    let mut overlays = Vec::new();
+    // `run` is optional, except that watching is only offered as `run --watch`
+    let run = args.get(1).map(String::as_str) == Some("run");
+    let mut rest = args[if run { 2 } else { 1 }..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--dot" => dot = true,
+            "--lint" => lint = true,
+            "--dry-run" => dry_run = true,
+            "--watch" if run => watching = true,
+            "--watch" => usage(&args[0]),
+            "--tap" => taps.push(rest.next().unwrap_or_else(|| usage(&args[0])).clone()),
+            "--record" => records.push(rest.next().unwrap_or_else(|| usage(&args[0])).clone()),
+            "--start-frame" => start_frame = rest.next().map(|v| v.parse::<u64>().unwrap()),
+            "--max-frames" => max_frames = rest.next().map(|v| v.parse::<u64>().unwrap()),
+            "--max-frame-bytes" => {
+                max_frame_bytes = rest.next().map(|v| v.parse::<usize>().unwrap())
+            }
+            "--report-json" => report = Some(rest.next().unwrap_or_else(|| usage(&args[0]))),
+            "--hash-links" => hash_links = true,
+            "--var" => {
+                let var = rest.next().unwrap_or_else(|| usage(&args[0]));
+                match var.splitn(2, '=').collect::<Vec<_>>().as_slice() {
+                    [key, value] => vars.insert(key.to_string(), value.to_string()),
+                    _ => usage(&args[0]),
+                };
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => overlays.push(arg),
+        }
+    }
+    let path = path.unwrap_or_else(|| usage(&args[0]));
+    // Link hashes are only written out as part of a report
+    if hash_links && report.is_none() {
+        usage(&args[0]);
+    }
+    // Overlays are only read once, so cannot be watched for changes, and watching only ever runs
+    // the project, so takes none of the options that inspect it or report on a single run
+    if watching && (!overlays.is_empty() || dot || lint || dry_run || report.is_some()) {
+        usage(&args[0]);
+    }
+    let open_overlays = || -> Vec<File> {
+        overlays
+            .iter()
+            .map(|overlay| {
+                File::open(overlay).unwrap_or_else(|e| {
+                    println!("Cannot open overlay {}: {}", overlay, e);
+                    exit(1);
+                })
+            })
+            .collect()
+    };
+
+    // Ctrl-C stops the sources, letting the rest of the graph finish and flush its output
+    let token = CancellationToken::new();
+    let handler_token = token.clone();
+    ctrlc::set_handler(move || handler_token.cancel()).unwrap();
+
+    let configure = |project: &mut Project| {
+        project.set_cancellation(token.clone());
+        if let Some(count) = max_frames {
+            project.limit_sources(count).unwrap();
+        }
+        if let Some(position) = start_frame {
+            project.seek_sources(position).unwrap();
+        }
+        if max_frame_bytes.is_some() {
+            project.set_max_frame_bytes(max_frame_bytes);
+        }
+        for tap in &taps {
+            install_tap(project, tap);
+        }
+        for record in &records {
+            install_recorder(project, record);
+        }
+    };
+
+    let proj_path = PathBuf::from_str(path).unwrap();
+    if watching {
+        watch::watch(&proj_path, &vars, &configure, &token).unwrap();
+    } else if let Ok(proj_manifest) = File::open(proj_path.join("manifest.yml")) {
+        if dry_run {
+            let registry = vidmod_core::nodes::registry();
+            print!(
+                "{}",
+                Project::dry_run_with_overlays(
+                    proj_manifest,
+                    open_overlays(),
+                    &proj_path,
+                    &registry,
+                    &vars
+                )
+                .unwrap()
+            );
+            return;
+        }
+        let registry = vidmod_core::nodes::registry();
+        let mut project = Project::load_with_overlays(
+            proj_manifest,
+            open_overlays(),
+            proj_path,
+            &registry,
+            &vars,
+        );
+        // Printed from the manifest as loaded, before any run options insert nodes, seek
+        // sources or open output files
+        if dot {
+            print!("{}", project.to_dot());
+            return;
+        }
+        if lint {
+            // Only warnings, so a manifest that lints with some still loads and runs
+            for warning in project.lint() {
+                println!("Warning: {}", warning);
+            }
+            return;
+        }
+        configure(&mut project);
+        if let Some(report) = report {
+            let hashes = if hash_links {
+                project.hash_links()
+            } else {
+                Default::default()
+            };
+            let start = Instant::now();
             project.run();
+            let hashes = hashes.lock().unwrap();
+            let json = RunReport::new(&project, start.elapsed(), &hashes).to_json();
+            fs::write(report, json).unwrap();
         } else {
-            println!("Cannot find manifest {:?}", proj_path.join("manifest.yml"));
-            exit(1);
+            project.run();
         }
+        finish_taps(&mut project);
     } else {
-        println!("{} [path]", args().next().unwrap());
+        println!("Cannot find manifest {:?}", proj_path.join("manifest.yml"));
+        exit(1);
+    }
+}
+
+// Print the links whose hashes diverged between two reports, exiting non-zero if any did
+fn compare(a: &str, b: &str) {
+    let read = |path: &str| {
+        fs::read_to_string(path).unwrap_or_else(|e| {
+            println!("Cannot read report {}: {}", path, e);
+            exit(2);
+        })
+    };
+    let diverged = compare_reports(&read(a), &read(b)).unwrap_or_else(|e| {
+        println!("Invalid report: {}", e);
+        exit(2);
+    });
+    for line in &diverged {
+        println!("{}", line);
+    }
+    if !diverged.is_empty() {
+        exit(1);
+    }
+}
+
+// Tap every link leaving `node.port`, logging a summary or dumping to `file` if given
+fn install_tap(project: &mut Project, spec: &str) {
+    let mut parts = spec.splitn(2, ':');
+    let port = parts.next().unwrap();
+    let file = parts.next();
+    let (node, port) = match port.splitn(2, '.').collect::<Vec<_>>().as_slice() {
+        [node, port] => (*node, *port),
+        _ => panic!("Invalid tap {}, expected node.port[:file]", spec),
+    };
+    let links: Vec<_> = project
+        .link_ids()
+        .into_iter()
+        .filter(|link| link.from.0 == node && link.from.1 == port)
+        .collect();
+    if links.is_empty() {
+        panic!("No links from {}.{}", node, port);
+    }
+    for (idx, link) in links.iter().enumerate() {
+        let tap: Box<dyn FrameTap> = match file {
+            Some(file) if links.len() > 1 => {
+                Box::new(FileTap::new(format!("{}.{}", file, idx)).unwrap())
+            }
+            Some(file) => Box::new(FileTap::new(file).unwrap()),
+            None => Box::new(SummaryTap::new()),
+        };
+        project
+            .tap_link((&link.from.0, &link.from.1), (&link.to.0, &link.to.1), tap)
+            .unwrap();
+    }
+}
+
+// Flush every tap and recording, exiting non-zero if any could not be written
+fn finish_taps(project: &mut Project) {
+    let failed = project.finish_taps();
+    for (link, e) in &failed {
+        println!("Cannot write tap on {}: {}", link, e);
+    }
+    if !failed.is_empty() {
         exit(1);
     }
 }
+
+// Record the frames moved along the first link leaving `node.port` to `file`, see
+// core::ReplaySource
+fn install_recorder(project: &mut Project, spec: &str) {
+    let mut parts = spec.splitn(2, '=');
+    let port = parts.next().unwrap();
+    let file = match parts.next() {
+        Some(file) => file,
+        None => panic!("Invalid record {}, expected node.port=file", spec),
+    };
+    let (node, port) = match port.splitn(2, '.').collect::<Vec<_>>().as_slice() {
+        [node, port] => (*node, *port),
+        _ => panic!("Invalid record {}, expected node.port=file", spec),
+    };
+    let link = project
+        .link_ids()
+        .into_iter()
+        .find(|link| link.from.0 == node && link.from.1 == port)
+        .unwrap_or_else(|| panic!("No links from {}.{}", node, port));
+    let tap = Box::new(RecordTap::new(file).unwrap());
+    project
+        .tap_link((&link.from.0, &link.from.1), (&link.to.0, &link.to.1), tap)
+        .unwrap();
+}
@@ -0,0 +1,149 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Length of the window the instantaneous rate of a link is sampled over
+pub const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// A source of wall time for metering links
+pub trait Clock: Debug {
+    /// Time elapsed since some fixed point, which only ever moves forward
+    fn now(&self) -> Duration;
+}
+
+/// Reads the system's monotonic clock
+#[derive(Debug)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock that only moves when advanced, shared between its clones
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// How many `tick_links` sweeps found a link starved or backed up, for tuning buffer sizes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufferPressure {
+    /// Sweeps where the producer's pull buffer held nothing to move
+    pub empty: usize,
+    /// Sweeps where the consumer's push buffer had no room left
+    pub full:  usize,
+}
+
+/// Counts the frames moved along a link against wall time
+///
+/// The average rate covers everything since metering began. The instantaneous rate covers the
+/// last window of at least `RATE_WINDOW`, a window closing at the first transfer or query after
+/// it has run its length.
+#[derive(Debug, Clone)]
+pub struct LinkMeter {
+    start:    Duration,
+    frames:   usize,
+    since:    Duration,
+    window:   usize,
+    instant:  f64,
+    pressure: BufferPressure,
+}
+
+impl LinkMeter {
+    pub fn new(now: Duration) -> Self {
+        Self {
+            start:    now,
+            frames:   0,
+            since:    now,
+            window:   0,
+            instant:  0.0,
+            pressure: BufferPressure::default(),
+        }
+    }
+
+    // Note what a sweep found in the link's buffers before moving anything
+    pub fn sweep(&mut self, ready: usize, room: usize) {
+        if ready == 0 {
+            self.pressure.empty += 1;
+        }
+        if room == 0 {
+            self.pressure.full += 1;
+        }
+    }
+
+    pub fn pressure(&self) -> BufferPressure {
+        self.pressure
+    }
+
+    pub fn record(&mut self, now: Duration, frames: usize) {
+        if now - self.since >= RATE_WINDOW {
+            self.instant = rate(self.window, now - self.since);
+            self.since = now;
+            self.window = 0;
+        }
+        self.frames += frames;
+        self.window += frames;
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    // Frames per second since metering began
+    pub fn average(&self, now: Duration) -> f64 {
+        rate(self.frames, now - self.start)
+    }
+
+    // Frames per second over the last complete window
+    pub fn instant(&self, now: Duration) -> f64 {
+        if now - self.since >= RATE_WINDOW {
+            rate(self.window, now - self.since)
+        } else {
+            self.instant
+        }
+    }
+}
+
+fn rate(frames: usize, elapsed: Duration) -> f64 {
+    if elapsed == Duration::from_secs(0) {
+        0.0
+    } else {
+        frames as f64 / elapsed.as_secs_f64()
+    }
+}
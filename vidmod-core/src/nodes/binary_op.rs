@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use ndarray::{ArcArray, Dimension, Zip};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle, RGBA8},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// An elementwise arithmetic operation
+///
+/// Integer kinds saturate at their bounds, and integer division by zero
+/// saturates to the maximum value. Float kinds follow IEEE semantics, so
+/// division by zero gives an infinity or NaN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Op {
+    fn parse(f: &str) -> Option<Self> {
+        Some(match f {
+            "add" => Op::Add,
+            "sub" => Op::Sub,
+            "mul" => Op::Mul,
+            "div" => Op::Div,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&str> for Op {
+    fn from(f: &str) -> Self {
+        Op::parse(f).unwrap_or_else(|| unimplemented!("Binary op {}", f))
+    }
+}
+
+trait Arith: Clone {
+    fn apply(op: Op, a: Self, b: Self) -> Self;
+}
+
+macro_rules! arith_int {
+    ($t:ty) => {
+        impl Arith for $t {
+            fn apply(op: Op, a: Self, b: Self) -> Self {
+                match op {
+                    Op::Add => a.saturating_add(b),
+                    Op::Sub => a.saturating_sub(b),
+                    Op::Mul => a.saturating_mul(b),
+                    Op::Div => a.checked_div(b).unwrap_or(<$t>::MAX),
+                }
+            }
+        }
+    };
+}
+
+arith_int!(u8);
+arith_int!(u16);
+
+impl Arith for f32 {
+    fn apply(op: Op, a: Self, b: Self) -> Self {
+        match op {
+            Op::Add => a + b,
+            Op::Sub => a - b,
+            Op::Mul => a * b,
+            Op::Div => a / b,
+        }
+    }
+}
+
+impl Arith for RGBA8 {
+    fn apply(op: Op, a: Self, b: Self) -> Self {
+        RGBA8 {
+            r: u8::apply(op, a.r, b.r),
+            g: u8::apply(op, a.g, b.g),
+            b: u8::apply(op, a.b, b.b),
+            a: u8::apply(op, a.a, b.a),
+        }
+    }
+}
+
+fn zip<T: Arith, D: Dimension>(op: Op, a: ArcArray<T, D>, b: ArcArray<T, D>) -> ArcArray<T, D> {
+    assert_eq!(a.shape(), b.shape(), "BinaryOp shape mismatch");
+    Zip::from(&a)
+        .and(&b)
+        .map_collect(|a, b| T::apply(op, a.clone(), b.clone()))
+        .into_shared()
+}
+
+/// Combines frames from "a" and "b" elementwise into "out"
+///
+/// The `op` message switches the operation from the next pair of frames on.
+#[node_decl]
+pub struct BinaryOp {
+    kind: FrameKind,
+    op:   Op,
+}
+
+impl BinaryOp {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let op = params.get("op").unwrap().as_str().into();
+        Self { kind, op }
+    }
+
+    fn apply(&self, a: FrameSingle, b: FrameSingle) -> FrameSingle {
+        let op = self.op;
+        match (a, b) {
+            (FrameSingle::U8(a), FrameSingle::U8(b)) => FrameSingle::U8(u8::apply(op, a, b)),
+            (FrameSingle::U8x1(a), FrameSingle::U8x1(b)) => FrameSingle::U8x1(zip(op, a, b)),
+            (FrameSingle::U8x2(a), FrameSingle::U8x2(b)) => FrameSingle::U8x2(zip(op, a, b)),
+            (FrameSingle::U16(a), FrameSingle::U16(b)) => FrameSingle::U16(u16::apply(op, a, b)),
+            (FrameSingle::U16x1(a), FrameSingle::U16x1(b)) => FrameSingle::U16x1(zip(op, a, b)),
+            (FrameSingle::U16x2(a), FrameSingle::U16x2(b)) => FrameSingle::U16x2(zip(op, a, b)),
+            (FrameSingle::F32(a), FrameSingle::F32(b)) => FrameSingle::F32(f32::apply(op, a, b)),
+            (FrameSingle::F32x1(a), FrameSingle::F32x1(b)) => FrameSingle::F32x1(zip(op, a, b)),
+            (FrameSingle::F32x2(a), FrameSingle::F32x2(b)) => FrameSingle::F32x2(zip(op, a, b)),
+            (FrameSingle::RGBA8x2(a), FrameSingle::RGBA8x2(b)) => {
+                FrameSingle::RGBA8x2(zip(op, a, b))
+            }
+            (a, b) => panic!(
+                "BinaryOp kind mismatch: {:?},{:?}",
+                FrameKind::from(&a),
+                FrameKind::from(&b)
+            ),
+        }
+    }
+}
+
+impl NodeImpl for BinaryOp {
+    fn init(&mut self) {
+        self.register_pushport("a", self.kind, 1);
+        self.register_pushport("b", self.kind, 1);
+        self.register_pullport("out", self.kind, 1);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.inbuf_avail("a") > 0
+            && self.inbuf_avail("b") > 0
+            && self.outbuf_avail("out") > 0
+            && self.budget_remaining() > 0
+        {
+            let a = self.inbuf_get_single("a");
+            let b = self.inbuf_get_single("b");
+            self.outbuf_put_single("out", self.apply(a, b));
+            self.consume_budget(1);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "op" => self.op = Op::parse(value).ok_or_else(|| anyhow!("Unknown op {}", value))?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
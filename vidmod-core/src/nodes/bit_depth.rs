@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, ensure, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, NodeImpl, NodePorts, VidmodError};
+
+/// Rescales U16 samples from "in" onto "out" between bit depths, rounding
+///
+/// Samples are stored in 16-bit words whatever their depth, so e.g. 10-bit 0..=1023 becomes 16-bit
+/// 0..=65535 with `from_bits: 10` and `to_bits: 16`. The ports declare the two depths.
+///
+/// The `to_bits` message changes the output depth from the next frame on, though "out" keeps the
+/// depth it declared at init.
+#[node_decl]
+pub struct BitDepth {
+    kind:      FrameKind,
+    from_bits: u8,
+    to_bits:   u8,
+    buf_size:  usize,
+}
+
+impl BitDepth {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params
+            .get("kind")
+            .map_or(FrameKind::U16, |v| v.as_str().into());
+        let from_bits = params.get("from_bits").unwrap().parse().unwrap();
+        let to_bits = params.get("to_bits").unwrap().parse().unwrap();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            from_bits,
+            to_bits,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for BitDepth {
+    fn init(&mut self) {
+        self.register_pushport_with_depth("in", self.kind, self.buf_size, self.from_bits);
+        self.register_pullport_with_depth("out", self.kind, self.buf_size, self.to_bits);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.outbuf_avail("out"));
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let frame = self.inbuf_get("in", count);
+        let frame = frame.rescale_depth(self.from_bits, self.to_bits).unwrap();
+        self.outbuf_put("out", frame);
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "to_bits" => {
+                let to_bits: u8 = value.parse()?;
+                ensure!((1..=16).contains(&to_bits), "Invalid bit depth {}", to_bits);
+                self.to_bits = to_bits;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
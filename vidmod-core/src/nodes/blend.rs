@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, ensure, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{Frame, FrameKind},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// How Blend combines its two inputs
+///
+/// Integer kinds saturate at their bounds, and `multiply` treats them as fractions of their
+/// maximum. `over` composites "a" over "b" and needs RGBA8x2.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    Add,
+    Sub,
+    Diff,
+    Multiply,
+    Over,
+}
+
+impl BlendMode {
+    fn parse(f: &str) -> Option<Self> {
+        Some(match f {
+            "add" => BlendMode::Add,
+            "sub" => BlendMode::Sub,
+            "diff" => BlendMode::Diff,
+            "multiply" => BlendMode::Multiply,
+            "over" => BlendMode::Over,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&str> for BlendMode {
+    fn from(f: &str) -> Self {
+        BlendMode::parse(f).unwrap_or_else(|| unimplemented!("Blend mode {}", f))
+    }
+}
+
+/// Combines frames from "a" and "b" pairwise into "out", see `BlendMode`
+///
+/// The `mode` message switches the mode from the next pair of frames on.
+#[node_decl]
+pub struct Blend {
+    kind:     FrameKind,
+    mode:     BlendMode,
+    buf_size: usize,
+}
+
+impl Blend {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let mode = params.get("mode").unwrap().as_str().into();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        assert!(
+            mode != BlendMode::Over || kind == FrameKind::RGBA8x2,
+            "Blend mode over needs RGBA8x2, got {:?}",
+            kind
+        );
+        Self {
+            kind,
+            mode,
+            buf_size,
+        }
+    }
+
+    fn blend(&self, a: &Frame, b: &Frame) -> Frame {
+        let res = match self.mode {
+            BlendMode::Add => a.add_saturating(b),
+            BlendMode::Sub => a.zip_map(b, |a, b| a - b),
+            BlendMode::Diff => a.sub_abs(b),
+            BlendMode::Multiply => a.mul(b),
+            BlendMode::Over => a.composite_over(b),
+        };
+        res.unwrap_or_else(|e| panic!("Blend failed: {}", e))
+    }
+}
+
+impl NodeImpl for Blend {
+    fn init(&mut self) {
+        self.register_pushport("a", self.kind, self.buf_size);
+        self.register_pushport("b", self.kind, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_min_avail(&["a", "b"]), self.outbuf_avail("out"));
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let a = self.inbuf_get("a", count);
+        let b = self.inbuf_get("b", count);
+        let out = self.blend(&a, &b);
+        self.outbuf_put("out", out);
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "mode" => {
+                let mode = BlendMode::parse(value)
+                    .ok_or_else(|| anyhow!("Unknown blend mode {}", value))?;
+                ensure!(
+                    mode != BlendMode::Over || self.kind == FrameKind::RGBA8x2,
+                    "Blend mode over needs RGBA8x2, got {:?}",
+                    self.kind
+                );
+                self.mode = mode;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// Emits a U8 on "out" for each frame on "in": 1 if its content hash differs from the previous
+/// frame's, 0 otherwise
+///
+/// The first frame always counts as changed, as does the first after a `reset` message.
+#[node_decl]
+pub struct ChangeDetect {
+    kind:     FrameKind,
+    last:     Option<u64>,
+    buf_size: usize,
+}
+
+impl ChangeDetect {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            last: None,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for ChangeDetect {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pullport("out", FrameKind::U8, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.outbuf_avail("out"));
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        for _ in 0..count {
+            let hash = self.inbuf_get_single("in").content_hash();
+            let changed = self.last != Some(hash);
+            self.last = Some(hash);
+            self.outbuf_put_single("out", FrameSingle::U8(changed as u8));
+        }
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, _value: &str) -> Result<()> {
+        match key {
+            "reset" => self.last = None,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, ensure, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, NodeImpl, NodePorts, VidmodError};
+
+/// Forwards all of "in0" to "out" until its upstream finishes, then "in1", and so on
+///
+/// Every input has the same `kind`, so linking a port of any other kind fails. The node only
+/// finishes once all `n` inputs have ended, so it keeps running while later inputs still have
+/// producers upstream.
+///
+/// The `next` message moves on to the next input straight away, and anything that still arrives
+/// on the inputs before it is dropped.
+#[node_decl]
+pub struct Concat {
+    kind:    FrameKind,
+    inputs:  Vec<String>,
+    current: usize,
+}
+
+impl Concat {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let count = params.get("n").map_or(2, |v| v.parse().unwrap());
+        let inputs = (0..count).map(|i| format!("in{}", i)).collect();
+        Self {
+            kind,
+            inputs,
+            current: 0,
+        }
+    }
+}
+
+impl NodeImpl for Concat {
+    fn init(&mut self) {
+        for name in &self.inputs.clone() {
+            self.register_pushport(name, self.kind, 1);
+        }
+        self.register_pullport("out", self.kind, 1);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        // Inputs left early are drained so their producers can still finish. The names are taken
+        // out while the buffers are borrowed, rather than cloned every tick
+        let inputs = std::mem::take(&mut self.inputs);
+        for name in &inputs[..self.current] {
+            if self.inbuf_avail(name) > 0 {
+                self.inbuf_get_all(name);
+                res = true;
+            }
+        }
+        self.inputs = inputs;
+        while let Some(name) = self.inputs.get(self.current).cloned() {
+            let count = usize::min(self.inbuf_avail(&name), self.outbuf_avail("out"));
+            let count = usize::min(count, self.budget_remaining());
+            if count > 0 {
+                self.consume_budget(count);
+                let frame = self.inbuf_get(&name, count);
+                self.outbuf_put("out", frame);
+                res = true;
+            } else if self.budget_remaining() == 0 {
+                break;
+            } else if self.inbuf_avail(&name) == 0 && self.inbuf_eos(&name) {
+                self.current += 1;
+                res = true;
+            } else {
+                break;
+            }
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.current >= self.inputs.len()
+    }
+
+    fn on_message(&mut self, key: &str, _value: &str) -> Result<()> {
+        match key {
+            "next" => {
+                ensure!(
+                    self.current + 1 < self.inputs.len(),
+                    "Concat is already on its last input"
+                );
+                self.current += 1;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
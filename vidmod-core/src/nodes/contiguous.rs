@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, InputPort, NodeImpl, NodePorts, OutputPort, VidmodError};
+
+/// Forwards frames from "in" to "out", copying any arrays into standard (row-major) layout
+///
+/// The `bypass` message, when true, forwards frames in whatever layout they arrive in.
+#[node_decl]
+pub struct Contiguous {
+    #[input("in")]
+    input:    InputPort,
+    #[output]
+    out:      OutputPort,
+    kind:     FrameKind,
+    bypass:   bool,
+    buf_size: usize,
+}
+
+impl Contiguous {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            bypass: false,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for Contiguous {
+    fn init(&mut self) {
+        self.register_input(self.input().name(), self.kind, self.buf_size);
+        self.register_output(self.out().name(), self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.input().avail(self), self.out().avail(self));
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let frame = self.input().get(self, count);
+        let frame = if self.bypass {
+            frame
+        } else {
+            frame.as_standard_layout()
+        };
+        self.out().put(self, frame);
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "bypass" => self.bypass = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
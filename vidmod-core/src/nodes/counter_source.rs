@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, SeekOutcome, VidmodError,
+};
+
+/// Emits `count` scalar frames on "out", starting at `start` and increasing by `step`
+///
+/// The `count` message changes how many frames are emitted in all.
+#[node_decl]
+pub struct CounterSource {
+    kind:     FrameKind,
+    start:    f64,
+    next:     f64,
+    step:     f64,
+    count:    usize,
+    emitted:  usize,
+    buf_size: usize,
+}
+
+impl CounterSource {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let start = params.get("start").map_or(0.0, |v| v.parse().unwrap());
+        let step = params.get("step").map_or(1.0, |v| v.parse().unwrap());
+        let count = params.get("count").unwrap().parse().unwrap();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            start,
+            next: start,
+            step,
+            count,
+            emitted: 0,
+            buf_size,
+        }
+    }
+
+    fn value(&self) -> FrameSingle {
+        match self.kind {
+            FrameKind::U8 => FrameSingle::U8(self.next as u8),
+            FrameKind::U16 => FrameSingle::U16(self.next as u16),
+            FrameKind::F32 => FrameSingle::F32(self.next as f32),
+            kind => unimplemented!("CounterSource of kind {:?}", kind),
+        }
+    }
+}
+
+impl NodeImpl for CounterSource {
+    fn init(&mut self) {
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.emitted < self.count
+            && self.outbuf_avail("out") > 0
+            && self.budget_remaining() > 0
+        {
+            self.outbuf_put_single("out", self.value());
+            self.next += self.step;
+            self.emitted += 1;
+            self.consume_budget(1);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.emitted >= self.count
+    }
+
+    fn seek(&mut self, position: u64) -> Result<SeekOutcome> {
+        self.emitted = usize::min(position as usize, self.count);
+        self.next = self.start + self.step * self.emitted as f64;
+        Ok(SeekOutcome {
+            position: self.emitted as u64,
+        })
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "count" => self.count = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{Frame, FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+// The shape `FrameSingle::zero` takes to make a frame like this one, or None for scalars
+fn shape_of(frame: &FrameSingle) -> Option<(usize, usize)> {
+    match frame {
+        FrameSingle::U8(_) | FrameSingle::U16(_) | FrameSingle::F32(_) => None,
+        FrameSingle::U8x1(a) => Some((a.len(), 1)),
+        FrameSingle::U16x1(a) => Some((a.len(), 1)),
+        FrameSingle::F32x1(a) => Some((a.len(), 1)),
+        FrameSingle::U8x2(a) => Some(a.dim()),
+        FrameSingle::U16x2(a) => Some(a.dim()),
+        FrameSingle::F32x2(a) => Some(a.dim()),
+        FrameSingle::RGBA8x2(a) => Some(a.dim()),
+    }
+}
+
+/// Delays the frames from "in" by `frames` places on "out", sending neutral frames ahead of them
+///
+/// The neutral frames are zero, or transparent black. Array kinds take their shape from `shape`,
+/// `len` for 1D kinds and `rows,cols` for 2D kinds, or else from the first frame to arrive, and
+/// are 1x1 if none does. Once the input ends the last `frames` frames are flushed, so the output is
+/// `frames` longer than the input, unless `trim: true` drops them to keep the lengths equal.
+/// `buf_size` must be at least `frames`, and defaults to the larger of 16 and `frames`.
+///
+/// The `trim` message switches trimming on or off, taking effect when the input ends.
+#[node_decl]
+pub struct Delay {
+    kind:      FrameKind,
+    frames:    usize,
+    shape:     Option<(usize, usize)>,
+    trim:      bool,
+    line:      Frame,
+    primed:    bool,
+    finishing: bool,
+    buf_size:  usize,
+}
+
+impl Delay {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let frames = params.get("frames").unwrap().parse().unwrap();
+        let shape = params.get("shape").map(|v| {
+            let dims: Vec<usize> = v.split(',').map(|d| d.trim().parse().unwrap()).collect();
+            match dims.as_slice() {
+                [len] => (*len, 1),
+                [rows, cols] => (*rows, *cols),
+                _ => panic!("Invalid Delay shape {:?}", dims),
+            }
+        });
+        let trim = params.get("trim").map_or(false, |v| v.parse().unwrap());
+        let buf_size = params
+            .get("buf_size")
+            .map_or(usize::max(16, frames), |v| v.parse().unwrap());
+        Self {
+            kind,
+            frames,
+            shape,
+            trim,
+            line: Frame::with_capacity(kind, frames + buf_size),
+            primed: false,
+            finishing: false,
+            buf_size,
+        }
+    }
+
+    // Fill the line with the neutral frames that go out ahead of the input
+    fn prime(&mut self, shape: Option<(usize, usize)>) {
+        let neutral = match shape {
+            Some(shape) => FrameSingle::zero(self.kind, Some(shape)).unwrap(),
+            None => self.kind.default_scalar(),
+        };
+        for _ in 0..self.frames {
+            self.line.add_single(neutral.clone()).unwrap();
+        }
+        self.primed = true;
+    }
+
+    fn step(&mut self) -> bool {
+        if !self.primed {
+            let shape = match self.inbuf_peek_single("in") {
+                Some(frame) => shape_of(&frame),
+                None if self.finishing => None,
+                None => return false,
+            };
+            self.prime(self.shape.or(shape));
+        }
+        let mut res = false;
+        loop {
+            let count = usize::min(self.inbuf_avail("in"), self.outbuf_avail("out"));
+            let count = usize::min(count, self.budget_remaining());
+            if count > 0 {
+                // Each frame in pushes the one `frames` places ahead of it out
+                self.consume_budget(count);
+                let frame = self.inbuf_get("in", count);
+                self.line.add(frame).unwrap();
+                let frame = self.line.remove(count).unwrap();
+                self.outbuf_put("out", frame);
+            } else if self.finishing && !self.trim && self.inbuf_avail("in") == 0 {
+                let count = usize::min(self.line.size(), self.outbuf_avail("out"));
+                if count == 0 {
+                    break;
+                }
+                let frame = self.line.remove(count).unwrap();
+                self.outbuf_put("out", frame);
+            } else {
+                break;
+            }
+            res = true;
+        }
+        res
+    }
+}
+
+impl NodeImpl for Delay {
+    fn init(&mut self) {
+        assert!(
+            self.buf_size >= self.frames,
+            "Delay of {} frames needs a buf_size of at least {}",
+            self.frames,
+            self.frames
+        );
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        self.step()
+    }
+
+    fn finish(&mut self) -> bool {
+        self.finishing = true;
+        self.step();
+        self.inbuf_avail("in") == 0 && (self.trim || self.line.size() == 0)
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "trim" => self.trim = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use ndarray::{ArcArray1, ArcArray2};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+use crate::expr;
+
+// 0 for scalar kinds, 1 for x1 kinds, 2 for x2 kinds
+fn dims(kind: FrameKind) -> usize {
+    match kind {
+        FrameKind::U8 | FrameKind::U16 | FrameKind::F32 => 0,
+        FrameKind::U8x1 | FrameKind::U16x1 | FrameKind::F32x1 => 1,
+        FrameKind::U8x2 | FrameKind::U16x2 | FrameKind::F32x2 => 2,
+        FrameKind::RGBA8x2 => unimplemented!("Expr for {:?}", kind),
+    }
+}
+
+// The values of a frame, and its shape if it is an array
+fn values(frame: FrameSingle) -> (Vec<f64>, Option<Vec<usize>>) {
+    fn array<T: Copy + Into<f64>, D: ndarray::Dimension>(
+        a: ndarray::ArcArray<T, D>,
+    ) -> (Vec<f64>, Option<Vec<usize>>) {
+        (
+            a.iter().map(|&v| v.into()).collect(),
+            Some(a.shape().to_vec()),
+        )
+    }
+    match frame {
+        FrameSingle::U8(v) => (vec![v.into()], None),
+        FrameSingle::U16(v) => (vec![v.into()], None),
+        FrameSingle::F32(v) => (vec![v.into()], None),
+        FrameSingle::U8x1(a) => array(a),
+        FrameSingle::U16x1(a) => array(a),
+        FrameSingle::F32x1(a) => array(a),
+        FrameSingle::U8x2(a) => array(a),
+        FrameSingle::U16x2(a) => array(a),
+        FrameSingle::F32x2(a) => array(a),
+        FrameSingle::RGBA8x2(_) => unimplemented!("Expr for RGBA8x2"),
+    }
+}
+
+// Integer kinds round, saturate at their bounds, and map NaN to zero
+fn frame(kind: FrameKind, values: Vec<f64>, shape: &[usize]) -> FrameSingle {
+    let u8s = |values: Vec<f64>| values.into_iter().map(|v| v.round() as u8).collect();
+    let u16s = |values: Vec<f64>| values.into_iter().map(|v| v.round() as u16).collect();
+    let f32s = |values: Vec<f64>| values.into_iter().map(|v| v as f32).collect();
+    let shape2 = || (shape[0], shape[1]);
+    match kind {
+        FrameKind::U8 => FrameSingle::U8(values[0].round() as u8),
+        FrameKind::U16 => FrameSingle::U16(values[0].round() as u16),
+        FrameKind::F32 => FrameSingle::F32(values[0] as f32),
+        FrameKind::U8x1 => FrameSingle::U8x1(ArcArray1::from_vec(u8s(values))),
+        FrameKind::U16x1 => FrameSingle::U16x1(ArcArray1::from_vec(u16s(values))),
+        FrameKind::F32x1 => FrameSingle::F32x1(ArcArray1::from_vec(f32s(values))),
+        FrameKind::U8x2 => {
+            FrameSingle::U8x2(ArcArray2::from_shape_vec(shape2(), u8s(values)).unwrap())
+        }
+        FrameKind::U16x2 => {
+            FrameSingle::U16x2(ArcArray2::from_shape_vec(shape2(), u16s(values)).unwrap())
+        }
+        FrameKind::F32x2 => {
+            FrameSingle::F32x2(ArcArray2::from_shape_vec(shape2(), f32s(values)).unwrap())
+        }
+        FrameKind::RGBA8x2 => unimplemented!("Expr for {:?}", kind),
+    }
+}
+
+/// Evaluates `expr` elementwise over its inputs into "out"
+///
+/// `inputs` lists the push ports as `name:kind` pairs, e.g. `a:F32x2,b:F32`, and each name is a
+/// variable in the expression. Scalar inputs are broadcast over array inputs, which must all
+/// share a shape. The output `kind` must be scalar if every input is, and otherwise have the
+/// same dimensions as the array inputs. Invalid expressions panic on construction, giving the
+/// position of the error.
+///
+/// The `expr` message replaces the expression, over the same inputs, from the next frame on.
+#[node_decl]
+pub struct Expr {
+    expr:     expr::Expr,
+    inputs:   Vec<(String, FrameKind)>,
+    kind:     FrameKind,
+    buf_size: usize,
+}
+
+impl Expr {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let inputs: Vec<(String, FrameKind)> = params
+            .get("inputs")
+            .unwrap()
+            .split(',')
+            .map(|input| {
+                let mut parts = input.splitn(2, ':').map(str::trim);
+                match (parts.next(), parts.next()) {
+                    (Some(name), Some(kind)) if !name.is_empty() => (name.to_owned(), kind.into()),
+                    _ => panic!("Invalid Expr input {:?}, expected name:kind", input),
+                }
+            })
+            .collect();
+        let out_dims = dims(kind);
+        for (name, input) in &inputs {
+            let input = dims(*input);
+            assert!(
+                input == 0 || input == out_dims,
+                "Expr input {} does not match output {:?}",
+                name,
+                kind
+            );
+        }
+        assert!(
+            out_dims == 0 || inputs.iter().any(|(_, input)| dims(*input) == out_dims),
+            "Expr output {:?} needs an array input",
+            kind
+        );
+        let names: Vec<&str> = inputs.iter().map(|(name, _)| name.as_str()).collect();
+        let src = params.get("expr").unwrap();
+        let expr = expr::Expr::parse(src, &names)
+            .unwrap_or_else(|e| panic!("Invalid expression {:?}: {}", src, e));
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            expr,
+            inputs,
+            kind,
+            buf_size,
+        }
+    }
+
+    fn eval(&self, frames: Vec<FrameSingle>) -> FrameSingle {
+        let inputs: Vec<_> = frames.into_iter().map(values).collect();
+        let mut shape = None;
+        for (input, (name, _)) in inputs.iter().zip(&self.inputs) {
+            if let Some(s) = &input.1 {
+                match &shape {
+                    None => shape = Some(s.clone()),
+                    Some(shape) => assert_eq!(shape, s, "Expr shape mismatch on {}", name),
+                }
+            }
+        }
+        let shape = shape.unwrap_or_default();
+        let len = shape.iter().product();
+        let mut vars = vec![0.0; inputs.len()];
+        let values = (0..len)
+            .map(|idx| {
+                for (var, (values, shape)) in vars.iter_mut().zip(&inputs) {
+                    *var = if shape.is_some() {
+                        values[idx]
+                    } else {
+                        values[0]
+                    };
+                }
+                self.expr.eval(&vars)
+            })
+            .collect();
+        frame(self.kind, values, &shape)
+    }
+}
+
+impl NodeImpl for Expr {
+    fn init(&mut self) {
+        for (name, kind) in self.inputs.clone() {
+            self.register_pushport(&name, kind, self.buf_size);
+        }
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.outbuf_avail("out") > 0
+            && self.budget_remaining() > 0
+            && self
+                .inputs
+                .iter()
+                .all(|(name, _)| self.inbuf_avail(name) > 0)
+        {
+            let frames = self
+                .inputs
+                .clone()
+                .iter()
+                .map(|(name, _)| self.inbuf_get_single(name))
+                .collect();
+            let out = self.eval(frames);
+            self.outbuf_put_single("out", out);
+            self.consume_budget(1);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "expr" => {
+                let names: Vec<&str> = self.inputs.iter().map(|(name, _)| name.as_str()).collect();
+                self.expr = expr::Expr::parse(value, &names)
+                    .map_err(|e| anyhow!("Invalid expression {:?}: {}", value, e))?;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
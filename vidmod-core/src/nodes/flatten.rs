@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, ensure, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{Frame, FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+// The 1D array kind holding elements of the scalar `kind`
+fn array_kind(kind: FrameKind) -> FrameKind {
+    match kind {
+        FrameKind::U8 => FrameKind::U8x1,
+        FrameKind::U16 => FrameKind::U16x1,
+        FrameKind::F32 => FrameKind::F32x1,
+        _ => panic!("Expected a scalar kind, got {:?}", kind),
+    }
+}
+
+fn split(array: FrameSingle) -> Frame {
+    match array {
+        FrameSingle::U8x1(a) => Frame::U8(a.iter().copied().collect()),
+        FrameSingle::U16x1(a) => Frame::U16(a.iter().copied().collect()),
+        FrameSingle::F32x1(a) => Frame::F32(a.iter().copied().collect()),
+        _ => unreachable!(),
+    }
+}
+
+fn group(frame: Frame) -> FrameSingle {
+    match frame {
+        Frame::U8(v) => FrameSingle::U8x1(v.iter().copied().collect()),
+        Frame::U16(v) => FrameSingle::U16x1(v.iter().copied().collect()),
+        Frame::F32(v) => FrameSingle::F32x1(v.iter().copied().collect()),
+        _ => unreachable!(),
+    }
+}
+
+/// Splits each 1D array from "in" into its elements on "out"
+///
+/// `kind` is the scalar kind of the elements, one of U8, U16 or F32.
+///
+/// The `skip` message drops that many of the next elements instead of sending them.
+#[node_decl]
+pub struct Flatten {
+    kind:     FrameKind,
+    pending:  Frame,
+    skip:     usize,
+    buf_size: usize,
+}
+
+impl Flatten {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        array_kind(kind);
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            pending: Frame::with_capacity(kind, 0),
+            skip: 0,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for Flatten {
+    fn init(&mut self) {
+        self.register_pushport("in", array_kind(self.kind), self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.budget_remaining() > 0 {
+            if self.pending.size() == 0 {
+                match self.inbuf_try_get_single("in") {
+                    Some(array) => self.pending = split(array),
+                    None => break,
+                }
+                res = true;
+                continue;
+            }
+            if self.skip > 0 {
+                let count = usize::min(self.skip, self.pending.size());
+                self.pending.remove(count).unwrap();
+                self.skip -= count;
+                res = true;
+                continue;
+            }
+            let count = usize::min(self.pending.size(), self.outbuf_avail("out"));
+            let count = usize::min(count, self.budget_remaining());
+            if count == 0 {
+                break;
+            }
+            self.consume_budget(count);
+            let frame = self.pending.remove(count).unwrap();
+            self.outbuf_put("out", frame);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.pending.size() == 0 && self.inbuf_avail("in") == 0
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "skip" => self.skip = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
+
+/// Groups every `len` elements from "in" into a 1D array on "out"
+///
+/// `kind` is the scalar kind of the elements, one of U8, U16 or F32, and `len` defaults to 1.
+/// Once the input ends, any elements left over are grouped into one shorter array.
+///
+/// The `len` message changes the length of the next arrays, up to the size of the input buffer.
+#[node_decl]
+pub struct Unflatten {
+    kind:        FrameKind,
+    len:         usize,
+    in_buf_size: usize,
+    buf_size:    usize,
+}
+
+impl Unflatten {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        array_kind(kind);
+        let len = params.get("len").map_or(1, |v| v.parse().unwrap());
+        assert!(len > 0, "Unflatten needs a len of at least 1");
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            len,
+            in_buf_size: usize::max(buf_size, len),
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for Unflatten {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.in_buf_size);
+        self.register_pullport("out", array_kind(self.kind), self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.outbuf_avail("out") > 0 && self.budget_remaining() > 0 {
+            let avail = self.inbuf_avail("in");
+            let count = if avail >= self.len {
+                self.len
+            } else if avail > 0 && self.inbuf_eos("in") {
+                avail
+            } else {
+                break;
+            };
+            self.consume_budget(1);
+            let frame = self.inbuf_get("in", count);
+            self.outbuf_put_single("out", group(frame));
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.inbuf_avail("in") == 0
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "len" => {
+                let len = value.parse()?;
+                ensure!(
+                    (1..=self.in_buf_size).contains(&len),
+                    "Unflatten len must be 1 to {}, got {}",
+                    self.in_buf_size,
+                    len
+                );
+                self.len = len;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
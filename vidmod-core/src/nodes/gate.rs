@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, NodeImpl, NodePorts, VidmodError};
+
+/// Passes each frame from "in" to "out" only if the U8 from "control" taken with it is non-zero
+///
+/// Frames are taken from "in" and "control" in pairs; a frame whose control is zero is dropped.
+/// The `invert` message, when true, passes the frames whose control is zero instead.
+#[node_decl]
+pub struct Gate {
+    kind:     FrameKind,
+    invert:   bool,
+    buf_size: usize,
+}
+
+impl Gate {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            invert: false,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for Gate {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pushport("control", FrameKind::U8, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(
+            self.inbuf_min_avail(&["in", "control"]),
+            self.outbuf_avail("out"),
+        );
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let mut frames = self.inbuf_get_zipped(&["in", "control"], count).unwrap();
+        let controls = frames.pop().unwrap().unwrap_u8();
+        let mut data = frames.pop().unwrap();
+        for control in controls.iter() {
+            let frame = data.remove_single().unwrap();
+            if (*control != 0) != self.invert {
+                self.outbuf_put_single("out", frame);
+            }
+        }
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        self.inbuf_min_avail(&["in", "control"]) == 0
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "invert" => self.invert = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
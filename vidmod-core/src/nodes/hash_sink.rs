@@ -0,0 +1,95 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, params::Params, NodeImpl, NodePorts, VidmodError};
+
+// Every frame kind, for sinks that accept any
+pub(crate) const KINDS: &[FrameKind] = &[
+    FrameKind::U8,
+    FrameKind::U8x1,
+    FrameKind::U8x2,
+    FrameKind::U16,
+    FrameKind::U16x1,
+    FrameKind::U16x2,
+    FrameKind::F32,
+    FrameKind::F32x1,
+    FrameKind::F32x2,
+    FrameKind::RGBA8x2,
+];
+
+/// Accumulates a rolling hash of every frame received on "in", of any kind
+///
+/// Once the input reaches end of stream the hash is printed, and written as hex to `file` if set.
+/// The `reset` message starts the hash and the frame count again from the next frame.
+#[node_decl]
+pub struct HashSink {
+    name:     String,
+    file:     Option<PathBuf>,
+    hash:     u64,
+    count:    usize,
+    reported: bool,
+}
+
+impl HashSink {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let params = Params::from(params);
+        let name = params.node_name().unwrap_or("HashSink").to_owned();
+        let file = params.output_path("file").ok();
+        Self {
+            name,
+            file,
+            hash: 0,
+            count: 0,
+            reported: false,
+        }
+    }
+
+    fn report(&mut self) {
+        if self.reported || !self.inbuf_eos("in") || self.inbuf_avail("in") > 0 {
+            return;
+        }
+        println!("{}: {:016x} ({} frames)", self.name, self.hash, self.count);
+        if let Some(file) = &self.file {
+            fs::write(file, format!("{:016x}\n", self.hash)).unwrap();
+        }
+        self.reported = true;
+    }
+}
+
+impl NodeImpl for HashSink {
+    fn init(&mut self) {
+        self.register_pushport_any("in", KINDS, 16);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.budget_remaining());
+        if count > 0 {
+            self.consume_budget(count);
+            let frame = self.inbuf_get("in", count);
+            self.hash = frame.rolling_hash(self.hash);
+            self.count += count;
+        }
+        self.report();
+        count > 0
+    }
+
+    fn finish(&mut self) -> bool {
+        self.report();
+        true
+    }
+
+    fn on_message(&mut self, key: &str, _value: &str) -> Result<()> {
+        match key {
+            "reset" => {
+                self.hash = 0;
+                self.count = 0;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use std::{collections::BTreeMap, fs::File, path::PathBuf};
+
+use anyhow::{bail, ensure, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::FrameKind, params::Params, NodeId, NodeImpl, NodePorts, PullPort, PushPort, VidmodError,
+};
+use vidmod_plugin::PluginRegistry;
+
+use crate::spec::{NodeGraph, Project};
+
+/// Runs the graph of the manifest `file` over each frame from "in", emitting the result to "out"
+///
+/// Each tick is one pass: the frame is pushed to the inner port `input`, the inner graph is run
+/// until it settles, and one frame is taken from the inner port `output`. That frame is emitted
+/// once `max_iters` passes have been made or the inner U8 port `done_port` last produced a
+/// non-zero frame, and is otherwise fed back to `input` for the next pass. Inner ports are given
+/// as `node.port`, and the inner graph is built with the outer project's path and plugins. Its
+/// nodes are started and stopped along with this one.
+///
+/// The `max_iters` message changes the cap, a frame already past a lowered cap being emitted
+/// after its current pass.
+#[node_decl]
+pub struct Iterate {
+    graph:      NodeGraph,
+    input:      PushPort,
+    output:     PullPort,
+    done:       Option<PullPort>,
+    max_iters:  usize,
+    buf_size:   usize,
+    pass:       Option<usize>,
+    iterations: Vec<usize>,
+}
+
+impl Iterate {
+    #[node_new]
+    pub fn load(params: BTreeMap<String, String>, registry: &PluginRegistry) -> Self {
+        let params = Params::new(params);
+        let path = PathBuf::from(params.path().unwrap_or("."));
+        let file = params
+            .resolve_path("file")
+            .unwrap_or_else(|e| panic!("{}", e));
+        let manifest =
+            File::open(&file).unwrap_or_else(|e| panic!("Cannot open {:?}: {}", file, e));
+        let mut graph = Project::load_with(manifest, path, registry).into_graph();
+
+        let (node, port) = inner_port(&graph, params.get("input").unwrap());
+        let input = graph.get_push_port(node, port).unwrap();
+        let (node, port) = inner_port(&graph, params.get("output").unwrap());
+        let output = graph.get_pull_port(node, port).unwrap();
+        let done = params.get("done_port").map(|done| {
+            let (node, port) = inner_port(&graph, done);
+            let port = graph.get_pull_port(node, port).unwrap();
+            assert_eq!(port.kind(), FrameKind::U8, "Done port must be U8");
+            port
+        });
+        let max_iters = params.get("max_iters").unwrap().parse().unwrap();
+        assert!(max_iters > 0, "Iterate needs at least one pass");
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            graph,
+            input,
+            output,
+            done,
+            max_iters,
+            buf_size,
+            pass: None,
+            iterations: Vec::new(),
+        }
+    }
+
+    /// How many passes each emitted frame took, in order
+    pub fn iterations(&self) -> &[usize] {
+        &self.iterations
+    }
+
+    // Whether the done port has signalled, discarding everything it produced
+    fn is_done(&mut self) -> bool {
+        let done = match &self.done {
+            Some(done) => done,
+            None => return false,
+        };
+        let count = self.graph.ready_to_pull(done);
+        let frame = self.graph.pull_frame(done, count).unwrap_u8();
+        frame.iter().next_back().map_or(false, |v| *v != 0)
+    }
+}
+
+// Look up a `node.port` of the inner graph, splitting at the last dot
+fn inner_port<'a>(graph: &NodeGraph, spec: &'a str) -> (NodeId, &'a str) {
+    let dot = spec
+        .rfind('.')
+        .unwrap_or_else(|| panic!("Invalid port {}, expected node.port", spec));
+    let (name, port) = (&spec[..dot], &spec[dot + 1..]);
+    match graph.find(name) {
+        Some(node) => (node, port),
+        None => panic!("Unknown inner node {}", name),
+    }
+}
+
+impl NodeImpl for Iterate {
+    fn init(&mut self) {
+        self.register_pushport("in", self.input.kind(), self.buf_size);
+        self.register_pullport("out", self.output.kind(), self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        if self.outbuf_avail("out") == 0 {
+            return false;
+        }
+        let pass = match self.pass {
+            Some(pass) => pass,
+            None if self.inbuf_avail("in") > 0 => {
+                let frame = self.inbuf_get("in", 1);
+                self.graph.push_frame(&self.input, frame);
+                0
+            }
+            None => return false,
+        };
+
+        self.graph.settle_all();
+        if let Some(error) = self.graph.failure() {
+            panic!("Inner graph failed: {}", error);
+        }
+        if self.graph.ready_to_pull(&self.output) == 0 {
+            panic!(
+                "Pass {} produced no output on {}",
+                pass + 1,
+                self.output.name()
+            );
+        }
+        let frame = self.graph.pull_frame(&self.output, 1);
+        let pass = pass + 1;
+        if self.is_done() || pass >= self.max_iters {
+            self.outbuf_put("out", frame);
+            self.iterations.push(pass);
+            self.pass = None;
+        } else {
+            self.graph.push_frame(&self.input, frame);
+            self.pass = Some(pass);
+        }
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        self.pass.is_none() && self.inbuf_avail("in") == 0
+    }
+
+    fn start(&mut self) -> Result<()> {
+        match self.graph.start_nodes().into_iter().next() {
+            Some((_, error)) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        match self.graph.stop_nodes().into_iter().next() {
+            Some((_, error)) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "max_iters" => {
+                let max_iters = value.parse()?;
+                ensure!(max_iters > 0, "Iterate needs at least one pass");
+                self.max_iters = max_iters;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
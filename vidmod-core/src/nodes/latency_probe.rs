@@ -0,0 +1,94 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{params::Params, NodeImpl, NodePorts, VidmodError};
+
+use super::hash_sink::KINDS;
+
+/// Measures how many graph ticks each frame received on "in", of any kind, took to arrive since
+/// it was first produced
+///
+/// On finish the minimum, mean and maximum latency are printed, and written to `file` if set as
+/// `min mean max` on one line. The `reset` message discards the measurements so far.
+#[node_decl]
+pub struct LatencyProbe {
+    name:  String,
+    file:  Option<PathBuf>,
+    count: u64,
+    total: u64,
+    min:   u64,
+    max:   u64,
+}
+
+impl LatencyProbe {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let params = Params::from(params);
+        let name = params.node_name().unwrap_or("LatencyProbe").to_owned();
+        let file = params.output_path("file").ok();
+        Self {
+            name,
+            file,
+            count: 0,
+            total: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+}
+
+impl NodeImpl for LatencyProbe {
+    fn init(&mut self) {
+        self.register_pushport_any("in", KINDS, 16);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let clock = self.clock();
+        for origin in self.inbuf_origins("in").into_iter().take(count) {
+            let latency = clock.saturating_sub(origin);
+            self.count += 1;
+            self.total += latency;
+            self.min = u64::min(self.min, latency);
+            self.max = u64::max(self.max, latency);
+        }
+        self.inbuf_get("in", count);
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        if self.count == 0 {
+            println!("{}: no frames", self.name);
+            return true;
+        }
+        let mean = self.total as f64 / self.count as f64;
+        println!(
+            "{}: latency min {}, mean {}, max {} ticks ({} frames)",
+            self.name, self.min, mean, self.max, self.count
+        );
+        if let Some(file) = &self.file {
+            fs::write(file, format!("{} {} {}\n", self.min, mean, self.max)).unwrap();
+        }
+        true
+    }
+
+    fn on_message(&mut self, key: &str, _value: &str) -> Result<()> {
+        match key {
+            "reset" => {
+                self.count = 0;
+                self.total = 0;
+                self.min = u64::MAX;
+                self.max = 0;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, NodeImpl, NodePorts, VidmodError};
+
+/// Forwards at most `count` frames from "in" to "out", then stops consuming
+///
+/// The `count` message sets how many more frames are forwarded.
+#[node_decl]
+pub struct Limit {
+    kind:      FrameKind,
+    remaining: usize,
+    buf_size:  usize,
+}
+
+impl Limit {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let remaining = params.get("count").unwrap().parse().unwrap();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            remaining,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for Limit {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(
+            self.remaining,
+            usize::min(self.inbuf_avail("in"), self.outbuf_avail("out")),
+        );
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let frame = self.inbuf_get("in", count);
+        self.outbuf_put("out", frame);
+        self.remaining -= count;
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "count" => self.remaining = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
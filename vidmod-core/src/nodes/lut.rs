@@ -0,0 +1,164 @@
+use std::{collections::BTreeMap, fs};
+
+use anyhow::{bail, ensure, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    params::Params,
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// A lookup table mapping each input value to an output value
+///
+/// U8 tables have one entry per value. U16 tables may have fewer than
+/// 65536 entries, spread evenly over the input range, in which case
+/// values between entries are linearly interpolated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LutTable {
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+}
+
+impl LutTable {
+    // Parse whitespace or comma separated entries for a frame kind
+    pub fn parse(kind: FrameKind, table: &str) -> Result<Self> {
+        let entries = table
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty());
+        Ok(match kind {
+            FrameKind::U8 | FrameKind::U8x1 | FrameKind::U8x2 => {
+                let table = entries.map(str::parse).collect::<Result<Vec<u8>, _>>()?;
+                ensure!(
+                    table.len() == 256,
+                    "U8 LUT needs 256 entries, got {}",
+                    table.len()
+                );
+                LutTable::U8(table)
+            }
+            FrameKind::U16 | FrameKind::U16x1 | FrameKind::U16x2 => {
+                let table = entries.map(str::parse).collect::<Result<Vec<u16>, _>>()?;
+                ensure!(
+                    (2..=65536).contains(&table.len()),
+                    "U16 LUT needs 2 to 65536 entries, got {}",
+                    table.len()
+                );
+                LutTable::U16(table)
+            }
+            _ => bail!("Lut for {:?}", kind),
+        })
+    }
+
+    fn lookup_u8(&self, v: u8) -> u8 {
+        match self {
+            LutTable::U8(table) => table[v as usize],
+            LutTable::U16(_) => unreachable!(),
+        }
+    }
+
+    fn lookup_u16(&self, v: u16) -> u16 {
+        match self {
+            LutTable::U16(table) => {
+                let steps = (table.len() - 1) as u64;
+                let pos = v as u64 * steps;
+                let idx = (pos / 65535) as usize;
+                let frac = (pos % 65535) as i64;
+                if frac == 0 {
+                    return table[idx];
+                }
+                let (a, b) = (table[idx] as i64, table[idx + 1] as i64);
+                (a + ((b - a) * frac * 2 + 65535).div_euclid(65535 * 2)) as u16
+            }
+            LutTable::U8(_) => unreachable!(),
+        }
+    }
+}
+
+/// Remaps each value from "in" through a lookup table into "out"
+///
+/// The table is given inline with `table` or loaded from `file`, as
+/// whitespace or comma separated entries. A relative `file` is found in
+/// the project directory. The `table` message replaces the table with inline entries from the
+/// next frame on.
+#[node_decl]
+pub struct Lut {
+    kind:     FrameKind,
+    table:    LutTable,
+    buf_size: usize,
+}
+
+impl Lut {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let params = Params::from(params);
+        let kind = params.get("kind").unwrap().into();
+        let table = match (params.get("table"), params.get("file")) {
+            (Some(table), None) => table.to_owned(),
+            (None, Some(_)) => {
+                let path = params
+                    .resolve_path("file")
+                    .unwrap_or_else(|e| panic!("{}", e));
+                fs::read_to_string(path).unwrap()
+            }
+            _ => panic!("Lut needs exactly one of table or file"),
+        };
+        let table = LutTable::parse(kind, &table).unwrap_or_else(|e| panic!("{}", e));
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            table,
+            buf_size,
+        }
+    }
+
+    fn apply(&self, frame: FrameSingle) -> FrameSingle {
+        let table = &self.table;
+        match frame {
+            FrameSingle::U8(v) => FrameSingle::U8(table.lookup_u8(v)),
+            FrameSingle::U8x1(v) => FrameSingle::U8x1(v.map(|&v| table.lookup_u8(v)).into_shared()),
+            FrameSingle::U8x2(v) => FrameSingle::U8x2(v.map(|&v| table.lookup_u8(v)).into_shared()),
+            FrameSingle::U16(v) => FrameSingle::U16(table.lookup_u16(v)),
+            FrameSingle::U16x1(v) => {
+                FrameSingle::U16x1(v.map(|&v| table.lookup_u16(v)).into_shared())
+            }
+            FrameSingle::U16x2(v) => {
+                FrameSingle::U16x2(v.map(|&v| table.lookup_u16(v)).into_shared())
+            }
+            frame => unimplemented!("Lut for {:?}", FrameKind::from(&frame)),
+        }
+    }
+}
+
+impl NodeImpl for Lut {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.inbuf_avail("in") > 0
+            && self.outbuf_avail("out") > 0
+            && self.budget_remaining() > 0
+        {
+            let frame = self.inbuf_get_single("in");
+            self.outbuf_put_single("out", self.apply(frame));
+            self.consume_budget(1);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "table" => self.table = LutTable::parse(self.kind, value)?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
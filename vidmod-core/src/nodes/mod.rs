@@ -0,0 +1,395 @@
+use vidmod_node::Node;
+use vidmod_plugin::{
+    ArgSpec,
+    ArgType::{self, Bool, Enum, Integer, Number},
+    PluginRegistry,
+};
+
+mod binary_op;
+mod bit_depth;
+mod blend;
+mod change_detect;
+mod concat;
+mod contiguous;
+mod counter_source;
+mod delay;
+mod expr;
+mod flatten;
+mod gate;
+mod hash_sink;
+mod iterate;
+mod latency_probe;
+mod limit;
+mod lut;
+mod noise;
+mod noise_source;
+mod null_sink;
+mod quantize;
+mod rate_convert;
+mod raw_file_sink;
+mod replay_source;
+mod resample;
+mod resize;
+mod stats;
+mod subsample;
+mod switch;
+mod test_pattern;
+mod tile;
+mod timecode_sink;
+mod timecode_source;
+mod transform_2d;
+mod zip;
+
+pub use binary_op::{BinaryOp, Op};
+pub use bit_depth::BitDepth;
+pub use blend::{Blend, BlendMode};
+pub use change_detect::ChangeDetect;
+pub use concat::Concat;
+pub use contiguous::Contiguous;
+pub use counter_source::CounterSource;
+pub use delay::Delay;
+pub use expr::Expr;
+pub use flatten::{Flatten, Unflatten};
+pub use gate::Gate;
+pub use hash_sink::HashSink;
+pub use iterate::Iterate;
+pub use latency_probe::LatencyProbe;
+pub use limit::Limit;
+pub use lut::{Lut, LutTable};
+pub use noise::{Distribution, Noise};
+pub use noise_source::NoiseSource;
+pub use null_sink::NullSink;
+pub use quantize::Quantize;
+pub use rate_convert::{RateConvert, RateMode};
+pub use raw_file_sink::{RawFileSink, RawWriter};
+pub use replay_source::ReplaySource;
+pub use resample::Resample;
+pub use resize::Resize;
+pub use stats::Stats;
+pub use subsample::{Subsample, SubsampleMode};
+pub use switch::{OnInvalid, Switch};
+pub use test_pattern::{Pattern, TestPattern};
+pub use tile::{Tile, Untile};
+pub use timecode_sink::TimecodeSink;
+pub use timecode_source::TimecodeSource;
+pub use transform_2d::{Transform, Transform2D};
+pub use zip::Zip;
+
+/// Create a registry containing all built-in nodes
+pub fn registry() -> PluginRegistry {
+    let mut registry = PluginRegistry::new();
+    register(&mut registry);
+    registry
+}
+
+/// Register all built-in nodes under the `core::` prefix
+pub fn register(registry: &mut PluginRegistry) {
+    registry.register("core::BinaryOp", |params| Node::new(BinaryOp::new(params)));
+    registry.register("core::BitDepth", |params| Node::new(BitDepth::new(params)));
+    registry.register("core::Blend", |params| Node::new(Blend::new(params)));
+    registry.register("core::ChangeDetect", |params| {
+        Node::new(ChangeDetect::new(params))
+    });
+    registry.register("core::Concat", |params| Node::new(Concat::new(params)));
+    registry.register("core::Contiguous", |params| {
+        Node::new(Contiguous::new(params))
+    });
+    registry.register("core::CounterSource", |params| {
+        Node::new(CounterSource::new(params))
+    });
+    registry.register("core::Delay", |params| Node::new(Delay::new(params)));
+    registry.register("core::Expr", |params| Node::new(Expr::new(params)));
+    registry.register("core::Flatten", |params| Node::new(Flatten::new(params)));
+    registry.register("core::Gate", |params| Node::new(Gate::new(params)));
+    registry.register("core::HashSink", |params| Node::new(HashSink::new(params)));
+    registry.register("core::LatencyProbe", |params| {
+        Node::new(LatencyProbe::new(params))
+    });
+    registry.register("core::Limit", |params| Node::new(Limit::new(params)));
+    registry.register("core::Lut", |params| Node::new(Lut::new(params)));
+    registry.register("core::Noise", |params| Node::new(Noise::new(params)));
+    registry.register("core::NoiseSource", |params| {
+        Node::new(NoiseSource::new(params))
+    });
+    registry.register("core::NullSink", |params| Node::new(NullSink::new(params)));
+    registry.register("core::Quantize", |params| Node::new(Quantize::new(params)));
+    registry.register("core::RateConvert", |params| {
+        Node::new(RateConvert::new(params))
+    });
+    registry.register("core::RawFileSink", |params| {
+        Node::new(RawFileSink::new(params))
+    });
+    registry.register("core::ReplaySource", |params| {
+        Node::new(ReplaySource::new(params))
+    });
+    registry.register("core::Resample", |params| Node::new(Resample::new(params)));
+    registry.register("core::Resize", |params| Node::new(Resize::new(params)));
+    registry.register("core::Stats", |params| Node::new(Stats::new(params)));
+    registry.register("core::Subsample", |params| {
+        Node::new(Subsample::new(params))
+    });
+    registry.register("core::Switch", |params| Node::new(Switch::new(params)));
+    registry.register("core::TestPattern", |params| {
+        Node::new(TestPattern::new(params))
+    });
+    registry.register("core::Tile", |params| Node::new(Tile::new(params)));
+    registry.register("core::TimecodeSink", |params| {
+        Node::new(TimecodeSink::new(params))
+    });
+    registry.register("core::TimecodeSource", |params| {
+        Node::new(TimecodeSource::new(params))
+    });
+    registry.register("core::Transform2D", |params| {
+        Node::new(Transform2D::new(params))
+    });
+    registry.register("core::Unflatten", |params| {
+        Node::new(Unflatten::new(params))
+    });
+    registry.register("core::Untile", |params| Node::new(Untile::new(params)));
+    registry.register("core::Zip", |params| Node::new(Zip::new(params)));
+    describe(registry);
+}
+
+const KIND: ArgType = Enum(&[
+    "U8", "U8x1", "U8x2", "U16", "U16x1", "U16x2", "F32", "F32x1", "F32x2", "RGBA8x2",
+]);
+
+// Kinds of the elements Flatten and Unflatten convert to and from arrays
+const SCALAR_KIND: ArgType = Enum(&["U8", "U16", "F32"]);
+
+// The args each built-in node reads, for manifest tooling
+fn describe(registry: &mut PluginRegistry) {
+    let req = ArgSpec::required;
+    let opt = ArgSpec::optional;
+    let string = ArgType::String;
+    let buf_size = || opt("buf_size", Integer);
+    registry.describe(
+        "core::BinaryOp",
+        vec![
+            req("kind", KIND),
+            req("op", Enum(&["add", "sub", "mul", "div"])),
+        ],
+    );
+    registry.describe(
+        "core::BitDepth",
+        vec![
+            req("from_bits", Integer),
+            req("to_bits", Integer),
+            opt("kind", Enum(&["U16", "U16x1", "U16x2"])),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Blend",
+        vec![
+            req("kind", KIND),
+            req("mode", Enum(&["add", "sub", "diff", "multiply", "over"])),
+            buf_size(),
+        ],
+    );
+    registry.describe("core::ChangeDetect", vec![req("kind", KIND), buf_size()]);
+    registry.describe("core::Concat", vec![req("kind", KIND), opt("n", Integer)]);
+    registry.describe("core::Contiguous", vec![req("kind", KIND), buf_size()]);
+    registry.describe(
+        "core::CounterSource",
+        vec![
+            req("kind", KIND),
+            req("count", Integer),
+            opt("start", Number),
+            opt("step", Number),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Delay",
+        vec![
+            req("kind", KIND),
+            req("frames", Integer),
+            opt("shape", string),
+            opt("trim", Bool),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Expr",
+        vec![
+            req("kind", KIND),
+            req("inputs", string),
+            req("expr", string),
+            buf_size(),
+        ],
+    );
+    registry.describe("core::Flatten", vec![req("kind", SCALAR_KIND), buf_size()]);
+    registry.describe("core::Gate", vec![req("kind", KIND), buf_size()]);
+    registry.describe("core::HashSink", vec![opt("file", string)]);
+    registry.describe("core::LatencyProbe", vec![opt("file", string)]);
+    registry.describe(
+        "core::Limit",
+        vec![req("kind", KIND), req("count", Integer), buf_size()],
+    );
+    registry.describe(
+        "core::Lut",
+        vec![
+            req("kind", KIND),
+            opt("table", string),
+            opt("file", string),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Noise",
+        vec![
+            req("kind", Enum(&["F32", "U16"])),
+            req("count", Integer),
+            opt("distribution", Enum(&["uniform", "gaussian"])),
+            opt("seed", Integer),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::NoiseSource",
+        vec![
+            req("kind", KIND),
+            req("count", Integer),
+            opt("seed", Integer),
+            opt("shape", string),
+            buf_size(),
+        ],
+    );
+    registry.describe("core::NullSink", vec![req("kind", KIND), buf_size()]);
+    registry.describe(
+        "core::Quantize",
+        vec![
+            req("kind", KIND),
+            req("to", Enum(&["U8", "U8x1", "U8x2", "U16", "U16x1", "U16x2"])),
+            opt(
+                "dither",
+                Enum(&["none", "ordered", "floyd_steinberg", "tpdf"]),
+            ),
+            opt("seed", Integer),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::RateConvert",
+        vec![
+            req("kind", KIND),
+            req("mode", Enum(&["drop", "repeat"])),
+            req("ratio", string),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::RawFileSink",
+        vec![
+            req("file", string),
+            req("kind", KIND),
+            opt("flush_every", Integer),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::ReplaySource",
+        vec![req("file", string), opt("kind", KIND), buf_size()],
+    );
+    registry.describe(
+        "core::Resample",
+        vec![
+            req("from_rate", Integer),
+            req("to_rate", Integer),
+            opt("taps", Integer),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Resize",
+        vec![
+            req("kind", KIND),
+            req("rows", Integer),
+            req("cols", Integer),
+            opt("filter", Enum(&["nearest", "bilinear"])),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Stats",
+        vec![
+            opt("file", string),
+            opt("bins", Integer),
+            opt("lo", Number),
+            opt("hi", Number),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Subsample",
+        vec![
+            opt("h_factor", Integer),
+            opt("v_factor", Integer),
+            opt("mode", Enum(&["average", "drop"])),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Switch",
+        vec![
+            req("kind", KIND),
+            opt("n", Integer),
+            opt("on_invalid", Enum(&["error", "drop", "clamp"])),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::TestPattern",
+        vec![
+            req("pattern", Enum(&["bars", "gradient", "checker", "solid"])),
+            req("width", Integer),
+            req("height", Integer),
+            req("frames", Integer),
+            opt("kind", Enum(&["RGBA8x2", "U8x2", "U16x2"])),
+            opt("square", Integer),
+            opt("level", Number),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Tile",
+        vec![
+            req("kind", KIND),
+            req("tile_w", Integer),
+            req("tile_h", Integer),
+            buf_size(),
+        ],
+    );
+    registry.describe("core::TimecodeSink", vec![opt("file", string)]);
+    registry.describe(
+        "core::TimecodeSource",
+        vec![
+            req("fps", Number),
+            req("count", Integer),
+            opt("drop_frame", Bool),
+            opt("start", string),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Transform2D",
+        vec![
+            req("kind", KIND),
+            req(
+                "op",
+                Enum(&["transpose", "rot90", "rot180", "flip_h", "flip_v"]),
+            ),
+            buf_size(),
+        ],
+    );
+    registry.describe(
+        "core::Unflatten",
+        vec![req("kind", SCALAR_KIND), opt("len", Integer), buf_size()],
+    );
+    registry.describe("core::Untile", vec![req("kind", KIND), buf_size()]);
+    registry.describe(
+        "core::Zip",
+        vec![req("kind", KIND), opt("n", Integer), opt("inputs", Integer)],
+    );
+}
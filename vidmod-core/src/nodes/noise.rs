@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    dsp::Prng,
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// How a Noise spreads its samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    Uniform,
+    Gaussian,
+}
+
+impl Distribution {
+    fn parse(f: &str) -> Option<Self> {
+        Some(match f {
+            "uniform" => Distribution::Uniform,
+            "gaussian" => Distribution::Gaussian,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&str> for Distribution {
+    fn from(f: &str) -> Self {
+        Distribution::parse(f).unwrap_or_else(|| unimplemented!("Noise distribution {}", f))
+    }
+}
+
+/// Emits `count` samples of white noise on "out", from a PRNG seeded with `seed`
+///
+/// F32 samples are centred on 0: uniform ones cover `[-1, 1)`, and gaussian ones have a standard
+/// deviation of 1/6 so that they stay within `[-1, 1]`, see `Prng::next_gaussian`. U16 samples
+/// are the same mapped onto `0..=65535`. The same seed gives the same samples on every run and
+/// platform.
+///
+/// The `distribution` message switches the distribution from the next sample on.
+#[node_decl]
+pub struct Noise {
+    kind:         FrameKind,
+    distribution: Distribution,
+    rng:          Prng,
+    count:        usize,
+    emitted:      usize,
+    buf_size:     usize,
+}
+
+impl Noise {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        assert!(
+            matches!(kind, FrameKind::F32 | FrameKind::U16),
+            "Noise of kind {:?}",
+            kind
+        );
+        let distribution = params
+            .get("distribution")
+            .map_or(Distribution::Uniform, |v| v.as_str().into());
+        let seed = params.get("seed").map_or(0, |v| v.parse().unwrap());
+        let count = params.get("count").unwrap().parse().unwrap();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            distribution,
+            rng: Prng::new(seed),
+            count,
+            emitted: 0,
+            buf_size,
+        }
+    }
+
+    fn sample(&mut self) -> FrameSingle {
+        let x = match self.distribution {
+            Distribution::Uniform => self.rng.next_f32() * 2.0 - 1.0,
+            Distribution::Gaussian => self.rng.next_gaussian() / 6.0,
+        };
+        match self.kind {
+            FrameKind::U16 => FrameSingle::U16(((x + 1.0) * 32767.5).round().min(65535.0) as u16),
+            _ => FrameSingle::F32(x),
+        }
+    }
+}
+
+impl NodeImpl for Noise {
+    fn init(&mut self) {
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.emitted < self.count
+            && self.outbuf_avail("out") > 0
+            && self.budget_remaining() > 0
+        {
+            let sample = self.sample();
+            self.outbuf_put_single("out", sample);
+            self.emitted += 1;
+            self.consume_budget(1);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.emitted >= self.count
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "distribution" => {
+                self.distribution = Distribution::parse(value)
+                    .ok_or_else(|| anyhow!("Unknown noise distribution {}", value))?
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use ndarray::{ArcArray1, ArcArray2};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    dsp::Prng,
+    frame::{FrameKind, FrameSingle, RGBA8},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+fn values<T>(rng: &mut Prng, len: usize, next: fn(&mut Prng) -> T) -> Vec<T> {
+    (0..len).map(|_| next(rng)).collect()
+}
+
+fn array2<T>(rng: &mut Prng, shape: (usize, usize), next: fn(&mut Prng) -> T) -> ArcArray2<T> {
+    ArcArray2::from_shape_vec(shape, values(rng, shape.0 * shape.1, next)).unwrap()
+}
+
+fn next_u8(rng: &mut Prng) -> u8 {
+    (rng.next_u64() >> 56) as u8
+}
+
+fn next_u16(rng: &mut Prng) -> u16 {
+    (rng.next_u64() >> 48) as u16
+}
+
+fn next_rgba8(rng: &mut Prng) -> RGBA8 {
+    let [r, g, b, a, ..] = rng.next_u64().to_le_bytes();
+    RGBA8::new(r, g, b, a)
+}
+
+/// Emits `count` frames of uniform noise on "out", from a PRNG seeded with `seed`
+///
+/// Integer kinds cover their whole range and F32 kinds cover `[0, 1)`. `shape` is `len` for 1D
+/// kinds and `rows,cols` for 2D kinds, and is not used by scalar kinds. The same seed gives the
+/// same frames on every run and platform.
+///
+/// The `seed` message reseeds the PRNG, so the frames after it are the first the new seed gives.
+#[node_decl]
+pub struct NoiseSource {
+    kind:     FrameKind,
+    shape:    (usize, usize),
+    rng:      Prng,
+    count:    usize,
+    emitted:  usize,
+    buf_size: usize,
+}
+
+impl NoiseSource {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let seed = params.get("seed").map_or(0, |v| v.parse().unwrap());
+        let dims: Vec<usize> = params.get("shape").map_or_else(Vec::new, |v| {
+            v.split(',').map(|d| d.trim().parse().unwrap()).collect()
+        });
+        let shape = match (kind, dims.as_slice()) {
+            (FrameKind::U8, []) | (FrameKind::U16, []) | (FrameKind::F32, []) => (0, 0),
+            (FrameKind::U8x1, [len]) | (FrameKind::U16x1, [len]) | (FrameKind::F32x1, [len]) => {
+                (*len, 1)
+            }
+            (FrameKind::U8x2, [rows, cols])
+            | (FrameKind::U16x2, [rows, cols])
+            | (FrameKind::F32x2, [rows, cols])
+            | (FrameKind::RGBA8x2, [rows, cols]) => (*rows, *cols),
+            _ => panic!("Invalid NoiseSource shape {:?} for {:?}", dims, kind),
+        };
+        let count = params.get("count").unwrap().parse().unwrap();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            shape,
+            rng: Prng::new(seed),
+            count,
+            emitted: 0,
+            buf_size,
+        }
+    }
+
+    fn frame(&mut self) -> FrameSingle {
+        let rng = &mut self.rng;
+        let shape = self.shape;
+        let len = shape.0;
+        match self.kind {
+            FrameKind::U8 => FrameSingle::U8(next_u8(rng)),
+            FrameKind::U16 => FrameSingle::U16(next_u16(rng)),
+            FrameKind::F32 => FrameSingle::F32(rng.next_f32()),
+            FrameKind::U8x1 => FrameSingle::U8x1(ArcArray1::from_vec(values(rng, len, next_u8))),
+            FrameKind::U16x1 => FrameSingle::U16x1(ArcArray1::from_vec(values(rng, len, next_u16))),
+            FrameKind::F32x1 => {
+                FrameSingle::F32x1(ArcArray1::from_vec(values(rng, len, Prng::next_f32)))
+            }
+            FrameKind::U8x2 => FrameSingle::U8x2(array2(rng, shape, next_u8)),
+            FrameKind::U16x2 => FrameSingle::U16x2(array2(rng, shape, next_u16)),
+            FrameKind::F32x2 => FrameSingle::F32x2(array2(rng, shape, Prng::next_f32)),
+            FrameKind::RGBA8x2 => FrameSingle::RGBA8x2(array2(rng, shape, next_rgba8)),
+        }
+    }
+}
+
+impl NodeImpl for NoiseSource {
+    fn init(&mut self) {
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.emitted < self.count
+            && self.outbuf_avail("out") > 0
+            && self.budget_remaining() > 0
+        {
+            let frame = self.frame();
+            self.outbuf_put_single("out", frame);
+            self.emitted += 1;
+            self.consume_budget(1);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.emitted >= self.count
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "seed" => self.rng = Prng::new(value.parse()?),
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
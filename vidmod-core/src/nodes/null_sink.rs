@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, NodeImpl, NodePorts, VidmodError};
+
+/// Discards every frame received on "in", for running sources on their own
+///
+/// The `paused` message, when true, leaves frames on "in" so that its producer backs up, e.g. to
+/// test back-pressure.
+#[node_decl]
+pub struct NullSink {
+    kind:     FrameKind,
+    paused:   bool,
+    buf_size: usize,
+}
+
+impl NullSink {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            paused: false,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for NullSink {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        !self.paused && self.inbuf_get_all("in").size() > 0
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "paused" => self.paused = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    dsp::Prng,
+    frame::{
+        ops::{quantize, DitherMode},
+        Frame, FrameKind,
+    },
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// Reduces frames from "in" to the U8 or U16 kind `to` on "out", dithering as it rounds
+///
+/// `dither` is one of none, the default, ordered, floyd_steinberg or tpdf, see `ops::quantize`.
+/// TPDF noise is drawn from a generator seeded with `seed`, continuing from frame to frame.
+///
+/// The `dither` message switches the mode from the next frame on.
+#[node_decl]
+pub struct Quantize {
+    kind:     FrameKind,
+    to:       FrameKind,
+    dither:   DitherMode,
+    rng:      Prng,
+    buf_size: usize,
+}
+
+impl Quantize {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let to = params.get("to").unwrap().as_str().into();
+        let dither = params
+            .get("dither")
+            .map_or(DitherMode::None, |v| v.as_str().into());
+        let seed = params.get("seed").map_or(0, |v| v.parse().unwrap());
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        // Check the kinds and mode go together before any frames arrive
+        quantize(&Frame::with_capacity(kind, 0), to, dither).unwrap();
+        Self {
+            kind,
+            to,
+            dither,
+            rng: Prng::new(seed),
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for Quantize {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pullport("out", self.to, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.outbuf_avail("out"));
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let frame = self.inbuf_get("in", count);
+        let dither = match self.dither {
+            DitherMode::Tpdf(_) => DitherMode::Tpdf(self.rng.next_u64()),
+            dither => dither,
+        };
+        let frame = quantize(&frame, self.to, dither).unwrap();
+        self.outbuf_put("out", frame);
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "dither" => {
+                let dither = DitherMode::parse(value)
+                    .ok_or_else(|| anyhow!("Unknown dither mode {}", value))?;
+                quantize(&Frame::with_capacity(self.kind, 0), self.to, dither)?;
+                self.dither = dither;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, ensure, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    dsp::RateController,
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// Whether a RateConvert lowers the frame rate by dropping frames or raises it by repeating them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateMode {
+    Drop,
+    Repeat,
+}
+
+impl From<&str> for RateMode {
+    fn from(f: &str) -> Self {
+        match f {
+            "drop" => RateMode::Drop,
+            "repeat" => RateMode::Repeat,
+            _ => unimplemented!("RateConvert mode {}", f),
+        }
+    }
+}
+
+/// Converts the frame rate from "in" to "out" by `ratio`, the output over the input rate as `a/b`
+///
+/// In `drop` mode the ratio must be at most 1 and frames are dropped, in `repeat` mode it must be
+/// at least 1 and frames are repeated, spread evenly by a `RateController`. Frames of any kind
+/// pass through unchanged. After `n` input frames exactly `floor(n * a / b)` have been output, so
+/// on finish a last frame that would only complete part of an output frame is dropped, and a
+/// last frame due to be repeated is output every time.
+///
+/// The `ratio` message changes the ratio from the next input frame on.
+#[node_decl]
+pub struct RateConvert {
+    kind:       FrameKind,
+    mode:       RateMode,
+    controller: RateController,
+    pending:    Option<(FrameSingle, usize)>,
+    buf_size:   usize,
+}
+
+impl RateConvert {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let mode = params.get("mode").unwrap().as_str().into();
+        let controller =
+            controller(mode, params.get("ratio").unwrap()).unwrap_or_else(|e| panic!("{}", e));
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            mode,
+            controller,
+            pending: None,
+            buf_size,
+        }
+    }
+}
+
+fn controller(mode: RateMode, ratio: &str) -> Result<RateController> {
+    let invalid = || anyhow!("Invalid ratio {}, expected a/b", ratio);
+    let parts = ratio
+        .splitn(2, '/')
+        .map(|v| v.trim().parse().map_err(|_| invalid()))
+        .collect::<Result<Vec<u64>>>()?;
+    let (output, input) = match parts.as_slice() {
+        [output, input] if *output > 0 && *input > 0 => (*output, *input),
+        _ => return Err(invalid()),
+    };
+    match mode {
+        RateMode::Drop => ensure!(output <= input, "Drop ratio {} is above 1", ratio),
+        RateMode::Repeat => ensure!(output >= input, "Repeat ratio {} is below 1", ratio),
+    }
+    Ok(RateController::new(output, input))
+}
+
+impl NodeImpl for RateConvert {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.budget_remaining() > 0 {
+            match self.pending.take() {
+                Some((_, 0)) => {}
+                Some((frame, count)) => {
+                    if self.outbuf_avail("out") == 0 {
+                        self.pending = Some((frame, count));
+                        break;
+                    }
+                    self.outbuf_put_single("out", frame.clone());
+                    self.consume_budget(1);
+                    self.pending = Some((frame, count - 1));
+                }
+                None => match self.inbuf_try_get_single("in") {
+                    Some(frame) => {
+                        let count = self.controller.next_count();
+                        self.pending = Some((frame, count));
+                    }
+                    None => break,
+                },
+            }
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.pending.is_none() && self.inbuf_avail("in") == 0
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "ratio" => self.controller = controller(self.mode, value)?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
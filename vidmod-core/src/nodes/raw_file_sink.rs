@@ -0,0 +1,108 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, params::Params, NodeImpl, NodePorts, VidmodError};
+
+/// Where a RawFileSink writes its bytes, usually a file
+pub trait RawWriter: Write + Debug {}
+
+impl<T: Write + Debug> RawWriter for T {}
+
+/// Writes the raw bytes of every frame received on "in" to `file`
+///
+/// Writes are buffered so many frames are coalesced into each write to the file. The buffer is
+/// flushed on finish, and also after every `flush_every` frames if set. The `flush_every` message
+/// changes that interval, or stops the periodic flushes with `none`.
+#[node_decl]
+pub struct RawFileSink {
+    kind:        FrameKind,
+    writer:      BufWriter<Box<dyn RawWriter>>,
+    flush_every: Option<usize>,
+    unflushed:   usize,
+    buf_size:    usize,
+}
+
+impl RawFileSink {
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let params = Params::from(params);
+        let path = params
+            .output_path("file")
+            .unwrap_or_else(|e| panic!("{}", e));
+        let mut sink = Self::with_writer(
+            params.get("kind").unwrap().into(),
+            Box::new(File::create(path).unwrap()),
+        );
+        sink.flush_every = params.get("flush_every").map(|v| v.parse().unwrap());
+        if let Some(buf_size) = params.get("buf_size") {
+            sink.buf_size = buf_size.parse().unwrap();
+        }
+        sink
+    }
+
+    // Write to any writer rather than a file, e.g. to capture the output in memory
+    #[node_new]
+    pub fn with_writer(kind: FrameKind, writer: Box<dyn RawWriter>) -> Self {
+        Self {
+            kind,
+            writer: BufWriter::new(writer),
+            flush_every: None,
+            unflushed: 0,
+            buf_size: 16,
+        }
+    }
+
+    pub fn set_flush_every(&mut self, frames: Option<usize>) {
+        self.flush_every = frames;
+    }
+}
+
+impl NodeImpl for RawFileSink {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let frame = self.inbuf_get("in", count);
+        frame.write_bytes(&mut self.writer).unwrap();
+        self.unflushed += count;
+        if let Some(every) = self.flush_every {
+            if self.unflushed >= every {
+                self.writer.flush().unwrap();
+                self.unflushed = 0;
+            }
+        }
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        self.writer.flush().unwrap();
+        self.unflushed = 0;
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "flush_every" => {
+                self.flush_every = match value {
+                    "none" => None,
+                    value => Some(value.parse()?),
+                }
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
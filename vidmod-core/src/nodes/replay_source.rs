@@ -0,0 +1,100 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{Frame, FrameKind},
+    params::Params,
+    NodeImpl, NodePorts, VidmodError,
+};
+
+use crate::record;
+
+/// Plays back the transfers recorded in the `.vmrec` file `file` on "out"
+///
+/// Each transfer is put on "out" whole, so frames leave in the batches they were recorded in, and
+/// the buffer is grown to fit the largest. `kind` is needed for an empty recording, and must
+/// match the recording otherwise. Unreadable recordings panic on construction.
+///
+/// The `skip` message drops that many of the transfers not yet played.
+#[node_decl]
+pub struct ReplaySource {
+    kind:      FrameKind,
+    transfers: VecDeque<Frame>,
+    buf_size:  usize,
+}
+
+impl ReplaySource {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let params = Params::from(params);
+        let path = params
+            .resolve_path("file")
+            .unwrap_or_else(|e| panic!("{}", e));
+        let transfers: VecDeque<Frame> = record::read_recording(&path)
+            .unwrap_or_else(|e| panic!("Cannot replay {:?}: {}", path, e))
+            .into_iter()
+            .map(|transfer| transfer.frame)
+            .collect();
+        let kind = match (params.get("kind"), transfers.front()) {
+            (Some(kind), _) => kind.into(),
+            (None, Some(frame)) => frame.into(),
+            (None, None) => panic!(
+                "Recording {:?} is empty, so ReplaySource needs a kind",
+                path
+            ),
+        };
+        for frame in &transfers {
+            let got = FrameKind::from(frame);
+            assert_eq!(
+                got, kind,
+                "Recording {:?} has {:?} frames, expected {:?}",
+                path, got, kind
+            );
+        }
+        let largest = transfers.iter().map(Frame::size).max().unwrap_or(0);
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            transfers,
+            buf_size: usize::max(buf_size, largest),
+        }
+    }
+}
+
+impl NodeImpl for ReplaySource {
+    fn init(&mut self) {
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while let Some(size) = self.transfers.front().map(Frame::size) {
+            if self.outbuf_avail("out") < size || self.budget_remaining() == 0 {
+                break;
+            }
+            let frame = self.transfers.pop_front().unwrap();
+            self.outbuf_put("out", frame);
+            self.consume_budget(size);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.transfers.is_empty()
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "skip" => {
+                let count = usize::min(value.parse()?, self.transfers.len());
+                self.transfers.drain(..count);
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use anyhow::{bail, ensure, Result};
+use ndarray::ArcArray1;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    dsp::Resampler,
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// Resamples blocks of F32x1 audio from "in" at `from_rate` to "out" at `to_rate`
+///
+/// Each input block gives an output block of the samples it completes, and the filter tail is
+/// flushed on finish. `taps` sets the filter length either side of each sample (default 16).
+///
+/// The `to_rate` message flushes the filter tail at the old rate, then carries on at the new rate
+/// from the next block.
+#[node_decl]
+pub struct Resample {
+    from_rate: usize,
+    taps:      usize,
+    resampler: Resampler,
+    pending:   VecDeque<ArcArray1<f32>>,
+    finishing: bool,
+    flushed:   bool,
+    buf_size:  usize,
+}
+
+impl Resample {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let from_rate = params.get("from_rate").unwrap().parse().unwrap();
+        let to_rate = params.get("to_rate").unwrap().parse().unwrap();
+        let taps = params.get("taps").map_or(16, |v| v.parse().unwrap());
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            from_rate,
+            taps,
+            resampler: Resampler::new(from_rate, to_rate, taps),
+            pending: VecDeque::new(),
+            finishing: false,
+            flushed: false,
+            buf_size,
+        }
+    }
+
+    fn queue(&mut self, samples: Vec<f32>) {
+        if !samples.is_empty() {
+            self.pending.push_back(ArcArray1::from(samples));
+        }
+    }
+
+    fn step(&mut self) -> bool {
+        let mut res = false;
+        loop {
+            if !self.pending.is_empty() {
+                if self.outbuf_avail("out") == 0 {
+                    break;
+                }
+                let block = self.pending.pop_front().unwrap();
+                self.outbuf_put_single("out", FrameSingle::F32x1(block));
+            } else if self.inbuf_avail("in") > 0 && self.budget_remaining() > 0 {
+                self.consume_budget(1);
+                let block = self.inbuf_get_single("in").unwrap_f32x1();
+                let mut out = Vec::new();
+                self.resampler.process(&block.to_vec(), &mut out);
+                self.queue(out);
+            } else if self.finishing && !self.flushed {
+                let mut out = Vec::new();
+                self.resampler.flush(&mut out);
+                self.flushed = true;
+                self.queue(out);
+            } else {
+                break;
+            }
+            res = true;
+        }
+        res
+    }
+}
+
+impl NodeImpl for Resample {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::F32x1, self.buf_size);
+        self.register_pullport("out", FrameKind::F32x1, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        self.step()
+    }
+
+    fn finish(&mut self) -> bool {
+        self.finishing = true;
+        self.step();
+        self.flushed && self.pending.is_empty()
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "to_rate" => {
+                let to_rate = value.parse()?;
+                ensure!(to_rate > 0, "Resample needs a to_rate above 0");
+                ensure!(!self.flushed, "Resample has already flushed");
+                let mut out = Vec::new();
+                self.resampler.flush(&mut out);
+                self.queue(out);
+                self.resampler = Resampler::new(self.from_rate, to_rate, self.taps);
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
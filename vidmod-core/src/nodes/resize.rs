@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{ops::ResizeFilter, FrameKind},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// Resizes each 2D frame from "in" to `rows` by `cols` and forwards it to "out"
+///
+/// `filter` is `nearest` or `bilinear` (the default). The `filter` message switches the filter
+/// from the next frame on.
+#[node_decl]
+pub struct Resize {
+    kind:     FrameKind,
+    shape:    (usize, usize),
+    filter:   ResizeFilter,
+    buf_size: usize,
+}
+
+impl Resize {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let rows = params.get("rows").unwrap().parse().unwrap();
+        let cols = params.get("cols").unwrap().parse().unwrap();
+        let filter = params
+            .get("filter")
+            .map_or("bilinear", |v| v.as_str())
+            .into();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            shape: (rows, cols),
+            filter,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for Resize {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.outbuf_avail("out"));
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        if let Some((from, to)) = self.inbuf_shape_changed("in") {
+            println!("Resize input changed from {:?} to {:?}", from, to);
+        }
+        let frame = self.inbuf_get("in", count);
+        let frame = frame
+            .resize(self.shape, self.filter)
+            .unwrap_or_else(|e| panic!("{}", e));
+        self.outbuf_put("out", frame);
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "filter" => {
+                self.filter = ResizeFilter::parse(value)
+                    .ok_or_else(|| anyhow!("Unknown resize filter {}", value))?
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
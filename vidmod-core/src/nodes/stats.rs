@@ -0,0 +1,182 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use anyhow::{bail, Result};
+use serde_json::json;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{Frame, FrameKind},
+    params::Params,
+    stats::{Histogram, OnlineStats},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+// Every numeric frame kind
+const NUMERIC_KINDS: &[FrameKind] = &[
+    FrameKind::U8,
+    FrameKind::U8x1,
+    FrameKind::U8x2,
+    FrameKind::U16,
+    FrameKind::U16x1,
+    FrameKind::U16x2,
+    FrameKind::F32,
+    FrameKind::F32x1,
+    FrameKind::F32x2,
+];
+
+// The range a histogram spans by default, the full range of integer kinds and [0, 1) for floats
+fn default_range(kind: FrameKind) -> (f64, f64) {
+    match kind {
+        FrameKind::U8 | FrameKind::U8x1 | FrameKind::U8x2 => (0.0, 256.0),
+        FrameKind::U16 | FrameKind::U16x1 | FrameKind::U16x2 => (0.0, 65536.0),
+        _ => (0.0, 1.0),
+    }
+}
+
+// Every value in a frame, including each element of array frames
+fn values(frame: &Frame) -> Vec<f64> {
+    match frame {
+        Frame::U8(v) => v.iter().map(|x| *x as f64).collect(),
+        Frame::U8x1(v) => v.iter().flatten().map(|x| *x as f64).collect(),
+        Frame::U8x2(v) => v.iter().flatten().map(|x| *x as f64).collect(),
+        Frame::U16(v) => v.iter().map(|x| *x as f64).collect(),
+        Frame::U16x1(v) => v.iter().flatten().map(|x| *x as f64).collect(),
+        Frame::U16x2(v) => v.iter().flatten().map(|x| *x as f64).collect(),
+        Frame::F32(v) => v.iter().map(|x| *x as f64).collect(),
+        Frame::F32x1(v) => v.iter().flatten().map(|x| *x as f64).collect(),
+        Frame::F32x2(v) => v.iter().flatten().map(|x| *x as f64).collect(),
+        _ => panic!("Stats of non-numeric frame {:?}", frame.kind()),
+    }
+}
+
+/// Accumulates statistics over every value received on "in", of any numeric kind
+///
+/// Array frames contribute each of their elements. The values are also counted into `bins` bins,
+/// 16 by default, spanning `lo` to `hi`, which default to the full range of integer kinds and to
+/// 0 to 1 for F32 kinds. F32 NaNs are counted on their own.
+///
+/// Once the input reaches end of stream a summary is printed, and written as JSON to `file` if
+/// set. The `reset` message discards the values so far.
+#[node_decl]
+pub struct Stats {
+    name:     String,
+    file:     Option<PathBuf>,
+    bins:     usize,
+    lo:       Option<f64>,
+    hi:       Option<f64>,
+    stats:    OnlineStats,
+    hist:     Option<Histogram>,
+    reported: bool,
+    buf_size: usize,
+}
+
+impl Stats {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let bins = params.get("bins").map_or(16, |v| v.parse().unwrap());
+        let lo = params.get("lo").map(|v| v.parse().unwrap());
+        let hi = params.get("hi").map(|v| v.parse().unwrap());
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        let params = Params::from(params);
+        let name = params.node_name().unwrap_or("Stats").to_owned();
+        let file = params.output_path("file").ok();
+        Self {
+            name,
+            file,
+            bins,
+            lo,
+            hi,
+            stats: OnlineStats::new(),
+            hist: None,
+            reported: false,
+            buf_size,
+        }
+    }
+
+    // The histogram can only be sized once the input kind is known
+    fn histogram(&mut self) -> &mut Histogram {
+        if self.hist.is_none() {
+            let (lo, hi) = self.inbuf_kind("in").map_or((0.0, 1.0), default_range);
+            let lo = self.lo.unwrap_or(lo);
+            let hi = self.hi.unwrap_or(hi);
+            self.hist = Some(Histogram::new(lo, hi, self.bins));
+        }
+        self.hist.as_mut().unwrap()
+    }
+
+    fn report(&mut self) {
+        if self.reported || !self.inbuf_eos("in") || self.inbuf_avail("in") > 0 {
+            return;
+        }
+        let fmt = |stat: Option<f64>| stat.map_or("-".to_owned(), |x| x.to_string());
+        println!(
+            "{}: {} values ({} NaN), min {}, mean {}, std dev {}, max {}",
+            self.name,
+            self.stats.count(),
+            self.stats.nan_count(),
+            fmt(self.stats.min()),
+            fmt(self.stats.mean()),
+            fmt(self.stats.std_dev()),
+            fmt(self.stats.max()),
+        );
+        if let Some(file) = self.file.clone() {
+            let hist = self.histogram();
+            let (lo, hi) = hist.range();
+            let histogram = json!({
+                "lo": lo,
+                "hi": hi,
+                "bins": hist.bins(),
+                "underflow": hist.underflow(),
+                "overflow": hist.overflow(),
+            });
+            let report = json!({
+                "count": self.stats.count(),
+                "nan": self.stats.nan_count(),
+                "min": self.stats.min(),
+                "max": self.stats.max(),
+                "mean": self.stats.mean(),
+                "std_dev": self.stats.std_dev(),
+                "histogram": histogram,
+            });
+            fs::write(file, serde_json::to_string_pretty(&report).unwrap()).unwrap();
+        }
+        self.reported = true;
+    }
+}
+
+impl NodeImpl for Stats {
+    fn init(&mut self) {
+        self.register_pushport_any("in", NUMERIC_KINDS, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.budget_remaining());
+        if count > 0 {
+            self.consume_budget(count);
+            let frame = self.inbuf_get("in", count);
+            for x in values(&frame) {
+                self.stats.push(x);
+                self.histogram().push(x);
+            }
+        }
+        self.report();
+        count > 0
+    }
+
+    fn finish(&mut self) -> bool {
+        self.report();
+        true
+    }
+
+    fn on_message(&mut self, key: &str, _value: &str) -> Result<()> {
+        match key {
+            "reset" => {
+                self.stats = OnlineStats::new();
+                self.hist = None;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
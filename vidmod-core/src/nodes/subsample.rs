@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use ndarray::{s, ArcArray2, Array2};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{Frame, FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// How a Subsample reduces each block of samples to one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubsampleMode {
+    Average,
+    Drop,
+}
+
+impl SubsampleMode {
+    fn parse(f: &str) -> Option<Self> {
+        Some(match f {
+            "average" => SubsampleMode::Average,
+            "drop" => SubsampleMode::Drop,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&str> for SubsampleMode {
+    fn from(f: &str) -> Self {
+        SubsampleMode::parse(f).unwrap_or_else(|| unimplemented!("Subsample mode {}", f))
+    }
+}
+
+/// Shrinks each U8x2 plane from "in" by `h_factor` across and `v_factor` down onto "out"
+///
+/// Each block of `v_factor` rows by `h_factor` columns becomes one sample, their rounded mean with
+/// `mode: average`, the default, or the top-left sample with `drop`. Blocks on the bottom and right
+/// edges are cut short when the plane is not a multiple of the factors. Subsampling a chroma plane
+/// by 2 both ways gives 4:2:0. The `mode` message switches the mode from the next frame on.
+#[node_decl]
+pub struct Subsample {
+    factors:  (usize, usize),
+    mode:     SubsampleMode,
+    buf_size: usize,
+}
+
+impl Subsample {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let h_factor = params.get("h_factor").map_or(2, |v| v.parse().unwrap());
+        let v_factor = params.get("v_factor").map_or(2, |v| v.parse().unwrap());
+        assert!(
+            h_factor > 0 && v_factor > 0,
+            "Subsample factors must be at least 1"
+        );
+        let mode = params
+            .get("mode")
+            .map_or(SubsampleMode::Average, |v| v.as_str().into());
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            factors: (v_factor, h_factor),
+            mode,
+            buf_size,
+        }
+    }
+
+    fn subsample(&self, plane: &ArcArray2<u8>) -> Array2<u8> {
+        let (rows, cols) = plane.dim();
+        let (v_factor, h_factor) = self.factors;
+        let shape = (
+            (rows + v_factor - 1) / v_factor,
+            (cols + h_factor - 1) / h_factor,
+        );
+        Array2::from_shape_fn(shape, |(r, c)| {
+            let (top, left) = (r * v_factor, c * h_factor);
+            match self.mode {
+                SubsampleMode::Drop => plane[[top, left]],
+                SubsampleMode::Average => {
+                    let block = plane.slice(s![
+                        top..usize::min(top + v_factor, rows),
+                        left..usize::min(left + h_factor, cols)
+                    ]);
+                    let sum: usize = block.iter().map(|&v| usize::from(v)).sum();
+                    ((sum + block.len() / 2) / block.len()) as u8
+                }
+            }
+        })
+    }
+}
+
+impl NodeImpl for Subsample {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U8x2, self.buf_size);
+        self.register_pullport("out", FrameKind::U8x2, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.outbuf_avail("out"));
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let planes = self.inbuf_get("in", count).unwrap_u8x2();
+        let frame = Frame::from_singles(
+            FrameKind::U8x2,
+            planes
+                .iter()
+                .map(|plane| FrameSingle::from_array2(self.subsample(plane))),
+        )
+        .unwrap();
+        self.outbuf_put("out", frame);
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "mode" => {
+                self.mode = SubsampleMode::parse(value)
+                    .ok_or_else(|| anyhow!("Unknown subsample mode {}", value))?
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, NodeImpl, NodePorts, VidmodError};
+
+/// What a Switch does with a frame whose selector names no output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnInvalid {
+    Error,
+    Drop,
+    Clamp,
+}
+
+impl OnInvalid {
+    fn parse(f: &str) -> Option<Self> {
+        Some(match f {
+            "error" => OnInvalid::Error,
+            "drop" => OnInvalid::Drop,
+            "clamp" => OnInvalid::Clamp,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&str> for OnInvalid {
+    fn from(f: &str) -> Self {
+        OnInvalid::parse(f).unwrap_or_else(|| unimplemented!("Switch on_invalid {}", f))
+    }
+}
+
+/// Routes each frame from "in" to the output "out0".."outN-1" numbered by the U8 from "select"
+///
+/// Frames are taken from "in" and "select" in pairs. A selector of `n` or above panics with
+/// `on_invalid: error`, the default, is discarded with its frame with `drop`, and sends the frame
+/// to the last output with `clamp`. Every output ends once the node finishes. The `on_invalid`
+/// message changes this from the next frame on.
+#[node_decl]
+pub struct Switch {
+    kind:       FrameKind,
+    outputs:    Vec<String>,
+    on_invalid: OnInvalid,
+    buf_size:   usize,
+}
+
+impl Switch {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let count = params.get("n").map_or(2, |v| v.parse().unwrap());
+        assert!(count > 0, "Switch needs at least one output");
+        let outputs = (0..count).map(|i| format!("out{}", i)).collect();
+        let on_invalid = params
+            .get("on_invalid")
+            .map_or(OnInvalid::Error, |v| v.as_str().into());
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            outputs,
+            on_invalid,
+            buf_size,
+        }
+    }
+
+    // The output a selector routes to, or None to drop the frame
+    fn target(&self, select: u8) -> Option<usize> {
+        let count = self.outputs.len();
+        if usize::from(select) < count {
+            return Some(usize::from(select));
+        }
+        match self.on_invalid {
+            OnInvalid::Error => panic!(
+                "Switch selector {} is out of range for {} outputs",
+                select, count
+            ),
+            OnInvalid::Drop => None,
+            OnInvalid::Clamp => Some(count - 1),
+        }
+    }
+}
+
+impl NodeImpl for Switch {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pushport("select", FrameKind::U8, self.buf_size);
+        for name in &self.outputs.clone() {
+            self.register_pullport(name, self.kind, self.buf_size);
+        }
+    }
+
+    fn tick(&mut self) -> bool {
+        // Any frame may go to any output, so only take as many as every output has room for
+        let room = self
+            .outputs
+            .iter()
+            .map(|name| self.outbuf_avail(name))
+            .min()
+            .unwrap();
+        let count = usize::min(self.inbuf_min_avail(&["in", "select"]), room);
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let mut frames = self.inbuf_get_zipped(&["in", "select"], count).unwrap();
+        let selectors = frames.pop().unwrap().unwrap_u8();
+        let mut data = frames.pop().unwrap();
+        for select in selectors.iter() {
+            let frame = data.remove_single().unwrap();
+            if let Some(index) = self.target(*select) {
+                let name = self.outputs[index].clone();
+                self.outbuf_put_single(&name, frame);
+            }
+        }
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        self.inbuf_min_avail(&["in", "select"]) == 0
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "on_invalid" => {
+                self.on_invalid = OnInvalid::parse(value)
+                    .ok_or_else(|| anyhow!("Unknown on_invalid {}", value))?
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
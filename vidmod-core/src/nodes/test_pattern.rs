@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use ndarray::ArcArray2;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle, RGBA8},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// The image a TestPattern draws
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern {
+    Bars,
+    Gradient,
+    Checker,
+    Solid,
+}
+
+impl From<&str> for Pattern {
+    fn from(f: &str) -> Self {
+        match f {
+            "bars" => Pattern::Bars,
+            "gradient" => Pattern::Gradient,
+            "checker" => Pattern::Checker,
+            "solid" => Pattern::Solid,
+            _ => unimplemented!("Test pattern {}", f),
+        }
+    }
+}
+
+// Full-level colour bars, left to right
+const BARS: [[f32; 3]; 8] = [
+    [1.0, 1.0, 1.0],
+    [1.0, 1.0, 0.0],
+    [0.0, 1.0, 1.0],
+    [0.0, 1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0],
+    [0.0, 0.0, 0.0],
+];
+
+// BT.601 luma, which is what the single-channel kinds draw
+fn luma([r, g, b]: [f32; 3]) -> f32 {
+    0.299 * r + 0.587 * g + 0.114 * b
+}
+
+fn to_u8(level: f32) -> u8 {
+    (level * 255.0).round() as u8
+}
+
+/// Emits `frames` copies of a `width` by `height` test image on "out"
+///
+/// `pattern` is one of:
+/// - `bars`: eight vertical colour bars, white, yellow, cyan, green, magenta, red, blue and black
+/// - `gradient`: a ramp from black on the left to white on the right
+/// - `checker`: alternating white and black squares `square` pixels wide, 8 by default, starting
+///   with white in the top left
+/// - `solid`: every pixel at `level`, from 0 for black to 1 for white, 0.5 by default
+///
+/// `kind` is RGBA8x2, the default, or U8x2 or U16x2, which draw the pattern's luma. The `frames`
+/// message changes how many frames are emitted in all.
+#[node_decl]
+pub struct TestPattern {
+    kind:     FrameKind,
+    image:    FrameSingle,
+    frames:   usize,
+    emitted:  usize,
+    buf_size: usize,
+}
+
+impl TestPattern {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let pattern: Pattern = params.get("pattern").unwrap().as_str().into();
+        let width: usize = params.get("width").unwrap().parse().unwrap();
+        let height: usize = params.get("height").unwrap().parse().unwrap();
+        let kind = params
+            .get("kind")
+            .map_or(FrameKind::RGBA8x2, |v| v.as_str().into());
+        let square: usize = params.get("square").map_or(8, |v| v.parse().unwrap());
+        let level: f32 = params.get("level").map_or(0.5, |v| v.parse().unwrap());
+        assert!(square > 0, "TestPattern needs a square of at least 1");
+        let frames = params.get("frames").unwrap().parse().unwrap();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+
+        let colour = |(y, x): (usize, usize)| match pattern {
+            Pattern::Bars => BARS[x * BARS.len() / width],
+            Pattern::Gradient if width > 1 => [x as f32 / (width - 1) as f32; 3],
+            Pattern::Gradient => [0.0; 3],
+            Pattern::Checker if (x / square + y / square) % 2 == 0 => [1.0; 3],
+            Pattern::Checker => [0.0; 3],
+            Pattern::Solid => [level; 3],
+        };
+        let shape = (height, width);
+        let image = match kind {
+            FrameKind::RGBA8x2 => FrameSingle::RGBA8x2(ArcArray2::from_shape_fn(shape, |pos| {
+                let [r, g, b] = colour(pos);
+                RGBA8::new(to_u8(r), to_u8(g), to_u8(b), 255)
+            })),
+            FrameKind::U8x2 => FrameSingle::U8x2(ArcArray2::from_shape_fn(shape, |pos| {
+                to_u8(luma(colour(pos)))
+            })),
+            FrameKind::U16x2 => FrameSingle::U16x2(ArcArray2::from_shape_fn(shape, |pos| {
+                (luma(colour(pos)) * 65535.0).round() as u16
+            })),
+            kind => panic!("TestPattern of kind {:?}", kind),
+        };
+        Self {
+            kind,
+            image,
+            frames,
+            emitted: 0,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for TestPattern {
+    fn init(&mut self) {
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.emitted < self.frames
+            && self.outbuf_avail("out") > 0
+            && self.budget_remaining() > 0
+        {
+            self.outbuf_put_single("out", self.image.clone());
+            self.emitted += 1;
+            self.consume_budget(1);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.emitted >= self.frames
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "frames" => self.frames = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
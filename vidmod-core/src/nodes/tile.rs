@@ -0,0 +1,297 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use anyhow::{bail, ensure, Result};
+use ndarray::ArcArray1;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{ops, FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+fn check_kind(kind: FrameKind) {
+    match kind {
+        FrameKind::U8x2 | FrameKind::U16x2 | FrameKind::F32x2 | FrameKind::RGBA8x2 => (),
+        _ => panic!("Tiles need a 2D kind, got {:?}", kind),
+    }
+}
+
+// Split a frame into its tiles, returning them with the frame's shape
+fn split(frame: FrameSingle, tile_w: usize, tile_h: usize) -> ((usize, usize), Vec<FrameSingle>) {
+    match frame {
+        FrameSingle::U8x2(a) => (
+            a.dim(),
+            ops::tile(&a, tile_w, tile_h)
+                .into_iter()
+                .map(FrameSingle::U8x2)
+                .collect(),
+        ),
+        FrameSingle::U16x2(a) => (
+            a.dim(),
+            ops::tile(&a, tile_w, tile_h)
+                .into_iter()
+                .map(FrameSingle::U16x2)
+                .collect(),
+        ),
+        FrameSingle::F32x2(a) => (
+            a.dim(),
+            ops::tile(&a, tile_w, tile_h)
+                .into_iter()
+                .map(FrameSingle::F32x2)
+                .collect(),
+        ),
+        FrameSingle::RGBA8x2(a) => (
+            a.dim(),
+            ops::tile(&a, tile_w, tile_h)
+                .into_iter()
+                .map(FrameSingle::RGBA8x2)
+                .collect(),
+        ),
+        frame => unimplemented!("Tile for {:?}", frame.kind()),
+    }
+}
+
+fn join(
+    kind: FrameKind,
+    tiles: Vec<FrameSingle>,
+    grid: (usize, usize),
+    shape: (usize, usize),
+) -> Result<FrameSingle, VidmodError> {
+    let (rows, cols) = grid;
+    Ok(match kind {
+        FrameKind::U8x2 => {
+            let tiles: Vec<_> = tiles.into_iter().map(FrameSingle::unwrap_u8x2).collect();
+            FrameSingle::U8x2(ops::untile(&tiles, cols, rows, shape)?)
+        }
+        FrameKind::U16x2 => {
+            let tiles: Vec<_> = tiles.into_iter().map(FrameSingle::unwrap_u16x2).collect();
+            FrameSingle::U16x2(ops::untile(&tiles, cols, rows, shape)?)
+        }
+        FrameKind::F32x2 => {
+            let tiles: Vec<_> = tiles.into_iter().map(FrameSingle::unwrap_f32x2).collect();
+            FrameSingle::F32x2(ops::untile(&tiles, cols, rows, shape)?)
+        }
+        FrameKind::RGBA8x2 => {
+            let tiles: Vec<_> = tiles.into_iter().map(FrameSingle::unwrap_rgba8x2).collect();
+            FrameSingle::RGBA8x2(ops::untile(&tiles, cols, rows, shape)?)
+        }
+        kind => unimplemented!("Untile for {:?}", kind),
+    })
+}
+
+// Layouts are sent as U16x1 frames of [height, width, tile height, tile width]
+fn layout(shape: (usize, usize), tile_h: usize, tile_w: usize) -> FrameSingle {
+    let dims = [shape.0, shape.1, tile_h, tile_w];
+    let dims = dims.iter().map(|&d| {
+        assert!(
+            d <= u16::MAX as usize,
+            "Tile layout {:?} is too large",
+            dims
+        );
+        d as u16
+    });
+    FrameSingle::U16x1(ArcArray1::from_iter(dims))
+}
+
+/// Splits each 2D frame from "in" into tiles of `tile_h` rows by `tile_w` columns on "out"
+///
+/// Tiles leave in row-major order, cut short on the bottom and right edges. Before each frame's
+/// tiles, its layout is put on "layout" as a U16x1 of its height, width, and the tile height and
+/// width, which `core::Untile` needs to reassemble it.
+///
+/// The `tile_w` and `tile_h` messages change the tile size from the next frame on.
+#[node_decl]
+pub struct Tile {
+    kind:     FrameKind,
+    tile_w:   usize,
+    tile_h:   usize,
+    pending:  VecDeque<FrameSingle>,
+    buf_size: usize,
+}
+
+impl Tile {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        check_kind(kind);
+        let tile_w = params.get("tile_w").unwrap().parse().unwrap();
+        let tile_h = params.get("tile_h").unwrap().parse().unwrap();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            tile_w,
+            tile_h,
+            pending: VecDeque::new(),
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for Tile {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+        self.register_pullport("layout", FrameKind::U16x1, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.budget_remaining() > 0 {
+            if let Some(tile) = self.pending.pop_front() {
+                if self.outbuf_avail("out") == 0 {
+                    self.pending.push_front(tile);
+                    break;
+                }
+                self.outbuf_put_single("out", tile);
+            } else if self.inbuf_avail("in") > 0 && self.outbuf_avail("layout") > 0 {
+                let frame = self.inbuf_get_single("in");
+                let (shape, tiles) = split(frame, self.tile_w, self.tile_h);
+                self.outbuf_put_single("layout", layout(shape, self.tile_h, self.tile_w));
+                self.pending = tiles.into();
+                self.consume_budget(1);
+            } else {
+                break;
+            }
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.pending.is_empty() && self.inbuf_avail("in") == 0
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "tile_w" | "tile_h" => {
+                let size = value.parse()?;
+                ensure!(size > 0, "Tiles need a {} of at least 1", key);
+                match key {
+                    "tile_w" => self.tile_w = size,
+                    _ => self.tile_h = size,
+                }
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
+
+/// Reassembles the tiles on "in" into 2D frames on "out", as described by "layout"
+///
+/// Tiles must arrive strictly in the order `core::Tile` sends them: each frame's tiles, in
+/// row-major order, one frame after another. Each layout says how many tiles make its frame, so a
+/// tile of the wrong size, or tiles left over once the layouts run out, panic.
+///
+/// The `skip` message drops that many of the next frames once their tiles are in, rather than
+/// putting them on "out".
+#[node_decl]
+pub struct Untile {
+    kind:      FrameKind,
+    // The frame being reassembled: its shape, its grid of tiles, and the tiles so far
+    shape:     Option<((usize, usize), (usize, usize))>,
+    collected: Vec<FrameSingle>,
+    skip:      usize,
+    buf_size:  usize,
+}
+
+impl Untile {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        check_kind(kind);
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            kind,
+            shape: None,
+            collected: Vec::new(),
+            skip: 0,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for Untile {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pushport("layout", FrameKind::U16x1, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.budget_remaining() > 0 {
+            let (shape, (rows, cols)) = match self.shape {
+                Some(shape) => shape,
+                None if self.inbuf_avail("layout") > 0 => {
+                    let dims = self.inbuf_get_single("layout").unwrap_u16x1();
+                    let dims: Vec<usize> = dims.iter().map(|&d| d as usize).collect();
+                    let (shape, tile) = ((dims[0], dims[1]), (dims[2], dims[3]));
+                    let grid = (
+                        (shape.0 + tile.0 - 1) / tile.0,
+                        (shape.1 + tile.1 - 1) / tile.1,
+                    );
+                    self.shape = Some((shape, grid));
+                    res = true;
+                    continue;
+                }
+                None => break,
+            };
+            if self.collected.len() < rows * cols {
+                match self.inbuf_try_get_single("in") {
+                    Some(tile) => self.collected.push(tile),
+                    None => break,
+                }
+            } else if self.skip > 0 {
+                self.collected.clear();
+                self.shape = None;
+                self.skip -= 1;
+                self.consume_budget(1);
+            } else if self.outbuf_avail("out") > 0 {
+                let tiles = std::mem::take(&mut self.collected);
+                let frame = join(self.kind, tiles, (rows, cols), shape)
+                    .unwrap_or_else(|e| panic!("Cannot untile: {}", e));
+                self.outbuf_put_single("out", frame);
+                self.shape = None;
+                self.consume_budget(1);
+            } else {
+                break;
+            }
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        let layouts_done = self.inbuf_eos("layout") && self.inbuf_avail("layout") == 0;
+        if layouts_done && self.shape.is_none() && self.inbuf_avail("in") > 0 {
+            panic!(
+                "Untile got {} tiles more than its layouts describe",
+                self.inbuf_avail("in")
+            );
+        }
+        if self.inbuf_eos("in") && self.inbuf_avail("in") == 0 {
+            if let Some((_, (rows, cols))) = self.shape {
+                if self.collected.len() < rows * cols {
+                    panic!(
+                        "Untile got {} of the {} tiles of a frame",
+                        self.collected.len(),
+                        rows * cols
+                    );
+                }
+            }
+        }
+        self.shape.is_none() && self.inbuf_avail("layout") == 0
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "skip" => self.skip = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
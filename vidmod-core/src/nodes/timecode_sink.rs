@@ -0,0 +1,75 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use anyhow::{bail, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, params::Params, NodeImpl, NodePorts, VidmodError};
+
+/// Logs the ASCII timecodes received on "in", one per line, to stdout or to `file` if set
+///
+/// The `flush` message writes out any timecodes still buffered for `file` straight away.
+#[node_decl]
+pub struct TimecodeSink {
+    name:   String,
+    writer: Option<BufWriter<File>>,
+}
+
+impl TimecodeSink {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let params = Params::from(params);
+        let name = params.node_name().unwrap_or("TimecodeSink").to_owned();
+        let writer = params
+            .output_path("file")
+            .ok()
+            .map(|path| BufWriter::new(File::create(path).unwrap()));
+        Self { name, writer }
+    }
+}
+
+impl NodeImpl for TimecodeSink {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U8x1, 16);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = usize::min(self.inbuf_avail("in"), self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let frame = self.inbuf_get("in", count).unwrap_u8x1();
+        for timecode in frame.iter() {
+            let timecode = String::from_utf8_lossy(timecode.as_slice().unwrap()).into_owned();
+            match &mut self.writer {
+                Some(writer) => writeln!(writer, "{}", timecode).unwrap(),
+                None => println!("{}: {}", self.name, timecode),
+            }
+        }
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        if let Some(writer) = &mut self.writer {
+            writer.flush().unwrap();
+        }
+        true
+    }
+
+    fn on_message(&mut self, key: &str, _value: &str) -> Result<()> {
+        match key {
+            "flush" => {
+                if let Some(writer) = &mut self.writer {
+                    writer.flush()?;
+                }
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use ndarray::ArcArray1;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, SeekOutcome, VidmodError,
+};
+
+use crate::timecode::{Timecode, TimecodeRate};
+
+/// Emits `count` SMPTE timecodes on "out" at `fps`, starting from `start`
+///
+/// Each frame is a U8x1 holding the ASCII timecode, e.g. "00:00:01:00". Fractional rates such as
+/// 29.97 use drop-frame timecode unless `drop_frame` is set to false. The `count` message changes
+/// how many timecodes are emitted in all.
+#[node_decl]
+pub struct TimecodeSource {
+    rate:     TimecodeRate,
+    start:    u64,
+    count:    usize,
+    emitted:  usize,
+    buf_size: usize,
+}
+
+impl TimecodeSource {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let drop_frame = params.get("drop_frame").map(|v| v.parse().unwrap());
+        let rate = TimecodeRate::parse(params.get("fps").unwrap(), drop_frame).unwrap();
+        let start: Timecode = params
+            .get("start")
+            .map_or("00:00:00:00", |v| v.as_str())
+            .parse()
+            .unwrap();
+        start.validate(rate).unwrap();
+        let count = params.get("count").unwrap().parse().unwrap();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self {
+            rate,
+            start: start.to_frame_number(rate),
+            count,
+            emitted: 0,
+            buf_size,
+        }
+    }
+
+    fn value(&self) -> FrameSingle {
+        let timecode = Timecode::from_frame_number(self.start + self.emitted as u64, self.rate);
+        FrameSingle::U8x1(ArcArray1::from(timecode.to_string().into_bytes()))
+    }
+}
+
+impl NodeImpl for TimecodeSource {
+    fn init(&mut self) {
+        self.register_pullport("out", FrameKind::U8x1, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.emitted < self.count
+            && self.outbuf_avail("out") > 0
+            && self.budget_remaining() > 0
+        {
+            self.outbuf_put_single("out", self.value());
+            self.emitted += 1;
+            self.consume_budget(1);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        self.emitted >= self.count
+    }
+
+    fn seek(&mut self, position: u64) -> Result<SeekOutcome> {
+        self.emitted = usize::min(position as usize, self.count);
+        Ok(SeekOutcome {
+            position: self.emitted as u64,
+        })
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "count" => self.count = value.parse()?,
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Result};
+use ndarray::{ArcArray2, Axis};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+/// A change of orientation for a 2D frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    Transpose,
+    /// Rotate a quarter turn clockwise
+    Rot90,
+    Rot180,
+    /// Mirror left to right
+    FlipH,
+    /// Mirror top to bottom
+    FlipV,
+}
+
+impl From<&str> for Transform {
+    fn from(f: &str) -> Self {
+        Transform::parse(f).unwrap_or_else(|| unimplemented!("Transform {}", f))
+    }
+}
+
+impl Transform {
+    fn parse(f: &str) -> Option<Self> {
+        Some(match f {
+            "transpose" => Transform::Transpose,
+            "rot90" => Transform::Rot90,
+            "rot180" => Transform::Rot180,
+            "flip_h" => Transform::FlipH,
+            "flip_v" => Transform::FlipV,
+            _ => return None,
+        })
+    }
+
+    // Only the strides change, so no elements are copied
+    pub fn apply<T>(self, mut a: ArcArray2<T>) -> ArcArray2<T> {
+        match self {
+            Transform::Transpose => a.reversed_axes(),
+            Transform::Rot90 => {
+                let mut a = a.reversed_axes();
+                a.invert_axis(Axis(1));
+                a
+            }
+            Transform::Rot180 => {
+                a.invert_axis(Axis(0));
+                a.invert_axis(Axis(1));
+                a
+            }
+            Transform::FlipH => {
+                a.invert_axis(Axis(1));
+                a
+            }
+            Transform::FlipV => {
+                a.invert_axis(Axis(0));
+                a
+            }
+        }
+    }
+}
+
+/// Transposes, rotates or flips each 2D frame from "in" into "out"
+///
+/// The `op` message switches the operation from the next frame on.
+#[node_decl]
+pub struct Transform2D {
+    kind:     FrameKind,
+    op:       Transform,
+    buf_size: usize,
+}
+
+impl Transform2D {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        match kind {
+            FrameKind::U8x2 | FrameKind::U16x2 | FrameKind::F32x2 | FrameKind::RGBA8x2 => (),
+            _ => panic!("Transform2D needs a 2D kind, got {:?}", kind),
+        }
+        let op = params.get("op").unwrap().as_str().into();
+        let buf_size = params.get("buf_size").map_or(16, |v| v.parse().unwrap());
+        Self { kind, op, buf_size }
+    }
+
+    fn apply(&self, frame: FrameSingle) -> FrameSingle {
+        let op = self.op;
+        match frame {
+            FrameSingle::U8x2(a) => FrameSingle::U8x2(op.apply(a)),
+            FrameSingle::U16x2(a) => FrameSingle::U16x2(op.apply(a)),
+            FrameSingle::F32x2(a) => FrameSingle::F32x2(op.apply(a)),
+            FrameSingle::RGBA8x2(a) => FrameSingle::RGBA8x2(op.apply(a)),
+            frame => unimplemented!("Transform2D for {:?}", FrameKind::from(&frame)),
+        }
+    }
+}
+
+impl NodeImpl for Transform2D {
+    fn init(&mut self) {
+        self.register_pushport("in", self.kind, self.buf_size);
+        self.register_pullport("out", self.kind, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.inbuf_avail("in") > 0
+            && self.outbuf_avail("out") > 0
+            && self.budget_remaining() > 0
+        {
+            let frame = self.inbuf_get_single("in");
+            self.outbuf_put_single("out", self.apply(frame));
+            self.consume_budget(1);
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "op" => {
+                self.op =
+                    Transform::parse(value).ok_or_else(|| anyhow!("Unknown transform {}", value))?
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, ensure, Result};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, NodeImpl, NodePorts, VidmodError};
+
+/// Interleaves frames from "in0".."inN-1" into "out", one from each input in turn
+///
+/// The input count is read from `n`, falling back to its older name `inputs`.
+///
+/// The `first` message picks the input each round starts from, so after `first: 1` the order is
+/// "in1".."inN-1" then "in0".
+#[node_decl]
+pub struct Zip {
+    kind:   FrameKind,
+    inputs: Vec<String>,
+    first:  usize,
+}
+
+impl Zip {
+    #[node_new]
+    pub fn new(params: BTreeMap<String, String>) -> Self {
+        let kind = params.get("kind").unwrap().as_str().into();
+        let count = params
+            .get("n")
+            .or_else(|| params.get("inputs"))
+            .map_or(2, |v| v.parse().unwrap());
+        let inputs = (0..count).map(|i| format!("in{}", i)).collect();
+        Self {
+            kind,
+            inputs,
+            first: 0,
+        }
+    }
+}
+
+impl NodeImpl for Zip {
+    fn init(&mut self) {
+        for name in &self.inputs.clone() {
+            self.register_pushport(name, self.kind, 1);
+        }
+        self.register_pullport("out", self.kind, self.inputs.len());
+    }
+
+    fn tick(&mut self) -> bool {
+        let inputs = self.inputs.clone();
+        let names: Vec<&str> = inputs.iter().map(String::as_str).collect();
+        let count = usize::min(
+            self.inbuf_min_avail(&names),
+            self.outbuf_avail("out") / names.len(),
+        );
+        let count = usize::min(count, self.budget_remaining());
+        if count == 0 {
+            return false;
+        }
+        self.consume_budget(count);
+        let mut frames = self.inbuf_get_zipped(&names, count).unwrap();
+        for _ in 0..count {
+            for i in 0..frames.len() {
+                let frame = &mut frames[(self.first + i) % names.len()];
+                self.outbuf_put_single("out", frame.remove_single().unwrap());
+            }
+        }
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "first" => {
+                let first = value.parse()?;
+                ensure!(first < self.inputs.len(), "Zip has no input {}", first);
+                self.first = first;
+            }
+            _ => bail!(VidmodError::MessageUnsupported {
+                key: key.to_owned(),
+            }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+//! Recording the frames moved along a link, to replay them with `core::ReplaySource`
+//!
+//! A `.vmrec` file is the magic bytes `VMREC` and the format version as a little-endian u32,
+//! followed by each transfer in the order it happened:
+//! - the frame kind, as a u8 index into `KINDS`
+//! - the time since recording started in nanoseconds, as a little-endian u64
+//! - the number of elements, as a little-endian u64
+//! - the shape of each element as little-endian u64s: nothing for scalar kinds, the length for
+//!   1D kinds, and rows then columns for 2D kinds
+//! - the values of every element, as written by `Frame::write_bytes`
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use ndarray::{ArcArray1, ArcArray2};
+use vidmod_node::{
+    frame::{Frame, FrameKind, RGBA8},
+    limvecdeque::LimVecDeque,
+};
+
+use crate::tap::{FrameTap, LinkId};
+
+/// The version of the `.vmrec` format written by this build, the only one it can read
+pub const VERSION: u32 = 1;
+
+const MAGIC: &[u8] = b"VMREC";
+
+const KINDS: [FrameKind; 10] = [
+    FrameKind::U8,
+    FrameKind::U8x1,
+    FrameKind::U8x2,
+    FrameKind::U16,
+    FrameKind::U16x1,
+    FrameKind::U16x2,
+    FrameKind::F32,
+    FrameKind::F32x1,
+    FrameKind::F32x2,
+    FrameKind::RGBA8x2,
+];
+
+/// One recorded transfer along a link
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub elapsed: Duration,
+    pub frame:   Frame,
+}
+
+/// Writes every transfer along a link to a `.vmrec` file
+///
+/// The first write error stops the recording rather than the run, and is returned by `finish`.
+#[derive(Debug)]
+pub struct RecordTap {
+    writer: BufWriter<File>,
+    start:  Instant,
+    error:  Option<io::Error>,
+}
+
+impl RecordTap {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+            error: None,
+        })
+    }
+}
+
+impl FrameTap for RecordTap {
+    fn on_transfer(&mut self, _link: &LinkId, frame: &Frame) {
+        if self.error.is_some() {
+            return;
+        }
+        let elapsed = self.start.elapsed();
+        if let Err(e) = write_transfer(&mut self.writer, elapsed, frame) {
+            self.error = Some(e);
+        }
+    }
+
+    // Flush the recording to the file, returning the first error, which dropping the tap only
+    // prints
+    fn finish(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.writer.flush(),
+        }
+    }
+}
+
+impl Drop for RecordTap {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            eprintln!("Cannot write recording: {}", e);
+        }
+    }
+}
+
+fn write_transfer<W: Write>(w: &mut W, elapsed: Duration, frame: &Frame) -> io::Result<()> {
+    let kind = FrameKind::from(frame);
+    let index = KINDS.iter().position(|k| *k == kind).unwrap();
+    w.write_all(&[index as u8])?;
+    w.write_all(&(elapsed.as_nanos() as u64).to_le_bytes())?;
+    w.write_all(&(frame.size() as u64).to_le_bytes())?;
+    for shape in frame.shapes() {
+        for dim in shape {
+            w.write_all(&(dim as u64).to_le_bytes())?;
+        }
+    }
+    frame.write_bytes(w)
+}
+
+/// Read every transfer from a `.vmrec` file, in the order they were recorded
+pub fn read_recording<P: AsRef<Path>>(path: P) -> Result<Vec<Transfer>> {
+    let mut r = BufReader::new(File::open(path)?);
+    let mut magic = [0; 5];
+    if r.read_exact(&mut magic).is_err() || magic != MAGIC {
+        bail!("Not a .vmrec file");
+    }
+    let version = read_u32(&mut r)?;
+    if version != VERSION {
+        bail!(
+            "Unsupported .vmrec version {}, expected version {}",
+            version,
+            VERSION
+        );
+    }
+    let mut transfers = Vec::new();
+    let mut index = [0];
+    while r.read(&mut index)? == 1 {
+        let kind = match KINDS.get(index[0] as usize) {
+            Some(kind) => *kind,
+            None => bail!(
+                "Invalid frame kind {} in transfer {}",
+                index[0],
+                transfers.len()
+            ),
+        };
+        let elapsed = Duration::from_nanos(read_u64(&mut r)?);
+        let count = read_u64(&mut r)? as usize;
+        let frame = read_frame(&mut r, kind, count)?;
+        transfers.push(Transfer { elapsed, frame });
+    }
+    Ok(transfers)
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_values<R: Read, T, F: Fn(&[u8]) -> T>(
+    r: &mut R,
+    len: usize,
+    width: usize,
+    decode: F,
+) -> io::Result<Vec<T>> {
+    let mut buf = vec![0; len * width];
+    r.read_exact(&mut buf)?;
+    Ok(buf.chunks(width).map(decode).collect())
+}
+
+fn read_shapes<R: Read>(r: &mut R, count: usize, dims: usize) -> io::Result<Vec<Vec<usize>>> {
+    (0..count)
+        .map(|_| (0..dims).map(|_| read_u64(r).map(|d| d as usize)).collect())
+        .collect()
+}
+
+fn read_arrays1<R: Read, T, F: Fn(&[u8]) -> T + Copy>(
+    r: &mut R,
+    count: usize,
+    width: usize,
+    decode: F,
+) -> Result<LimVecDeque<ArcArray1<T>>> {
+    let shapes = read_shapes(r, count, 1)?;
+    let mut arrays = Vec::with_capacity(count);
+    for shape in shapes {
+        arrays.push(ArcArray1::from_vec(read_values(
+            r, shape[0], width, decode,
+        )?));
+    }
+    Ok(LimVecDeque::from(arrays))
+}
+
+fn read_arrays2<R: Read, T, F: Fn(&[u8]) -> T + Copy>(
+    r: &mut R,
+    count: usize,
+    width: usize,
+    decode: F,
+) -> Result<LimVecDeque<ArcArray2<T>>> {
+    let shapes = read_shapes(r, count, 2)?;
+    let mut arrays = Vec::with_capacity(count);
+    for shape in shapes {
+        let values = read_values(r, shape[0] * shape[1], width, decode)?;
+        arrays.push(ArcArray2::from_shape_vec((shape[0], shape[1]), values)?);
+    }
+    Ok(LimVecDeque::from(arrays))
+}
+
+fn read_frame<R: Read>(r: &mut R, kind: FrameKind, count: usize) -> Result<Frame> {
+    let u8_le = |b: &[u8]| b[0];
+    let u16_le = |b: &[u8]| u16::from_le_bytes([b[0], b[1]]);
+    let f32_le = |b: &[u8]| f32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+    let rgba8 = |b: &[u8]| RGBA8::from_bytes([b[0], b[1], b[2], b[3]]);
+    Ok(match kind {
+        FrameKind::U8 => Frame::U8(read_values(r, count, 1, u8_le)?.into()),
+        FrameKind::U8x1 => Frame::U8x1(read_arrays1(r, count, 1, u8_le)?),
+        FrameKind::U8x2 => Frame::U8x2(read_arrays2(r, count, 1, u8_le)?),
+        FrameKind::U16 => Frame::U16(read_values(r, count, 2, u16_le)?.into()),
+        FrameKind::U16x1 => Frame::U16x1(read_arrays1(r, count, 2, u16_le)?),
+        FrameKind::U16x2 => Frame::U16x2(read_arrays2(r, count, 2, u16_le)?),
+        FrameKind::F32 => Frame::F32(read_values(r, count, 4, f32_le)?.into()),
+        FrameKind::F32x1 => Frame::F32x1(read_arrays1(r, count, 4, f32_le)?),
+        FrameKind::F32x2 => Frame::F32x2(read_arrays2(r, count, 4, f32_le)?),
+        FrameKind::RGBA8x2 => Frame::RGBA8x2(read_arrays2(r, count, 4, rgba8)?),
+    })
+}
@@ -0,0 +1,92 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    spec::Project,
+    tap::{LinkHash, LinkId},
+};
+
+/// A summary of a finished run, for writing out as JSON
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub nodes:            usize,
+    pub links:            usize,
+    pub elapsed_ms:       u128,
+    /// Keyed by `from_node.port->to_node.port`, empty unless links were hashed
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub link_hashes:      BTreeMap<String, LinkHashReport>,
+    /// Ticks that ended with the node's quota used up, keyed by node name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub budget_exhausted: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkHashReport {
+    pub frames: usize,
+    pub hash:   String,
+}
+
+impl RunReport {
+    pub fn new(project: &Project, elapsed: Duration, hashes: &BTreeMap<LinkId, LinkHash>) -> Self {
+        let link_hashes = hashes
+            .iter()
+            .map(|(link, hash)| {
+                (
+                    format!(
+                        "{}.{}->{}.{}",
+                        link.from.0, link.from.1, link.to.0, link.to.1
+                    ),
+                    LinkHashReport {
+                        frames: hash.frames,
+                        hash:   format!("{:016x}", hash.hash),
+                    },
+                )
+            })
+            .collect();
+        Self {
+            nodes: project.node_count(),
+            links: project.link_ids().len(),
+            elapsed_ms: elapsed.as_millis(),
+            link_hashes,
+            budget_exhausted: project.budget_exhaustions(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+// The part of a report compared by `compare_reports`
+#[derive(Deserialize)]
+struct HashSection {
+    #[serde(default)]
+    link_hashes: BTreeMap<String, LinkHashReport>,
+}
+
+/// Compare the link hashes of two JSON reports, describing each link that diverged
+///
+/// A link diverges if its hash or frame count differ, or if only one report has it. The result
+/// is sorted by link and empty if the reports agree.
+pub fn compare_reports(a: &str, b: &str) -> serde_json::Result<Vec<String>> {
+    let a: HashSection = serde_json::from_str(a)?;
+    let b: HashSection = serde_json::from_str(b)?;
+    let mut links: Vec<_> = a.link_hashes.keys().chain(b.link_hashes.keys()).collect();
+    links.sort();
+    links.dedup();
+    Ok(links
+        .into_iter()
+        .filter_map(
+            |link| match (a.link_hashes.get(link), b.link_hashes.get(link)) {
+                (Some(x), Some(y)) if x == y => None,
+                (Some(x), Some(y)) => Some(format!(
+                    "{}: {} ({} frames) != {} ({} frames)",
+                    link, x.hash, x.frames, y.hash, y.frames
+                )),
+                (Some(_), None) => Some(format!("{}: only in first report", link)),
+                (None, _) => Some(format!("{}: only in second report", link)),
+            },
+        )
+        .collect())
+}
@@ -1,17 +1,70 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fmt};
 
+use anyhow::{bail, Result};
 use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use vidmod_node::{params::check_user_arg, PortDirection, VidmodError};
+use vidmod_plugin::{ArgSpec, ArgType, PluginRegistry};
 
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ProjectManifest {
-    pub nodes: BTreeMap<String, ManifestNode>,
-    pub links: Vec<ManifestLink>,
+    pub nodes:           BTreeMap<String, ManifestNode>,
+    pub links:           Vec<ManifestLink>,
+    #[serde(default)]
+    pub groups:          BTreeMap<String, ManifestGroup>,
+    #[serde(default)]
+    pub start_frame:     Option<u64>,
+    #[serde(default)]
+    pub max_frames:      Option<u64>,
+    /// Default per-tick element quota for every node, overridden by `vidmod.tick_quota`
+    #[serde(default)]
+    pub tick_quota:      Option<usize>,
+    /// Values substituted for `${name}` in node and group args
+    #[serde(default)]
+    pub vars:            BTreeMap<String, String>,
+    /// Stop the run once frames in port buffers hold more bytes, see `NodeGraph::set_max_frame_bytes`
+    #[serde(default)]
+    pub max_frame_bytes: Option<usize>,
+    /// Fill the sources' buffers before ticking anything else, see `NodeGraph::set_priming`
+    #[serde(default)]
+    pub prime:           bool,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ManifestNode {
+    #[serde(default)]
+    pub name:  Option<String>,
+    #[serde(default)]
+    pub group: Option<String>,
+    /// A node built by the runtime rather than a plugin
+    #[serde(default, rename = "type")]
+    pub kind:  Option<NodeType>,
+    #[serde(default)]
+    pub args:  BTreeMap<String, String>,
+}
+
+/// The nodes the runtime builds itself, named by a node's `type`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeType {
+    /// Runs the graph of another manifest over each frame, see `nodes::Iterate`
+    Iterate,
+}
+
+impl fmt::Display for NodeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Iterate => write!(f, "iterate"),
+        }
+    }
+}
+
+/// A plugin and base arguments shared by every node in the group
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestGroup {
     pub name: String,
     #[serde(default)]
     pub args: BTreeMap<String, String>,
@@ -22,4 +75,319 @@ pub struct ManifestNode {
 pub struct ManifestLink {
     pub from: (String, String),
     pub to:   (String, String),
+    /// Only move the frames the consumer requests, see `NodeGraph::set_lazy`
+    #[serde(default)]
+    pub lazy: bool,
+}
+
+impl fmt::Display for ManifestLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{} -> {}.{}",
+            self.from.0, self.from.1, self.to.0, self.to.1
+        )
+    }
+}
+
+impl ManifestLink {
+    /// Describe why the link could not be made, in terms of the manifest's nodes and ports
+    pub fn explain(&self, error: &VidmodError) -> String {
+        let (from, to) = (&self.from.0, &self.to.0);
+        let reason = match error {
+            VidmodError::PortNotFound {
+                port,
+                direction: Some(PortDirection::Pull),
+                ..
+            } => format!("{} has no output {}", from, port),
+            VidmodError::PortNotFound {
+                port,
+                direction: Some(PortDirection::Push),
+                ..
+            } => format!("{} has no input {}", to, port),
+            // The expected kind is that of the port being attached to, on the producer or consumer
+            VidmodError::KindMismatch {
+                direction: Some(PortDirection::Pull),
+                expected,
+                got,
+                ..
+            } => {
+                format!(
+                    "{} produces {:?} but {} takes {:?}",
+                    from, expected, to, got
+                )
+            }
+            VidmodError::KindMismatch { expected, got, .. } => {
+                format!(
+                    "{} produces {:?} but {} takes {:?}",
+                    from, got, to, expected
+                )
+            }
+            VidmodError::KindNotAccepted { accepted, got } => {
+                format!("{} takes one of {:?}, not {:?}", to, accepted, got)
+            }
+            error => error.to_string(),
+        };
+        format!("Cannot link {}: {}", self, reason)
+    }
+}
+
+/// A node's plugin and arguments once its group has been merged in
+///
+/// A node with a `type` is named after it, having no plugin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedNode {
+    pub name: String,
+    pub args: BTreeMap<String, String>,
+    pub kind: Option<NodeType>,
+}
+
+impl ProjectManifest {
+    // Merge `overlay` over this manifest. Its nodes and groups replace any of the same name, its
+    // links replace any into the same port, and any settings it makes replace these
+    pub fn merge(&mut self, overlay: ProjectManifest) {
+        self.nodes.extend(overlay.nodes);
+        self.groups.extend(overlay.groups);
+        self.vars.extend(overlay.vars);
+        let links = &overlay.links;
+        self.links
+            .retain(|link| links.iter().all(|other| other.to != link.to));
+        self.links.extend(overlay.links);
+        self.start_frame = overlay.start_frame.or(self.start_frame);
+        self.max_frames = overlay.max_frames.or(self.max_frames);
+        self.tick_quota = overlay.tick_quota.or(self.tick_quota);
+        self.max_frame_bytes = overlay.max_frame_bytes.or(self.max_frame_bytes);
+        self.prime |= overlay.prime;
+    }
+
+    // Merge each node's group into it, instance args overriding the group's, then substitute vars
+    pub fn resolve_nodes(
+        &mut self,
+        registry: &PluginRegistry,
+    ) -> Result<BTreeMap<String, ResolvedNode>> {
+        for (name, group) in &self.groups {
+            if registry.get(&group.name).is_none() {
+                bail!("Group {} uses unknown plugin {}", name, group.name);
+            }
+        }
+        let mut res = BTreeMap::new();
+        for (name, node) in std::mem::take(&mut self.nodes) {
+            if let Some(kind) = node.kind {
+                if node.name.is_some() || node.group.is_some() {
+                    bail!("Node {} sets a type and a plugin or group", name);
+                }
+                let resolved = ResolvedNode {
+                    name: kind.to_string(),
+                    args: node.args,
+                    kind: Some(kind),
+                };
+                res.insert(name, resolved);
+                continue;
+            }
+            let resolved = match (node.name, node.group) {
+                (Some(plugin), None) => ResolvedNode {
+                    name: plugin,
+                    args: node.args,
+                    kind: None,
+                },
+                (None, Some(group_name)) => {
+                    let group = match self.groups.get(&group_name) {
+                        Some(group) => group,
+                        None => bail!("Node {} references unknown group {}", name, group_name),
+                    };
+                    let mut args = group.args.clone();
+                    args.extend(node.args);
+                    ResolvedNode {
+                        name: group.name.clone(),
+                        args,
+                        kind: None,
+                    }
+                }
+                (Some(_), Some(_)) => bail!("Node {} sets both a plugin and a group", name),
+                (None, None) => bail!("Node {} sets neither a plugin, a group nor a type", name),
+            };
+            res.insert(name, resolved);
+        }
+        for (name, node) in &mut res {
+            for key in node.args.keys() {
+                check_user_arg(name, key)?;
+            }
+            for value in node.args.values_mut() {
+                *value = match substitute(value, &self.vars) {
+                    Ok(value) => value,
+                    Err(e) => bail!("Node {}: {}", name, e),
+                };
+            }
+        }
+        Ok(res)
+    }
+}
+
+// Replace each `${name}` in `value` with the var's value
+fn substitute(value: &str, vars: &BTreeMap<String, String>) -> Result<String> {
+    let mut res = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        res.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => bail!("Unterminated var in {:?}", value),
+        };
+        let name = &rest[start + 2..end];
+        match vars.get(name) {
+            Some(var) => res.push_str(var),
+            None => bail!("Unknown var {}", name),
+        }
+        rest = &rest[end + 1..];
+    }
+    res.push_str(rest);
+    Ok(res)
+}
+
+/// A JSON Schema for manifests, without anything specific to the plugins available
+///
+/// Args are scalars, since YAML manifests may leave numbers and booleans unquoted, and keys under
+/// `vidmod.` are reserved for the runtime.
+pub fn static_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "vidmod project manifest",
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["nodes", "links"],
+        "properties": {
+            "nodes": {
+                "type": "object",
+                "additionalProperties": { "$ref": "#/definitions/node" }
+            },
+            "links": {
+                "type": "array",
+                "items": { "$ref": "#/definitions/link" }
+            },
+            "groups": {
+                "type": "object",
+                "additionalProperties": { "$ref": "#/definitions/group" }
+            },
+            "vars": { "$ref": "#/definitions/args" },
+            "start_frame": { "type": "integer", "minimum": 0 },
+            "max_frames": { "type": "integer", "minimum": 0 },
+            "tick_quota": { "type": "integer", "minimum": 0 },
+            "max_frame_bytes": { "type": "integer", "minimum": 0 },
+            "prime": { "type": "boolean" }
+        },
+        "definitions": {
+            "plugin": { "type": "string" },
+            "arg": { "type": ["string", "number", "boolean"] },
+            "args": {
+                "type": "object",
+                "additionalProperties": { "$ref": "#/definitions/arg" }
+            },
+            "node": {
+                "type": "object",
+                "additionalProperties": false,
+                "oneOf": [
+                    { "required": ["name"], "not": { "required": ["group"] } },
+                    { "required": ["group"], "not": { "required": ["name"] } },
+                    { "required": ["type"], "properties": { "name": false, "group": false } }
+                ],
+                "properties": {
+                    "name": { "$ref": "#/definitions/plugin" },
+                    "group": { "type": "string" },
+                    "type": { "enum": ["iterate"] },
+                    "args": { "$ref": "#/definitions/args" }
+                }
+            },
+            "group": {
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["name"],
+                "properties": {
+                    "name": { "$ref": "#/definitions/plugin" },
+                    "args": { "$ref": "#/definitions/args" }
+                }
+            },
+            "port": {
+                "type": "array",
+                "items": [{ "type": "string" }, { "type": "string" }],
+                "minItems": 2,
+                "maxItems": 2
+            },
+            "link": {
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["from", "to"],
+                "properties": {
+                    "from": { "$ref": "#/definitions/port" },
+                    "to": { "$ref": "#/definitions/port" },
+                    "lazy": { "type": "boolean" }
+                }
+            }
+        }
+    })
+}
+
+/// A JSON Schema for manifests using the plugins in `registry`
+///
+/// Plugin names are enumerated, and the args of each plugin that describes them are checked. A
+/// node's required args are only enforced when it names its plugin directly, as a group may
+/// provide them otherwise.
+pub fn json_schema(registry: &PluginRegistry) -> Value {
+    let mut schema = static_schema();
+    let plugins = registry.plugins();
+    if plugins.is_empty() {
+        return schema;
+    }
+    let defs = &mut schema["definitions"];
+    defs["plugin"] = json!({ "enum": plugins.keys().collect::<Vec<_>>() });
+    let mut node_rules = Vec::new();
+    let mut group_rules = Vec::new();
+    for (name, plugin) in plugins {
+        if let Some(args) = &plugin.args {
+            node_rules.push(plugin_rule(name, args_schema(args, true)));
+            group_rules.push(plugin_rule(name, args_schema(args, false)));
+        }
+    }
+    defs["node"]["allOf"] = Value::Array(node_rules);
+    defs["group"]["allOf"] = Value::Array(group_rules);
+    schema
+}
+
+// Check `args` against `schema` when `name` is the plugin
+fn plugin_rule(name: &str, schema: Value) -> Value {
+    json!({
+        "if": { "required": ["name"], "properties": { "name": { "const": name } } },
+        "then": { "properties": { "args": schema } }
+    })
+}
+
+fn args_schema(args: &[ArgSpec], enforce_required: bool) -> Value {
+    let mut properties = Map::new();
+    for arg in args {
+        properties.insert(arg.name.to_owned(), arg_schema(arg.ty));
+    }
+    let required: Vec<&str> = args
+        .iter()
+        .filter(|arg| enforce_required && arg.required)
+        .map(|arg| arg.name)
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "patternProperties": { "^vidmod\\.": { "$ref": "#/definitions/arg" } },
+        "additionalProperties": false,
+        "required": required
+    })
+}
+
+fn arg_schema(ty: ArgType) -> Value {
+    match ty {
+        ArgType::String => json!({ "$ref": "#/definitions/arg" }),
+        ArgType::Integer => json!({ "type": ["integer", "string"], "pattern": "^-?[0-9]+$" }),
+        ArgType::Number => json!({
+            "type": ["number", "string"],
+            "pattern": "^-?([0-9]+\\.?[0-9]*|\\.[0-9]+)([eE][-+]?[0-9]+)?$"
+        }),
+        ArgType::Bool => json!({ "enum": [true, false, "true", "false"] }),
+        ArgType::Enum(values) => json!({ "enum": values }),
+    }
 }
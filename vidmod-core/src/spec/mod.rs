@@ -1,17 +1,46 @@
 use std::{
-    collections::{BTreeMap, BTreeSet},
-    fmt::Debug,
-    fs::File,
+    any::Any,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::{Debug, Write},
+    fs::{self, File},
+    io,
     iter::FromIterator,
-    path::PathBuf,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    time::Instant,
 };
 
 use anyhow::Result;
-use vidmod_node::{frame::Frame, FinishNode, Node, PullPort, PushPort, TickNode};
+use vidmod_node::{
+    frame::Frame,
+    params::{
+        Params, LENIENT_ARG, NODE_INDEX_ARG, NODE_NAME_ARG, PATH_ARG, STATE_DIR_ARG, TICK_QUOTA_ARG,
+    },
+    BatchHint, FinishNode, FrameAccounting, Node, NodeId, PortDirection, PullPort, PushPort,
+    SeekOutcome, TickNode, VidmodError,
+};
+use vidmod_plugin::PluginRegistry;
+
+use self::{
+    manifest::{NodeType, ProjectManifest},
+    slots::NodeSlots,
+};
+use crate::{
+    budget::{BudgetState, TickBudget},
+    cancel::CancellationToken,
+    meter::{BufferPressure, Clock, LinkMeter, SystemClock},
+    nodes::{Iterate, Limit},
+    tap::{FrameTap, HashTap, LinkHashes, LinkId},
+};
+
+pub mod manifest;
+mod slots;
 
-use self::manifest::ProjectManifest;
+// The bound on priming passes for manifests setting `prime: true`, see NodeGraph::set_priming
+pub const PRIME_PASSES: usize = 64;
 
-mod manifest;
+// The most control messages that can wait for delivery to one node, see NodeGraph::send_message
+pub const MESSAGE_QUEUE: usize = 64;
 
 #[derive(Debug)]
 pub struct Project {
@@ -20,148 +49,1381 @@ pub struct Project {
 
 impl Project {
     pub fn load(f: File, path: PathBuf) -> Self {
-        let manifest: manifest::ProjectManifest = serde_yaml::from_reader(f).unwrap();
-        Project::from_manifest(manifest, path)
+        Project::load_with(f, path, &crate::nodes::registry())
+    }
+
+    pub fn load_with(f: File, path: PathBuf, registry: &PluginRegistry) -> Self {
+        Project::load_with_vars(f, path, registry, &BTreeMap::new())
+    }
+
+    // Load with `vars` overriding the manifest's own
+    pub fn load_with_vars(
+        f: File,
+        path: PathBuf,
+        registry: &PluginRegistry,
+        vars: &BTreeMap<String, String>,
+    ) -> Self {
+        Project::load_with_overlays(f, Vec::new(), path, registry, vars)
+    }
+
+    // Load with each of `overlays` merged over the manifest in turn, see ProjectManifest::merge,
+    // and then `vars` overriding their vars
+    pub fn load_with_overlays(
+        f: File,
+        overlays: Vec<File>,
+        path: PathBuf,
+        registry: &PluginRegistry,
+        vars: &BTreeMap<String, String>,
+    ) -> Self {
+        let manifest = Project::read_manifest(f, overlays, vars).unwrap();
+        Project::from_manifest(manifest, path, registry)
+    }
+
+    fn read_manifest(
+        f: File,
+        overlays: Vec<File>,
+        vars: &BTreeMap<String, String>,
+    ) -> Result<manifest::ProjectManifest> {
+        let mut manifest: manifest::ProjectManifest = serde_yaml::from_reader(f)?;
+        for overlay in overlays {
+            manifest.merge(serde_yaml::from_reader(overlay)?);
+        }
+        manifest.vars.extend(vars.clone());
+        Ok(manifest)
+    }
+
+    pub fn dry_run(f: File, path: &Path, registry: &PluginRegistry) -> Result<String> {
+        Project::dry_run_with_vars(f, path, registry, &BTreeMap::new())
+    }
+
+    // Describe each node's plugin and fully merged args, and the size of any state it has kept
+    // under `path`, without constructing anything
+    pub fn dry_run_with_vars(
+        f: File,
+        path: &Path,
+        registry: &PluginRegistry,
+        vars: &BTreeMap<String, String>,
+    ) -> Result<String> {
+        Project::dry_run_with_overlays(f, Vec::new(), path, registry, vars)
+    }
+
+    // A dry run of the manifest with `overlays` merged over it, see `load_with_overlays`
+    pub fn dry_run_with_overlays(
+        f: File,
+        overlays: Vec<File>,
+        path: &Path,
+        registry: &PluginRegistry,
+        vars: &BTreeMap<String, String>,
+    ) -> Result<String> {
+        let mut manifest = Project::read_manifest(f, overlays, vars)?;
+        let mut res = String::new();
+        for (name, node) in manifest.resolve_nodes(registry)? {
+            writeln!(res, "{} ({})", name, node.name).unwrap();
+            for (key, value) in &node.args {
+                writeln!(res, "    {}: {}", key, value).unwrap();
+            }
+            let state = Project::state_root(path).join(&name);
+            if state.is_dir() {
+                writeln!(res, "    state: {} bytes", dir_size(&state)?).unwrap();
+            }
+        }
+        Ok(res)
+    }
+
+    // The directory under a project holding each node's state directory, see Params::state_dir
+    pub fn state_root(path: &Path) -> PathBuf {
+        path.join(".vidmod").join("state")
+    }
+
+    // The project directory as injected into nodes: canonical when it exists, so `proj` and
+    // `./proj` agree, and otherwise at least absolute
+    fn absolute_root(path: PathBuf) -> PathBuf {
+        fs::canonicalize(&path).unwrap_or_else(|_| match std::env::current_dir() {
+            Ok(cwd) => cwd.join(path),
+            Err(_) => path,
+        })
+    }
+
+    // Remove every node's state from the project at `path`
+    pub fn clean(path: &Path) -> io::Result<()> {
+        let root = Project::state_root(path);
+        if root.exists() {
+            fs::remove_dir_all(root)?;
+        }
+        Ok(())
     }
 
     pub fn tick(&mut self) -> bool {
         self.nodes.tick()
     }
 
+    // Stop `tick` moving frames until `resume`, keeping everything buffered, see NodeGraph::pause
+    pub fn pause(&mut self) {
+        self.nodes.pause()
+    }
+
+    pub fn resume(&mut self) {
+        self.nodes.resume()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.nodes.is_paused()
+    }
+
     pub fn run(&mut self) {
         self.nodes.run()
     }
 
-    fn from_manifest(manifest: ProjectManifest, path: PathBuf) -> Self {
-        let mut graph = NodeGraph::new();
+    pub fn try_run(&mut self) -> Result<(), VidmodError> {
+        self.nodes.try_run()
+    }
+
+    pub fn run_mode(&mut self, mode: RunMode) -> Vec<(String, VidmodError)> {
+        self.nodes.run_mode(mode)
+    }
+
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.nodes.set_cancellation(token)
+    }
+
+    pub fn set_max_inner_iterations(&mut self, max: Option<usize>) {
+        self.nodes.set_max_inner_iterations(max)
+    }
 
-        let mut node_map = BTreeMap::new();
+    pub fn set_source_backpressure(&mut self, enabled: bool) {
+        self.nodes.set_source_backpressure(enabled)
+    }
+
+    pub fn set_max_frame_bytes(&mut self, max: Option<usize>) {
+        self.nodes.set_max_frame_bytes(max)
+    }
 
-        for (name, mut node) in manifest.nodes {
+    pub fn set_priming(&mut self, passes: Option<usize>) {
+        self.nodes.set_priming(passes)
+    }
+
+    pub fn to_dot(&self) -> String {
+        self.nodes.to_dot()
+    }
+
+    pub fn into_graph(self) -> NodeGraph {
+        self.nodes
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.node_count()
+    }
+
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes.find(name)
+    }
+
+    pub fn node_name(&self, id: NodeId) -> Option<&str> {
+        self.nodes.node_name(id)
+    }
+
+    pub fn link_ids(&self) -> Vec<LinkId> {
+        self.nodes.link_ids()
+    }
+
+    pub fn budget_exhaustions(&self) -> BTreeMap<String, usize> {
+        self.nodes.budget_exhaustions()
+    }
+
+    pub fn link_rates(&self) -> Vec<(String, String, f64)> {
+        self.nodes.link_rates()
+    }
+
+    pub fn buffer_pressure(&self) -> Vec<(String, String, BufferPressure)> {
+        self.nodes.buffer_pressure()
+    }
+
+    pub fn idle_ports(&self) -> Vec<(String, u64)> {
+        self.nodes.idle_ports()
+    }
+
+    pub fn stalled_links(&self) -> Vec<(String, String, Stall)> {
+        self.nodes.stalled_links()
+    }
+
+    pub fn lint(&self) -> Vec<VidmodError> {
+        self.nodes.lint()
+    }
+
+    pub fn send_message(&mut self, node: &str, key: &str, value: &str) -> Result<(), VidmodError> {
+        self.nodes.send_message(node, key, value)
+    }
+
+    pub fn warnings(&self) -> &[VidmodError] {
+        self.nodes.warnings()
+    }
+
+    pub fn seek_sources(&mut self, position: u64) -> Result<BTreeMap<String, SeekOutcome>> {
+        self.nodes.seek_sources(position)
+    }
+
+    // Tap every link with a HashTap, returning the hashes they record
+    pub fn hash_links(&mut self) -> LinkHashes {
+        let hashes = LinkHashes::default();
+        for link in self.link_ids() {
+            hashes
+                .lock()
+                .unwrap()
+                .insert(link.clone(), Default::default());
+            let tap = Box::new(HashTap::new(hashes.clone()));
+            self.tap_link((&link.from.0, &link.from.1), (&link.to.0, &link.to.1), tap)
+                .unwrap();
+        }
+        hashes
+    }
+
+    pub fn limit_sources(&mut self, count: u64) -> Result<(), VidmodError> {
+        self.nodes.limit_sources(count)
+    }
+
+    pub fn tap_link(
+        &mut self,
+        from: (&str, &str),
+        to: (&str, &str),
+        tap: Box<dyn FrameTap>,
+    ) -> Result<(), VidmodError> {
+        self.nodes.tap_link(from, to, tap)
+    }
+
+    pub fn finish_taps(&mut self) -> Vec<(LinkId, io::Error)> {
+        self.nodes.finish_taps()
+    }
+
+    fn from_manifest(
+        mut manifest: ProjectManifest,
+        path: PathBuf,
+        registry: &PluginRegistry,
+    ) -> Self {
+        let path = Project::absolute_root(path);
+        let mut graph = NodeGraph::new();
+
+        let nodes = manifest
+            .resolve_nodes(registry)
+            .unwrap_or_else(|e| panic!("{}", e));
+        for (index, (name, mut node)) in nodes.into_iter().enumerate() {
+            node.args
+                .insert(PATH_ARG.to_string(), path.to_str().unwrap().to_string());
+            node.args.insert(NODE_NAME_ARG.to_string(), name.clone());
+            node.args
+                .insert(NODE_INDEX_ARG.to_string(), index.to_string());
+            // Only named here; the directory is made when the node first writes state, so loading
+            // a project to inspect it leaves nothing behind
+            let state = Project::state_root(&path).join(&name);
             node.args.insert(
-                "vidmod.path".to_string(),
-                path.to_str().unwrap().to_string(),
+                STATE_DIR_ARG.to_string(),
+                state.to_str().unwrap().to_string(),
             );
 
-            let plugin = vidmod_plugin::PLUGINS
-                .get(&node.name)
-                .unwrap_or_else(|| panic!("Unknown plugin {}", node.name));
-            let mut node = (plugin.make_node)(node.args);
+            let params = Params::new(node.args.clone());
+            let budget = TickBudget::from_params(&params)
+                .unwrap_or_else(|e| panic!("Invalid tick budget for node {}: {}", name, e));
+            let lenient = params.get(LENIENT_ARG) == Some("true");
+            let quota = match params.get(TICK_QUOTA_ARG) {
+                Some(quota) => Some(
+                    quota
+                        .parse()
+                        .unwrap_or_else(|e| panic!("Invalid tick quota for node {}: {}", name, e)),
+                ),
+                None => manifest.tick_quota,
+            };
+
+            let mut node = match node.kind {
+                Some(NodeType::Iterate) => Node::new(Iterate::load(node.args, registry)),
+                None => {
+                    let plugin = registry
+                        .get(&node.name)
+                        .unwrap_or_else(|| panic!("Unknown plugin {}", node.name));
+                    (plugin.make_node)(node.args)
+                }
+            };
             node.init();
-            let id = graph.insert(node, name.clone());
-            node_map.insert(name, id);
+            let id = graph
+                .insert(node, name.clone())
+                .unwrap_or_else(|e| panic!("{}", e));
+            if let Some(budget) = budget {
+                graph.set_tick_budget(id, budget);
+            }
+            graph.set_lenient(id, lenient);
+            graph.set_tick_quota(id, quota);
         }
         for link in manifest.links {
-            let p1 = graph
-                .get_pull_port(*node_map.get(&link.from.0).unwrap(), &link.from.1)
-                .unwrap();
-            let p2 = graph
-                .get_push_port(*node_map.get(&link.to.0).unwrap(), &link.to.1)
-                .unwrap();
-            graph.add_link(p1, p2).unwrap();
+            let id = |name: &String| match graph.find(name) {
+                Some(id) => id,
+                None => panic!("Link {} uses unknown node {}", link, name),
+            };
+            let (from, to) = (id(&link.from.0), id(&link.to.0));
+            let res = graph.get_pull_port(from, &link.from.1).and_then(|p1| {
+                let p2 = graph.get_push_port(to, &link.to.1)?;
+                if link.lazy {
+                    graph.set_lazy(&p2, true);
+                }
+                graph.add_link(p1, p2)
+            });
+            if let Err(e) = res {
+                panic!("{}", link.explain(&e));
+            }
+        }
+
+        graph.set_max_frame_bytes(manifest.max_frame_bytes);
+        if manifest.prime {
+            graph.set_priming(Some(PRIME_PASSES));
         }
+        let mut project = Self { nodes: graph };
+        if let Some(count) = manifest.max_frames {
+            project.limit_sources(count).unwrap();
+        }
+        if let Some(position) = manifest.start_frame {
+            project.seek_sources(position).unwrap();
+        }
+        project
+    }
+}
 
-        Self { nodes: graph }
+// The total size of the files beneath a directory
+fn dir_size(dir: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        size += if meta.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            meta.len()
+        };
     }
+    Ok(size)
+}
+
+/// Why a link holding frames moves none of them, see NodeGraph::stalled_links
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stall {
+    /// Frames waiting in the producer's pull buffer
+    pub ready: usize,
+    /// Room left in the consumer's push buffer, before rounding to the batch hint
+    pub free:  usize,
+    /// The consumer's batch hint, if it has one
+    pub hint:  Option<BatchHint>,
+}
+
+/// How a run treats a node failing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunMode {
+    /// Stop the run at the first failure
+    AbortOnError,
+    /// Stop ticking failed nodes, and run the rest of the graph to completion
+    CollectErrors,
 }
 
 #[derive(Debug)]
 pub struct NodeGraph {
-    nodes:      Vec<Node>,
-    links:      Vec<(PullPort, PushPort)>,
-    node_names: Vec<String>,
+    nodes:         NodeSlots,
+    links:         Vec<(PullPort, PushPort)>,
+    node_names:    Vec<String>,
+    link_batching: bool,
+    backpressure:  bool,
+    downstream:    BTreeMap<usize, Vec<usize>>,
+    taps:          Vec<(usize, LinkId, Box<dyn FrameTap>)>,
+    budgets:       BTreeMap<usize, BudgetState>,
+    failures:      Vec<(usize, VidmodError)>,
+    mode:          RunMode,
+    cancel:        Option<CancellationToken>,
+    lenient:       BTreeSet<usize>,
+    exhausted:     BTreeMap<usize, usize>,
+    clock:         u64,
+    in_transit:    Vec<u64>,
+    lazy:          BTreeSet<(usize, String)>,
+    lazy_links:    Vec<bool>,
+    max_inner:     Option<usize>,
+    max_bytes:     Option<usize>,
+    prime:         Option<usize>,
+    progressed:    BTreeSet<usize>,
+    warnings:      Vec<VidmodError>,
+    meters:        Vec<LinkMeter>,
+    wall:          Box<dyn Clock>,
+    messages:      BTreeMap<usize, VecDeque<(String, String)>>,
+    names:         BTreeMap<String, usize>,
+    paused:        bool,
 }
 
 impl NodeGraph {
     pub fn new() -> Self {
         Self {
-            nodes:      Vec::new(),
-            links:      Vec::new(),
-            node_names: Vec::new(),
+            nodes:         NodeSlots::default(),
+            links:         Vec::new(),
+            node_names:    Vec::new(),
+            link_batching: false,
+            backpressure:  false,
+            downstream:    BTreeMap::new(),
+            taps:          Vec::new(),
+            budgets:       BTreeMap::new(),
+            failures:      Vec::new(),
+            mode:          RunMode::AbortOnError,
+            cancel:        None,
+            lenient:       BTreeSet::new(),
+            exhausted:     BTreeMap::new(),
+            clock:         0,
+            in_transit:    Vec::new(),
+            lazy:          BTreeSet::new(),
+            lazy_links:    Vec::new(),
+            max_inner:     None,
+            max_bytes:     None,
+            prime:         None,
+            progressed:    BTreeSet::new(),
+            warnings:      Vec::new(),
+            meters:        Vec::new(),
+            wall:          Box::new(SystemClock::new()),
+            messages:      BTreeMap::new(),
+            names:         BTreeMap::new(),
+            paused:        false,
+        }
+    }
+
+    // When enabled, tick_links gathers everything a producer whose output buffer is smaller than
+    // the space downstream has ready into one delivery. Producers are still only ticked by
+    // tick_slots, so finished, held and paused nodes stay untouched
+    pub fn set_link_batching(&mut self, enabled: bool) {
+        self.link_batching = enabled;
+    }
+
+    // When enabled, a source is not ticked while any buffer downstream of it is full, so a slow
+    // consumer several hops away throttles it rather than it being ticked only to find no room.
+    // If a pass makes no progress the held sources are ticked anyway, so a node waiting on another
+    // branch of the same source can't deadlock the graph. Sources and the links downstream of them
+    // are found when a run starts
+    pub fn set_source_backpressure(&mut self, enabled: bool) {
+        self.backpressure = enabled;
+    }
+
+    // Time every tick of the node, see TickBudget
+    pub fn set_tick_budget(&mut self, id: NodeId, budget: TickBudget) {
+        let idx = self.live(id, None);
+        self.budgets.insert(idx, BudgetState::new(budget));
+    }
+
+    // Stop ticking the graph after `max` passes in a row that made progress, so a node that
+    // always claims progress can't spin a run forever. Unlimited by default
+    pub fn set_max_inner_iterations(&mut self, max: Option<usize>) {
+        self.max_inner = max;
+    }
+
+    // Stop the run once frames waiting in port buffers across the process hold more than `max`
+    // bytes, checked after every outer pass, see FrameAccounting
+    pub fn set_max_frame_bytes(&mut self, max: Option<usize>) {
+        self.max_bytes = max;
+    }
+
+    // Before a run, tick only the sources and their links until every buffer they feed is full or
+    // they stop producing, for at most `passes` passes. Only then are the other nodes ticked, so
+    // they first see as full an input as the sources can give
+    pub fn set_priming(&mut self, passes: Option<usize>) {
+        self.prime = passes;
+    }
+
+    // Meter link rates against `clock` rather than the system clock, restarting every meter
+    pub fn set_meter_clock(&mut self, clock: Box<dyn Clock>) {
+        let now = clock.now();
+        self.wall = clock;
+        for meter in &mut self.meters {
+            *meter = LinkMeter::new(now);
+        }
+    }
+
+    // Frames per second moved along each link since metering began, as (from, to, rate) with
+    // each end named `node.port`
+    pub fn link_rates(&self) -> Vec<(String, String, f64)> {
+        let now = self.wall.now();
+        self.metered(|meter| meter.average(now))
+    }
+
+    // Frames per second moved along each link over the last window, see LinkMeter
+    pub fn instant_link_rates(&self) -> Vec<(String, String, f64)> {
+        let now = self.wall.now();
+        self.metered(|meter| meter.instant(now))
+    }
+
+    // How often `tick_links` found each link's pull buffer empty or push buffer full, as
+    // (from, to, pressure) with each end named `node.port`
+    pub fn buffer_pressure(&self) -> Vec<(String, String, BufferPressure)> {
+        self.metered(LinkMeter::pressure)
+    }
+
+    // Graph ticks each port has gone without its node putting or getting frames, as (port, idle)
+    // with the port named `node.port`. Counted up to the node's last tick, so a node no longer
+    // being ticked stops ageing
+    pub fn idle_ports(&self) -> Vec<(String, u64)> {
+        let mut res = Vec::new();
+        for idx in self.nodes.indices() {
+            for (port, _) in self.nodes[idx].buffer_bytes() {
+                let idle = self.nodes[idx].idle_for(&port);
+                res.push((format!("{}.{}", self.node_names[idx], port), idle));
+            }
+        }
+        res
+    }
+
+    // Links with frames ready that `tick_links` would not move, as (from, to, stall) with each
+    // end named `node.port`. Lazy links are left out, as they wait on their consumer's requests
+    pub fn stalled_links(&self) -> Vec<(String, String, Stall)> {
+        let mut res = Vec::new();
+        let links = self.link_ids().into_iter().zip(&self.links).enumerate();
+        for (link, (id, (pull, push))) in links {
+            let ready = self.pull_ready(pull);
+            if ready == 0 || self.is_lazy(link) {
+                continue;
+            }
+            let hint = push.batch_hint();
+            let count = usize::min(ready, self.push_ready(push));
+            if hint.map_or(count, |hint| hint.round(count)) > 0 {
+                continue;
+            }
+            res.push((
+                format!("{}.{}", id.from.0, id.from.1),
+                format!("{}.{}", id.to.0, id.to.1),
+                Stall {
+                    ready,
+                    free: self.push_free(push),
+                    hint,
+                },
+            ));
+        }
+        res
+    }
+
+    // The graph tick the node last put or got frames in, or 0 if it never has
+    pub fn last_progress(&self, id: NodeId) -> u64 {
+        self.nodes[self.live(id, None)].last_progress()
+    }
+
+    fn metered<T>(&self, stat: impl Fn(&LinkMeter) -> T) -> Vec<(String, String, T)> {
+        self.link_ids()
+            .into_iter()
+            .zip(&self.meters)
+            .map(|(id, meter)| {
+                (
+                    format!("{}.{}", id.from.0, id.from.1),
+                    format!("{}.{}", id.to.0, id.to.1),
+                    stat(meter),
+                )
+            })
+            .collect()
+    }
+
+    // Queue a control message for the named node, delivered in order just before its next tick.
+    // A node rejecting a message is recorded as a warning rather than stopping the run
+    pub fn send_message(&mut self, node: &str, key: &str, value: &str) -> Result<(), VidmodError> {
+        let id = self
+            .find(node)
+            .ok_or_else(|| VidmodError::NodeNameNotFound {
+                node: node.to_owned(),
+            })?;
+        let queue = self.messages.entry(id.index()).or_default();
+        if queue.len() >= MESSAGE_QUEUE {
+            return Err(VidmodError::MessageQueueFull {
+                node:     node.to_owned(),
+                capacity: MESSAGE_QUEUE,
+            });
+        }
+        queue.push_back((key.to_owned(), value.to_owned()));
+        Ok(())
+    }
+
+    fn deliver_messages(&mut self, idx: usize) {
+        let queue = match self.messages.remove(&idx) {
+            Some(queue) => queue,
+            None => return,
+        };
+        for (key, value) in queue {
+            let mut res = Ok(());
+            self.guard(idx, |node| {
+                res = node.on_message(&key, &value);
+                true
+            });
+            if let Err(e) = res {
+                let warning = VidmodError::MessageFailed {
+                    node: self.node_names[idx].clone(),
+                    key,
+                    message: e.to_string(),
+                };
+                println!("Warning: {}", warning);
+                self.warnings.push(warning);
+            }
+        }
+    }
+
+    // Ask the node to process at most `quota` elements per tick, see NodeCore::consume_budget
+    pub fn set_tick_quota(&mut self, id: NodeId, quota: Option<usize>) {
+        let idx = self.live(id, None);
+        self.nodes[idx].set_tick_quota(quota);
+    }
+
+    // How many ticks each node ended with its quota used up, by node name
+    pub fn budget_exhaustions(&self) -> BTreeMap<String, usize> {
+        self.exhausted
+            .iter()
+            .map(|(idx, count)| (self.node_names[*idx].clone(), *count))
+            .collect()
+    }
+
+    // The error that marked the first node failed. Outside of a CollectErrors run no more nodes
+    // are ticked after it
+    pub fn failure(&self) -> Option<&VidmodError> {
+        self.failures.first().map(|(_, error)| error)
+    }
+
+    // Problems found while building the graph that do not stop it running, such as linked ports
+    // declaring different bit depths
+    pub fn warnings(&self) -> &[VidmodError] {
+        &self.warnings
+    }
+
+    fn fail(&mut self, idx: usize, error: VidmodError) {
+        if !self.is_failed(idx) {
+            self.failures.push((idx, error));
+        }
+    }
+
+    fn is_failed(&self, idx: usize) -> bool {
+        self.failures.iter().any(|(failed, _)| *failed == idx)
+    }
+
+    fn take_failures(&mut self) -> Vec<(String, VidmodError)> {
+        std::mem::take(&mut self.failures)
+            .into_iter()
+            .map(|(idx, error)| (self.node_names[idx].clone(), error))
+            .collect()
+    }
+
+    // Once the token is cancelled sources are no longer ticked, so a run drains what they have
+    // already produced and finishes every node in order
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = Some(token);
+    }
+
+    // A lenient node's misuse of the buffer API fails it at the end of the tick rather than
+    // panicking, so the run can stop cleanly
+    pub fn set_lenient(&mut self, id: NodeId, lenient: bool) {
+        let idx = self.live(id, None);
+        self.nodes[idx].set_lenient(lenient);
+        if lenient {
+            self.lenient.insert(idx);
+        } else {
+            self.lenient.remove(&idx);
+        }
+    }
+
+    // A lazy push port only receives the frames its node has asked for with NodeCore::request,
+    // on the tick_links pass after the request. The port may be marked before or after it is
+    // linked; links into it are flagged by index so tick_links needn't look the port up
+    pub fn set_lazy(&mut self, port: &PushPort, lazy: bool) {
+        let key = (
+            self.live(port.id(), Some(port.name())),
+            port.name().to_owned(),
+        );
+        for (link, (_, push)) in self.links.iter().enumerate() {
+            if push.id() == port.id() && push.name() == port.name() {
+                self.lazy_links[link] = lazy;
+            }
+        }
+        if lazy {
+            self.lazy.insert(key);
+        } else {
+            self.lazy.remove(&key);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .map_or(false, CancellationToken::is_cancelled)
+    }
+
+    // Insert a node under `name`, which no other node in the graph may have
+    pub fn insert(&mut self, node: Node, name: String) -> Result<NodeId, VidmodError> {
+        if self.names.contains_key(&name) {
+            return Err(VidmodError::DuplicateNodeName { node: name });
+        }
+        let id = self.nodes.insert(node);
+        self.names.insert(name.clone(), id.index());
+        if id.index() < self.node_names.len() {
+            self.node_names[id.index()] = name;
+        } else {
+            self.node_names.push(name);
+        }
+        Ok(id)
+    }
+
+    // Give a node a new name, which no other node in the graph may have. Taps report the node's
+    // links under the new name from then on
+    pub fn rename(&mut self, id: NodeId, name: String) -> Result<(), VidmodError> {
+        self.nodes.check(id, None)?;
+        let idx = id.index();
+        match self.names.get(&name) {
+            Some(other) if *other == idx => return Ok(()),
+            Some(_) => return Err(VidmodError::DuplicateNodeName { node: name }),
+            None => {}
+        }
+        let old = std::mem::replace(&mut self.node_names[idx], name.clone());
+        self.names.remove(&old);
+        self.names.insert(name.clone(), idx);
+        for (_, link, _) in &mut self.taps {
+            for end in [&mut link.from, &mut link.to] {
+                if end.0 == old {
+                    end.0 = name.clone();
+                }
+            }
         }
+        Ok(())
     }
 
-    pub fn insert(&mut self, node: Node, name: String) -> usize {
-        self.nodes.push(node);
-        self.node_names.push(name);
-        self.nodes.len() - 1
+    // Remove a node along with its links, returning it. Its slot is freed for the next insert,
+    // under a new generation, so IDs and ports of the removed node are rejected rather than
+    // reaching whatever node takes its place
+    pub fn remove(&mut self, id: NodeId) -> Result<Node, VidmodError> {
+        let node = self.nodes.remove(id)?;
+        let idx = id.index();
+        let mut kept = Vec::new();
+        let mut meters = Vec::new();
+        let mut lazy_links = Vec::new();
+        let links = std::mem::take(&mut self.links);
+        for (link, ((pull, push), meter)) in links.into_iter().zip(&self.meters).enumerate() {
+            if pull.id() == id || push.id() == id {
+                self.taps.retain(|(tapped, _, _)| *tapped != link);
+                continue;
+            }
+            for (tapped, _, _) in &mut self.taps {
+                if *tapped == link {
+                    *tapped = kept.len();
+                }
+            }
+            kept.push((pull, push));
+            meters.push(meter.clone());
+            lazy_links.push(self.lazy_links[link]);
+        }
+        self.links = kept;
+        self.meters = meters;
+        self.lazy_links = lazy_links;
+        self.budgets.remove(&idx);
+        self.failures.retain(|(failed, _)| *failed != idx);
+        self.lenient.remove(&idx);
+        self.exhausted.remove(&idx);
+        self.messages.remove(&idx);
+        self.names.remove(&self.node_names[idx]);
+        self.lazy = std::mem::take(&mut self.lazy)
+            .into_iter()
+            .filter(|(node, _)| *node != idx)
+            .collect();
+        Ok(node)
     }
 
-    pub fn get_pull_port(&mut self, id: usize, name: &str) -> Result<PullPort> {
-        self.nodes[id].0.get_pull_port(id, name)
+    pub fn get_pull_port(&mut self, id: NodeId, name: &str) -> Result<PullPort, VidmodError> {
+        self.nodes.check(id, Some(name))?;
+        self.nodes[id.index()].get_pull_port(id, name)
     }
 
-    pub fn get_push_port(&mut self, id: usize, name: &str) -> Result<PushPort> {
-        self.nodes[id].0.get_push_port(id, name)
+    pub fn get_push_port(&mut self, id: NodeId, name: &str) -> Result<PushPort, VidmodError> {
+        self.nodes.check(id, Some(name))?;
+        self.nodes[id.index()].get_push_port(id, name)
     }
 
-    pub fn add_link(&mut self, p1: PullPort, p2: PushPort) -> Result<()> {
-        let p1i = p1.id();
+    pub fn add_link(&mut self, p1: PullPort, p2: PushPort) -> Result<(), VidmodError> {
         let p1n = p1.name();
-        let p2i = p2.id();
         let p2n = p2.name();
-        self.nodes[p1i].0.attach_push_port(p1n, p2.clone())?;
-        self.nodes[p2i].0.attach_pull_port(p2n, p1.clone())?;
+        self.nodes.check(p1.id(), Some(p1n))?;
+        self.nodes.check(p2.id(), Some(p2n))?;
+        let p1i = p1.id().index();
+        let p2i = p2.id().index();
+        if let (Some(from_depth), Some(to_depth)) = (p1.bit_depth(), p2.bit_depth()) {
+            if from_depth != to_depth {
+                let warning = VidmodError::BitDepthMismatch {
+                    from: (self.node_names[p1i].clone(), p1n.to_owned()),
+                    to: (self.node_names[p2i].clone(), p2n.to_owned()),
+                    from_depth,
+                    to_depth,
+                };
+                println!("Warning: {}", warning);
+                self.warnings.push(warning);
+            }
+        }
+        self.nodes[p1i]
+            .attach_push_port(p1n, p2.clone())
+            .map_err(|e| on_node(e, p1i))?;
+        self.nodes[p2i]
+            .attach_pull_port(p2n, p1.clone())
+            .map_err(|e| on_node(e, p2i))?;
+        let p2 = self.get_push_port(p2.id(), p2n)?;
 
+        let lazy = self.lazy.contains(&(p2i, p2.name().to_owned()));
         self.links.push((p1, p2));
+        self.meters.push(LinkMeter::new(self.wall.now()));
+        self.lazy_links.push(lazy);
+        Ok(())
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // The node inserted under `name`
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.names.get(name).map(|idx| self.nodes.id(*idx))
+    }
+
+    // The name the node was inserted under, or None once it has been removed
+    pub fn node_name(&self, id: NodeId) -> Option<&str> {
+        self.nodes
+            .check(id, None)
+            .ok()
+            .map(|_| self.node_names[id.index()].as_str())
+    }
+
+    // Push frames straight to a node, e.g. to a port left unlinked for input from outside
+    pub fn push_frame(&mut self, port: &PushPort, frame: Frame) {
+        self.push_to(port, frame)
+    }
+
+    pub fn ready_to_pull(&self, port: &PullPort) -> usize {
+        self.pull_ready(port)
+    }
+
+    // Pull frames straight from a node, e.g. from a port left unlinked for output to outside
+    pub fn pull_frame(&mut self, port: &PullPort, count: usize) -> Frame {
+        let idx = self.live(port.id(), Some(port.name()));
+        self.nodes[idx].pull_frame(port, count)
+    }
+
+    pub fn link_ids(&self) -> Vec<LinkId> {
+        self.links
+            .iter()
+            .map(|(pull, push)| self.link_id(pull, push))
+            .collect()
+    }
+
+    // Install a tap that sees every frame moved along the link, before it is delivered
+    pub fn tap_link(
+        &mut self,
+        from: (&str, &str),
+        to: (&str, &str),
+        tap: Box<dyn FrameTap>,
+    ) -> Result<(), VidmodError> {
+        let id = LinkId {
+            from: (from.0.to_owned(), from.1.to_owned()),
+            to:   (to.0.to_owned(), to.1.to_owned()),
+        };
+        if let Some(idx) = self.link_ids().iter().position(|x| x == &id) {
+            self.taps.push((idx, id, tap));
+            Ok(())
+        } else {
+            Err(VidmodError::LinkNotFound {
+                from: id.from,
+                to:   id.to,
+            })
+        }
+    }
+
+    // Finish every tap once the run is over, see FrameTap::finish, returning the error of each
+    // that failed by link
+    pub fn finish_taps(&mut self) -> Vec<(LinkId, io::Error)> {
+        let mut res = Vec::new();
+        for (_, id, tap) in &mut self.taps {
+            if let Err(e) = tap.finish() {
+                res.push((id.clone(), e));
+            }
+        }
+        res
+    }
+
+    // Nodes with no incoming links
+    pub fn sources(&self) -> Vec<NodeId> {
+        self.source_indices()
+            .into_iter()
+            .map(|idx| self.nodes.id(idx))
+            .collect()
+    }
+
+    fn source_indices(&self) -> Vec<usize> {
+        self.nodes
+            .indices()
+            .filter(|idx| self.links.iter().all(|(_, push)| push.id().index() != *idx))
+            .collect()
+    }
+
+    // Weakly-connected components over the links, ordered by their lowest node index. Nodes
+    // with no links form a component on their own
+    pub fn connected_components(&self) -> Vec<BTreeSet<NodeId>> {
+        let mut neighbours = vec![Vec::new(); self.nodes.slots()];
+        for (pull, push) in &self.links {
+            neighbours[pull.id().index()].push(push.id());
+            neighbours[push.id().index()].push(pull.id());
+        }
+        let mut seen = BTreeSet::new();
+        let mut res = Vec::new();
+        for start in self.nodes.indices().map(|idx| self.nodes.id(idx)) {
+            if !seen.insert(start) {
+                continue;
+            }
+            let mut component = BTreeSet::new();
+            let mut stack = vec![start];
+            while let Some(id) = stack.pop() {
+                component.insert(id);
+                for &next in &neighbours[id.index()] {
+                    if seen.insert(next) {
+                        stack.push(next);
+                    }
+                }
+            }
+            res.push(component);
+        }
+        res
+    }
+
+    // Warnings for nodes with no links, and for ports of linked nodes that nothing consumes from
+    // or produces into. A node with no links is reported once rather than port by port
+    pub fn lint(&self) -> Vec<VidmodError> {
+        let mut res = Vec::new();
+        for idx in self.nodes.indices() {
+            let node = &self.node_names[idx];
+            let linked = |direction: PortDirection, port: &str| {
+                self.links.iter().any(|(pull, push)| match direction {
+                    PortDirection::Pull => pull.id().index() == idx && pull.name() == port,
+                    PortDirection::Push => push.id().index() == idx && push.name() == port,
+                })
+            };
+            let touches = |(pull, push): &(PullPort, PushPort)| {
+                pull.id().index() == idx || push.id().index() == idx
+            };
+            if !self.links.iter().any(touches) {
+                res.push(VidmodError::UnlinkedNode { node: node.clone() });
+                continue;
+            }
+            for (port, direction) in self.nodes[idx].port_names() {
+                if !linked(direction, &port) {
+                    res.push(VidmodError::UnlinkedPort {
+                        node: node.clone(),
+                        port,
+                        direction,
+                    });
+                }
+            }
+        }
+        res
+    }
+
+    pub fn seek_sources(&mut self, position: u64) -> Result<BTreeMap<String, SeekOutcome>> {
+        let mut res = BTreeMap::new();
+        for idx in self.source_indices() {
+            let outcome = self.nodes[idx].seek(position)?;
+            if outcome.position != position {
+                println!(
+                    "Seeked {} to {} instead of {}",
+                    self.node_names[idx], outcome.position, position
+                );
+            }
+            res.insert(self.node_names[idx].clone(), outcome);
+        }
+        Ok(res)
+    }
+
+    // Insert a core::Limit on every link leaving a source, so at most `count` frames flow on it
+    pub fn limit_sources(&mut self, count: u64) -> Result<(), VidmodError> {
+        let sources = self.sources();
+        for idx in 0..self.links.len() {
+            let (pull, push) = self.links[idx].clone();
+            if !sources.contains(&pull.id()) {
+                continue;
+            }
+            let mut params = BTreeMap::new();
+            params.insert("kind".to_owned(), format!("{:?}", pull.kind()));
+            params.insert("count".to_owned(), count.to_string());
+            let mut limiter = Node::new(Limit::new(params));
+            limiter.init();
+            let name = format!(
+                "{}.{}.limit",
+                self.node_names[pull.id().index()],
+                pull.name()
+            );
+            let id = self.insert(limiter, name)?;
+
+            let limit_in = self.get_push_port(id, "in")?;
+            self.nodes[pull.id().index()].attach_push_port(pull.name(), limit_in.clone())?;
+            self.nodes[id.index()].attach_pull_port("in", pull.clone())?;
+            self.links[idx].1 = limit_in;
+            self.lazy_links[idx] = false;
+
+            let limit_out = self.get_pull_port(id, "out")?;
+            self.add_link(limit_out, push)?;
+        }
         Ok(())
     }
 
     pub fn tick(&mut self) -> bool {
+        if self.paused {
+            return false;
+        }
         self.tick_nodes(None) || self.tick_links()
     }
 
-    pub fn tick_nodes(&mut self, nodes: Option<&BTreeSet<usize>>) -> bool {
-        let mut res = false;
-        for (idx, node) in self.nodes.iter_mut().enumerate() {
+    // While paused `tick` does nothing and returns false. Frames stay in their buffers and nodes
+    // keep their state, so after `resume` the graph carries on from where it stopped
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // Each call is one graph tick, the unit node clocks and frame origins count in
+    pub fn tick_nodes(&mut self, nodes: Option<&BTreeSet<NodeId>>) -> bool {
+        let slots = nodes.map(|nodes| {
+            nodes
+                .iter()
+                .map(|id| self.live(*id, None))
+                .collect::<BTreeSet<_>>()
+        });
+        self.tick_slots(slots.as_ref())
+    }
+
+    // Tick every node and link until none make progress, returning whether anything did
+    pub fn settle_all(&mut self) -> bool {
+        let nodes = BTreeSet::from_iter(self.nodes.indices());
+        self.settle(&nodes)
+    }
+
+    fn tick_slots(&mut self, nodes: Option<&BTreeSet<usize>>) -> bool {
+        self.clock += 1;
+        self.progressed.clear();
+        let mut held = Vec::new();
+        for idx in self.nodes.indices().collect::<Vec<_>>() {
             if let Some(nodes) = &nodes {
                 if !nodes.contains(&idx) {
                     continue;
                 }
             }
-            res |= node.tick();
+            if self.backpressure && self.downstream_full(idx) {
+                held.push(idx);
+                continue;
+            }
+            if self.tick_node(idx) {
+                self.progressed.insert(idx);
+            }
+        }
+        if self.progressed.is_empty() {
+            for idx in held {
+                if self.tick_node(idx) {
+                    self.progressed.insert(idx);
+                }
+            }
+        }
+        !self.progressed.is_empty()
+    }
+
+    // Whether the node is a source with a full input buffer at the end of any link downstream
+    fn downstream_full(&self, idx: usize) -> bool {
+        self.downstream.get(&idx).map_or(false, |links| {
+            links
+                .iter()
+                .any(|&link| self.push_ready(&self.links[link].1) == 0)
+        })
+    }
+
+    // The indices of the links reachable from each source, for downstream_full
+    fn index_downstream(&mut self) {
+        let mut downstream = BTreeMap::new();
+        for source in self.source_indices() {
+            let mut links = Vec::new();
+            let mut seen = BTreeSet::new();
+            let mut stack = vec![source];
+            while let Some(node) = stack.pop() {
+                for (link, (pull, push)) in self.links.iter().enumerate() {
+                    if pull.id().index() != node {
+                        continue;
+                    }
+                    links.push(link);
+                    if seen.insert(push.id().index()) {
+                        stack.push(push.id().index());
+                    }
+                }
+            }
+            downstream.insert(source, links);
+        }
+        self.downstream = downstream;
+    }
+
+    // Tick the nodes and links until neither makes progress or the inner iteration cap is hit,
+    // returning whether anything made progress
+    fn settle(&mut self, nodes: &BTreeSet<usize>) -> bool {
+        let mut progress = false;
+        let mut iterations = 0;
+        while {
+            let mut inner_progress = false;
+            inner_progress |= self.tick_slots(Some(nodes));
+            inner_progress |= self.tick_links();
+            progress |= inner_progress;
+            inner_progress
+        } {
+            iterations += 1;
+            if self.max_inner.map_or(false, |max| iterations >= max) {
+                println!(
+                    "Warning: stopped after {} inner iterations, still progressing: {:?}",
+                    iterations,
+                    self.progressed
+                        .iter()
+                        .map(|idx| &self.node_names[*idx])
+                        .collect::<Vec<_>>()
+                );
+                break;
+            }
+        }
+        progress
+    }
+
+    fn tick_node(&mut self, idx: usize) -> bool {
+        let aborted = self.mode == RunMode::AbortOnError && !self.failures.is_empty();
+        if aborted || self.is_failed(idx) {
+            return false;
+        }
+        if self.is_cancelled() && self.links.iter().all(|(_, push)| push.id().index() != idx) {
+            return false;
+        }
+        self.deliver_messages(idx);
+        if self.is_failed(idx) {
+            return false;
+        }
+        self.nodes[idx].reset_budget();
+        self.nodes[idx].set_clock(self.clock);
+        let start = Instant::now();
+        let res = self.guard(idx, |node| node.tick());
+        let elapsed = start.elapsed();
+        if self.nodes[idx].budget_exhausted() {
+            *self.exhausted.entry(idx).or_default() += 1;
+        }
+        if let Some(budget) = self.budgets.get_mut(&idx) {
+            if let Err(e) = budget.record(&self.node_names[idx], elapsed) {
+                self.fail(idx, e);
+            }
         }
         res
     }
 
+    // Call into a node, marking it failed with its name if it panics
+    fn guard<F: FnOnce(&mut Node) -> bool>(&mut self, idx: usize, f: F) -> bool {
+        let node = &mut self.nodes[idx];
+        let res = panic::catch_unwind(AssertUnwindSafe(|| f(node)));
+        // A lenient node may panic later in the tick because of its own misuse, so report that
+        if self.lenient.contains(&idx) {
+            if let Some(error) = self.nodes[idx].take_error() {
+                let error = VidmodError::NodeFailed {
+                    node:  self.node_names[idx].clone(),
+                    error: Box::new(error),
+                };
+                println!("{}", error);
+                self.fail(idx, error);
+                return false;
+            }
+        }
+        match res {
+            Ok(res) => res,
+            Err(payload) => {
+                let error = VidmodError::NodePanicked {
+                    node:    self.node_names[idx].clone(),
+                    message: panic_message(payload),
+                };
+                self.fail(idx, error);
+                false
+            }
+        }
+    }
+
+    // The links are taken out for the sweep rather than cloned, so moving frames along them
+    // doesn't allocate. Nothing reached from here touches `self.links`
     pub fn tick_links(&mut self) -> bool {
         let mut res = false;
-        for (pull, push) in self.links.clone() {
-            let pull_count = self.pull_ready(&pull);
-            let push_count = self.push_ready(&push);
-            let count = usize::min(pull_count, push_count);
+        let links = std::mem::take(&mut self.links);
+        for (idx, (pull, push)) in links.iter().enumerate() {
+            let pull_count = self.pull_ready(pull);
+            let push_count = self.push_ready(push);
+            self.meters[idx].sweep(pull_count, push_count);
+            if self.is_lazy(idx) {
+                let count = self.lazy_count(push, usize::min(pull_count, push_count));
+                if count > 0 {
+                    let frame = self.pull_from(pull, count);
+                    self.deliver(idx, push, frame);
+                    res = true;
+                }
+                continue;
+            }
+            if self.link_batching && pull_count < push_count && push.batch_hint().is_none() {
+                let frame = self.gather(pull, push_count);
+                if frame.size() > 0 {
+                    self.deliver(idx, push, frame);
+                    res = true;
+                }
+                continue;
+            }
+            let mut count = usize::min(pull_count, push_count);
+            if let Some(hint) = push.batch_hint() {
+                count = hint.round(count);
+            }
             if count > 0 {
-                let frame = self.pull_from(&pull, count);
-                self.push_to(&push, frame);
+                let frame = self.pull_from(pull, count);
+                self.deliver(idx, push, frame);
                 res = true;
             }
         }
+        self.links = links;
+        res
+    }
+
+    // Gather what a producer already has ready, up to `space` frames, leaving it to tick_slots
+    // to tick the producer for more
+    fn gather(&mut self, pull: &PullPort, space: usize) -> Frame {
+        let mut gathered = Frame::with_capacity(pull.kind(), space);
+        let count = usize::min(self.pull_ready(pull), space);
+        if count > 0 {
+            gathered.add_partial(&mut self.pull_from(pull, count));
+        }
+        gathered
+    }
+
+    pub fn flush_links(&mut self, finished: &BTreeSet<NodeId>) -> bool {
+        let mut res = false;
+        let links = std::mem::take(&mut self.links);
+        for (idx, (pull, push)) in links.iter().enumerate() {
+            if !finished.contains(&pull.id()) {
+                continue;
+            }
+            // The last frames from a finished producer go however many there are, so a final
+            // batch short of the hint is limited by the raw free space rather than push_ready
+            let pull_count = self.pull_ready(pull);
+            let mut count = usize::min(pull_count, self.push_free(push));
+            if self.is_lazy(idx) {
+                count = self.lazy_count(push, count);
+            }
+            if count > 0 {
+                let frame = self.pull_from(pull, count);
+                self.deliver(idx, push, frame);
+                res = true;
+            }
+            let idx = self.live(push.id(), Some(push.name()));
+            if count == pull_count && !self.nodes[idx].inbuf_eos(push.name()) {
+                self.nodes[idx].signal_eos(push);
+                res = true;
+            }
+        }
+        self.links = links;
         res
     }
 
     pub fn run(&mut self) {
-        let mut nodes = BTreeSet::from_iter(0..self.nodes.len());
+        if let Err(e) = self.try_run() {
+            panic!("{}", e);
+        }
+    }
+
+    // Run to completion, stopping early if a node is marked failed
+    pub fn try_run(&mut self) -> Result<(), VidmodError> {
+        match self.run_mode(RunMode::AbortOnError).into_iter().next() {
+            Some((_, error)) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    // Run to completion, returning the error of each node that failed by name. Aborting returns
+    // at most one from the run itself, followed by any from stopping the nodes
+    pub fn run_mode(&mut self, mode: RunMode) -> Vec<(String, VidmodError)> {
+        let res = self.start_nodes();
+        if !res.is_empty() {
+            return res;
+        }
+        let mut res = self.run_started(mode);
+        res.extend(self.stop_nodes());
+        res
+    }
+
+    // Start every node in insertion order, returning the error of the first that failed. Those
+    // already started are then stopped again
+    pub fn start_nodes(&mut self) -> Vec<(String, VidmodError)> {
+        let indices: Vec<usize> = self.nodes.indices().collect();
+        for (count, &idx) in indices.iter().enumerate() {
+            if let Err(e) = self.nodes[idx].start() {
+                let error = VidmodError::StartFailed {
+                    node:    self.node_names[idx].clone(),
+                    message: e.to_string(),
+                };
+                println!("{}", error);
+                for &started in indices[..count].iter().rev() {
+                    self.stop_node(started);
+                }
+                return vec![(self.node_names[idx].clone(), error)];
+            }
+        }
+        Vec::new()
+    }
+
+    // Stop every node in reverse insertion order, returning the errors of those that failed
+    pub fn stop_nodes(&mut self) -> Vec<(String, VidmodError)> {
+        let indices: Vec<usize> = self.nodes.indices().collect();
+        indices
+            .into_iter()
+            .rev()
+            .filter_map(|idx| {
+                self.stop_node(idx)
+                    .map(|error| (self.node_names[idx].clone(), error))
+            })
+            .collect()
+    }
+
+    fn stop_node(&mut self, idx: usize) -> Option<VidmodError> {
+        let e = self.nodes[idx].stop().err()?;
+        let error = VidmodError::StopFailed {
+            node:    self.node_names[idx].clone(),
+            message: e.to_string(),
+        };
+        println!("{}", error);
+        Some(error)
+    }
+
+    fn run_started(&mut self, mode: RunMode) -> Vec<(String, VidmodError)> {
+        self.mode = mode;
+        self.index_downstream();
+        if let Some(passes) = self.prime {
+            self.prime_sources(passes);
+            if self.mode == RunMode::AbortOnError && !self.failures.is_empty() {
+                return self.take_failures();
+            }
+        }
+        let mut nodes = BTreeSet::from_iter(self.nodes.indices());
+        let mut finished = BTreeSet::new();
         while {
-            let mut progress = false;
             println!("Running nodes");
-            while {
-                let mut inner_progress = false;
-                inner_progress |= self.tick_nodes(Some(&nodes));
-                inner_progress |= self.tick_links();
-                progress |= inner_progress;
-                inner_progress
-            } {
-                //println!("Inner made progress!");
+            let mut progress = self.settle(&nodes);
+            if self.mode == RunMode::AbortOnError && !self.failures.is_empty() {
+                return self.take_failures();
+            }
+            if let Err(error) = self.check_frame_bytes() {
+                println!("{}", error);
+                let mut res = self.take_failures();
+                res.push(("graph".to_owned(), error));
+                return res;
             }
             println!("Pruning nodes");
             let nodes_cur = nodes.clone();
             nodes = BTreeSet::new();
             for node in &nodes_cur {
                 for (pull, push) in &self.links {
-                    if &push.id() != node {
+                    if &push.id().index() != node {
                         continue;
                     }
-                    if !nodes_cur.contains(&pull.id()) {
+                    if !nodes_cur.contains(&pull.id().index()) {
                         continue;
                     }
                     nodes.insert(*node);
@@ -178,36 +1440,160 @@ impl NodeGraph {
             );
             for node in to_prune {
                 println!("Finishing node: {:?}", self.node_names.get(*node).unwrap());
-                if !self.nodes[*node].finish() {
+                finished.insert(self.nodes.id(*node));
+                if !self.is_failed(*node) && !self.guard(*node, |node| node.finish()) {
                     println!("  Running to allow finish");
-                    while self.tick_nodes(Some(&nodes_cur)) || self.tick_links() {
-                        println!("   Inner made progress!");
-                    }
+                    self.settle(&nodes_cur);
                 } else {
                     println!("  Immediate finish allowed");
                 }
+                while self.flush_links(&finished) {
+                    self.settle(&nodes_cur);
+                }
                 progress = true;
             }
+            if self.mode == RunMode::AbortOnError && !self.failures.is_empty() {
+                return self.take_failures();
+            }
             progress
         } {
             println!("Outer made progress!");
         }
         println!("Done!");
+        self.take_failures()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut res = String::from("digraph {\n");
+        for idx in self.nodes.indices() {
+            writeln!(res, "    {} [label={:?}];", idx, self.node_names[idx]).unwrap();
+        }
+        for (link, (pull, push)) in self.links.iter().enumerate() {
+            writeln!(
+                res,
+                "    {} -> {} [taillabel={:?}, headlabel={:?}{}];",
+                pull.id().index(),
+                push.id().index(),
+                pull.name(),
+                push.name(),
+                if self.is_lazy(link) {
+                    ", style=dashed"
+                } else {
+                    ""
+                }
+            )
+            .unwrap();
+        }
+        res.push_str("}\n");
+        res
+    }
+
+    fn prime_sources(&mut self, passes: usize) {
+        let sources = BTreeSet::from_iter(self.source_indices());
+        for _ in 0..passes {
+            let progress = self.tick_slots(Some(&sources));
+            if !(self.tick_links() || progress) {
+                break;
+            }
+        }
+    }
+
+    // The graph's five largest port buffers are reported along with the bytes held
+    fn check_frame_bytes(&self) -> Result<(), VidmodError> {
+        let limit = match self.max_bytes {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        let held = FrameAccounting::bytes();
+        if held <= limit {
+            return Ok(());
+        }
+        let mut largest: Vec<(String, usize)> = self
+            .nodes
+            .indices()
+            .flat_map(|idx| {
+                self.nodes[idx]
+                    .buffer_bytes()
+                    .into_iter()
+                    .map(move |(port, bytes)| (format!("{}.{}", self.node_names[idx], port), bytes))
+            })
+            .collect();
+        largest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        largest.truncate(5);
+        Err(VidmodError::FrameBytesExceeded {
+            limit,
+            held,
+            largest,
+        })
+    }
+
+    fn is_lazy(&self, link: usize) -> bool {
+        self.lazy_links[link]
+    }
+
+    // How much of `count` a lazy link may move, counted against the consumer's requests
+    fn lazy_count(&mut self, p: &PushPort, count: usize) -> usize {
+        let idx = self.live(p.id(), Some(p.name()));
+        let count = usize::min(count, self.nodes[idx].requested(p.name()));
+        self.nodes[idx].fulfil_request(p, count);
+        count
+    }
+
+    // The slot of a node about to be reached through a handle, which must not be stale
+    fn live(&self, id: NodeId, port: Option<&str>) -> usize {
+        if let Err(e) = self.nodes.check(id, port) {
+            panic!("{}", e);
+        }
+        id.index()
     }
 
     fn pull_ready(&self, p: &PullPort) -> usize {
-        self.nodes[p.id()].0.ready_to_pull(p)
+        self.nodes[self.live(p.id(), Some(p.name()))].ready_to_pull(p)
     }
     fn push_ready(&self, p: &PushPort) -> usize {
-        self.nodes[p.id()].0.ready_to_push(p)
+        self.nodes[self.live(p.id(), Some(p.name()))].ready_to_push(p)
+    }
+    fn push_free(&self, p: &PushPort) -> usize {
+        self.nodes[self.live(p.id(), Some(p.name()))].free_to_push(p)
     }
 
+    // The origins of the pulled frames travel with them until they are delivered
     fn pull_from(&mut self, port: &PullPort, count: usize) -> Frame {
-        self.nodes[port.id()].0.pull_frame(port, count)
+        let idx = self.live(port.id(), Some(port.name()));
+        let frame = self.nodes[idx].pull_frame(port, count);
+        self.in_transit
+            .extend_from_slice(self.nodes[idx].pulled_origins());
+        frame
     }
 
     fn push_to(&mut self, p: &PushPort, f: Frame) {
-        self.nodes[p.id()].0.push_frame(p, f)
+        let idx = self.live(p.id(), Some(p.name()));
+        self.nodes[idx].push_frame(p, f)
+    }
+
+    fn deliver(&mut self, link: usize, p: &PushPort, f: Frame) {
+        for (idx, id, tap) in &mut self.taps {
+            if *idx == link {
+                tap.on_transfer(id, &f);
+            }
+        }
+        self.meters[link].record(self.wall.now(), f.size());
+        self.push_to(p, f);
+        self.nodes[p.id().index()].set_pushed_origins(p, &self.in_transit);
+        self.in_transit.clear();
+    }
+
+    fn link_id(&self, pull: &PullPort, push: &PushPort) -> LinkId {
+        LinkId {
+            from: (
+                self.node_names[pull.id().index()].clone(),
+                pull.name().to_owned(),
+            ),
+            to:   (
+                self.node_names[push.id().index()].clone(),
+                push.name().to_owned(),
+            ),
+        }
     }
 }
 
@@ -216,3 +1602,29 @@ impl Default for NodeGraph {
         Self::new()
     }
 }
+
+// Attach errors do not know the ID of the node they came from
+fn on_node(error: VidmodError, id: usize) -> VidmodError {
+    match error {
+        VidmodError::PortNotFound {
+            node: None,
+            port,
+            direction,
+        } => VidmodError::PortNotFound {
+            node: Some(id),
+            port,
+            direction,
+        },
+        error => error,
+    }
+}
+
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "panic".to_owned()
+    }
+}
@@ -0,0 +1,83 @@
+use std::ops::{Index, IndexMut};
+
+use vidmod_node::{Node, NodeId, VidmodError};
+
+// A graph's nodes, by slot. Removing a node frees its slot for the next insert, which bumps the
+// slot's generation so IDs of the removed node stop matching
+#[derive(Debug, Default)]
+pub(crate) struct NodeSlots {
+    nodes:       Vec<Option<Node>>,
+    generations: Vec<u32>,
+}
+
+impl NodeSlots {
+    pub fn insert(&mut self, node: Node) -> NodeId {
+        if let Some(index) = self.nodes.iter().position(Option::is_none) {
+            self.generations[index] += 1;
+            self.nodes[index] = Some(node);
+            NodeId::new(index, self.generations[index])
+        } else {
+            self.nodes.push(Some(node));
+            self.generations.push(0);
+            NodeId::new(self.nodes.len() - 1, 0)
+        }
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Result<Node, VidmodError> {
+        self.check(id, None)?;
+        Ok(self.nodes[id.index()].take().unwrap())
+    }
+
+    // The ID of the node now in slot `index`
+    pub fn id(&self, index: usize) -> NodeId {
+        NodeId::new(index, self.generations[index])
+    }
+
+    pub fn check(&self, id: NodeId, port: Option<&str>) -> Result<(), VidmodError> {
+        let live = matches!(self.nodes.get(id.index()), Some(Some(_)));
+        if live && self.generations[id.index()] == id.generation() {
+            Ok(())
+        } else {
+            Err(VidmodError::StaleHandle {
+                node: id,
+                port: port.map(str::to_owned),
+            })
+        }
+    }
+
+    // The number of slots, occupied or not
+    pub fn slots(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.iter().filter(|node| node.is_some()).count()
+    }
+
+    // The occupied slots, in order
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.is_some())
+            .map(|(index, _)| index)
+    }
+}
+
+impl Index<usize> for NodeSlots {
+    type Output = Node;
+
+    fn index(&self, index: usize) -> &Node {
+        self.nodes[index]
+            .as_ref()
+            .unwrap_or_else(|| panic!("Node slot {} is empty", index))
+    }
+}
+
+impl IndexMut<usize> for NodeSlots {
+    fn index_mut(&mut self, index: usize) -> &mut Node {
+        self.nodes[index]
+            .as_mut()
+            .unwrap_or_else(|| panic!("Node slot {} is empty", index))
+    }
+}
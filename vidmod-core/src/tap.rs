@@ -0,0 +1,158 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Display},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use vidmod_node::frame::{Frame, FrameKind};
+
+/// Identifies a link by the names of the nodes and ports at either end
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LinkId {
+    pub from: (String, String),
+    pub to:   (String, String),
+}
+
+impl Display for LinkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{} -> {}.{}",
+            self.from.0, self.from.1, self.to.0, self.to.1
+        )
+    }
+}
+
+/// Observes the frames moved along a link, before they are delivered
+pub trait FrameTap: Debug {
+    fn on_transfer(&mut self, link: &LinkId, frame: &Frame);
+
+    // Flush anything the tap has buffered once the run is over, returning the first error it met
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Logs the number, kind and range of the frames moved along a link
+#[derive(Debug, Default)]
+pub struct SummaryTap {
+    count: usize,
+    range: Option<(f64, f64)>,
+}
+
+impl SummaryTap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl FrameTap for SummaryTap {
+    fn on_transfer(&mut self, link: &LinkId, frame: &Frame) {
+        self.count += frame.size();
+        if let Some((min, max)) = frame_range(frame) {
+            self.range = Some(match self.range {
+                Some((lo, hi)) => (lo.min(min), hi.max(max)),
+                None => (min, max),
+            });
+        }
+        match self.range {
+            Some((min, max)) => println!(
+                "{}: {} frames of {:?}, min {}, max {}",
+                link,
+                self.count,
+                FrameKind::from(frame),
+                min,
+                max
+            ),
+            None => println!(
+                "{}: {} frames of {:?}",
+                link,
+                self.count,
+                FrameKind::from(frame)
+            ),
+        }
+    }
+}
+
+/// Writes the raw bytes of every frame moved along a link to a file
+#[derive(Debug)]
+pub struct FileTap {
+    writer: BufWriter<File>,
+}
+
+impl FileTap {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+}
+
+impl FrameTap for FileTap {
+    fn on_transfer(&mut self, _link: &LinkId, frame: &Frame) {
+        frame.write_bytes(&mut self.writer).unwrap();
+    }
+}
+
+impl Drop for FileTap {
+    fn drop(&mut self) {
+        self.writer.flush().unwrap();
+    }
+}
+
+/// The number of frames moved along a link and their running hash
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LinkHash {
+    pub frames: usize,
+    pub hash:   u64,
+}
+
+/// Per-link hashes shared between HashTaps and whoever reports them
+pub type LinkHashes = Arc<Mutex<BTreeMap<LinkId, LinkHash>>>;
+
+/// Records a running hash of the frames moved along a link, see `Frame::rolling_hash`
+#[derive(Debug)]
+pub struct HashTap {
+    hashes: LinkHashes,
+}
+
+impl HashTap {
+    pub fn new(hashes: LinkHashes) -> Self {
+        Self { hashes }
+    }
+}
+
+impl FrameTap for HashTap {
+    fn on_transfer(&mut self, link: &LinkId, frame: &Frame) {
+        let mut hashes = self.hashes.lock().unwrap();
+        let entry = hashes.entry(link.clone()).or_default();
+        entry.frames += frame.size();
+        entry.hash = frame.rolling_hash(entry.hash);
+    }
+}
+
+fn range<T: Copy + Into<f64>, I: Iterator<Item = T>>(iter: I) -> Option<(f64, f64)> {
+    iter.map(Into::into).fold(None, |acc, x: f64| match acc {
+        Some((lo, hi)) => Some((f64::min(lo, x), f64::max(hi, x))),
+        None => Some((x, x)),
+    })
+}
+
+fn frame_range(frame: &Frame) -> Option<(f64, f64)> {
+    match frame {
+        Frame::U8(v) => range(v.iter().copied()),
+        Frame::U8x1(v) => range(v.iter().flat_map(|a| a.iter().copied())),
+        Frame::U8x2(v) => range(v.iter().flat_map(|a| a.iter().copied())),
+        Frame::U16(v) => range(v.iter().copied()),
+        Frame::U16x1(v) => range(v.iter().flat_map(|a| a.iter().copied())),
+        Frame::U16x2(v) => range(v.iter().flat_map(|a| a.iter().copied())),
+        Frame::F32(v) => range(v.iter().copied()),
+        Frame::F32x1(v) => range(v.iter().flat_map(|a| a.iter().copied())),
+        Frame::F32x2(v) => range(v.iter().flat_map(|a| a.iter().copied())),
+        Frame::RGBA8x2(_) => None,
+    }
+}
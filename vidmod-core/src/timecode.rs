@@ -0,0 +1,140 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Error, Result};
+
+/// A frame rate as used for SMPTE timecode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimecodeRate {
+    /// The whole number of frames counted per second, e.g. 30 for 29.97
+    pub nominal:    u32,
+    /// Whether frame numbers are skipped to keep timecode in step with wall-clock time
+    pub drop_frame: bool,
+}
+
+impl TimecodeRate {
+    /// Parse a rate such as "25" or "29.97", which defaults to drop-frame
+    pub fn parse(fps: &str, drop_frame: Option<bool>) -> Result<Self> {
+        let fps: f64 = fps.parse()?;
+        let nominal = fps.round() as u32;
+        if nominal == 0 {
+            bail!("Invalid timecode rate: {}", fps);
+        }
+        let fractional = (fps - nominal as f64).abs() > 1e-6;
+        let drop_frame = drop_frame.unwrap_or(fractional);
+        if drop_frame && nominal % 30 != 0 {
+            bail!("Drop-frame timecode needs a multiple of 30fps, got {}", fps);
+        }
+        Ok(Self {
+            nominal,
+            drop_frame,
+        })
+    }
+
+    // Frame numbers skipped at the start of each minute not divisible by 10
+    fn dropped(&self) -> u64 {
+        if self.drop_frame {
+            self.nominal as u64 / 15
+        } else {
+            0
+        }
+    }
+}
+
+/// An SMPTE timecode, displayed as HH:MM:SS:FF, or HH:MM:SS;FF for drop-frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours:      u32,
+    pub minutes:    u32,
+    pub seconds:    u32,
+    pub frames:     u32,
+    pub drop_frame: bool,
+}
+
+impl Timecode {
+    /// The timecode of the given zero-based frame number
+    pub fn from_frame_number(mut number: u64, rate: TimecodeRate) -> Self {
+        let fps = rate.nominal as u64;
+        let dropped = rate.dropped();
+        if dropped > 0 {
+            let per_minute = fps * 60 - dropped;
+            let per_ten_minutes = per_minute * 10 + dropped;
+            let tens = number / per_ten_minutes;
+            let rem = number % per_ten_minutes;
+            number += dropped * 9 * tens;
+            if rem >= dropped {
+                number += dropped * ((rem - dropped) / per_minute);
+            }
+        }
+        let seconds = number / fps;
+        Self {
+            hours:      (seconds / 3600 % 24) as u32,
+            minutes:    (seconds / 60 % 60) as u32,
+            seconds:    (seconds % 60) as u32,
+            frames:     (number % fps) as u32,
+            drop_frame: rate.drop_frame,
+        }
+    }
+
+    /// The zero-based frame number of this timecode
+    pub fn to_frame_number(&self, rate: TimecodeRate) -> u64 {
+        let total_minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let nominal =
+            (total_minutes * 60 + self.seconds as u64) * rate.nominal as u64 + self.frames as u64;
+        nominal - rate.dropped() * (total_minutes - total_minutes / 10)
+    }
+
+    /// Check the timecode is valid at the given rate, including that it is not a dropped number
+    pub fn validate(&self, rate: TimecodeRate) -> Result<()> {
+        if self.hours >= 24
+            || self.minutes >= 60
+            || self.seconds >= 60
+            || self.frames >= rate.nominal
+        {
+            bail!("Timecode {} out of range at {}fps", self, rate.nominal);
+        }
+        if self.seconds == 0 && self.minutes % 10 != 0 && (self.frames as u64) < rate.dropped() {
+            bail!("Timecode {} is skipped in drop-frame", self);
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Timecode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let drop_frame = s.contains(';');
+        let parts = s
+            .split(|c| c == ':' || c == ';')
+            .map(|part| part.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("Invalid timecode: {}", s))?;
+        match parts.as_slice() {
+            [hours, minutes, seconds, frames] => Ok(Self {
+                hours: *hours,
+                minutes: *minutes,
+                seconds: *seconds,
+                frames: *frames,
+                drop_frame,
+            }),
+            _ => bail!("Invalid timecode: {}", s),
+        }
+    }
+}
+
+impl Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours,
+            self.minutes,
+            self.seconds,
+            if self.drop_frame { ';' } else { ':' },
+            self.frames
+        )
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Error, Result};
+use notify::{RecursiveMode, Watcher};
+use vidmod_plugin::PluginRegistry;
+
+use crate::{
+    cancel::CancellationToken,
+    spec::{manifest::ProjectManifest, panic_message, Project},
+};
+
+// How long the watched files must be quiet before a change triggers a rerun
+const DEBOUNCE: Duration = Duration::from_millis(200);
+// How often the cancellation token is checked while waiting for changes
+const POLL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    pub nodes:   usize,
+    pub links:   usize,
+    pub elapsed: Duration,
+}
+
+/// Load the project at `path` from scratch and run it to completion
+///
+/// A manifest that fails to load or a run that panics is reported as an error, so a watch loop
+/// can carry on waiting for the next change.
+pub fn rerun(
+    path: &Path,
+    registry: &PluginRegistry,
+    vars: &BTreeMap<String, String>,
+    configure: &dyn Fn(&mut Project),
+) -> Result<RunSummary> {
+    let start = Instant::now();
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let manifest = File::open(path.join("manifest.yml"))?;
+        let mut project = Project::load_with_vars(manifest, path.to_owned(), registry, vars);
+        configure(&mut project);
+        project.run();
+        Ok(RunSummary {
+            nodes:   project.node_count(),
+            links:   project.link_ids().len(),
+            elapsed: start.elapsed(),
+        })
+    }))
+    .unwrap_or_else(|e| Err(Error::msg(panic_message(e))))
+}
+
+/// The manifest, plus every file referenced by a node argument
+pub fn watched_paths(path: &Path) -> Vec<PathBuf> {
+    let manifest_path = path.join("manifest.yml");
+    let mut res = BTreeSet::new();
+    res.insert(manifest_path.clone());
+    if let Ok(f) = File::open(&manifest_path) {
+        if let Ok(manifest) = serde_yaml::from_reader::<_, ProjectManifest>(f) {
+            let args = manifest.nodes.values().map(|node| &node.args);
+            for args in args.chain(manifest.groups.values().map(|group| &group.args)) {
+                for value in args.values() {
+                    let file = path.join(value);
+                    if file.is_file() {
+                        res.insert(file);
+                    }
+                }
+            }
+        }
+    }
+    res.into_iter()
+        .map(|p| p.canonicalize().unwrap_or(p))
+        .collect()
+}
+
+/// Block until one of `paths` changes, returning false if cancelled first
+pub fn wait_for_change(paths: &[PathBuf], token: &CancellationToken) -> Result<bool> {
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    // Watch the parent directories, so files replaced by editors are still seen
+    let dirs: BTreeSet<&Path> = paths.iter().filter_map(|p| p.parent()).collect();
+    for dir in dirs {
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    }
+
+    let mut changed = None;
+    while !token.is_cancelled() {
+        match rx.recv_timeout(POLL) {
+            Ok(event) => {
+                let event = event?;
+                if !event.kind.is_access() && event.paths.iter().any(|p| paths.contains(p)) {
+                    changed = Some(Instant::now());
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(false),
+        }
+        if let Some(time) = changed {
+            if time.elapsed() >= DEBOUNCE {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Run the project, then rerun it every time its manifest or inputs change until cancelled
+pub fn watch(
+    path: &Path,
+    vars: &BTreeMap<String, String>,
+    configure: &dyn Fn(&mut Project),
+    token: &CancellationToken,
+) -> Result<()> {
+    let mut iteration = 0;
+    while !token.is_cancelled() {
+        iteration += 1;
+        match rerun(path, &crate::nodes::registry(), vars, configure) {
+            Ok(summary) => println!(
+                "[{}] ok: {} nodes, {} links in {:.2?}",
+                iteration, summary.nodes, summary.links, summary.elapsed
+            ),
+            Err(e) => println!("[{}] failed: {}", iteration, e),
+        }
+        println!("Watching for changes...");
+        if !wait_for_change(&watched_paths(path), token)? {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use vidmod_core::spec::NodeGraph;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, NodeImpl, NodePorts, VidmodError};
+
+mod common;
+
+use common::{insert, link, TestSource};
+
+/// Accepts frames but never consumes them
+#[node_decl]
+struct Stall {}
+
+impl Stall {
+    #[node_new]
+    fn new() -> Self {
+        Self {}
+    }
+}
+
+impl NodeImpl for Stall {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U16, 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        false
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn stalled_frames_exceed_limit() {
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(16, 4), "source");
+    let stall = insert(&mut graph, Stall::new(), "stall");
+    link(&mut graph, (source, "out"), (stall, "in"));
+    graph.set_max_frame_bytes(Some(1));
+
+    match graph.try_run() {
+        Err(VidmodError::FrameBytesExceeded {
+            limit,
+            held,
+            largest,
+        }) => {
+            assert_eq!(limit, 1);
+            // Other tests share the process, so only a lower bound holds
+            assert!(held >= 16);
+            assert_eq!(
+                largest,
+                vec![("source.out".to_owned(), 8), ("stall.in".to_owned(), 8)]
+            );
+        }
+        res => panic!("Expected FrameBytesExceeded, got {:?}", res),
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::{Arc, Mutex};
+
+use vidmod_core::spec::{NodeGraph, Stall};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{frame::FrameKind, BatchHint, NodeCore, NodeImpl, NodePorts};
+
+mod common;
+
+use common::{insert, link, TestSource};
+
+#[node_decl]
+struct BatchSink {
+    batches: Arc<Mutex<Vec<usize>>>,
+    eos:     Arc<Mutex<bool>>,
+}
+
+impl BatchSink {
+    #[node_new]
+    fn new(batches: Arc<Mutex<Vec<usize>>>, eos: Arc<Mutex<bool>>) -> Self {
+        Self { batches, eos }
+    }
+}
+
+impl NodeImpl for BatchSink {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U16, 1024);
+        self.set_batch(
+            "in",
+            BatchHint {
+                multiple: 512,
+                min:      512,
+            },
+        )
+        .unwrap();
+    }
+
+    fn tick(&mut self) -> bool {
+        *self.eos.lock().unwrap() = self.inbuf_eos("in");
+        let count = self.inbuf_avail("in");
+        if count > 0 {
+            self.inbuf_get("in", count);
+            self.batches.lock().unwrap().push(count);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+/// Leaves everything in its 1000 frame buffer until end of stream, so a batch hint of 512 leaves
+/// less than a whole batch free for the last frames
+#[node_decl]
+struct HoldingSink {
+    received: Arc<Mutex<usize>>,
+}
+
+impl HoldingSink {
+    #[node_new]
+    fn new(received: Arc<Mutex<usize>>) -> Self {
+        Self { received }
+    }
+}
+
+impl NodeImpl for HoldingSink {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U16, 1000);
+        self.set_batch(
+            "in",
+            BatchHint {
+                multiple: 512,
+                min:      512,
+            },
+        )
+        .unwrap();
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = self.inbuf_avail("in");
+        if count > 0 && self.inbuf_eos("in") {
+            self.inbuf_get("in", count);
+            *self.received.lock().unwrap() += count;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn batches_are_multiples_except_last() {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let eos = Arc::new(Mutex::new(false));
+
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(1300, 600), "source");
+    let sink = insert(
+        &mut graph,
+        BatchSink::new(batches.clone(), eos.clone()),
+        "sink",
+    );
+    link(&mut graph, (source, "out"), (sink, "in"));
+    graph.run();
+
+    let batches = batches.lock().unwrap();
+    let (last, rest) = batches.split_last().unwrap();
+    assert!(rest.iter().all(|count| count % 512 == 0));
+    assert_eq!(*last, 1300 % 512);
+    assert_eq!(batches.iter().sum::<usize>(), 1300);
+    assert!(*eos.lock().unwrap());
+}
+
+#[test]
+fn oversized_hint_is_rejected() {
+    let mut node = NodeCore::new();
+    node.register_pushport("in", FrameKind::U16, 16);
+    assert!(node
+        .set_batch(
+            "in",
+            BatchHint {
+                multiple: 32,
+                min:      32,
+            }
+        )
+        .is_err());
+}
+
+#[test]
+fn short_final_batch_flushes_into_nearly_full_buffer() {
+    let received = Arc::new(Mutex::new(0));
+
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(600, 600), "source");
+    let sink = insert(&mut graph, HoldingSink::new(received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+
+    graph.settle_all();
+    assert_eq!(
+        graph.stalled_links(),
+        vec![(
+            "source.out".to_owned(),
+            "sink.in".to_owned(),
+            Stall {
+                ready: 88,
+                free:  488,
+                hint:  Some(BatchHint {
+                    multiple: 512,
+                    min:      512,
+                }),
+            }
+        )]
+    );
+
+    graph.run();
+    assert_eq!(*received.lock().unwrap(), 600);
+    assert!(graph.stalled_links().is_empty());
+}
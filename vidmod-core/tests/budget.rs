@@ -0,0 +1,114 @@
+use std::{collections::BTreeMap, thread, time::Duration};
+
+use vidmod_core::{
+    budget::{TickBudget, DEFAULT_STRIKES},
+    spec::NodeGraph,
+};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{params::Params, NodeImpl, VidmodError};
+
+mod common;
+
+use common::insert;
+
+/// Sleeps for `sleep` on each of its first `ticks` ticks
+#[node_decl]
+struct Sleeper {
+    sleep: Duration,
+    ticks: usize,
+}
+
+impl Sleeper {
+    #[node_new]
+    fn new(sleep: Duration, ticks: usize) -> Self {
+        Self { sleep, ticks }
+    }
+}
+
+impl NodeImpl for Sleeper {
+    fn init(&mut self) {}
+
+    fn tick(&mut self) -> bool {
+        if self.ticks == 0 {
+            return false;
+        }
+        thread::sleep(self.sleep);
+        self.ticks -= 1;
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+fn budget(warn: Option<u64>, limit: Option<u64>) -> TickBudget {
+    TickBudget {
+        warn:    warn.map(Duration::from_millis),
+        limit:   limit.map(Duration::from_millis),
+        strikes: DEFAULT_STRIKES,
+    }
+}
+
+#[test]
+fn over_budget_only_warns() {
+    let mut graph = NodeGraph::new();
+    let id = insert(
+        &mut graph,
+        Sleeper::new(Duration::from_millis(10), 3),
+        "slow",
+    );
+    graph.set_tick_budget(id, budget(Some(1), None));
+    assert!(graph.try_run().is_ok());
+}
+
+#[test]
+fn over_limit_fails_node() {
+    let mut graph = NodeGraph::new();
+    let id = insert(
+        &mut graph,
+        Sleeper::new(Duration::from_millis(10), 100),
+        "slow",
+    );
+    graph.set_tick_budget(id, budget(Some(1), Some(5)));
+    match graph.try_run() {
+        Err(VidmodError::TickLimitExceeded {
+            node, limit, count, ..
+        }) => {
+            assert_eq!(node, "slow");
+            assert_eq!(limit, Duration::from_millis(5));
+            assert_eq!(count, DEFAULT_STRIKES);
+        }
+        res => panic!("Expected tick limit failure, got {:?}", res),
+    }
+}
+
+#[test]
+#[should_panic(expected = "Node slow exceeded its tick limit of 5ms 3 times")]
+fn run_panics_on_failure() {
+    let mut graph = NodeGraph::new();
+    let id = insert(
+        &mut graph,
+        Sleeper::new(Duration::from_millis(10), 100),
+        "slow",
+    );
+    graph.set_tick_budget(id, budget(None, Some(5)));
+    graph.run();
+}
+
+#[test]
+fn budget_from_params() {
+    let mut args = BTreeMap::new();
+    assert_eq!(
+        TickBudget::from_params(&Params::new(args.clone())).unwrap(),
+        None
+    );
+    args.insert("vidmod.tick_budget_ms".to_owned(), "20".to_owned());
+    args.insert("vidmod.tick_limit_ms".to_owned(), "100".to_owned());
+    assert_eq!(
+        TickBudget::from_params(&Params::new(args.clone())).unwrap(),
+        Some(budget(Some(20), Some(100)))
+    );
+    args.insert("vidmod.tick_limit_ms".to_owned(), "soon".to_owned());
+    assert!(TickBudget::from_params(&Params::new(args)).is_err());
+}
@@ -0,0 +1,241 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+// A project in a fresh temp directory linking a counter to a sink, using only core nodes
+fn core_project(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vidmod-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("manifest.yml"),
+        r#"
+nodes:
+  input:
+    name: core::CounterSource
+    args:
+      kind: U8
+      count: '4'
+  output:
+    name: core::NullSink
+    args:
+      kind: U8
+links:
+  - from: [input, out]
+    to: [output, in]
+"#,
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn dot_prints_graph() {
+    let dir = core_project("dot");
+    let output = Command::new(env!("CARGO_BIN_EXE_vidmod-core"))
+        .arg("--dot")
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let dot = String::from_utf8(output.stdout).unwrap();
+    assert!(dot.starts_with("digraph {"));
+    assert!(dot.trim_end().ends_with('}'));
+    assert!(dot.contains("label=\"input\""));
+    assert!(dot.contains("label=\"output\""));
+    assert!(dot.contains("->"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dot_and_lint_leave_project_untouched() {
+    let dir = core_project("untouched");
+    let tap = dir.join("tap.txt");
+    for flag in &["--dot", "--lint"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_vidmod-core"))
+            .arg(flag)
+            .arg("--max-frames")
+            .arg("2")
+            .arg("--tap")
+            .arg(format!("input.out:{}", tap.display()))
+            .arg(&dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(!String::from_utf8(output.stdout).unwrap().contains("limit"));
+        assert!(!tap.exists());
+        assert!(!dir.join(".vidmod").exists());
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn clean_removes_state() {
+    let dir = std::env::temp_dir().join(format!("vidmod-clean-{}", std::process::id()));
+    let state = dir.join(".vidmod").join("state").join("node");
+    fs::create_dir_all(&state).unwrap();
+    fs::write(state.join("cache"), "data").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_vidmod-core"))
+        .arg("clean")
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!dir.join(".vidmod").join("state").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn schema_lists_core_nodes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_vidmod-core"))
+        .arg("schema")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(schema["definitions"]["plugin"]["enum"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("core::Zip")));
+}
+
+// Run the project in `dir` with `start`, returning its report as written to `name`
+fn run_report(dir: &Path, name: &str, start: &str, hash_links: bool) -> PathBuf {
+    let report = dir.join(name);
+    let mut command = Command::new(env!("CARGO_BIN_EXE_vidmod-core"));
+    command.arg("--report-json").arg(&report);
+    if hash_links {
+        command.arg("--hash-links");
+    }
+    let output = command
+        .arg("--var")
+        .arg(format!("start={}", start))
+        .arg(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    report
+}
+
+fn read_json(path: &Path) -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn compare_reports(a: &Path, b: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_vidmod-core"))
+        .arg("compare-reports")
+        .arg(a)
+        .arg(b)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn compare_reports_lists_diverged_links() {
+    let dir = std::env::temp_dir().join(format!("vidmod-compare-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("manifest.yml"),
+        r#"
+vars:
+  start: '0'
+nodes:
+  counter:
+    name: core::CounterSource
+    args:
+      kind: F32
+      count: '8'
+      start: ${start}
+  limit:
+    name: core::Limit
+    args:
+      kind: F32
+      count: '4'
+  sink:
+    name: core::NullSink
+    args:
+      kind: F32
+links:
+  - from: [counter, out]
+    to: [limit, in]
+  - from: [limit, out]
+    to: [sink, in]
+"#,
+    )
+    .unwrap();
+
+    let unhashed = run_report(&dir, "unhashed.json", "0", false);
+    assert!(read_json(&unhashed).get("link_hashes").is_none());
+
+    let first = run_report(&dir, "first.json", "0", true);
+    assert_eq!(
+        read_json(&first)["link_hashes"]["limit.out->sink.in"]["frames"],
+        4
+    );
+
+    let output = compare_reports(&first, &run_report(&dir, "same.json", "0", true));
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let output = compare_reports(&first, &run_report(&dir, "perturbed.json", "1", true));
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let links: Vec<_> = stdout
+        .lines()
+        .map(|l| l.split(':').next().unwrap())
+        .collect();
+    assert_eq!(links, ["counter.out->limit.in", "limit.out->sink.in"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn lint_prints_warnings() {
+    let dir = std::env::temp_dir().join(format!("vidmod-lint-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("manifest.yml"),
+        "nodes:\n  orphan:\n    name: core::NullSink\n    args:\n      kind: U8\nlinks: []\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_vidmod-core"))
+        .arg("--lint")
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Warning: Node orphan has no links"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn watch_is_only_offered_by_run() {
+    let dir = core_project("watch-flags");
+    let rejected: &[&[&str]] = &[
+        &["--watch"],
+        &["run", "--watch", "--dot"],
+        &["run", "--watch", "--lint"],
+        &["run", "--watch", "--dry-run"],
+        &["run", "--watch", "--report-json", "report.json"],
+    ];
+    for args in rejected {
+        let output = Command::new(env!("CARGO_BIN_EXE_vidmod-core"))
+            .args(*args)
+            .arg(&dir)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_vidmod-core"))
+        .arg("run")
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,96 @@
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+use vidmod_core::spec::NodeGraph;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    Node, NodeId, NodeImpl, NodeObject, NodePorts,
+};
+
+pub fn insert<T: NodeObject + 'static>(graph: &mut NodeGraph, node: T, name: &str) -> NodeId {
+    let mut node = Node::new(node);
+    node.init();
+    graph.insert(node, name.to_owned()).unwrap()
+}
+
+pub fn link(graph: &mut NodeGraph, from: (NodeId, &str), to: (NodeId, &str)) {
+    let p1 = graph.get_pull_port(from.0, from.1).unwrap();
+    let p2 = graph.get_push_port(to.0, to.1).unwrap();
+    graph.add_link(p1, p2).unwrap();
+}
+
+/// Emits a U16 counter from 0 up to `count`
+#[node_decl]
+pub struct TestSource {
+    count:    u16,
+    next:     u16,
+    buf_size: usize,
+}
+
+impl TestSource {
+    #[node_new]
+    pub fn new(count: u16, buf_size: usize) -> Self {
+        Self {
+            count,
+            next: 0,
+            buf_size,
+        }
+    }
+}
+
+impl NodeImpl for TestSource {
+    fn init(&mut self) {
+        self.register_pullport("out", FrameKind::U16, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.next < self.count && self.outbuf_avail("out") > 0 {
+            self.outbuf_put_single("out", FrameSingle::U16(self.next));
+            self.next += 1;
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+/// Records every U16 it receives
+#[node_decl]
+pub struct TestSink {
+    buf_size: usize,
+    received: Arc<Mutex<Vec<u16>>>,
+}
+
+impl TestSink {
+    #[node_new]
+    pub fn new(buf_size: usize, received: Arc<Mutex<Vec<u16>>>) -> Self {
+        Self { buf_size, received }
+    }
+}
+
+impl NodeImpl for TestSink {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U16, self.buf_size);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = self.inbuf_avail("in");
+        if count > 0 {
+            let data = self.inbuf_get("in", count).unwrap_u16();
+            self.received.lock().unwrap().extend(data.iter());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
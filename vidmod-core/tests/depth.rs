@@ -0,0 +1,123 @@
+use std::sync::{Arc, Mutex};
+
+use vidmod_core::spec::NodeGraph;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeCore, NodeImpl, NodePorts, VidmodError,
+};
+
+mod common;
+
+use common::{insert, link};
+
+/// Emits 4 samples of the largest value with `depth` significant bits
+#[node_decl]
+struct ScanSource {
+    depth:     u8,
+    remaining: usize,
+}
+
+impl ScanSource {
+    #[node_new]
+    fn new(depth: u8) -> Self {
+        Self {
+            depth,
+            remaining: 4,
+        }
+    }
+}
+
+impl NodeImpl for ScanSource {
+    fn init(&mut self) {
+        self.register_pullport_with_depth("out", FrameKind::U16, 4, self.depth);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.remaining > 0 && self.outbuf_avail("out") > 0 {
+            let max = (1u32 << self.depth) - 1;
+            self.outbuf_put_single("out", FrameSingle::U16(max as u16));
+            self.remaining -= 1;
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+/// Records the depth of its input, optionally declaring one of its own
+#[node_decl]
+struct DepthSink {
+    depth: Option<u8>,
+    seen:  Arc<Mutex<Option<u8>>>,
+}
+
+impl DepthSink {
+    #[node_new]
+    fn new(depth: Option<u8>, seen: Arc<Mutex<Option<u8>>>) -> Self {
+        Self { depth, seen }
+    }
+}
+
+impl NodeImpl for DepthSink {
+    fn init(&mut self) {
+        match self.depth {
+            Some(depth) => self.register_pushport_with_depth("in", FrameKind::U16, 4, depth),
+            None => self.register_pushport("in", FrameKind::U16, 4),
+        }
+    }
+
+    fn tick(&mut self) -> bool {
+        *self.seen.lock().unwrap() = self.inbuf_bit_depth("in");
+        let count = self.inbuf_avail("in");
+        self.inbuf_get("in", count);
+        count > 0
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+fn run_with(source: u8, sink: Option<u8>) -> (Option<u8>, Vec<VidmodError>) {
+    let seen = Arc::new(Mutex::new(None));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, ScanSource::new(source), "scan");
+    let sink = insert(&mut graph, DepthSink::new(sink, seen.clone()), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph.run();
+    let res = *seen.lock().unwrap();
+    (res, graph.warnings().to_vec())
+}
+
+#[test]
+fn depth_negotiated_at_attach() {
+    assert_eq!(run_with(10, None), (Some(10), vec![]));
+}
+
+#[test]
+fn depth_mismatch_is_a_warning() {
+    assert_eq!(
+        run_with(10, Some(12)),
+        (
+            Some(12),
+            vec![VidmodError::BitDepthMismatch {
+                from:       ("scan".to_owned(), "out".to_owned()),
+                to:         ("sink".to_owned(), "in".to_owned()),
+                from_depth: 10,
+                to_depth:   12,
+            }]
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid bit depth 10 for U8")]
+fn depth_needs_u16_kind() {
+    let mut node = NodeCore::new();
+    node.register_pullport_with_depth("out", FrameKind::U8, 4, 10);
+}
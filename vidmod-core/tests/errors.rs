@@ -0,0 +1,209 @@
+use std::{collections::BTreeMap, fs, fs::File, panic};
+
+use vidmod_core::{
+    nodes::CounterSource,
+    spec::{manifest::ManifestLink, NodeGraph, Project},
+};
+use vidmod_node::{
+    frame::{Frame, FrameKind},
+    limvecdeque::LimVecDeque,
+    NodeCore, PortDirection, VidmodError,
+};
+
+mod common;
+
+use common::{insert, TestSink, TestSource};
+
+#[test]
+fn bad_port_name() {
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(1, 1), "src");
+    match graph.get_pull_port(src, "nope") {
+        Err(VidmodError::PortNotFound {
+            node,
+            port,
+            direction,
+        }) => {
+            assert_eq!(node, Some(src.index()));
+            assert_eq!(port, "nope");
+            assert_eq!(direction, Some(PortDirection::Pull));
+        }
+        res => panic!("Unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn kind_mismatch() {
+    let mut params = BTreeMap::new();
+    params.insert("kind".to_owned(), "U8".to_owned());
+    params.insert("count".to_owned(), "1".to_owned());
+
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, CounterSource::new(params), "src");
+    let sink = insert(&mut graph, TestSink::new(1, Default::default()), "sink");
+    let p1 = graph.get_pull_port(src, "out").unwrap();
+    let p2 = graph.get_push_port(sink, "in").unwrap();
+    let err = graph.add_link(p1, p2).unwrap_err();
+    assert_eq!(
+        err,
+        VidmodError::KindMismatch {
+            port:      Some("out".to_owned()),
+            direction: Some(PortDirection::Pull),
+            expected:  FrameKind::U8,
+            got:       FrameKind::U16,
+        }
+    );
+
+    let err: anyhow::Error = err.into();
+    assert!(err.downcast_ref::<VidmodError>().is_some());
+}
+
+#[test]
+fn missing_push_port() {
+    let mut graph = NodeGraph::new();
+    let sink = insert(&mut graph, TestSink::new(1, Default::default()), "sink");
+    assert_eq!(
+        graph.get_push_port(sink, "out").unwrap_err(),
+        VidmodError::PortNotFound {
+            node:      Some(sink.index()),
+            port:      "out".to_owned(),
+            direction: Some(PortDirection::Push),
+        }
+    );
+}
+
+#[test]
+fn buffer_misuse_variants() {
+    let mut node = NodeCore::new();
+    node.set_lenient(true);
+    node.register_pullport("out", FrameKind::U8, 2);
+    node.register_pushport("in", FrameKind::U8, 2);
+
+    node.outbuf_put("out", Frame::U8(LimVecDeque::from(vec![1, 2, 3])));
+    assert_eq!(
+        node.take_error(),
+        Some(VidmodError::BufferFull {
+            port:     "out".to_owned(),
+            capacity: 2,
+        })
+    );
+    node.inbuf_get("in", 1);
+    assert_eq!(
+        node.take_error(),
+        Some(VidmodError::NotEnoughFrames {
+            port:      "in".to_owned(),
+            wanted:    1,
+            available: 0,
+        })
+    );
+    node.inbuf_avail("out");
+    assert_eq!(
+        node.take_error(),
+        Some(VidmodError::PortNotFound {
+            node:      None,
+            port:      "out".to_owned(),
+            direction: Some(PortDirection::Push),
+        })
+    );
+    assert!(node.is_port_empty("nope"));
+    assert_eq!(
+        node.take_error(),
+        Some(VidmodError::PortNotFound {
+            node:      None,
+            port:      "nope".to_owned(),
+            direction: None,
+        })
+    );
+}
+
+// The panic message from loading a manifest with a single link
+fn link_failure(from: &str, to: &str) -> String {
+    let dir = std::env::temp_dir().join(format!(
+        "vidmod-link-error-{}-{}",
+        std::process::id(),
+        from.replace('.', "-")
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    let (from, to): (Vec<&str>, Vec<&str>) = (from.split('.').collect(), to.split('.').collect());
+    fs::write(
+        dir.join("manifest.yml"),
+        format!(
+            r#"
+nodes:
+  src:
+    name: core::CounterSource
+    args:
+      kind: U8
+      count: 1
+  lim:
+    name: core::Limit
+    args:
+      kind: U16
+      count: 1
+links:
+  - from: [{}, {}]
+    to: [{}, {}]
+"#,
+            from[0], from[1], to[0], to[1]
+        ),
+    )
+    .unwrap();
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let path = dir.clone();
+    let payload = panic::catch_unwind(|| Project::load(manifest, path)).unwrap_err();
+    fs::remove_dir_all(&dir).unwrap();
+    payload.downcast_ref::<String>().unwrap().clone()
+}
+
+#[test]
+fn manifest_link_messages() {
+    assert_eq!(
+        link_failure("src.out", "lim.in"),
+        "Cannot link src.out -> lim.in: src produces U8 but lim takes U16"
+    );
+    assert_eq!(
+        link_failure("src.nope", "lim.in"),
+        "Cannot link src.nope -> lim.in: src has no output nope"
+    );
+    assert_eq!(
+        link_failure("src.out", "lim.nope"),
+        "Cannot link src.out -> lim.nope: lim has no input nope"
+    );
+    assert_eq!(
+        link_failure("src.out", "other.in"),
+        "Link src.out -> other.in uses unknown node other"
+    );
+}
+
+#[test]
+fn kind_mismatch_explained_from_either_side() {
+    // Both ends share a port name, so only the direction tells them apart
+    let link = ManifestLink {
+        from: ("src".to_owned(), "data".to_owned()),
+        to:   ("sink".to_owned(), "data".to_owned()),
+        lazy: false,
+    };
+    let mismatch = |direction, expected, got| VidmodError::KindMismatch {
+        port: Some("data".to_owned()),
+        direction: Some(direction),
+        expected,
+        got,
+    };
+    let explained = "Cannot link src.data -> sink.data: src produces U8 but sink takes U16";
+    assert_eq!(
+        link.explain(&mismatch(
+            PortDirection::Pull,
+            FrameKind::U8,
+            FrameKind::U16
+        )),
+        explained
+    );
+    assert_eq!(
+        link.explain(&mismatch(
+            PortDirection::Push,
+            FrameKind::U16,
+            FrameKind::U8
+        )),
+        explained
+    );
+}
@@ -0,0 +1,179 @@
+use std::{collections::BTreeMap, fs, fs::File};
+
+use ndarray::arr2;
+use vidmod_core::{expr::Expr, nodes, spec::Project};
+use vidmod_node::{
+    frame::{Frame, FrameSingle},
+    NodeImpl, NodePorts,
+};
+
+fn params(args: &[(&str, &str)]) -> BTreeMap<String, String> {
+    args.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn eval(src: &str, vars: &[(&str, f64)]) -> f64 {
+    let names: Vec<&str> = vars.iter().map(|(name, _)| *name).collect();
+    let values: Vec<f64> = vars.iter().map(|(_, v)| *v).collect();
+    Expr::parse(src, &names).unwrap().eval(&values)
+}
+
+fn parse_error(src: &str) -> String {
+    Expr::parse(src, &["a", "b"]).unwrap_err().to_string()
+}
+
+fn single(frame: FrameSingle) -> Frame {
+    let mut res = Frame::with_capacity((&frame).into(), 1);
+    res.add_single(frame).unwrap();
+    res
+}
+
+fn run(node: &mut nodes::Expr, inputs: Vec<(&str, FrameSingle)>) -> FrameSingle {
+    node.init();
+    for (name, frame) in inputs {
+        let port = node.get_push_port(0.into(), name).unwrap();
+        node.push_frame(&port, single(frame));
+    }
+    assert!(node.tick());
+    let port = node.get_pull_port(0.into(), "out").unwrap();
+    node.pull_frame(&port, 1).remove_single().unwrap()
+}
+
+#[test]
+fn precedence_and_functions() {
+    assert_eq!(eval("2 + 3 * 4 ^ 2", &[]), 50.0);
+    assert_eq!(eval("-2 ^ 2", &[]), -4.0);
+    assert_eq!(eval("2 ^ 3 ^ 2", &[]), 512.0);
+    assert_eq!(eval("(1 + 2) * 3 - 7 % 4", &[]), 6.0);
+    assert_eq!(
+        eval("clamp(a * 1.5 + b, 0, 1)", &[("a", 0.5), ("b", 0.25)]),
+        1.0
+    );
+    assert_eq!(
+        eval("max(a, b) - min(a, b)", &[("a", 2.0), ("b", 5.0)]),
+        3.0
+    );
+}
+
+#[test]
+fn parse_errors_give_position() {
+    assert_eq!(parse_error("a + c"), "Unknown variable c at position 4");
+    assert_eq!(parse_error("a * (b + 1"), "Expected ')' at position 10");
+    assert_eq!(parse_error("a $ b"), "Unexpected '$' at position 2");
+    assert_eq!(
+        parse_error("clamp(a, 1)"),
+        "clamp takes 3 arguments, got 2 at position 0"
+    );
+    assert_eq!(
+        parse_error("a b"),
+        "Unexpected trailing input at position 2"
+    );
+    assert_eq!(
+        parse_error(""),
+        "Unexpected end of expression at position 0"
+    );
+}
+
+#[test]
+fn scalar_inputs() {
+    let mut node = nodes::Expr::new(params(&[
+        ("expr", "clamp(a * 1.5 + b, 0, 1)"),
+        ("inputs", "a:F32, b:F32"),
+        ("kind", "F32"),
+    ]));
+    let out = run(
+        &mut node,
+        vec![
+            ("a", FrameSingle::F32(0.25)),
+            ("b", FrameSingle::F32(0.125)),
+        ],
+    );
+    assert_eq!(out.unwrap_f32(), 0.5);
+}
+
+#[test]
+fn broadcasts_scalars_over_2d_inputs() {
+    let mut node = nodes::Expr::new(params(&[
+        ("expr", "a * 2 + b"),
+        ("inputs", "a:U8x2,b:U8"),
+        ("kind", "U8x2"),
+    ]));
+    let out = run(
+        &mut node,
+        vec![
+            (
+                "a",
+                FrameSingle::U8x2(arr2(&[[0, 10], [100, 200]]).into_shared()),
+            ),
+            ("b", FrameSingle::U8(1)),
+        ],
+    );
+    // Integer outputs saturate
+    assert_eq!(out.unwrap_u8x2(), arr2(&[[1, 21], [201, 255]]));
+}
+
+#[test]
+fn division_by_zero() {
+    let inputs = || {
+        vec![
+            (
+                "a",
+                FrameSingle::F32x1(ndarray::arr1(&[1.0, 0.0, -1.0]).into_shared()),
+            ),
+            ("b", FrameSingle::F32(0.0)),
+        ]
+    };
+    let mut node = nodes::Expr::new(params(&[
+        ("expr", "a / b"),
+        ("inputs", "a:F32x1,b:F32"),
+        ("kind", "F32x1"),
+    ]));
+    let out = run(&mut node, inputs()).unwrap_f32x1();
+    assert_eq!(out[0], f32::INFINITY);
+    assert!(out[1].is_nan());
+    assert_eq!(out[2], f32::NEG_INFINITY);
+
+    // Integer outputs saturate infinities and map NaN to zero
+    let mut node = nodes::Expr::new(params(&[
+        ("expr", "a / b"),
+        ("inputs", "a:F32x1,b:F32"),
+        ("kind", "U16x1"),
+    ]));
+    let out = run(&mut node, inputs()).unwrap_u16x1();
+    assert_eq!(out.to_vec(), vec![65535, 0, 0]);
+}
+
+#[test]
+#[should_panic(expected = "Unknown function clmap at position 0")]
+fn invalid_expression_rejected_at_load() {
+    let dir = std::env::temp_dir().join(format!("vidmod-test-expr-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("manifest.yml"),
+        r#"
+nodes:
+  expr:
+    name: core::Expr
+    args:
+      expr: clmap(a, 0, 1)
+      inputs: a:F32
+      kind: F32
+links: []
+"#,
+    )
+    .unwrap();
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+    Project::load_with(manifest, dir, &nodes::registry());
+}
+
+#[test]
+#[should_panic(expected = "Expr input a does not match output F32")]
+fn array_input_needs_array_output() {
+    nodes::Expr::new(params(&[
+        ("expr", "a"),
+        ("inputs", "a:F32x2"),
+        ("kind", "F32"),
+    ]));
+}
@@ -0,0 +1,262 @@
+use std::{
+    collections::BTreeSet,
+    iter::FromIterator,
+    sync::{Arc, Mutex},
+};
+
+use vidmod_core::{bench::bench_pipeline, spec::NodeGraph};
+use vidmod_macros::test_source;
+use vidmod_node::{Node, VidmodError};
+
+mod common;
+
+use common::{insert, link, TestSink, TestSource};
+
+test_source!(Squares, U16, |i: usize| if i < 20 {
+    Some((i * i) as u16)
+} else {
+    None
+});
+
+#[test]
+fn connected_components_split_disjoint_pipelines() {
+    let mut graph = NodeGraph::new();
+    let mut pair = |name: &str| {
+        let source = insert(
+            &mut graph,
+            TestSource::new(4, 4),
+            &format!("{}_source", name),
+        );
+        let sink = insert(
+            &mut graph,
+            TestSink::new(4, Arc::new(Mutex::new(Vec::new()))),
+            &format!("{}_sink", name),
+        );
+        link(&mut graph, (source, "out"), (sink, "in"));
+        (source, sink)
+    };
+    let (a_source, a_sink) = pair("a");
+    let (b_source, b_sink) = pair("b");
+
+    assert_eq!(
+        graph.connected_components(),
+        vec![
+            BTreeSet::from_iter(vec![a_source, a_sink]),
+            BTreeSet::from_iter(vec![b_source, b_sink]),
+        ]
+    );
+}
+
+#[test]
+fn to_dot_lists_nodes_and_links() {
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(4, 4), "source");
+    let sink = insert(
+        &mut graph,
+        TestSink::new(4, Arc::new(Mutex::new(Vec::new()))),
+        "sink",
+    );
+    link(&mut graph, (source, "out"), (sink, "in"));
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph {"));
+    assert!(dot.contains("0 [label=\"source\"];"));
+    assert!(dot.contains("1 [label=\"sink\"];"));
+    assert!(dot.contains("0 -> 1 [taillabel=\"out\", headlabel=\"in\"];"));
+}
+
+#[test]
+fn link_batching_leaves_ticking_to_nodes() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(10, 1), "source");
+    let sink = insert(&mut graph, TestSink::new(1000, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+    graph.set_link_batching(true);
+
+    // Only what the source made in its own tick moves; the link doesn't tick it for more
+    graph.tick_nodes(Some(&BTreeSet::from_iter([source])));
+    assert!(graph.tick_links());
+    graph.tick_nodes(Some(&BTreeSet::from_iter([sink])));
+    assert_eq!(*received.lock().unwrap(), vec![0]);
+
+    graph.run();
+    assert_eq!(*received.lock().unwrap(), (0..10).collect::<Vec<u16>>());
+}
+
+#[test]
+fn without_link_batching_one_frame_per_sweep() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(10, 1), "source");
+    let sink = insert(&mut graph, TestSink::new(1000, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+
+    graph.tick_nodes(Some(&BTreeSet::from_iter([source])));
+    assert!(graph.tick_links());
+    graph.tick_nodes(Some(&BTreeSet::from_iter([sink])));
+    assert_eq!(*received.lock().unwrap(), vec![0]);
+}
+
+#[test]
+fn bench_pipeline_counts_delivered_frames() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let nodes = vec![
+        Node::new(TestSource::new(40, 8)),
+        Node::new(TestSink::new(8, received.clone())),
+    ];
+    let frames = bench_pipeline(nodes, 100);
+    assert_eq!(frames, 40);
+    assert_eq!(received.lock().unwrap().len(), 40);
+}
+
+#[test]
+fn test_source_macro_runs_in_graph() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, Squares::new(), "squares");
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+    graph.run();
+
+    let expected: Vec<u16> = (0..20).map(|i| i * i).collect();
+    assert_eq!(*received.lock().unwrap(), expected);
+}
+
+#[test]
+fn removed_node_handles_are_stale() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let mut graph = NodeGraph::new();
+    let old = insert(&mut graph, TestSource::new(4, 4), "old");
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    let old_out = graph.get_pull_port(old, "out").unwrap();
+    link(&mut graph, (old, "out"), (sink, "in"));
+
+    graph.remove(old).unwrap();
+    assert!(graph.link_ids().is_empty());
+    let new = insert(&mut graph, TestSource::new(2, 4), "new");
+    assert_eq!(new.index(), old.index());
+    assert_ne!(new, old);
+
+    let stale = VidmodError::StaleHandle {
+        node: old,
+        port: Some("out".to_owned()),
+    };
+    assert_eq!(graph.get_pull_port(old, "out").unwrap_err(), stale);
+    let sink_in = graph.get_push_port(sink, "in").unwrap();
+    assert_eq!(graph.add_link(old_out, sink_in).unwrap_err(), stale);
+    assert_eq!(
+        stale.to_string(),
+        "Stale port handle out on node 0 (generation 0), which has been removed"
+    );
+    assert!(graph.remove(old).is_err());
+
+    link(&mut graph, (new, "out"), (sink, "in"));
+    graph.run();
+    assert_eq!(*received.lock().unwrap(), vec![0, 1]);
+}
+
+#[test]
+fn nodes_resolve_by_name() {
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(4, 4), "source");
+    let sink = insert(&mut graph, TestSink::new(4, Default::default()), "sink");
+
+    assert_eq!(graph.find("source"), Some(source));
+    assert_eq!(graph.find("sink"), Some(sink));
+    assert_eq!(graph.find("missing"), None);
+    assert_eq!(graph.node_name(source), Some("source"));
+    assert_eq!(graph.node_name(sink), Some("sink"));
+
+    graph.remove(source).unwrap();
+    assert_eq!(graph.find("source"), None);
+    assert_eq!(graph.node_name(source), None);
+    let other = insert(&mut graph, TestSource::new(4, 4), "other");
+    assert_eq!(graph.node_name(other), Some("other"));
+}
+
+#[test]
+fn duplicate_names_are_rejected() {
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(4, 4), "source");
+    let sink = insert(&mut graph, TestSink::new(4, Default::default()), "sink");
+
+    let mut node = Node::new(TestSource::new(4, 4));
+    node.init();
+    assert_eq!(
+        graph.insert(node, "source".to_owned()),
+        Err(VidmodError::DuplicateNodeName {
+            node: "source".to_owned(),
+        })
+    );
+    assert_eq!(graph.find("source"), Some(source));
+
+    assert_eq!(
+        graph.rename(sink, "source".to_owned()),
+        Err(VidmodError::DuplicateNodeName {
+            node: "source".to_owned(),
+        })
+    );
+    graph.rename(sink, "sink".to_owned()).unwrap();
+    graph.rename(sink, "output".to_owned()).unwrap();
+    assert_eq!(graph.find("sink"), None);
+    assert_eq!(graph.find("output"), Some(sink));
+    assert_eq!(graph.node_name(sink), Some("output"));
+
+    graph.remove(source).unwrap();
+    assert!(graph.rename(source, "gone".to_owned()).is_err());
+    insert(&mut graph, TestSource::new(4, 4), "source");
+}
+
+#[test]
+fn paused_graph_moves_no_frames() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(10, 2), "source");
+    let sink = insert(&mut graph, TestSink::new(1000, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+
+    for _ in 0..3 {
+        assert!(graph.tick());
+    }
+    let before = received.lock().unwrap().clone();
+    assert!(!before.is_empty() && before.len() < 10);
+
+    graph.pause();
+    assert!(graph.is_paused());
+    for _ in 0..10 {
+        assert!(!graph.tick());
+    }
+    assert_eq!(*received.lock().unwrap(), before);
+
+    graph.resume();
+    while graph.tick() {}
+    assert_eq!(*received.lock().unwrap(), (0..10).collect::<Vec<u16>>());
+}
+
+#[test]
+fn idle_counters_age_once_source_stops() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(4, 8), "source");
+    let sink = insert(&mut graph, TestSink::new(8, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+
+    // Pass 1 fills the source's buffer, pass 2 moves it along the link and pass 3 drains it
+    for _ in 0..10 {
+        graph.tick();
+    }
+    assert_eq!(*received.lock().unwrap(), vec![0, 1, 2, 3]);
+    assert_eq!(
+        graph.idle_ports(),
+        vec![("source.out".to_owned(), 9), ("sink.in".to_owned(), 7)]
+    );
+    assert_eq!(graph.last_progress(source), 1);
+    assert_eq!(graph.last_progress(sink), 3);
+}
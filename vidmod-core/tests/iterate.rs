@@ -0,0 +1,170 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use vidmod_core::{
+    nodes::{self, Iterate},
+    spec::Project,
+};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{Frame, FrameKind, FrameSingle},
+    limvecdeque::LimVecDeque,
+    params::PATH_ARG,
+    Node, NodeImpl, NodePorts,
+};
+use vidmod_plugin::PluginRegistry;
+
+/// Halves each U16 from "in" onto "out", flagging on "done" once the result is at most 1
+#[node_decl]
+struct Halve {}
+
+impl Halve {
+    #[node_new]
+    fn new(_: BTreeMap<String, String>) -> Self {
+        Self {}
+    }
+}
+
+impl NodeImpl for Halve {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U16, 4);
+        self.register_pullport("out", FrameKind::U16, 4);
+        self.register_pullport("done", FrameKind::U8, 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        let space = usize::min(self.outbuf_avail("out"), self.outbuf_avail("done"));
+        if self.inbuf_avail("in") == 0 || space == 0 {
+            return false;
+        }
+        let half = self.inbuf_get_single("in").unwrap_u16() / 2;
+        self.outbuf_put_single("out", FrameSingle::U16(half));
+        self.outbuf_put_single("done", FrameSingle::U8((half <= 1) as u8));
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+fn registry() -> PluginRegistry {
+    let mut registry = nodes::registry();
+    registry.register("test::Halve", |params: BTreeMap<String, String>| {
+        Node::new(Halve::new(params))
+    });
+    registry
+}
+
+fn project_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vidmod-test-iterate-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("pass.yml"),
+        r#"
+nodes:
+  halve:
+    name: test::Halve
+links: []
+"#,
+    )
+    .unwrap();
+    dir
+}
+
+fn iterate(dir: &Path, max_iters: usize) -> Iterate {
+    let mut params = BTreeMap::new();
+    for (key, value) in [
+        ("file", "pass.yml"),
+        ("input", "halve.in"),
+        ("output", "halve.out"),
+        ("done_port", "halve.done"),
+    ] {
+        params.insert(key.to_owned(), value.to_owned());
+    }
+    params.insert("max_iters".to_owned(), max_iters.to_string());
+    params.insert(PATH_ARG.to_owned(), dir.to_str().unwrap().to_owned());
+    let mut node = Iterate::load(params, &registry());
+    node.init();
+    node
+}
+
+fn run(node: &mut Iterate, value: u16) -> u16 {
+    let port = node.get_push_port(0.into(), "in").unwrap();
+    node.push_frame(&port, Frame::U16(LimVecDeque::from(vec![value])));
+    while node.tick() {}
+    let port = node.get_pull_port(0.into(), "out").unwrap();
+    node.pull_frame(&port, 1)
+        .remove_single()
+        .unwrap()
+        .unwrap_u16()
+}
+
+#[test]
+fn halving_converges() {
+    let dir = project_dir("converges");
+    let mut node = iterate(&dir, 10);
+    // 100, 50, 25, 12, 6, 3, 1
+    assert_eq!(run(&mut node, 100), 1);
+    assert_eq!(run(&mut node, 2), 1);
+    assert_eq!(node.iterations(), &[6, 1]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn max_iters_caps_passes() {
+    let dir = project_dir("capped");
+    let mut node = iterate(&dir, 3);
+    assert_eq!(run(&mut node, 100), 12);
+    assert_eq!(node.iterations(), &[3]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn iterate_type_in_manifest() {
+    let dir = project_dir("manifest");
+    fs::write(
+        dir.join("manifest.yml"),
+        r#"
+nodes:
+  counter:
+    name: core::CounterSource
+    args:
+      kind: U16
+      start: '100'
+      step: '100'
+      count: '2'
+  pass:
+    type: iterate
+    args:
+      file: pass.yml
+      input: halve.in
+      output: halve.out
+      max_iters: '5'
+      done_port: halve.done
+  sink:
+    name: core::RawFileSink
+    args:
+      kind: U16
+      file: out.raw
+links:
+  - from: [counter, out]
+    to: [pass, in]
+  - from: [pass, out]
+    to: [sink, in]
+"#,
+    )
+    .unwrap();
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let mut project = Project::load_with(manifest, dir.clone(), &registry());
+    project.run();
+
+    // Neither converges within 5 passes, 100 reaching 3 and 200 reaching 6
+    assert_eq!(fs::read(dir.join("out.raw")).unwrap(), vec![3, 0, 6, 0]);
+    fs::remove_dir_all(&dir).unwrap();
+}
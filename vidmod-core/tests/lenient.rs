@@ -0,0 +1,99 @@
+use std::{collections::BTreeMap, fs::File};
+
+use vidmod_core::spec::{NodeGraph, Project};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    Node, NodeImpl, NodePorts, PortDirection, VidmodError,
+};
+use vidmod_plugin::PluginRegistry;
+
+mod common;
+
+use common::insert;
+
+/// Writes to a misspelled port
+#[node_decl]
+struct Buggy {}
+
+impl Buggy {
+    #[node_new]
+    fn new() -> Self {
+        Self {}
+    }
+}
+
+impl NodeImpl for Buggy {
+    fn init(&mut self) {
+        self.register_pullport("out", FrameKind::U8, 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        self.outbuf_put_single("output", FrameSingle::U8(1));
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+fn run(lenient: bool) -> Result<(), VidmodError> {
+    let mut graph = NodeGraph::new();
+    let id = insert(&mut graph, Buggy::new(), "buggy");
+    graph.set_lenient(id, lenient);
+    graph.try_run()
+}
+
+#[test]
+fn strict_mode_aborts() {
+    assert_eq!(
+        run(false),
+        Err(VidmodError::NodePanicked {
+            node:    "buggy".to_owned(),
+            message: "No pull port: output".to_owned(),
+        })
+    );
+}
+
+#[test]
+fn lenient_mode_reports_error() {
+    assert_eq!(
+        run(true),
+        Err(VidmodError::NodeFailed {
+            node:  "buggy".to_owned(),
+            error: Box::new(VidmodError::PortNotFound {
+                node:      None,
+                port:      "output".to_owned(),
+                direction: Some(PortDirection::Pull),
+            }),
+        })
+    );
+}
+
+#[test]
+fn lenient_from_manifest() {
+    let dir = std::env::temp_dir().join("vidmod-test-lenient");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("manifest.yml"),
+        r#"
+nodes:
+  buggy:
+    name: test::Buggy
+    args:
+      vidmod.lenient: 'true'
+links: []
+"#,
+    )
+    .unwrap();
+    let mut registry = PluginRegistry::new();
+    registry.register("test::Buggy", |_: BTreeMap<String, String>| {
+        Node::new(Buggy::new())
+    });
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let mut project = Project::load_with(manifest, dir.clone(), &registry);
+    let err = project.try_run().unwrap_err();
+    assert_eq!(err.to_string(), "Node buggy failed: No pull port: output");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
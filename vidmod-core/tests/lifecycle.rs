@@ -0,0 +1,126 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{bail, Result};
+use vidmod_core::spec::NodeGraph;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, VidmodError,
+};
+
+mod common;
+
+use common::{insert, link, TestSink};
+
+/// Stands in for an external resource, logging who holds it
+#[derive(Debug, Default)]
+struct Device {
+    open: bool,
+    log:  Vec<&'static str>,
+}
+
+/// Emits 4 frames, which it can only do while its device is open
+#[node_decl]
+struct DeviceSource {
+    device:    Arc<Mutex<Device>>,
+    fail:      bool,
+    remaining: u16,
+}
+
+impl DeviceSource {
+    #[node_new]
+    fn new(device: Arc<Mutex<Device>>, fail: bool) -> Self {
+        Self {
+            device,
+            fail,
+            remaining: 4,
+        }
+    }
+}
+
+impl NodeImpl for DeviceSource {
+    fn init(&mut self) {
+        self.register_pullport("out", FrameKind::U16, 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        {
+            let mut device = self.device.lock().unwrap();
+            assert!(device.open, "Ticked while the device is closed");
+            device.log.push("tick");
+        }
+        let mut res = false;
+        while self.remaining > 0 && self.outbuf_avail("out") > 0 {
+            self.outbuf_put_single("out", FrameSingle::U16(self.remaining));
+            self.remaining -= 1;
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+
+    fn start(&mut self) -> Result<()> {
+        if self.fail {
+            bail!("no device");
+        }
+        let mut device = self.device.lock().unwrap();
+        device.open = true;
+        device.log.push("start");
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        let mut device = self.device.lock().unwrap();
+        device.open = false;
+        device.log.push("stop");
+        Ok(())
+    }
+}
+
+#[test]
+fn device_released_in_stop() {
+    let device = Arc::new(Mutex::new(Device::default()));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let source = insert(
+        &mut graph,
+        DeviceSource::new(device.clone(), false),
+        "source",
+    );
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+    graph.run();
+
+    assert_eq!(*received.lock().unwrap(), vec![4, 3, 2, 1]);
+    let device = device.lock().unwrap();
+    assert!(!device.open);
+    assert_eq!(device.log.first(), Some(&"start"));
+    assert_eq!(device.log.last(), Some(&"stop"));
+}
+
+#[test]
+fn failed_start_stops_started_nodes() {
+    let first = Arc::new(Mutex::new(Device::default()));
+    let second = Arc::new(Mutex::new(Device::default()));
+    let mut graph = NodeGraph::new();
+    insert(&mut graph, DeviceSource::new(first.clone(), false), "first");
+    insert(
+        &mut graph,
+        DeviceSource::new(second.clone(), true),
+        "second",
+    );
+
+    let err = graph.try_run().unwrap_err();
+    assert_eq!(
+        err,
+        VidmodError::StartFailed {
+            node:    "second".to_owned(),
+            message: "no device".to_owned(),
+        }
+    );
+    assert_eq!(first.lock().unwrap().log, vec!["start", "stop"]);
+    assert!(second.lock().unwrap().log.is_empty());
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use vidmod_core::{
+    nodes::{Expr, Lut, RateConvert},
+    spec::{NodeGraph, MESSAGE_QUEUE},
+};
+use vidmod_node::{frame::Frame, limvecdeque::LimVecDeque, NodeId, VidmodError};
+
+mod common;
+
+use common::insert;
+
+fn params(args: &[(&str, &str)]) -> BTreeMap<String, String> {
+    args.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn rate_graph() -> (NodeGraph, NodeId) {
+    let params = params(&[("kind", "U16"), ("mode", "drop"), ("ratio", "1/1")]);
+    let mut graph = NodeGraph::new();
+    let rate = insert(&mut graph, RateConvert::new(params), "rate");
+    (graph, rate)
+}
+
+// Push `values` to a U16 node's "in", tick it once and return what it put on "out"
+fn step(graph: &mut NodeGraph, node: NodeId, values: Vec<u16>) -> Vec<u16> {
+    let input = graph.get_push_port(node, "in").unwrap();
+    graph.push_frame(&input, Frame::U16(LimVecDeque::from(values)));
+    graph.tick_nodes(None);
+    let output = graph.get_pull_port(node, "out").unwrap();
+    let count = graph.ready_to_pull(&output);
+    let res = graph.pull_frame(&output, count).unwrap_u16();
+    res.iter().copied().collect()
+}
+
+#[test]
+fn ratio_change_applies_from_next_tick() {
+    let (mut graph, rate) = rate_graph();
+    assert_eq!(step(&mut graph, rate, vec![0, 1, 2, 3]), vec![0, 1, 2, 3]);
+    graph.send_message("rate", "ratio", "1/2").unwrap();
+    assert_eq!(step(&mut graph, rate, vec![4, 5, 6, 7]), vec![5, 7]);
+    assert!(graph.warnings().is_empty());
+}
+
+#[test]
+fn messages_delivered_in_order() {
+    let (mut graph, rate) = rate_graph();
+    graph.send_message("rate", "ratio", "1/2").unwrap();
+    graph.send_message("rate", "ratio", "1/4").unwrap();
+    assert_eq!(step(&mut graph, rate, (0..8).collect()), vec![3, 7]);
+}
+
+#[test]
+fn rejected_messages_are_warnings() {
+    let (mut graph, rate) = rate_graph();
+    graph.send_message("rate", "ratio", "2/1").unwrap();
+    graph.send_message("rate", "speed", "2").unwrap();
+    assert_eq!(step(&mut graph, rate, vec![0, 1]), vec![0, 1]);
+    assert_eq!(
+        graph.warnings(),
+        &[
+            VidmodError::MessageFailed {
+                node:    "rate".to_owned(),
+                key:     "ratio".to_owned(),
+                message: "Drop ratio 2/1 is above 1".to_owned(),
+            },
+            VidmodError::MessageFailed {
+                node:    "rate".to_owned(),
+                key:     "speed".to_owned(),
+                message: "Message speed not supported".to_owned(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn message_queue_is_bounded() {
+    let (mut graph, _) = rate_graph();
+    for _ in 0..MESSAGE_QUEUE {
+        graph.send_message("rate", "ratio", "1/1").unwrap();
+    }
+    assert_eq!(
+        graph.send_message("rate", "ratio", "1/1"),
+        Err(VidmodError::MessageQueueFull {
+            node:     "rate".to_owned(),
+            capacity: MESSAGE_QUEUE,
+        })
+    );
+    assert_eq!(
+        graph.send_message("missing", "ratio", "1/1"),
+        Err(VidmodError::NodeNameNotFound {
+            node: "missing".to_owned(),
+        })
+    );
+}
+
+#[test]
+fn expr_message_replaces_expression() {
+    let mut graph = NodeGraph::new();
+    let node = Expr::new(params(&[
+        ("kind", "U16"),
+        ("inputs", "in:U16"),
+        ("expr", "in + 1"),
+    ]));
+    let expr = insert(&mut graph, node, "expr");
+    assert_eq!(step(&mut graph, expr, vec![1, 2]), vec![2, 3]);
+    graph.send_message("expr", "expr", "in * 3").unwrap();
+    graph.send_message("expr", "expr", "in +").unwrap();
+    assert_eq!(step(&mut graph, expr, vec![1, 2]), vec![3, 6]);
+    assert_eq!(graph.warnings().len(), 1);
+}
+
+#[test]
+fn lut_message_replaces_table() {
+    let mut graph = NodeGraph::new();
+    let node = Lut::new(params(&[("kind", "U16"), ("table", "0 65535")]));
+    let lut = insert(&mut graph, node, "lut");
+    assert_eq!(step(&mut graph, lut, vec![0, 65535]), vec![0, 65535]);
+    graph.send_message("lut", "table", "65535 0").unwrap();
+    assert_eq!(step(&mut graph, lut, vec![0, 65535]), vec![65535, 0]);
+    graph.send_message("lut", "table", "0").unwrap();
+    assert_eq!(step(&mut graph, lut, vec![0]), vec![65535]);
+    assert_eq!(
+        graph.warnings(),
+        &[VidmodError::MessageFailed {
+            node:    "lut".to_owned(),
+            key:     "table".to_owned(),
+            message: "U16 LUT needs 2 to 65536 entries, got 1".to_owned(),
+        }]
+    );
+}
@@ -0,0 +1,71 @@
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use vidmod_core::{
+    meter::{BufferPressure, ManualClock},
+    spec::NodeGraph,
+};
+
+mod common;
+
+use common::{insert, link, TestSink, TestSource};
+
+// Move 10 frames per step: 10 steps 100ms apart, then 5 steps 200ms apart
+#[test]
+fn rates_match_frames_moved() {
+    let clock = ManualClock::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(1000, 10), "source");
+    let sink = insert(&mut graph, TestSink::new(10, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+    graph.set_meter_clock(Box::new(clock.clone()));
+
+    for step in 0..15 {
+        graph.tick_nodes(None);
+        graph.tick_links();
+        clock.advance(Duration::from_millis(if step < 10 { 100 } else { 200 }));
+    }
+
+    assert_eq!(received.lock().unwrap().len(), 140);
+    let from = "source.out".to_owned();
+    let to = "sink.in".to_owned();
+    // 150 frames over 2s, the last 50 over the second half
+    assert_eq!(graph.link_rates(), vec![(from.clone(), to.clone(), 75.0)]);
+    assert_eq!(graph.instant_link_rates(), vec![(from, to, 50.0)]);
+}
+
+// The sink only runs every fourth step, so the source fills its buffer in between
+#[test]
+fn slow_sink_registers_full_buffers() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(1000, 8), "source");
+    let sink = insert(&mut graph, TestSink::new(8, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+    let source_only: BTreeSet<_> = vec![source].into_iter().collect();
+
+    for step in 0..20 {
+        if step % 4 == 0 {
+            graph.tick_nodes(None);
+        } else {
+            graph.tick_nodes(Some(&source_only));
+        }
+        graph.tick_links();
+    }
+
+    assert_eq!(
+        graph.buffer_pressure(),
+        vec![(
+            "source.out".to_owned(),
+            "sink.in".to_owned(),
+            BufferPressure {
+                empty: 0,
+                full:  15,
+            }
+        )]
+    );
+}
@@ -0,0 +1,141 @@
+use std::sync::{Arc, Mutex};
+
+use vidmod_core::spec::NodeGraph;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts,
+};
+
+mod common;
+
+use common::{insert, link, TestSource};
+
+/// Emits `count` copies of a single value
+#[node_decl]
+struct ConstSource {
+    value: FrameSingle,
+    count: usize,
+}
+
+impl ConstSource {
+    #[node_new]
+    fn new(value: FrameSingle, count: usize) -> Self {
+        Self { value, count }
+    }
+}
+
+impl NodeImpl for ConstSource {
+    fn init(&mut self) {
+        let kind = match self.value {
+            FrameSingle::U8(_) => FrameKind::U8,
+            FrameSingle::F32(_) => FrameKind::F32,
+            _ => unimplemented!(),
+        };
+        self.register_pullport("out", kind, 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.count > 0 && self.outbuf_avail("out") > 0 {
+            self.outbuf_put_single("out", self.value.clone());
+            self.count -= 1;
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+/// Counts frames of any scalar kind
+#[node_decl]
+struct AnySink {
+    seen: Arc<Mutex<(Option<FrameKind>, usize)>>,
+}
+
+impl AnySink {
+    #[node_new]
+    fn new(seen: Arc<Mutex<(Option<FrameKind>, usize)>>) -> Self {
+        Self { seen }
+    }
+}
+
+impl NodeImpl for AnySink {
+    fn init(&mut self) {
+        self.register_pushport_any("in", &[FrameKind::U8, FrameKind::F32], 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = self.inbuf_avail("in");
+        if count > 0 {
+            let frame = self.inbuf_get("in", count);
+            let mut seen = self.seen.lock().unwrap();
+            seen.0 = self.inbuf_kind("in");
+            seen.1 += frame.size();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+fn run_with(value: FrameSingle) -> (Option<FrameKind>, usize) {
+    let seen = Arc::new(Mutex::new((None, 0)));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, ConstSource::new(value, 10), "src");
+    let sink = insert(&mut graph, AnySink::new(seen.clone()), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph.run();
+    let res = *seen.lock().unwrap();
+    res
+}
+
+#[test]
+fn negotiates_u8() {
+    assert_eq!(run_with(FrameSingle::U8(7)), (Some(FrameKind::U8), 10));
+}
+
+#[test]
+fn negotiates_f32() {
+    assert_eq!(run_with(FrameSingle::F32(0.5)), (Some(FrameKind::F32), 10));
+}
+
+#[test]
+fn unlinked_port_has_no_kind() {
+    let mut sink = AnySink::new(Arc::new(Mutex::new((None, 0))));
+    sink.init();
+    assert_eq!(sink.inbuf_kind("in"), None);
+    assert_eq!(sink.inbuf_avail("in"), 0);
+}
+
+#[test]
+fn rejects_unaccepted_kind() {
+    let seen = Arc::new(Mutex::new((None, 0)));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(4, 4), "src");
+    let sink = insert(&mut graph, AnySink::new(seen), "sink");
+    let p1 = graph.get_pull_port(src, "out").unwrap();
+    let p2 = graph.get_push_port(sink, "in").unwrap();
+    assert!(graph.add_link(p1, p2).is_err());
+}
+
+#[test]
+fn first_attach_wins() {
+    let seen = Arc::new(Mutex::new((None, 0)));
+    let mut graph = NodeGraph::new();
+    let a = insert(&mut graph, ConstSource::new(FrameSingle::U8(1), 1), "a");
+    let b = insert(&mut graph, ConstSource::new(FrameSingle::F32(1.0), 1), "b");
+    let sink = insert(&mut graph, AnySink::new(seen), "sink");
+    link(&mut graph, (a, "out"), (sink, "in"));
+    let p1 = graph.get_pull_port(b, "out").unwrap();
+    let p2 = graph.get_push_port(sink, "in").unwrap();
+    assert_eq!(p2.kind(), FrameKind::U8);
+    assert!(graph.add_link(p1, p2).is_err());
+}
@@ -0,0 +1,1099 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use ndarray::{ArcArray1, ArcArray2};
+use vidmod_core::{
+    nodes::{
+        BinaryOp, BitDepth, Blend, ChangeDetect, Concat, Contiguous, CounterSource, Delay, Expr,
+        Flatten, Gate, HashSink, LatencyProbe, Lut, Noise, NoiseSource, NullSink, Quantize,
+        RateConvert, RawFileSink, Resample, Resize, Subsample, Switch, TestPattern, Tile,
+        Transform2D, Unflatten, Untile, Zip,
+    },
+    spec::NodeGraph,
+    tap::{HashTap, LinkHashes},
+};
+use vidmod_macros::test_source;
+use vidmod_node::{
+    frame::{Frame, FrameKind, FrameSingle, RGBA8},
+    limvecdeque::LimVecDeque,
+    NodeImpl, NodePorts, PortDirection, VidmodError,
+};
+
+mod common;
+
+use common::{insert, link, TestSink, TestSource};
+
+fn params(args: &[(&str, &str)]) -> BTreeMap<String, String> {
+    args.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn push<T: NodePorts>(node: &mut T, name: &str, frame: Frame) {
+    let port = node.get_push_port(0.into(), name).unwrap();
+    node.push_frame(&port, frame);
+}
+
+fn pull<T: NodePorts>(node: &mut T, name: &str) -> Frame {
+    let port = node.get_pull_port(0.into(), name).unwrap();
+    let count = node.ready_to_pull(&port);
+    node.pull_frame(&port, count)
+}
+
+#[test]
+fn binary_op_sub_saturates() {
+    let mut node = BinaryOp::new(params(&[("kind", "U16"), ("op", "sub")]));
+    node.init();
+
+    let mut res: Vec<u16> = Vec::new();
+    for (a, b) in [(5, 10), (10, 3), (0, 65535)] {
+        push(&mut node, "a", Frame::U16(LimVecDeque::from(vec![a])));
+        push(&mut node, "b", Frame::U16(LimVecDeque::from(vec![b])));
+        assert!(node.tick());
+        res.extend(pull(&mut node, "out").unwrap_u16().iter());
+    }
+    assert_eq!(res, vec![0, 7, 0]);
+}
+
+#[test]
+fn binary_op_div_by_zero() {
+    let mut node = BinaryOp::new(params(&[("kind", "F32x1"), ("op", "div")]));
+    node.init();
+
+    let a = ndarray::arr1(&[1.0f32, -1.0, 0.0]).into_shared();
+    let b = ndarray::arr1(&[0.0f32, 0.0, 0.0]).into_shared();
+    push(&mut node, "a", Frame::F32x1(LimVecDeque::from(vec![a])));
+    push(&mut node, "b", Frame::F32x1(LimVecDeque::from(vec![b])));
+    assert!(node.tick());
+
+    let out = pull(&mut node, "out").remove_single().unwrap();
+    let out = match out {
+        FrameSingle::F32x1(v) => v,
+        _ => panic!("Wrong kind"),
+    };
+    assert_eq!(out[0], f32::INFINITY);
+    assert_eq!(out[1], f32::NEG_INFINITY);
+    assert!(out[2].is_nan());
+}
+
+fn blend_u8(mode: &str, a: Vec<u8>, b: Vec<u8>) -> Vec<u8> {
+    let mut node = Blend::new(params(&[("kind", "U8"), ("mode", mode)]));
+    node.init();
+    push(&mut node, "a", Frame::U8(LimVecDeque::from(a)));
+    push(&mut node, "b", Frame::U8(LimVecDeque::from(b)));
+    assert!(node.tick());
+    pull(&mut node, "out").unwrap_u8().iter().copied().collect()
+}
+
+#[test]
+fn blend_modes_saturate() {
+    let (a, b) = (vec![200, 10, 255], vec![100, 30, 128]);
+    assert_eq!(blend_u8("add", a.clone(), b.clone()), vec![255, 40, 255]);
+    assert_eq!(blend_u8("sub", a.clone(), b.clone()), vec![100, 0, 127]);
+    assert_eq!(blend_u8("diff", a.clone(), b.clone()), vec![100, 20, 127]);
+    assert_eq!(blend_u8("multiply", a, b), vec![78, 1, 128]);
+}
+
+#[test]
+fn blend_over_composites_rgba() {
+    let mut node = Blend::new(params(&[("kind", "RGBA8x2"), ("mode", "over")]));
+    node.init();
+    let fg = ArcArray2::from_shape_vec((1, 2), vec![RGBA8::new(255, 0, 0, 128); 2]).unwrap();
+    let bg = ArcArray2::from_shape_vec(
+        (1, 2),
+        vec![RGBA8::new(0, 0, 255, 255), RGBA8::new(0, 0, 255, 0)],
+    )
+    .unwrap();
+    push(&mut node, "a", Frame::RGBA8x2(LimVecDeque::from(vec![fg])));
+    push(&mut node, "b", Frame::RGBA8x2(LimVecDeque::from(vec![bg])));
+
+    assert!(node.tick());
+    let res = pull(&mut node, "out").unwrap_rgba8x2().pop_front().unwrap();
+    let res: Vec<_> = res.iter().map(|p| (p.r, p.g, p.b, p.a)).collect();
+    assert_eq!(res, vec![(128, 0, 127, 255), (255, 0, 0, 128)]);
+}
+
+#[test]
+#[should_panic(expected = "Blend mode over needs RGBA8x2")]
+fn blend_over_needs_rgba() {
+    Blend::new(params(&[("kind", "U8x2"), ("mode", "over")]));
+}
+
+#[test]
+fn bit_depth_round_trips_10_bit() {
+    let rescale = |from: u8, to: u8, values: Vec<u16>| {
+        let from_bits = from.to_string();
+        let to_bits = to.to_string();
+        let mut node = BitDepth::new(params(&[
+            ("from_bits", from_bits.as_str()),
+            ("to_bits", to_bits.as_str()),
+        ]));
+        node.init();
+        push(&mut node, "in", Frame::U16(LimVecDeque::from(values)));
+        assert!(node.tick());
+        let res = pull(&mut node, "out").unwrap_u16();
+        res.iter().copied().collect::<Vec<_>>()
+    };
+
+    let full = rescale(10, 16, vec![0, 512, 1023]);
+    assert_eq!(full, vec![0, 32800, 65535]);
+    assert_eq!(rescale(16, 10, full), vec![0, 512, 1023]);
+    assert_eq!(rescale(10, 12, vec![1023]), vec![4095]);
+}
+
+#[test]
+fn change_detect_flags_new_frames() {
+    let mut node = ChangeDetect::new(params(&[("kind", "U8x1")]));
+    node.init();
+    let a = ArcArray1::from(vec![1u8, 2, 3]);
+    let b = ArcArray1::from(vec![1u8, 2, 4]);
+    push(
+        &mut node,
+        "in",
+        Frame::U8x1(LimVecDeque::from(vec![a.clone(), a, b.clone(), b])),
+    );
+
+    assert!(node.tick());
+    let res = pull(&mut node, "out").unwrap_u8();
+    assert_eq!(res.iter().copied().collect::<Vec<_>>(), vec![1, 0, 1, 0]);
+}
+
+#[test]
+fn zip_interleaves_inputs() {
+    let mut node = Zip::new(params(&[("kind", "U8"), ("n", "2")]));
+    node.init();
+
+    push(&mut node, "in0", Frame::U8(LimVecDeque::from(vec![1])));
+    assert!(!node.tick());
+    push(&mut node, "in1", Frame::U8(LimVecDeque::from(vec![2])));
+    assert!(node.tick());
+    let out: Vec<u8> = pull(&mut node, "out").unwrap_u8().iter().copied().collect();
+    assert_eq!(out, vec![1, 2]);
+}
+
+#[test]
+fn zip_still_accepts_inputs_param() {
+    let mut node = Zip::new(params(&[("kind", "U8"), ("inputs", "3")]));
+    node.init();
+
+    push(&mut node, "in0", Frame::U8(LimVecDeque::from(vec![1])));
+    push(&mut node, "in1", Frame::U8(LimVecDeque::from(vec![2])));
+    assert!(!node.tick());
+    push(&mut node, "in2", Frame::U8(LimVecDeque::from(vec![3])));
+    assert!(node.tick());
+    let out: Vec<u8> = pull(&mut node, "out").unwrap_u8().iter().copied().collect();
+    assert_eq!(out, vec![1, 2, 3]);
+}
+
+#[test]
+fn concat_forwards_inputs_in_order() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let mut graph = NodeGraph::new();
+    let first = insert(&mut graph, TestSource::new(3, 2), "first");
+    let second = insert(&mut graph, TestSource::new(5, 2), "second");
+    let concat = insert(
+        &mut graph,
+        Concat::new(params(&[("kind", "U16"), ("n", "2")])),
+        "concat",
+    );
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    link(&mut graph, (first, "out"), (concat, "in0"));
+    link(&mut graph, (second, "out"), (concat, "in1"));
+    link(&mut graph, (concat, "out"), (sink, "in"));
+    graph.run();
+
+    assert_eq!(*received.lock().unwrap(), vec![0, 1, 2, 0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn concat_waits_for_later_producers() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let mut graph = NodeGraph::new();
+    let first = insert(&mut graph, TestSource::new(2, 1), "first");
+    let second = insert(&mut graph, TestSource::new(3, 1), "second");
+    let third = insert(&mut graph, TestSource::new(4, 1), "third");
+    // The last input is a node further from its source, so outlives the first pruning pass
+    let relay = insert(
+        &mut graph,
+        Contiguous::new(params(&[("kind", "U16"), ("buf_size", "1")])),
+        "relay",
+    );
+    let concat = insert(
+        &mut graph,
+        Concat::new(params(&[("kind", "U16"), ("n", "3")])),
+        "concat",
+    );
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    link(&mut graph, (first, "out"), (concat, "in0"));
+    link(&mut graph, (second, "out"), (concat, "in1"));
+    link(&mut graph, (third, "out"), (relay, "in"));
+    link(&mut graph, (relay, "out"), (concat, "in2"));
+    link(&mut graph, (concat, "out"), (sink, "in"));
+    graph.run();
+
+    assert_eq!(*received.lock().unwrap(), vec![0, 1, 0, 1, 2, 0, 1, 2, 3]);
+}
+
+#[test]
+fn concat_rejects_other_kinds() {
+    let mut graph = NodeGraph::new();
+    let bytes = insert(
+        &mut graph,
+        CounterSource::new(params(&[("kind", "U8"), ("count", "2")])),
+        "bytes",
+    );
+    let concat = insert(
+        &mut graph,
+        Concat::new(params(&[("kind", "U16"), ("n", "2")])),
+        "concat",
+    );
+    let p1 = graph.get_pull_port(bytes, "out").unwrap();
+    let p2 = graph.get_push_port(concat, "in1").unwrap();
+    assert_eq!(
+        graph.add_link(p1, p2),
+        Err(VidmodError::KindMismatch {
+            port:      Some("out".to_owned()),
+            direction: Some(PortDirection::Pull),
+            expected:  FrameKind::U8,
+            got:       FrameKind::U16,
+        })
+    );
+}
+
+#[test]
+fn contiguous_makes_standard_layout() {
+    let mut node = Contiguous::new(params(&[("kind", "F32x2")]));
+    node.init();
+    let array = ArcArray2::from_shape_fn((3, 4), |(y, x)| (y * 4 + x) as f32);
+    let transposed = array.t().to_shared();
+    assert!(!transposed.is_standard_layout());
+    push(
+        &mut node,
+        "in",
+        Frame::F32x2(LimVecDeque::from(vec![transposed.clone()])),
+    );
+
+    assert!(node.tick());
+    let res = pull(&mut node, "out").unwrap_f32x2().pop_front().unwrap();
+    assert!(res.is_standard_layout());
+    assert_eq!(res, transposed);
+}
+
+#[test]
+fn unflatten_groups_and_flatten_splits() {
+    let values: Vec<u8> = (0..8).collect();
+    let mut group = Unflatten::new(params(&[("kind", "U8"), ("len", "8")]));
+    group.init();
+    push(
+        &mut group,
+        "in",
+        Frame::U8(LimVecDeque::from(values.clone())),
+    );
+    assert!(group.tick());
+    let mut arrays = pull(&mut group, "out").unwrap_u8x1();
+    assert_eq!(arrays.len(), 1);
+    let array = arrays.pop_front().unwrap();
+    assert_eq!(array, ArcArray1::from(values.clone()));
+
+    let mut split = Flatten::new(params(&[("kind", "U8")]));
+    split.init();
+    push(
+        &mut split,
+        "in",
+        Frame::U8x1(LimVecDeque::from(vec![array])),
+    );
+    assert!(split.tick());
+    let res = pull(&mut split, "out").unwrap_u8();
+    assert_eq!(res.iter().copied().collect::<Vec<_>>(), values);
+}
+
+#[test]
+fn lut_inverts_u8() {
+    let table = (0..=255u8)
+        .map(|v| (255 - v).to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut node = Lut::new(params(&[("kind", "U8x2"), ("table", &table)]));
+    node.init();
+    let array = ArcArray2::from_shape_fn((16, 16), |(y, x)| (y * 16 + x) as u8);
+    push(
+        &mut node,
+        "in",
+        Frame::U8x2(LimVecDeque::from(vec![array.clone()])),
+    );
+
+    assert!(node.tick());
+    let res = pull(&mut node, "out").unwrap_u8x2().pop_front().unwrap();
+    assert_eq!(res, array.map(|v| 255 - v));
+}
+
+#[test]
+fn lut_interpolates_u16_from_file() {
+    let file = std::env::temp_dir().join(format!("vidmod-lut-{}.txt", std::process::id()));
+    std::fs::write(&file, "0\n1000\n0\n").unwrap();
+    let mut node = Lut::new(params(&[("kind", "U16"), ("file", file.to_str().unwrap())]));
+    std::fs::remove_file(&file).unwrap();
+    node.init();
+    push(
+        &mut node,
+        "in",
+        Frame::U16(LimVecDeque::from(vec![0, 16384, 32767, 65535])),
+    );
+
+    assert!(node.tick());
+    let res = pull(&mut node, "out").unwrap_u16();
+    assert_eq!(
+        res.iter().copied().collect::<Vec<_>>(),
+        vec![0, 500, 1000, 0]
+    );
+}
+
+#[test]
+#[should_panic(expected = "U8 LUT needs 256 entries")]
+fn lut_rejects_short_u8_table() {
+    Lut::new(params(&[("kind", "U8"), ("table", "0,1,2")]));
+}
+
+#[test]
+fn resample_flushes_tail_on_finish() {
+    let mut node = Resample::new(params(&[("from_rate", "44100"), ("to_rate", "48000")]));
+    node.init();
+    let blocks = (0..4)
+        .map(|_| ArcArray1::from_elem(441, 0.5f32))
+        .collect::<Vec<_>>();
+    push(&mut node, "in", Frame::F32x1(LimVecDeque::from(blocks)));
+
+    let drain = |node: &mut Resample| {
+        let out = pull(node, "out").unwrap_f32x1();
+        assert!(out.iter().flatten().all(|v| v.is_finite()));
+        out.iter().map(|block| block.len()).sum::<usize>()
+    };
+    assert!(node.tick());
+    let mut len = drain(&mut node);
+    assert!(len < 1920);
+    assert!(node.finish());
+    len += drain(&mut node);
+    // 1764 samples at 160/147
+    assert_eq!(len, 1920);
+}
+
+// Counts the write calls that reach it and keeps the bytes written
+#[derive(Debug, Clone, Default)]
+struct CountingWriter(Arc<Mutex<(usize, Vec<u8>)>>);
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self.0.lock().unwrap();
+        inner.0 += 1;
+        inner.1.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_small_frames(sink: &mut RawFileSink) {
+    sink.init();
+    for batch in 0..100 {
+        let frames = (0..10)
+            .map(|i| ArcArray1::from_elem(16, (batch * 10 + i) as u8))
+            .collect::<Vec<_>>();
+        push(sink, "in", Frame::U8x1(LimVecDeque::from(frames)));
+        assert!(sink.tick());
+    }
+}
+
+#[test]
+fn raw_file_sink_coalesces_writes() {
+    let writer = CountingWriter::default();
+    let mut sink = RawFileSink::with_writer(FrameKind::U8x1, Box::new(writer.clone()));
+    write_small_frames(&mut sink);
+    assert!(sink.finish());
+
+    let (writes, bytes) = &*writer.0.lock().unwrap();
+    assert!(*writes < 10, "{} writes for 1000 frames", writes);
+    assert_eq!(bytes.len(), 16000);
+    assert!(bytes
+        .chunks(16)
+        .enumerate()
+        .all(|(i, c)| c.iter().all(|&v| v == i as u8)));
+}
+
+#[test]
+fn raw_file_sink_flush_every() {
+    let writer = CountingWriter::default();
+    let mut sink = RawFileSink::with_writer(FrameKind::U8x1, Box::new(writer.clone()));
+    sink.set_flush_every(Some(100));
+    write_small_frames(&mut sink);
+    assert_eq!(writer.0.lock().unwrap().0, 10);
+    assert!(sink.finish());
+    assert_eq!(writer.0.lock().unwrap().1.len(), 16000);
+}
+
+#[test]
+fn raw_file_sink_writes_file() {
+    let dir = std::env::temp_dir();
+    let file = format!("vidmod-raw-{}.bin", std::process::id());
+    let mut sink = RawFileSink::new(params(&[
+        ("kind", "U16"),
+        ("file", &file),
+        ("vidmod.path", dir.to_str().unwrap()),
+    ]));
+    sink.init();
+    push(
+        &mut sink,
+        "in",
+        Frame::U16(LimVecDeque::from(vec![1, 0x0203])),
+    );
+    assert!(sink.tick());
+    assert!(sink.finish());
+    let path = dir.join(file);
+    assert_eq!(std::fs::read(&path).unwrap(), vec![1, 0, 3, 2]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn resize_changes_shape() {
+    let mut node = Resize::new(params(&[
+        ("kind", "U8x2"),
+        ("rows", "2"),
+        ("cols", "3"),
+        ("filter", "nearest"),
+    ]));
+    node.init();
+    push(
+        &mut node,
+        "in",
+        Frame::U8x2(LimVecDeque::from(vec![
+            ArcArray2::from_elem((1, 1), 7),
+            ArcArray2::from_elem((4, 4), 9),
+        ])),
+    );
+
+    assert!(node.tick());
+    let res = pull(&mut node, "out");
+    assert_eq!(res.shapes_2d(), vec![(2, 3), (2, 3)]);
+    let mut res = res.unwrap_u8x2();
+    assert!(res.pop_front().unwrap().iter().all(|&v| v == 7));
+    assert!(res.pop_front().unwrap().iter().all(|&v| v == 9));
+}
+
+fn transform(op: &str, array: ArcArray2<u16>) -> ArcArray2<u16> {
+    let mut node = Transform2D::new(params(&[("kind", "U16x2"), ("op", op)]));
+    node.init();
+    push(
+        &mut node,
+        "in",
+        Frame::U16x2(LimVecDeque::from(vec![array])),
+    );
+    assert!(node.tick());
+    pull(&mut node, "out").unwrap_u16x2().pop_front().unwrap()
+}
+
+#[test]
+fn transform_2d_orientations() {
+    let array = ndarray::arr2(&[[1, 2, 3], [4, 5, 6]]).into_shared();
+    let res = transform("transpose", array.clone());
+    assert_eq!(res.dim(), (3, 2));
+    assert_eq!(res, ndarray::arr2(&[[1, 4], [2, 5], [3, 6]]));
+    assert_eq!(
+        transform("rot180", array.clone()),
+        ndarray::arr2(&[[6, 5, 4], [3, 2, 1]])
+    );
+    assert_eq!(
+        transform("rot90", array.clone()),
+        ndarray::arr2(&[[4, 1], [5, 2], [6, 3]])
+    );
+    assert_eq!(
+        transform("flip_h", array.clone()),
+        ndarray::arr2(&[[3, 2, 1], [6, 5, 4]])
+    );
+    assert_eq!(
+        transform("flip_v", array),
+        ndarray::arr2(&[[4, 5, 6], [1, 2, 3]])
+    );
+}
+
+#[test]
+#[should_panic(expected = "Transform2D needs a 2D kind, got U16x1")]
+fn transform_2d_rejects_1d_kinds() {
+    Transform2D::new(params(&[("kind", "U16x1"), ("op", "flip_h")]));
+}
+
+#[test]
+fn counter_source_sequence() {
+    let mut node = CounterSource::new(params(&[
+        ("kind", "U16"),
+        ("start", "10"),
+        ("step", "5"),
+        ("count", "3"),
+    ]));
+    node.init();
+
+    assert!(!node.finish());
+    assert!(node.tick());
+    let res: Vec<u16> = pull(&mut node, "out")
+        .unwrap_u16()
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(res, vec![10, 15, 20]);
+    assert!(!node.tick());
+    assert!(node.finish());
+}
+
+#[test]
+fn hash_sink_writes_rolling_hash() {
+    let file = std::env::temp_dir().join(format!("vidmod-hash-{}.txt", std::process::id()));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(100, 7), "src");
+    let sink = insert(
+        &mut graph,
+        HashSink::new(params(&[("file", file.to_str().unwrap())])),
+        "sink",
+    );
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph.run();
+
+    let expected = Frame::U16(LimVecDeque::from((0..100).collect::<Vec<u16>>())).rolling_hash(0);
+    let written = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(written, format!("{:016x}\n", expected));
+}
+
+#[test]
+fn latency_probe_measures_pipeline_depth() {
+    let file = std::env::temp_dir().join(format!("vidmod-latency-{}.txt", std::process::id()));
+    let stage = || {
+        Expr::new(params(&[
+            ("expr", "a"),
+            ("inputs", "a:U16"),
+            ("kind", "U16"),
+        ]))
+    };
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(40, 16), "src");
+    let first = insert(&mut graph, stage(), "first");
+    let second = insert(&mut graph, stage(), "second");
+    let probe = insert(
+        &mut graph,
+        LatencyProbe::new(params(&[("file", file.to_str().unwrap())])),
+        "probe",
+    );
+    link(&mut graph, (src, "out"), (first, "a"));
+    link(&mut graph, (first, "out"), (second, "a"));
+    link(&mut graph, (second, "out"), (probe, "in"));
+    graph.run();
+
+    let written = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(written, "3 3 3\n");
+}
+
+fn rate_convert(count: u16, mode: &str, ratio: &str) -> Vec<u16> {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(count, 4), "src");
+    let convert = insert(
+        &mut graph,
+        RateConvert::new(params(&[
+            ("kind", "U16"),
+            ("mode", mode),
+            ("ratio", ratio),
+            ("buf_size", "3"),
+        ])),
+        "convert",
+    );
+    let sink = insert(&mut graph, TestSink::new(5, received.clone()), "sink");
+    link(&mut graph, (src, "out"), (convert, "in"));
+    link(&mut graph, (convert, "out"), (sink, "in"));
+    graph.run();
+    let res = received.lock().unwrap().clone();
+    res
+}
+
+#[test]
+fn rate_convert_repeats() {
+    assert_eq!(rate_convert(5, "repeat", "3/2"), vec![0, 1, 1, 2, 3, 3, 4]);
+    assert_eq!(rate_convert(11, "repeat", "3/2").len(), 16);
+}
+
+#[test]
+fn rate_convert_drops() {
+    assert_eq!(rate_convert(7, "drop", "2/3"), vec![1, 2, 4, 5]);
+    assert_eq!(rate_convert(11, "drop", "2/3").len(), 7);
+    assert_eq!(rate_convert(10, "drop", "1/2"), vec![1, 3, 5, 7, 9]);
+}
+
+#[test]
+fn rate_convert_unity_passes_through() {
+    assert_eq!(rate_convert(13, "drop", "1/1"), (0..13).collect::<Vec<_>>());
+    assert_eq!(
+        rate_convert(13, "repeat", "4/4"),
+        (0..13).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+#[should_panic(expected = "Drop ratio 3/2 is above 1")]
+fn rate_convert_rejects_drop_above_one() {
+    RateConvert::new(params(&[
+        ("kind", "U16"),
+        ("mode", "drop"),
+        ("ratio", "3/2"),
+    ]));
+}
+
+fn noise_bytes(args: &[(&str, &str)]) -> Vec<u8> {
+    let mut node = NoiseSource::new(params(args));
+    node.init();
+    let mut bytes = Vec::new();
+    while !node.finish() {
+        assert!(node.tick());
+        let frame = pull(&mut node, "out");
+        assert_eq!(frame.shapes()[0], vec![3, 4]);
+        frame.write_bytes(&mut bytes).unwrap();
+    }
+    bytes
+}
+
+#[test]
+fn noise_source_is_repeatable() {
+    let args = [
+        ("kind", "U16x2"),
+        ("seed", "42"),
+        ("shape", "3,4"),
+        ("count", "40"),
+    ];
+    let a = noise_bytes(&args);
+    assert_eq!(a.len(), 40 * 3 * 4 * 2);
+    assert_eq!(a, noise_bytes(&args));
+    let mut other = args;
+    other[1] = ("seed", "43");
+    assert_ne!(a, noise_bytes(&other));
+}
+
+fn noise_samples(args: &[(&str, &str)]) -> Vec<f64> {
+    let mut node = Noise::new(params(args));
+    node.init();
+    let mut samples = Vec::new();
+    while !node.finish() {
+        assert!(node.tick());
+        match pull(&mut node, "out") {
+            Frame::F32(v) => samples.extend(v.iter().map(|x| *x as f64)),
+            Frame::U16(v) => samples.extend(v.iter().map(|x| *x as f64)),
+            _ => panic!("Wrong kind"),
+        }
+    }
+    samples
+}
+
+fn mean_variance(samples: &[f64]) -> (f64, f64) {
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n;
+    (mean, variance)
+}
+
+#[test]
+fn noise_is_repeatable() {
+    let args = [
+        ("kind", "F32"),
+        ("distribution", "gaussian"),
+        ("seed", "7"),
+        ("count", "100"),
+    ];
+    let a = noise_samples(&args);
+    assert_eq!(a.len(), 100);
+    assert_eq!(a, noise_samples(&args));
+    let mut other = args;
+    other[2] = ("seed", "8");
+    assert_ne!(a, noise_samples(&other));
+}
+
+#[test]
+fn noise_statistics_match_distribution() {
+    let uniform = noise_samples(&[("kind", "F32"), ("count", "20000")]);
+    assert!(uniform.iter().all(|x| (-1.0..1.0).contains(x)));
+    let (mean, variance) = mean_variance(&uniform);
+    assert!(mean.abs() < 0.02, "{}", mean);
+    assert!((variance - 1.0 / 3.0).abs() < 0.01, "{}", variance);
+
+    let gaussian = noise_samples(&[
+        ("kind", "F32"),
+        ("distribution", "gaussian"),
+        ("count", "20000"),
+    ]);
+    assert!(gaussian.iter().all(|x| (-1.0..=1.0).contains(x)));
+    let (mean, variance) = mean_variance(&gaussian);
+    assert!(mean.abs() < 0.01, "{}", mean);
+    assert!((variance - 1.0 / 36.0).abs() < 0.002, "{}", variance);
+
+    let gaussian = noise_samples(&[
+        ("kind", "U16"),
+        ("distribution", "gaussian"),
+        ("count", "20000"),
+    ]);
+    let (mean, variance) = mean_variance(&gaussian);
+    assert!((mean - 32767.5).abs() < 200.0, "{}", mean);
+    let expected = 65535.0 / 12.0;
+    assert!(
+        (variance.sqrt() - expected).abs() < expected * 0.05,
+        "{}",
+        variance
+    );
+}
+
+fn test_pattern(args: &[(&str, &str)]) -> FrameSingle {
+    let mut node = TestPattern::new(params(args));
+    node.init();
+    assert!(node.tick());
+    pull(&mut node, "out").remove_single().unwrap()
+}
+
+#[test]
+fn test_pattern_draws_patterns() {
+    let bars = match test_pattern(&[
+        ("pattern", "bars"),
+        ("kind", "U8x2"),
+        ("width", "8"),
+        ("height", "2"),
+        ("frames", "1"),
+    ]) {
+        FrameSingle::U8x2(v) => v,
+        _ => panic!("Wrong kind"),
+    };
+    assert_eq!(bars.shape(), [2, 8]);
+    assert_eq!(bars.row(1).to_vec(), [255, 226, 179, 150, 105, 76, 29, 0]);
+
+    let gradient = match test_pattern(&[
+        ("pattern", "gradient"),
+        ("width", "4"),
+        ("height", "1"),
+        ("frames", "1"),
+    ]) {
+        FrameSingle::RGBA8x2(v) => v,
+        _ => panic!("Wrong kind"),
+    };
+    let levels: Vec<_> = gradient.iter().map(|p| (p.r, p.g, p.b, p.a)).collect();
+    assert_eq!(
+        levels,
+        [
+            (0, 0, 0, 255),
+            (85, 85, 85, 255),
+            (170, 170, 170, 255),
+            (255, 255, 255, 255)
+        ]
+    );
+
+    let checker = match test_pattern(&[
+        ("pattern", "checker"),
+        ("kind", "U16x2"),
+        ("square", "2"),
+        ("width", "4"),
+        ("height", "4"),
+        ("frames", "1"),
+    ]) {
+        FrameSingle::U16x2(v) => v,
+        _ => panic!("Wrong kind"),
+    };
+    assert_eq!(checker.column(0).to_vec(), [65535, 65535, 0, 0]);
+    assert_eq!(checker.row(3).to_vec(), [0, 0, 65535, 65535]);
+}
+
+#[test]
+fn test_pattern_stops_after_frames() {
+    let args = [
+        ("pattern", "solid"),
+        ("width", "3"),
+        ("height", "2"),
+        ("frames", "20"),
+    ];
+    let mut node = TestPattern::new(params(&args));
+    node.init();
+    assert!(node.tick());
+    let first = pull(&mut node, "out");
+    assert_eq!(first.size(), 16);
+    assert!(!node.finish());
+    assert!(node.tick());
+    assert_eq!(pull(&mut node, "out").size(), 4);
+    assert!(node.finish());
+    assert!(!node.tick());
+
+    let mut again = TestPattern::new(params(&args));
+    again.init();
+    again.tick();
+    assert_eq!(pull(&mut again, "out").content_hash(), first.content_hash());
+}
+
+#[test]
+fn null_sink_drains_source() {
+    let hashes = LinkHashes::default();
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(1000, 4), "src");
+    let sink = insert(
+        &mut graph,
+        NullSink::new(params(&[("kind", "U16")])),
+        "sink",
+    );
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph
+        .tap_link(
+            ("src", "out"),
+            ("sink", "in"),
+            Box::new(HashTap::new(hashes.clone())),
+        )
+        .unwrap();
+    graph.run();
+
+    let hashes = hashes.lock().unwrap();
+    assert_eq!(hashes.values().map(|h| h.frames).sum::<usize>(), 1000);
+}
+
+#[test]
+fn untile_reverses_tile() {
+    let mut tile = Tile::new(params(&[
+        ("kind", "U16x2"),
+        ("tile_w", "4"),
+        ("tile_h", "3"),
+    ]));
+    let mut untile = Untile::new(params(&[("kind", "U16x2")]));
+    tile.init();
+    untile.init();
+    let frames: Vec<ArcArray2<u16>> = vec![
+        ArcArray2::from_shape_fn((7, 10), |(y, x)| (y * 100 + x) as u16),
+        ArcArray2::from_shape_fn((2, 3), |(y, x)| (x * 100 + y) as u16 + 7),
+    ];
+    push(
+        &mut tile,
+        "in",
+        Frame::U16x2(LimVecDeque::from(frames.clone())),
+    );
+
+    while tile.tick() {
+        push(&mut untile, "layout", pull(&mut tile, "layout"));
+        push(&mut untile, "in", pull(&mut tile, "out"));
+        untile.tick();
+    }
+    assert!(tile.finish());
+    let res = pull(&mut untile, "out").unwrap_u16x2();
+    assert_eq!(res.iter().cloned().collect::<Vec<_>>(), frames);
+}
+
+test_source!(Selectors, U8, |i: usize| if i < 9 {
+    Some([2, 0, 1, 1, 0, 2, 2, 2, 0][i])
+} else {
+    None
+});
+
+#[test]
+fn switch_routes_by_selector() {
+    let received: Vec<_> = (0..3).map(|_| Arc::new(Mutex::new(Vec::new()))).collect();
+
+    let mut graph = NodeGraph::new();
+    let data = insert(&mut graph, TestSource::new(9, 4), "data");
+    let select = insert(&mut graph, Selectors::new(), "select");
+    let switch = insert(
+        &mut graph,
+        Switch::new(params(&[("kind", "U16"), ("n", "3"), ("buf_size", "2")])),
+        "switch",
+    );
+    link(&mut graph, (data, "out"), (switch, "in"));
+    link(&mut graph, (select, "out"), (switch, "select"));
+    for (i, received) in received.iter().enumerate() {
+        let name = format!("sink{}", i);
+        let sink = insert(&mut graph, TestSink::new(4, received.clone()), &name);
+        let port = format!("out{}", i);
+        link(&mut graph, (switch, port.as_str()), (sink, "in"));
+    }
+    graph.run();
+
+    assert_eq!(*received[0].lock().unwrap(), vec![1, 4, 8]);
+    assert_eq!(*received[1].lock().unwrap(), vec![2, 3]);
+    assert_eq!(*received[2].lock().unwrap(), vec![0, 5, 6, 7]);
+}
+
+fn switch_invalid(on_invalid: &str) -> (Vec<u8>, Vec<u8>) {
+    let mut node = Switch::new(params(&[("kind", "U8"), ("on_invalid", on_invalid)]));
+    node.init();
+    push(
+        &mut node,
+        "in",
+        Frame::U8(LimVecDeque::from(vec![10, 11, 12])),
+    );
+    push(
+        &mut node,
+        "select",
+        Frame::U8(LimVecDeque::from(vec![0, 7, 1])),
+    );
+    assert!(node.tick());
+    let out0 = pull(&mut node, "out0")
+        .unwrap_u8()
+        .iter()
+        .copied()
+        .collect();
+    let out1 = pull(&mut node, "out1")
+        .unwrap_u8()
+        .iter()
+        .copied()
+        .collect();
+    (out0, out1)
+}
+
+#[test]
+fn switch_drops_or_clamps_invalid_selectors() {
+    assert_eq!(switch_invalid("drop"), (vec![10], vec![12]));
+    assert_eq!(switch_invalid("clamp"), (vec![10], vec![11, 12]));
+}
+
+#[test]
+#[should_panic(expected = "Switch selector 7 is out of range for 2 outputs")]
+fn switch_rejects_invalid_selectors() {
+    switch_invalid("error");
+}
+
+#[test]
+fn gate_drops_frames_with_zero_control() {
+    let mut node = Gate::new(params(&[("kind", "U8")]));
+    node.init();
+    push(
+        &mut node,
+        "in",
+        Frame::U8(LimVecDeque::from(vec![b'A', b'B', b'C'])),
+    );
+    push(
+        &mut node,
+        "control",
+        Frame::U8(LimVecDeque::from(vec![1, 0, 1])),
+    );
+    assert!(node.tick());
+    let out: Vec<_> = pull(&mut node, "out").unwrap_u8().iter().copied().collect();
+    assert_eq!(out, b"AC".to_vec());
+    assert_eq!(node.inbuf_avail("control"), 0);
+    assert!(node.finish());
+}
+
+#[test]
+fn quantize_reduces_to_target_kind() {
+    let mut node = Quantize::new(params(&[
+        ("kind", "U16x2"),
+        ("to", "U8x2"),
+        ("dither", "ordered"),
+    ]));
+    node.init();
+    push(
+        &mut node,
+        "in",
+        Frame::U16x2(LimVecDeque::from(vec![
+            ArcArray2::from_elem((2, 2), 0),
+            ArcArray2::from_elem((2, 2), 65535),
+        ])),
+    );
+    assert!(node.tick());
+    let out: Vec<_> = pull(&mut node, "out")
+        .unwrap_u8x2()
+        .iter()
+        .map(|img| img.iter().copied().collect::<Vec<_>>())
+        .collect();
+    assert_eq!(out, vec![vec![0; 4], vec![255; 4]]);
+}
+
+fn delayed(trim: &str) -> Vec<u16> {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, TestSource::new(5, 2), "source");
+    let delay = insert(
+        &mut graph,
+        Delay::new(params(&[
+            ("kind", "U16"),
+            ("frames", "3"),
+            ("trim", trim),
+            ("buf_size", "4"),
+        ])),
+        "delay",
+    );
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (delay, "in"));
+    link(&mut graph, (delay, "out"), (sink, "in"));
+    graph.run();
+    let res = received.lock().unwrap().clone();
+    res
+}
+
+#[test]
+fn delay_offsets_frames() {
+    assert_eq!(delayed("false"), vec![0, 0, 0, 0, 1, 2, 3, 4]);
+    assert_eq!(delayed("true"), vec![0, 0, 0, 0, 1]);
+}
+
+#[test]
+fn delay_learns_array_shape() {
+    let mut node = Delay::new(params(&[("kind", "U8x2"), ("frames", "1")]));
+    node.init();
+    push(
+        &mut node,
+        "in",
+        Frame::U8x2(LimVecDeque::from(vec![ArcArray2::from_elem((2, 3), 9)])),
+    );
+    assert!(node.tick());
+    let out = pull(&mut node, "out").unwrap_u8x2().pop_front().unwrap();
+    assert_eq!(out, ArcArray2::from_elem((2, 3), 0));
+    assert!(node.finish());
+    let out = pull(&mut node, "out").unwrap_u8x2().pop_front().unwrap();
+    assert_eq!(out, ArcArray2::from_elem((2, 3), 9));
+}
+
+#[test]
+#[should_panic(expected = "Delay of 5 frames needs a buf_size of at least 5")]
+fn delay_needs_room_for_its_frames() {
+    Delay::new(params(&[
+        ("kind", "U8"),
+        ("frames", "5"),
+        ("buf_size", "4"),
+    ]))
+    .init();
+}
+
+#[test]
+fn subsample_averages_blocks() {
+    let plane = ArcArray2::from_shape_vec(
+        (4, 4),
+        vec![
+            0, 2, 10, 20, //
+            4, 6, 30, 41, //
+            100, 100, 255, 255, //
+            100, 101, 255, 254,
+        ],
+    )
+    .unwrap();
+    let subsample = |mode: &str| {
+        let mut node = Subsample::new(params(&[
+            ("h_factor", "2"),
+            ("v_factor", "2"),
+            ("mode", mode),
+        ]));
+        node.init();
+        push(
+            &mut node,
+            "in",
+            Frame::U8x2(LimVecDeque::from(vec![plane.clone()])),
+        );
+        assert!(node.tick());
+        pull(&mut node, "out").unwrap_u8x2().pop_front().unwrap()
+    };
+    assert_eq!(
+        subsample("average"),
+        ArcArray2::from_shape_vec((2, 2), vec![3, 25, 100, 255]).unwrap()
+    );
+    assert_eq!(
+        subsample("drop"),
+        ArcArray2::from_shape_vec((2, 2), vec![0, 10, 100, 255]).unwrap()
+    );
+}
@@ -0,0 +1,93 @@
+use std::sync::{Arc, Mutex};
+
+use vidmod_core::spec::{NodeGraph, RunMode};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{NodeImpl, VidmodError};
+
+mod common;
+
+use common::{insert, link, TestSink, TestSource};
+
+/// Panics on its first tick
+#[node_decl]
+struct Faulty {}
+
+impl Faulty {
+    #[node_new]
+    fn new() -> Self {
+        Self {}
+    }
+}
+
+impl NodeImpl for Faulty {
+    fn init(&mut self) {}
+
+    fn tick(&mut self) -> bool {
+        panic!("deliberate failure");
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+fn graph() -> NodeGraph {
+    let mut graph = NodeGraph::new();
+    insert(&mut graph, TestSource::new(4, 4), "healthy");
+    insert(&mut graph, Faulty::new(), "broken_filter");
+    graph
+}
+
+#[test]
+fn panic_reports_node_name() {
+    let mut graph = graph();
+    assert_eq!(
+        graph.try_run(),
+        Err(VidmodError::NodePanicked {
+            node:    "broken_filter".to_owned(),
+            message: "deliberate failure".to_owned(),
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "Node broken_filter panicked: deliberate failure")]
+fn run_panics_with_node_name() {
+    graph().run();
+}
+
+// A healthy source feeding a sink, beside a faulty node
+fn branches(received: Arc<Mutex<Vec<u16>>>) -> NodeGraph {
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(20, 4), "healthy");
+    let sink = insert(&mut graph, TestSink::new(4, received), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+    insert(&mut graph, Faulty::new(), "broken_filter");
+    graph
+}
+
+#[test]
+fn collect_mode_finishes_healthy_branch() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let errors = branches(received.clone()).run_mode(RunMode::CollectErrors);
+    assert_eq!(
+        errors,
+        vec![(
+            "broken_filter".to_owned(),
+            VidmodError::NodePanicked {
+                node:    "broken_filter".to_owned(),
+                message: "deliberate failure".to_owned(),
+            }
+        )]
+    );
+    assert_eq!(*received.lock().unwrap(), (0..20).collect::<Vec<u16>>());
+}
+
+#[test]
+fn abort_mode_stops_at_first_error() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let errors = branches(received.clone()).run_mode(RunMode::AbortOnError);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "broken_filter");
+    assert!(received.lock().unwrap().len() < 20);
+}
@@ -0,0 +1,151 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use vidmod_core::spec::Project;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    params::{Params, PATH_ARG},
+    Node, NodeImpl, VidmodError,
+};
+use vidmod_plugin::PluginRegistry;
+
+/// Writes the project root it was given to `root.txt` inside it
+#[node_decl]
+struct RootWriter {
+    params: Params,
+}
+
+impl RootWriter {
+    #[node_new]
+    fn new(params: BTreeMap<String, String>) -> Self {
+        Self {
+            params: Params::new(params),
+        }
+    }
+}
+
+impl NodeImpl for RootWriter {
+    fn init(&mut self) {}
+
+    fn tick(&mut self) -> bool {
+        false
+    }
+
+    fn finish(&mut self) -> bool {
+        let root = self.params.project_root().unwrap();
+        fs::write(root.join("root.txt"), root.to_str().unwrap()).unwrap();
+        true
+    }
+}
+
+fn project_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vidmod-test-paths-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn params(root: &Path, file: &str) -> Params {
+    let mut args = BTreeMap::new();
+    args.insert(PATH_ARG.to_owned(), root.to_str().unwrap().to_owned());
+    args.insert("file".to_owned(), file.to_owned());
+    Params::new(args)
+}
+
+// The same directory, reached by a path relative to the working directory
+#[cfg(unix)]
+fn relative_to_cwd(dir: &Path) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap();
+    let mut rel = PathBuf::new();
+    for _ in cwd.components().skip(1) {
+        rel.push("..");
+    }
+    rel.join(dir.strip_prefix("/").unwrap())
+}
+
+#[cfg(unix)]
+#[test]
+fn relative_project_root_is_absolute() {
+    let dir = project_dir("relative");
+    fs::write(
+        dir.join("manifest.yml"),
+        r#"
+nodes:
+  writer:
+    name: test::RootWriter
+links: []
+"#,
+    )
+    .unwrap();
+    let mut registry = PluginRegistry::new();
+    registry.register("test::RootWriter", |params| {
+        Node::new(RootWriter::new(params))
+    });
+
+    let relative = relative_to_cwd(&dir);
+    assert!(relative.is_relative());
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let mut project = Project::load_with(manifest, relative, &registry);
+    project.run();
+
+    let root = fs::read_to_string(dir.join("root.txt")).unwrap();
+    assert_eq!(Path::new(&root), fs::canonicalize(&dir).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn relative_files_join_project_root() {
+    let dir = project_dir("join");
+    fs::write(dir.join("table.txt"), "0 1").unwrap();
+
+    let params = params(&dir, "table.txt");
+    assert_eq!(params.project_root(), Some(dir.as_path()));
+    assert_eq!(params.output_path("file").unwrap(), dir.join("table.txt"));
+    assert_eq!(
+        params.resolve_path("file").unwrap(),
+        fs::canonicalize(dir.join("table.txt")).unwrap()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn absolute_files_ignore_project_root() {
+    let dir = project_dir("absolute");
+    let file = dir.join("table.txt");
+    fs::write(&file, "0 1").unwrap();
+
+    let params = params(Path::new("/elsewhere"), file.to_str().unwrap());
+    assert_eq!(params.output_path("file").unwrap(), file);
+    assert_eq!(
+        params.resolve_path("file").unwrap(),
+        fs::canonicalize(&file).unwrap()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn missing_files_name_key_and_path() {
+    let dir = project_dir("missing");
+
+    let err = params(&dir, "missing.txt")
+        .resolve_path("file")
+        .unwrap_err();
+    assert_eq!(
+        err,
+        VidmodError::PathNotFound {
+            key:  "file".to_owned(),
+            path: dir.join("missing.txt"),
+        }
+    );
+    assert!(err.to_string().contains("missing.txt"));
+    assert_eq!(
+        params(&dir, "missing.txt").resolve_path("table"),
+        Err(VidmodError::MissingArg {
+            key: "table".to_owned(),
+        })
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
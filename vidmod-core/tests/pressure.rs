@@ -0,0 +1,80 @@
+use std::sync::{Arc, Mutex};
+
+use vidmod_core::spec::NodeGraph;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts, PortStats, Pressure,
+};
+
+mod common;
+
+use common::{insert, link, TestSink};
+
+#[node_decl]
+struct PressureSource {
+    remaining: u16,
+    seen:      Arc<Mutex<Vec<Pressure>>>,
+    stats:     Arc<Mutex<PortStats>>,
+}
+
+impl PressureSource {
+    #[node_new]
+    fn new(seen: Arc<Mutex<Vec<Pressure>>>, stats: Arc<Mutex<PortStats>>) -> Self {
+        Self {
+            remaining: 32,
+            seen,
+            stats,
+        }
+    }
+}
+
+impl NodeImpl for PressureSource {
+    fn init(&mut self) {
+        self.register_pullport("out", FrameKind::U16, 8);
+        self.set_watermarks("out", 2, 6).unwrap();
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.remaining > 0 && self.outbuf_avail("out") > 0 {
+            self.outbuf_put_single("out", FrameSingle::U16(self.remaining));
+            self.remaining -= 1;
+            res = true;
+        }
+        self.seen.lock().unwrap().push(self.outbuf_pressure("out"));
+        *self.stats.lock().unwrap() = self.port_stats("out");
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn slow_sink_drives_pressure_high_then_low() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let stats = Arc::new(Mutex::new(PortStats::default()));
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let mut graph = NodeGraph::new();
+    let source = insert(
+        &mut graph,
+        PressureSource::new(seen.clone(), stats.clone()),
+        "source",
+    );
+    let sink = insert(&mut graph, TestSink::new(1, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+    graph.run();
+
+    let seen = seen.lock().unwrap();
+    let first_high = seen.iter().position(|p| *p == Pressure::High).unwrap();
+    assert!(seen[first_high..].contains(&Pressure::Low));
+    assert_eq!(seen.last(), Some(&Pressure::Low));
+
+    let stats = stats.lock().unwrap();
+    assert!(stats.high_pressure_count >= 1);
+    assert!(stats.pressure_transitions >= 2);
+    assert_eq!(received.lock().unwrap().len(), 32);
+}
@@ -0,0 +1,104 @@
+use std::sync::{Arc, Mutex};
+
+use vidmod_core::spec::NodeGraph;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts,
+};
+
+mod common;
+
+use common::{insert, link};
+
+/// Emits one U16 per tick, up to `count`
+#[node_decl]
+struct Trickle {
+    count: u16,
+    next:  u16,
+}
+
+impl Trickle {
+    #[node_new]
+    fn new(count: u16) -> Self {
+        Self { count, next: 0 }
+    }
+}
+
+impl NodeImpl for Trickle {
+    fn init(&mut self) {
+        self.register_pullport("out", FrameKind::U16, 8);
+    }
+
+    fn tick(&mut self) -> bool {
+        if self.next == self.count || self.outbuf_avail("out") == 0 {
+            return false;
+        }
+        self.outbuf_put_single("out", FrameSingle::U16(self.next));
+        self.next += 1;
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+/// Analyses a window of 4 frames, recording how many it was given on each tick
+#[node_decl]
+struct Window {
+    seen: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Window {
+    #[node_new]
+    fn new(seen: Arc<Mutex<Vec<usize>>>) -> Self {
+        Self { seen }
+    }
+}
+
+impl NodeImpl for Window {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U16, 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = self.inbuf_avail("in");
+        self.seen.lock().unwrap().push(count);
+        self.inbuf_get("in", count);
+        count > 0
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+fn first_window(priming: Option<usize>) -> usize {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let source = insert(&mut graph, Trickle::new(16), "source");
+    let window = insert(&mut graph, Window::new(seen.clone()), "window");
+    link(&mut graph, (source, "out"), (window, "in"));
+    graph.set_priming(priming);
+    graph.run();
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.iter().sum::<usize>(), 16);
+    seen[0]
+}
+
+#[test]
+fn primed_consumer_sees_full_window() {
+    assert_eq!(first_window(Some(64)), 4);
+}
+
+#[test]
+fn unprimed_consumer_ticks_empty() {
+    assert_eq!(first_window(None), 0);
+}
+
+#[test]
+fn priming_stops_after_passes() {
+    assert_eq!(first_window(Some(2)), 2);
+}
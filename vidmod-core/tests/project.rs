@@ -0,0 +1,443 @@
+use std::{collections::BTreeMap, fs, fs::File, path::PathBuf, time::Duration};
+
+use vidmod_core::{report::RunReport, spec::Project};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{params::Params, Node, NodeImpl, VidmodError};
+use vidmod_plugin::PluginRegistry;
+
+/// Writes its own instance name and index to a file named after itself
+#[node_decl]
+struct NamedWriter {
+    params: Params,
+}
+
+impl NamedWriter {
+    #[node_new]
+    fn new(params: BTreeMap<String, String>) -> Self {
+        Self {
+            params: Params::new(params),
+        }
+    }
+}
+
+impl NodeImpl for NamedWriter {
+    fn init(&mut self) {}
+
+    fn tick(&mut self) -> bool {
+        false
+    }
+
+    fn finish(&mut self) -> bool {
+        let name = self.params.node_name().unwrap();
+        let path = PathBuf::from(self.params.path().unwrap()).join(format!("{}.txt", name));
+        fs::write(path, self.params.node_index().unwrap().to_string()).unwrap();
+        true
+    }
+}
+
+fn make_named_writer(params: BTreeMap<String, String>) -> Node {
+    Node::new(NamedWriter::new(params))
+}
+
+/// Counts how many times it has been loaded, keeping the count in its state directory
+#[node_decl]
+struct StateCounter {
+    params: Params,
+}
+
+impl StateCounter {
+    #[node_new]
+    fn new(params: BTreeMap<String, String>) -> Self {
+        Self {
+            params: Params::new(params),
+        }
+    }
+}
+
+impl NodeImpl for StateCounter {
+    fn init(&mut self) {
+        let count = match self.params.read_state("count").unwrap() {
+            Some(data) => String::from_utf8(data).unwrap().parse::<u32>().unwrap(),
+            None => 0,
+        };
+        self.params
+            .write_state("count", (count + 1).to_string().as_bytes())
+            .unwrap();
+    }
+
+    fn tick(&mut self) -> bool {
+        false
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+fn make_state_counter(params: BTreeMap<String, String>) -> Node {
+    Node::new(StateCounter::new(params))
+}
+
+fn project_dir(name: &str, manifest: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vidmod-test-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("manifest.yml"), manifest).unwrap();
+    dir
+}
+
+fn registry() -> PluginRegistry {
+    let mut registry = PluginRegistry::new();
+    registry.register("test::NamedWriter", make_named_writer);
+    registry.register("test::StateCounter", make_state_counter);
+    registry
+}
+
+#[test]
+fn nodes_receive_identity_args() {
+    let dir = project_dir(
+        "identity",
+        r#"
+nodes:
+  first:
+    name: test::NamedWriter
+  second:
+    name: test::NamedWriter
+links: []
+"#,
+    );
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let mut project = Project::load_with(manifest, dir.clone(), &registry());
+    project.run();
+
+    assert_eq!(fs::read_to_string(dir.join("first.txt")).unwrap(), "0");
+    assert_eq!(fs::read_to_string(dir.join("second.txt")).unwrap(), "1");
+}
+
+#[test]
+#[should_panic(expected = "reserved argument vidmod.node_name")]
+fn reserved_args_are_rejected() {
+    let dir = project_dir(
+        "reserved",
+        r#"
+nodes:
+  first:
+    name: test::NamedWriter
+    args:
+      vidmod.node_name: other
+links: []
+"#,
+    );
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    Project::load_with(manifest, dir, &registry());
+}
+
+#[test]
+fn only_user_args_may_use_reserved_prefix() {
+    let dir = project_dir(
+        "reserved-prefix",
+        r#"
+nodes:
+  first:
+    name: test::NamedWriter
+    args:
+      vidmod.tick_quota: '4'
+      vidmod.lenient: 'true'
+      vidmod.tick_quot: '4'
+links: []
+"#,
+    );
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let err = Project::dry_run(manifest, &dir, &registry()).unwrap_err();
+    assert_eq!(
+        err.downcast::<VidmodError>().unwrap(),
+        VidmodError::ReservedArg {
+            node: "first".to_owned(),
+            key:  "vidmod.tick_quot".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn report_includes_link_hashes() {
+    let dir = project_dir(
+        "report",
+        r#"
+nodes:
+  counter:
+    name: core::CounterSource
+    args:
+      kind: U8
+      count: '10'
+  sink:
+    name: core::HashSink
+links:
+  - from: [counter, out]
+    to: [sink, in]
+"#,
+    );
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let mut project = Project::load_with(manifest, dir.clone(), &vidmod_core::nodes::registry());
+    let hashes = project.hash_links();
+    project.run();
+
+    let hashes = hashes.lock().unwrap();
+    let report = RunReport::new(&project, Duration::from_millis(5), &hashes);
+    let link = &report.link_hashes["counter.out->sink.in"];
+    assert_eq!(link.frames, 10);
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(
+        json["link_hashes"]["counter.out->sink.in"]["hash"],
+        link.hash.as_str()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn lint_reports_unlinked_nodes_and_ports() {
+    let dir = project_dir(
+        "lint",
+        r#"
+nodes:
+  counter:
+    name: core::CounterSource
+    args:
+      kind: U8
+      count: '4'
+  gate:
+    name: core::Gate
+    args:
+      kind: U8
+  orphan:
+    name: core::NullSink
+    args:
+      kind: U8
+links:
+  - from: [counter, out]
+    to: [gate, in]
+"#,
+    );
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let project = Project::load_with(manifest, dir.clone(), &vidmod_core::nodes::registry());
+
+    let mut warnings: Vec<_> = project.lint().iter().map(ToString::to_string).collect();
+    warnings.sort();
+    assert_eq!(
+        warnings,
+        [
+            "Node orphan has no links",
+            "Nothing consumes gate.out",
+            "Nothing produces into gate.control",
+        ]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn group_manifest(node: &str) -> String {
+    format!(
+        r#"
+groups:
+  writers:
+    name: test::NamedWriter
+    args:
+      gain: '1'
+      mode: shared
+nodes:
+{}
+links: []
+"#,
+        node
+    )
+}
+
+#[test]
+fn group_args_merge() {
+    let dir = project_dir(
+        "groups",
+        &group_manifest(
+            r#"
+  first:
+    group: writers
+    args:
+      gain: '2'
+  second:
+    group: writers
+  third:
+    name: test::NamedWriter
+    args:
+      gain: '3'"#,
+        ),
+    );
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let desc = Project::dry_run(manifest, &dir, &registry()).unwrap();
+    assert_eq!(
+        desc,
+        "first (test::NamedWriter)\n    gain: 2\n    mode: shared\n\
+         second (test::NamedWriter)\n    gain: 1\n    mode: shared\n\
+         third (test::NamedWriter)\n    gain: 3\n"
+    );
+
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let mut project = Project::load_with(manifest, dir.clone(), &registry());
+    project.run();
+    assert_eq!(fs::read_to_string(dir.join("second.txt")).unwrap(), "1");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unknown_group_is_rejected() {
+    let dir = project_dir(
+        "unknown-group",
+        &group_manifest("  first:\n    group: readers"),
+    );
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let err = Project::dry_run(manifest, &dir, &registry()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Node first references unknown group readers"
+    );
+}
+
+#[test]
+#[should_panic(expected = "Group writers uses unknown plugin test::NamedWriter")]
+fn group_with_unknown_plugin_is_rejected() {
+    let dir = project_dir(
+        "unknown-plugin",
+        &group_manifest("  first:\n    group: writers"),
+    );
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    Project::load_with(manifest, dir, &PluginRegistry::new());
+}
+
+#[test]
+fn node_needs_plugin_or_group() {
+    let dir = project_dir(
+        "plugin-and-group",
+        &group_manifest("  first:\n    name: test::NamedWriter\n    group: writers"),
+    );
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    assert!(Project::dry_run(manifest, &dir, &registry()).is_err());
+}
+
+#[test]
+fn node_state_persists_across_loads() {
+    let dir = project_dir(
+        "state",
+        r#"
+nodes:
+  counter:
+    name: test::StateCounter
+links: []
+"#,
+    );
+    let state = Project::state_root(&dir).join("counter");
+    for _ in 0..2 {
+        let manifest = File::open(dir.join("manifest.yml")).unwrap();
+        Project::load_with(manifest, dir.clone(), &registry()).run();
+    }
+    assert_eq!(fs::read_to_string(state.join("count")).unwrap(), "2");
+    assert!(!state.join("count.tmp").exists());
+
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let desc = Project::dry_run(manifest, &dir, &registry()).unwrap();
+    assert_eq!(desc, "counter (test::StateCounter)\n    state: 1 bytes\n");
+
+    Project::clean(&dir).unwrap();
+    assert!(!Project::state_root(&dir).exists());
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    Project::load_with(manifest, dir.clone(), &registry()).run();
+    assert_eq!(fs::read_to_string(state.join("count")).unwrap(), "1");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn vars_substitute_into_args() {
+    let dir = project_dir(
+        "vars",
+        r#"
+vars:
+  input: default.raw
+  gain: '2'
+groups:
+  writers:
+    name: test::NamedWriter
+    args:
+      gain: x${gain}
+nodes:
+  first:
+    group: writers
+    args:
+      file: ${input}
+links: []
+"#,
+    );
+    let mut vars = BTreeMap::new();
+    vars.insert("input".to_owned(), "custom.raw".to_owned());
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let desc = Project::dry_run_with_vars(manifest, &dir, &registry(), &vars).unwrap();
+    assert_eq!(
+        desc,
+        "first (test::NamedWriter)\n    file: custom.raw\n    gain: x2\n"
+    );
+
+    fs::write(
+        dir.join("manifest.yml"),
+        "nodes:\n  first:\n    name: test::NamedWriter\n    args:\n      file: ${output}\nlinks: []\n",
+    )
+    .unwrap();
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let err = Project::dry_run_with_vars(manifest, &dir, &registry(), &vars).unwrap_err();
+    assert_eq!(err.to_string(), "Node first: Unknown var output");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn overlay_replaces_nodes() {
+    let dir = project_dir(
+        "overlay",
+        r#"
+nodes:
+  first:
+    name: test::NamedWriter
+    args:
+      file: a.raw
+  second:
+    name: test::NamedWriter
+    args:
+      file: b.raw
+links: []
+"#,
+    );
+    fs::write(
+        dir.join("overlay.yml"),
+        "nodes:\n  second:\n    name: test::NamedWriter\n    args:\n      file: c.raw\nlinks: []\n",
+    )
+    .unwrap();
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let overlay = File::open(dir.join("overlay.yml")).unwrap();
+    let desc = Project::dry_run_with_overlays(
+        manifest,
+        vec![overlay],
+        &dir,
+        &registry(),
+        &BTreeMap::new(),
+    )
+    .unwrap();
+    assert_eq!(
+        desc,
+        "first (test::NamedWriter)\n    file: a.raw\nsecond (test::NamedWriter)\n    file: c.raw\n"
+    );
+
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let overlay = File::open(dir.join("overlay.yml")).unwrap();
+    let project = Project::load_with_overlays(
+        manifest,
+        vec![overlay],
+        dir.clone(),
+        &registry(),
+        &BTreeMap::new(),
+    );
+    assert_eq!(project.node_count(), 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,111 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    sync::{Arc, Mutex},
+};
+
+use vidmod_core::{
+    nodes::{self, Contiguous, CounterSource},
+    spec::{NodeGraph, Project},
+};
+
+mod common;
+
+use common::{insert, link, TestSink};
+
+fn params(args: &[(&str, &str)]) -> BTreeMap<String, String> {
+    args.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+// A 1000 element source feeding a filter then a sink, all with room for everything at once
+fn pipeline(quota: Option<usize>) -> (NodeGraph, Arc<Mutex<Vec<u16>>>) {
+    let mut graph = NodeGraph::new();
+    let big = [("kind", "U16"), ("buf_size", "1000")];
+    let source = insert(
+        &mut graph,
+        CounterSource::new(params(&[big[0], big[1], ("count", "1000")])),
+        "source",
+    );
+    let filter = insert(&mut graph, Contiguous::new(params(&big)), "filter");
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = insert(&mut graph, TestSink::new(1000, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (filter, "in"));
+    link(&mut graph, (filter, "out"), (sink, "in"));
+    for &id in &[source, filter, sink] {
+        graph.set_tick_quota(id, quota);
+    }
+    (graph, received)
+}
+
+fn pass(graph: &mut NodeGraph) {
+    graph.tick_nodes(None);
+    graph.tick_links();
+}
+
+#[test]
+fn unlimited_source_fills_buffer_in_one_tick() {
+    let (mut graph, received) = pipeline(None);
+    for _ in 0..3 {
+        pass(&mut graph);
+    }
+    assert_eq!(received.lock().unwrap().len(), 1000);
+    assert!(graph.budget_exhaustions().is_empty());
+}
+
+#[test]
+fn quota_interleaves_source_with_filter() {
+    let (mut graph, received) = pipeline(Some(10));
+    for _ in 0..3 {
+        pass(&mut graph);
+    }
+    // One pass each to reach the filter, the link to the sink, and the sink
+    assert_eq!(*received.lock().unwrap(), (0..10).collect::<Vec<_>>());
+    for n in 4..=10 {
+        pass(&mut graph);
+        assert_eq!(received.lock().unwrap().len(), (n - 2) * 10);
+    }
+
+    graph.run();
+    assert_eq!(*received.lock().unwrap(), (0..1000).collect::<Vec<_>>());
+    let exhausted = graph.budget_exhaustions();
+    assert_eq!(exhausted["source"], 100);
+    assert_eq!(exhausted["filter"], 100);
+    assert!(!exhausted.contains_key("sink"));
+}
+
+#[test]
+fn manifest_sets_default_quota() {
+    let dir = std::env::temp_dir().join(format!("vidmod-test-quota-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("manifest.yml"),
+        r#"
+tick_quota: 5
+nodes:
+  counter:
+    name: core::CounterSource
+    args:
+      kind: U8
+      count: '20'
+      buf_size: '64'
+  sink:
+    name: core::HashSink
+    args:
+      vidmod.tick_quota: '20'
+links:
+  - from: [counter, out]
+    to: [sink, in]
+"#,
+    )
+    .unwrap();
+    let manifest = File::open(dir.join("manifest.yml")).unwrap();
+    let mut project = Project::load_with(manifest, dir.clone(), &nodes::registry());
+    fs::remove_dir_all(&dir).unwrap();
+    project.run();
+
+    let exhausted = project.budget_exhaustions();
+    assert_eq!(exhausted["counter"], 4);
+    assert!(!exhausted.contains_key("sink"));
+}
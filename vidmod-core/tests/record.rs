@@ -0,0 +1,174 @@
+use std::{
+    fs,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use ndarray::{ArcArray1, ArcArray2};
+use vidmod_core::{
+    record::{read_recording, RecordTap, Transfer, VERSION},
+    spec::Project,
+    tap::{FrameTap, LinkId},
+};
+use vidmod_node::{
+    frame::{Frame, RGBA8},
+    limvecdeque::LimVecDeque,
+};
+
+fn project_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vidmod-record-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(dir: &Path, manifest: &str, record: Option<&str>) {
+    fs::write(dir.join("manifest.yml"), manifest).unwrap();
+    let file = File::open(dir.join("manifest.yml")).unwrap();
+    let mut project = Project::load(file, dir.to_path_buf());
+    if let Some(record) = record {
+        let link = project.link_ids().remove(0);
+        let tap = Box::new(RecordTap::new(dir.join(record)).unwrap());
+        project
+            .tap_link((&link.from.0, &link.from.1), (&link.to.0, &link.to.1), tap)
+            .unwrap();
+    }
+    project.run();
+}
+
+#[test]
+fn replay_matches_recorded_run() {
+    let dir = project_dir("replay");
+    run(
+        &dir,
+        r#"
+nodes:
+  counter:
+    name: core::CounterSource
+    args:
+      kind: U16
+      count: '100'
+      step: '7'
+      buf_size: '6'
+  sink:
+    name: core::RawFileSink
+    args:
+      kind: U16
+      file: recorded.raw
+      buf_size: '4'
+links:
+  - from: [counter, out]
+    to: [sink, in]
+"#,
+        Some("run.vmrec"),
+    );
+    let transfers = read_recording(dir.join("run.vmrec")).unwrap();
+    assert_eq!(transfers.iter().map(|t| t.frame.size()).sum::<usize>(), 100);
+    assert!(transfers.windows(2).all(|t| t[0].elapsed <= t[1].elapsed));
+
+    run(
+        &dir,
+        r#"
+nodes:
+  replay:
+    name: core::ReplaySource
+    args:
+      file: run.vmrec
+  sink:
+    name: core::RawFileSink
+    args:
+      kind: U16
+      file: replayed.raw
+      buf_size: '4'
+links:
+  - from: [replay, out]
+    to: [sink, in]
+"#,
+        Some("replay.vmrec"),
+    );
+    let replayed = read_recording(dir.join("replay.vmrec")).unwrap();
+    let sizes = |transfers: &[Transfer]| -> Vec<usize> {
+        transfers.iter().map(|t| t.frame.size()).collect()
+    };
+    assert_eq!(sizes(&replayed), sizes(&transfers));
+    let recorded = fs::read(dir.join("recorded.raw")).unwrap();
+    assert_eq!(recorded.len(), 200);
+    assert_eq!(fs::read(dir.join("replayed.raw")).unwrap(), recorded);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn arrays_round_trip() {
+    let dir = project_dir("arrays");
+    let path = dir.join("arrays.vmrec");
+    let link = LinkId {
+        from: ("a".to_owned(), "out".to_owned()),
+        to:   ("b".to_owned(), "in".to_owned()),
+    };
+    let frames = vec![
+        Frame::U8x1(LimVecDeque::from(vec![
+            ArcArray1::from_vec(vec![1, 2, 3]),
+            ArcArray1::from_vec(vec![4]),
+        ])),
+        Frame::F32x2(LimVecDeque::from(vec![ArcArray2::from_shape_vec(
+            (2, 3),
+            vec![0.5, -1.0, 2.0, f32::MAX, 0.0, 3.25],
+        )
+        .unwrap()])),
+        Frame::RGBA8x2(LimVecDeque::from(vec![ArcArray2::from_elem(
+            (3, 1),
+            RGBA8::new(1, 2, 3, 4),
+        )])),
+    ];
+    let mut tap = RecordTap::new(&path).unwrap();
+    for frame in &frames {
+        tap.on_transfer(&link, frame);
+    }
+    tap.finish().unwrap();
+
+    let transfers = read_recording(&path).unwrap();
+    assert_eq!(transfers.len(), frames.len());
+    for (transfer, frame) in transfers.iter().zip(&frames) {
+        assert_eq!(transfer.frame.rolling_hash(0), frame.rolling_hash(0));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn other_versions_are_rejected() {
+    let dir = project_dir("version");
+    let path = dir.join("future.vmrec");
+    let mut bytes = b"VMREC".to_vec();
+    bytes.extend_from_slice(&(VERSION + 1).to_le_bytes());
+    fs::write(&path, bytes).unwrap();
+    assert_eq!(
+        read_recording(&path).unwrap_err().to_string(),
+        format!(
+            "Unsupported .vmrec version {}, expected version {}",
+            VERSION + 1,
+            VERSION
+        )
+    );
+
+    fs::write(&path, b"RIFF").unwrap();
+    assert_eq!(
+        read_recording(&path).unwrap_err().to_string(),
+        "Not a .vmrec file"
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn write_errors_stop_recording_until_finish() {
+    let link = LinkId {
+        from: ("src".to_owned(), "out".to_owned()),
+        to:   ("sink".to_owned(), "in".to_owned()),
+    };
+    // Larger than the write buffer, so each transfer reaches the full device
+    let frame = Frame::U8(LimVecDeque::from(vec![0; 1 << 16]));
+    let mut tap = RecordTap::new("/dev/full").unwrap();
+    tap.on_transfer(&link, &frame);
+    tap.on_transfer(&link, &frame);
+    assert!(tap.finish().is_err());
+}
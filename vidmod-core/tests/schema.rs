@@ -0,0 +1,88 @@
+use std::{collections::BTreeMap, env, fs, path::Path};
+
+use serde_json::{json, Value};
+use vidmod_core::{nodes, spec::manifest};
+use vidmod_node::Node;
+use vidmod_plugin::{ArgSpec, ArgType, PluginRegistry};
+
+mod common;
+
+use common::TestSource;
+
+// Set UPDATE_SNAPSHOTS to rewrite the snapshot after an intended change
+#[test]
+fn static_schema_matches_snapshot() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/manifest_schema.json");
+    let schema = manifest::static_schema();
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, serde_json::to_string_pretty(&schema).unwrap() + "\n").unwrap();
+    }
+    let snapshot: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(schema, snapshot);
+}
+
+fn test_registry() -> PluginRegistry {
+    let mut registry = PluginRegistry::new();
+    registry.register("test::Source", |params: BTreeMap<String, String>| {
+        let count = params.get("count").unwrap().parse().unwrap();
+        Node::new(TestSource::new(count, 4))
+    });
+    registry.describe(
+        "test::Source",
+        vec![
+            ArgSpec::required("count", ArgType::Integer),
+            ArgSpec::optional("mode", ArgType::Enum(&["fast", "slow"])),
+        ],
+    );
+    registry.register("test::Opaque", |_: BTreeMap<String, String>| {
+        Node::new(TestSource::new(0, 4))
+    });
+    registry
+}
+
+#[test]
+fn plugin_names_are_enumerated() {
+    let schema = manifest::json_schema(&test_registry());
+    let names = schema["definitions"]["plugin"]["enum"].as_array().unwrap();
+    assert!(names.contains(&json!("test::Source")));
+    assert!(names.contains(&json!("test::Opaque")));
+}
+
+#[test]
+fn described_args_are_checked() {
+    let schema = manifest::json_schema(&test_registry());
+    let rules = schema["definitions"]["node"]["allOf"].as_array().unwrap();
+    // Plugins without descriptions accept any args
+    assert_eq!(rules.len(), 1);
+    assert_eq!(
+        rules[0]["if"]["properties"]["name"]["const"],
+        json!("test::Source")
+    );
+    let args = &rules[0]["then"]["properties"]["args"];
+    assert_eq!(args["required"], json!(["count"]));
+    assert_eq!(args["additionalProperties"], json!(false));
+    assert_eq!(args["properties"]["count"]["pattern"], json!("^-?[0-9]+$"));
+    assert_eq!(args["properties"]["mode"]["enum"], json!(["fast", "slow"]));
+
+    // Groups may leave required args to their nodes
+    let rules = schema["definitions"]["group"]["allOf"].as_array().unwrap();
+    assert_eq!(
+        rules[0]["then"]["properties"]["args"]["required"],
+        json!([])
+    );
+}
+
+#[test]
+fn core_nodes_are_described() {
+    let registry = nodes::registry();
+    for (name, plugin) in registry.plugins() {
+        if name.starts_with("core::") {
+            assert!(plugin.args.is_some(), "{} has no arg descriptions", name);
+        }
+    }
+    let schema = manifest::json_schema(&registry);
+    assert!(schema["definitions"]["plugin"]["enum"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("core::Expr")));
+}
@@ -0,0 +1,64 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use vidmod_core::{nodes::CounterSource, spec::NodeGraph};
+use vidmod_node::{NodeImpl, SeekOutcome};
+
+mod common;
+
+use common::{insert, link, TestSink, TestSource};
+
+fn counter(count: usize) -> CounterSource {
+    let mut params = BTreeMap::new();
+    params.insert("kind".to_owned(), "U16".to_owned());
+    params.insert("start".to_owned(), "100".to_owned());
+    params.insert("count".to_owned(), count.to_string());
+    CounterSource::new(params)
+}
+
+#[test]
+fn seek_starts_at_offset() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, counter(10), "src");
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+
+    let outcomes = graph.seek_sources(4).unwrap();
+    assert_eq!(outcomes["src"], SeekOutcome { position: 4 });
+    graph.run();
+    assert_eq!(
+        *received.lock().unwrap(),
+        vec![104, 105, 106, 107, 108, 109]
+    );
+}
+
+#[test]
+fn seek_past_end_reports_achieved_position() {
+    let mut node = counter(10);
+    node.init();
+    assert_eq!(node.seek(20).unwrap(), SeekOutcome { position: 10 });
+}
+
+#[test]
+fn seek_unsupported() {
+    let mut graph = NodeGraph::new();
+    insert(&mut graph, TestSource::new(4, 4), "src");
+    assert!(graph.seek_sources(1).is_err());
+}
+
+#[test]
+fn limit_and_seek() {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, counter(1000), "src");
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+
+    graph.limit_sources(3).unwrap();
+    graph.seek_sources(500).unwrap();
+    graph.run();
+    assert_eq!(*received.lock().unwrap(), vec![600, 601, 602]);
+}
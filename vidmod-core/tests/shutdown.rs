@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex};
+
+use vidmod_core::{cancel::CancellationToken, spec::NodeGraph};
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::{
+    frame::{FrameKind, FrameSingle},
+    NodeImpl, NodePorts,
+};
+
+mod common;
+
+use common::{insert, link};
+
+/// Emits a U16 counter forever, cancelling `token` once `cancel_at` frames are out, as a Ctrl-C
+/// arriving mid-run would, and emitting nothing more once the token is cancelled
+#[node_decl]
+struct EndlessSource {
+    next:      u16,
+    cancel_at: u16,
+    token:     CancellationToken,
+}
+
+impl EndlessSource {
+    #[node_new]
+    fn new(cancel_at: u16, token: CancellationToken) -> Self {
+        Self {
+            next: 0,
+            cancel_at,
+            token,
+        }
+    }
+}
+
+impl NodeImpl for EndlessSource {
+    fn init(&mut self) {
+        self.register_pullport("out", FrameKind::U16, 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.outbuf_avail("out") > 0 && !self.token.is_cancelled() {
+            self.outbuf_put_single("out", FrameSingle::U16(self.next));
+            self.next = self.next.wrapping_add(1);
+            if self.next == self.cancel_at {
+                self.token.cancel();
+            }
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        false
+    }
+}
+
+/// Buffers everything it receives, only writing it to `output` when finished
+#[node_decl]
+struct BufferedSink {
+    pending: Vec<u16>,
+    output:  Arc<Mutex<Option<Vec<u16>>>>,
+}
+
+impl BufferedSink {
+    #[node_new]
+    fn new(output: Arc<Mutex<Option<Vec<u16>>>>) -> Self {
+        Self {
+            pending: Vec::new(),
+            output,
+        }
+    }
+}
+
+impl NodeImpl for BufferedSink {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U16, 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        let count = self.inbuf_avail("in");
+        if count > 0 {
+            let frame = self.inbuf_get("in", count).unwrap_u16();
+            self.pending.extend(frame.iter().copied());
+        }
+        count > 0
+    }
+
+    fn finish(&mut self) -> bool {
+        *self.output.lock().unwrap() = Some(std::mem::take(&mut self.pending));
+        true
+    }
+}
+
+#[test]
+fn cancel_finishes_and_flushes() {
+    let token = CancellationToken::new();
+    let output = Arc::new(Mutex::new(None));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, EndlessSource::new(100, token.clone()), "src");
+    let sink = insert(&mut graph, BufferedSink::new(output.clone()), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph.set_cancellation(token);
+    graph.run();
+
+    // Frames still in flight when the token fires may or may not make it out, but whatever does
+    // must be an unbroken run from the start, and nothing past the cancel point
+    let output = output.lock().unwrap().take().unwrap();
+    assert!(
+        !output.is_empty() && output.len() <= 100,
+        "got {} frames",
+        output.len()
+    );
+    assert_eq!(output, (0..output.len() as u16).collect::<Vec<u16>>());
+}
+
+#[test]
+fn cancelled_before_run() {
+    let token = CancellationToken::new();
+    token.cancel();
+    let output = Arc::new(Mutex::new(None));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, EndlessSource::new(0, token.clone()), "src");
+    let sink = insert(&mut graph, BufferedSink::new(output.clone()), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph.set_cancellation(token);
+    graph.run();
+
+    assert_eq!(output.lock().unwrap().take(), Some(vec![]));
+}
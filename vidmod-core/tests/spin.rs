@@ -0,0 +1,55 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use vidmod_core::spec::NodeGraph;
+use vidmod_macros::{node_decl, node_new};
+use vidmod_node::NodeImpl;
+
+mod common;
+
+use common::{insert, link, TestSink, TestSource};
+
+/// Claims progress on every tick without doing anything
+#[node_decl]
+struct Spinner {
+    ticks: Arc<AtomicUsize>,
+}
+
+impl Spinner {
+    #[node_new]
+    fn new(ticks: Arc<AtomicUsize>) -> Self {
+        Self { ticks }
+    }
+}
+
+impl NodeImpl for Spinner {
+    fn init(&mut self) {}
+
+    fn tick(&mut self) -> bool {
+        self.ticks.fetch_add(1, Ordering::SeqCst);
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn inner_iteration_cap_stops_spinning_node() {
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    insert(&mut graph, Spinner::new(ticks.clone()), "spinner");
+    let source = insert(&mut graph, TestSource::new(8, 4), "source");
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    link(&mut graph, (source, "out"), (sink, "in"));
+    graph.set_max_inner_iterations(Some(10));
+
+    graph.run();
+    // Capped once while running the graph, and once more while running it to finish the source
+    assert_eq!(ticks.load(Ordering::SeqCst), 20);
+    assert_eq!(*received.lock().unwrap(), (0..8).collect::<Vec<u16>>());
+}
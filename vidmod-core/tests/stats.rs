@@ -0,0 +1,93 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use vidmod_core::{nodes::Stats, spec::NodeGraph};
+use vidmod_macros::test_source;
+
+mod common;
+
+use common::{insert, link, TestSource};
+
+test_source!(WithNan, F32, |i: usize| match i {
+    3 => Some(f32::NAN),
+    i if i < 6 => Some(i as f32 / 10.0),
+    _ => None,
+});
+
+fn params(args: &[(&str, &str)]) -> BTreeMap<String, String> {
+    args.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn report_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("vidmod-stats-{}-{}.json", name, std::process::id()))
+}
+
+fn read_report(file: &Path) -> Value {
+    let report = serde_json::from_str(&std::fs::read_to_string(file).unwrap()).unwrap();
+    std::fs::remove_file(file).unwrap();
+    report
+}
+
+#[test]
+fn stats_reports_moments_and_bins() {
+    let file = report_file("u16");
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(100, 7), "src");
+    let sink = insert(
+        &mut graph,
+        Stats::new(params(&[
+            ("file", file.to_str().unwrap()),
+            ("bins", "4"),
+            ("lo", "0"),
+            ("hi", "100"),
+        ])),
+        "stats",
+    );
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph.run();
+
+    let report = read_report(&file);
+    assert_eq!(report["count"], 100);
+    assert_eq!(report["nan"], 0);
+    assert_eq!(report["min"], 0.0);
+    assert_eq!(report["max"], 99.0);
+    assert_eq!(report["mean"], 49.5);
+    let std_dev = report["std_dev"].as_f64().unwrap();
+    assert!((std_dev - (9999.0f64 / 12.0).sqrt()).abs() < 1e-9);
+    assert_eq!(
+        report["histogram"]["bins"],
+        serde_json::json!([25, 25, 25, 25])
+    );
+    assert_eq!(report["histogram"]["underflow"], 0);
+    assert_eq!(report["histogram"]["overflow"], 0);
+}
+
+#[test]
+fn stats_counts_nans_apart() {
+    let file = report_file("f32");
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, WithNan::new(), "src");
+    let sink = insert(
+        &mut graph,
+        Stats::new(params(&[("file", file.to_str().unwrap())])),
+        "stats",
+    );
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph.run();
+
+    let report = read_report(&file);
+    assert_eq!(report["count"], 5);
+    assert_eq!(report["nan"], 1);
+    assert_eq!(report["min"], 0.0);
+    assert_eq!(report["max"], 0.5);
+    assert_eq!(report["histogram"]["lo"], 0.0);
+    assert_eq!(report["histogram"]["hi"], 1.0);
+    let bins: Vec<u64> = serde_json::from_value(report["histogram"]["bins"].clone()).unwrap();
+    let filled: Vec<usize> = (0..bins.len()).filter(|i| bins[*i] > 0).collect();
+    assert_eq!(filled, [0, 1, 3, 6, 8]);
+}
@@ -0,0 +1,110 @@
+use std::{
+    fs,
+    sync::{Arc, Mutex},
+};
+
+use vidmod_core::{
+    spec::NodeGraph,
+    tap::{FileTap, FrameTap, HashTap, LinkHashes, LinkId},
+};
+use vidmod_node::frame::Frame;
+
+mod common;
+
+use common::{insert, link, TestSink, TestSource};
+
+#[derive(Debug)]
+struct CountingTap {
+    seen: Arc<Mutex<Vec<u16>>>,
+}
+
+impl FrameTap for CountingTap {
+    fn on_transfer(&mut self, link: &LinkId, frame: &Frame) {
+        assert_eq!(link.from, ("src".to_owned(), "out".to_owned()));
+        if let Frame::U16(v) = frame {
+            self.seen.lock().unwrap().extend(v.iter());
+        } else {
+            panic!("Unexpected frame kind");
+        }
+    }
+}
+
+#[test]
+fn tap_sees_delivered_frames() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(100, 7), "src");
+    let sink = insert(&mut graph, TestSink::new(5, received.clone()), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph
+        .tap_link(
+            ("src", "out"),
+            ("sink", "in"),
+            Box::new(CountingTap { seen: seen.clone() }),
+        )
+        .unwrap();
+    graph.run();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 100);
+    assert_eq!(*seen.lock().unwrap(), *received);
+}
+
+#[test]
+fn tap_missing_link() {
+    let mut graph = NodeGraph::new();
+    insert(&mut graph, TestSource::new(1, 1), "src");
+    let tap = Box::new(CountingTap {
+        seen: Arc::new(Mutex::new(Vec::new())),
+    });
+    assert!(graph.tap_link(("src", "out"), ("sink", "in"), tap).is_err());
+}
+
+#[test]
+fn file_tap_dumps_bytes() {
+    let path = std::env::temp_dir().join(format!("vidmod-tap-{}.raw", std::process::id()));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(4, 4), "src");
+    let sink = insert(&mut graph, TestSink::new(4, received), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph
+        .tap_link(
+            ("src", "out"),
+            ("sink", "in"),
+            Box::new(FileTap::new(&path).unwrap()),
+        )
+        .unwrap();
+    graph.run();
+    drop(graph);
+
+    let bytes = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(bytes, vec![0, 0, 1, 0, 2, 0, 3, 0]);
+}
+
+#[test]
+fn hash_tap_matches_received() {
+    let hashes = LinkHashes::default();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = NodeGraph::new();
+    let src = insert(&mut graph, TestSource::new(50, 3), "src");
+    let sink = insert(&mut graph, TestSink::new(4, received.clone()), "sink");
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph
+        .tap_link(
+            ("src", "out"),
+            ("sink", "in"),
+            Box::new(HashTap::new(hashes.clone())),
+        )
+        .unwrap();
+    graph.run();
+
+    let received = received.lock().unwrap().clone();
+    let expected = Frame::U16(received.into()).rolling_hash(0);
+    let hashes = hashes.lock().unwrap();
+    let hash = hashes.values().next().unwrap();
+    assert_eq!(hash.frames, 50);
+    assert_eq!(hash.hash, expected);
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use vidmod_core::{
+    nodes::{TimecodeSink, TimecodeSource},
+    spec::NodeGraph,
+    timecode::{Timecode, TimecodeRate},
+};
+
+mod common;
+
+use common::{insert, link};
+
+fn params(args: &[(&str, &str)]) -> BTreeMap<String, String> {
+    args.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+// `name` keeps the output file apart from those of tests running alongside
+fn run(name: &str, fps: &str, start: &str, count: usize) -> Vec<String> {
+    let file = std::env::temp_dir().join(format!(
+        "vidmod-timecode-{}-{}.txt",
+        std::process::id(),
+        name
+    ));
+    let mut graph = NodeGraph::new();
+    let src = insert(
+        &mut graph,
+        TimecodeSource::new(params(&[
+            ("fps", fps),
+            ("start", start),
+            ("count", &count.to_string()),
+        ])),
+        "src",
+    );
+    let sink = insert(
+        &mut graph,
+        TimecodeSink::new(params(&[("file", file.to_str().unwrap())])),
+        "sink",
+    );
+    link(&mut graph, (src, "out"), (sink, "in"));
+    graph.run();
+
+    let written = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+    written.lines().map(str::to_owned).collect()
+}
+
+#[test]
+fn second_boundary_at_25fps() {
+    let res = run("second_boundary", "25", "00:00:00:00", 26);
+    assert_eq!(res.len(), 26);
+    assert_eq!(res[24], "00:00:00:24");
+    assert_eq!(res[25], "00:00:01:00");
+}
+
+#[test]
+fn drop_frame_skips_minute_start() {
+    let res = run("drop_frame_skip", "29.97", "00:00:59;28", 4);
+    assert_eq!(
+        res,
+        vec!["00:00:59;28", "00:00:59;29", "00:01:00;02", "00:01:00;03"]
+    );
+}
+
+#[test]
+fn drop_frame_keeps_tenth_minute() {
+    let res = run("drop_frame_tenth", "29.97", "00:09:59;29", 2);
+    assert_eq!(res, vec!["00:09:59;29", "00:10:00;00"]);
+}
+
+#[test]
+fn non_drop_frame_at_2997() {
+    let rate = TimecodeRate::parse("29.97", Some(false)).unwrap();
+    let tc = Timecode::from_frame_number(1800, rate);
+    assert_eq!(tc.to_string(), "00:01:00:00");
+}
+
+#[test]
+fn frame_number_round_trip() {
+    let rate = TimecodeRate::parse("29.97", None).unwrap();
+    for number in (0..200_000).step_by(7) {
+        let tc = Timecode::from_frame_number(number, rate);
+        tc.validate(rate).unwrap();
+        assert_eq!(tc.to_frame_number(rate), number);
+    }
+    // One hour of drop-frame timecode is 107892 frames
+    let hour: Timecode = "01:00:00;00".parse().unwrap();
+    assert_eq!(hour.to_frame_number(rate), 107_892);
+}
+
+#[test]
+fn rejects_dropped_timecode() {
+    let rate = TimecodeRate::parse("29.97", None).unwrap();
+    let tc: Timecode = "00:01:00;00".parse().unwrap();
+    assert!(tc.validate(rate).is_err());
+    assert!("00:00:00".parse::<Timecode>().is_err());
+    assert!(TimecodeRate::parse("25", Some(true)).is_err());
+}
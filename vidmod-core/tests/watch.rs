@@ -0,0 +1,61 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use vidmod_core::{nodes::registry, watch};
+
+fn project_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vidmod-watch-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const MANIFEST: &str = "
+nodes:
+  counter:
+    name: core::CounterSource
+    args:
+      kind: U16
+      count: '10'
+  limit:
+    name: core::Limit
+    args:
+      kind: U16
+      count: '5'
+links:
+  - from: [counter, out]
+    to: [limit, in]
+";
+
+#[test]
+fn rerun_picks_up_manifest_changes() {
+    let dir = project_dir("rerun");
+    fs::write(dir.join("manifest.yml"), MANIFEST).unwrap();
+    let summary = watch::rerun(&dir, &registry(), &BTreeMap::new(), &|_| {}).unwrap();
+    assert_eq!((summary.nodes, summary.links), (2, 1));
+
+    let changed = MANIFEST.replace("links:", "  extra:\n    name: core::CounterSource\n    args:\n      kind: U8\n      count: '1'\nlinks:");
+    fs::write(dir.join("manifest.yml"), changed).unwrap();
+    let summary = watch::rerun(&dir, &registry(), &BTreeMap::new(), &|_| {}).unwrap();
+    assert_eq!((summary.nodes, summary.links), (3, 1));
+
+    fs::write(dir.join("manifest.yml"), "nodes: [").unwrap();
+    assert!(watch::rerun(&dir, &registry(), &BTreeMap::new(), &|_| {}).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn watched_paths_include_referenced_files() {
+    let dir = project_dir("paths");
+    fs::write(dir.join("input.raw"), [0u8; 4]).unwrap();
+    fs::write(
+        dir.join("manifest.yml"),
+        MANIFEST.replace("count: '10'", "count: '10'\n      file: input.raw"),
+    )
+    .unwrap();
+
+    let paths = watch::watched_paths(&dir);
+    let dir = dir.canonicalize().unwrap();
+    assert_eq!(paths, vec![dir.join("input.raw"), dir.join("manifest.yml")]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
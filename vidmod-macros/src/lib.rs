@@ -8,49 +8,162 @@ extern crate quote;
 #[proc_macro_attribute]
 pub fn node_decl(_: TokenStream, item: TokenStream) -> TokenStream {
     let input_struct = syn::parse_macro_input!(item as syn::ItemStruct);
+    node_decl_impl(input_struct).into()
+}
+
+// A field annotated `#[input]` or `#[output]`, optionally with the port's name as in
+// `#[input("in")]`, which becomes a method returning a handle to the port instead of being stored
+struct PortField {
+    field:  syn::Field,
+    handle: proc_macro2::TokenStream,
+    name:   String,
+}
+
+fn port_field(field: &syn::Field) -> syn::Result<Option<PortField>> {
+    let mut res = None;
+    let mut field = field.clone();
+    let mut attrs = Vec::new();
+    for attr in field.attrs.drain(..) {
+        let handle = if attr.path.is_ident("input") {
+            quote!(vidmod_node::InputPort)
+        } else if attr.path.is_ident("output") {
+            quote!(vidmod_node::OutputPort)
+        } else {
+            attrs.push(attr);
+            continue;
+        };
+        if res.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "A port field takes one annotation",
+            ));
+        }
+        let name = if attr.tokens.is_empty() {
+            field.ident.as_ref().unwrap().to_string()
+        } else {
+            attr.parse_args::<syn::LitStr>()?.value()
+        };
+        res = Some((handle, name));
+    }
+    field.attrs = attrs;
+    Ok(res.map(|(handle, name)| PortField {
+        field,
+        handle,
+        name,
+    }))
+}
+
+fn node_decl_impl(input_struct: syn::ItemStruct) -> proc_macro2::TokenStream {
     let ident = input_struct.ident.clone();
-    let fields1 = input_struct.fields.iter();
+    let mut fields1 = Vec::new();
+    let mut ports = Vec::new();
+    for field in input_struct.fields.iter() {
+        match port_field(field) {
+            Ok(Some(port)) => ports.push(port),
+            Ok(None) => fields1.push(field),
+            Err(e) => return e.to_compile_error(),
+        }
+    }
+    let port_fns = ports.iter().map(|port| {
+        let PortField {
+            field,
+            handle,
+            name,
+        } = port;
+        let (attrs, vis, ty) = (&field.attrs, &field.vis, &field.ty);
+        let field_ident = &field.ident;
+        quote! {
+            #(#attrs)*
+            #vis fn #field_ident(&self) -> #ty {
+                let port: #handle = #handle::new(#name);
+                port
+            }
+        }
+    });
     let output = quote! {
         #[derive(Debug)]
         pub struct #ident{
             #(#fields1,)*
-            __node_node: vidmod_node::Node2,
+            __node_node: vidmod_node::NodeCore,
         }
 
         impl #ident{
+            #(#port_fns)*
         }
 
-        impl vidmod_node::Node2MT for #ident{
+        impl vidmod_node::NodePorts for #ident{
             fn register_pullport(&mut self, name:&str, kind: vidmod_node::frame::FrameKind, buf_size: usize) {
                 self.__node_node.register_pullport(name,kind,buf_size)
             }
             fn register_pushport(&mut self, name:&str, kind: vidmod_node::frame::FrameKind, buf_size: usize) {
                 self.__node_node.register_pushport(name,kind,buf_size)
             }
-            fn get_pull_port(&self, id: usize, name: &str) -> anyhow::Result<PullPort> {
+            fn try_register_pullport(&mut self, name:&str, kind: vidmod_node::frame::FrameKind, buf_size: usize) -> ::std::result::Result<(), vidmod_node::VidmodError> {
+                self.__node_node.try_register_pullport(name,kind,buf_size)
+            }
+            fn try_register_pushport(&mut self, name:&str, kind: vidmod_node::frame::FrameKind, buf_size: usize) -> ::std::result::Result<(), vidmod_node::VidmodError> {
+                self.__node_node.try_register_pushport(name,kind,buf_size)
+            }
+            fn register_pullport_with_policy(&mut self, name:&str, kind: vidmod_node::frame::FrameKind, buf_size: usize, policy: vidmod_node::OverflowPolicy) {
+                self.__node_node.register_pullport_with_policy(name,kind,buf_size,policy)
+            }
+            fn register_pushport_with_policy(&mut self, name:&str, kind: vidmod_node::frame::FrameKind, buf_size: usize, policy: vidmod_node::OverflowPolicy) {
+                self.__node_node.register_pushport_with_policy(name,kind,buf_size,policy)
+            }
+            fn register_pullport_with_depth(&mut self, name:&str, kind: vidmod_node::frame::FrameKind, buf_size: usize, depth: u8) {
+                self.__node_node.register_pullport_with_depth(name,kind,buf_size,depth)
+            }
+            fn register_pushport_with_depth(&mut self, name:&str, kind: vidmod_node::frame::FrameKind, buf_size: usize, depth: u8) {
+                self.__node_node.register_pushport_with_depth(name,kind,buf_size,depth)
+            }
+            fn register_pushport_any(&mut self, name:&str, kinds: &[vidmod_node::frame::FrameKind], buf_size: usize) {
+                self.__node_node.register_pushport_any(name,kinds,buf_size)
+            }
+            fn inbuf_kind(&self, name: &str) -> Option<vidmod_node::frame::FrameKind> {
+                self.__node_node.inbuf_kind(name)
+            }
+            fn inbuf_bit_depth(&self, name: &str) -> Option<u8> {
+                self.__node_node.inbuf_bit_depth(name)
+            }
+            fn set_batch(&mut self, name: &str, hint: vidmod_node::BatchHint) -> ::std::result::Result<(), vidmod_node::VidmodError> {
+                self.__node_node.set_batch(name,hint)
+            }
+            fn batch_hint(&self, name: &str) -> Option<vidmod_node::BatchHint> {
+                self.__node_node.batch_hint(name)
+            }
+            fn set_watermarks(&mut self, name: &str, low: usize, high: usize) -> ::std::result::Result<(), vidmod_node::VidmodError> {
+                self.__node_node.set_watermarks(name,low,high)
+            }
+            fn get_pull_port(&self, id: vidmod_node::NodeId, name: &str) -> ::std::result::Result<vidmod_node::PullPort, vidmod_node::VidmodError> {
                 self.__node_node.get_pull_port(id,name)
             }
-            fn get_push_port(&self, id: usize, name: &str) -> anyhow::Result<PushPort> {
+            fn get_push_port(&self, id: vidmod_node::NodeId, name: &str) -> ::std::result::Result<vidmod_node::PushPort, vidmod_node::VidmodError> {
                 self.__node_node.get_push_port(id,name)
             }
-            fn attach_pull_port(&self, name: &str, port: PullPort) -> anyhow::Result<()> {
+            fn attach_pull_port(&mut self, name: &str, port: vidmod_node::PullPort) -> ::std::result::Result<(), vidmod_node::VidmodError> {
                 self.__node_node.attach_pull_port(name,port)
             }
-            fn attach_push_port(&self, name: &str, port: PushPort) -> anyhow::Result<()> {
+            fn attach_push_port(&mut self, name: &str, port: vidmod_node::PushPort) -> ::std::result::Result<(), vidmod_node::VidmodError> {
                 self.__node_node.attach_push_port(name,port)
             }
-            fn ready_to_pull(&self, port: &PullPort) -> usize {
+            fn ready_to_pull(&self, port: &vidmod_node::PullPort) -> usize {
                 self.__node_node.ready_to_pull(port)
             }
-            fn ready_to_push(&self, port: &PushPort) -> usize {
+            fn ready_to_push(&self, port: &vidmod_node::PushPort) -> usize {
                 self.__node_node.ready_to_push(port)
             }
-            fn pull_frame(&mut self, port: &PullPort, count: usize) -> vidmod_node::frame::Frame {
+            fn free_to_push(&self, port: &vidmod_node::PushPort) -> usize {
+                self.__node_node.free_to_push(port)
+            }
+            fn pull_frame(&mut self, port: &vidmod_node::PullPort, count: usize) -> vidmod_node::frame::Frame {
                 self.__node_node.pull_frame(port,count)
             }
-            fn push_frame(&mut self, port: &PushPort, frame: vidmod_node::frame::Frame) {
+            fn push_frame(&mut self, port: &vidmod_node::PushPort, frame: vidmod_node::frame::Frame) {
                 self.__node_node.push_frame(port,frame)
             }
+            fn signal_eos(&mut self, port: &vidmod_node::PushPort) {
+                self.__node_node.signal_eos(port)
+            }
             fn inbuf_avail(&self, name: &str) -> usize {
                 self.__node_node.inbuf_avail(name)
             }
@@ -66,26 +179,119 @@ pub fn node_decl(_: TokenStream, item: TokenStream) -> TokenStream {
             fn inbuf_get(&mut self, name: &str, count: usize) -> vidmod_node::frame::Frame {
                 self.__node_node.inbuf_get(name,count)
             }
-            fn inbuf_peek(&mut self, name: &str, count: usize) -> vidmod_node::frame::Frame {
+            fn inbuf_peek(&self, name: &str, count: usize) -> vidmod_node::frame::Frame {
                 self.__node_node.inbuf_peek(name,count)
             }
+            fn inbuf_peek_single(&self, name: &str) -> Option<vidmod_node::frame::FrameSingle> {
+                self.__node_node.inbuf_peek_single(name)
+            }
             fn inbuf_get_single(&mut self, name: &str) -> vidmod_node::frame::FrameSingle {
                 self.__node_node.inbuf_get_single(name)
             }
+            fn inbuf_try_get_single(&mut self, name: &str) -> Option<vidmod_node::frame::FrameSingle> {
+                self.__node_node.inbuf_try_get_single(name)
+            }
             fn inbuf_get_all(&mut self, name: &str) -> vidmod_node::frame::Frame {
                 self.__node_node.inbuf_get_all(name)
             }
+            fn inbuf_min_avail(&self, names: &[&str]) -> usize {
+                self.__node_node.inbuf_min_avail(names)
+            }
+            fn inbuf_get_zipped(&mut self, names: &[&str], count: usize) -> Option<Vec<vidmod_node::frame::Frame>> {
+                self.__node_node.inbuf_get_zipped(names,count)
+            }
+            fn inbuf_eos(&self, name: &str) -> bool {
+                self.__node_node.inbuf_eos(name)
+            }
+            fn outbuf_pressure(&self, name: &str) -> vidmod_node::Pressure {
+                self.__node_node.outbuf_pressure(name)
+            }
+            fn port_stats(&self, name: &str) -> vidmod_node::PortStats {
+                self.__node_node.port_stats(name)
+            }
+            fn buffer_bytes(&self) -> ::std::vec::Vec<(::std::string::String, usize)> {
+                self.__node_node.buffer_bytes()
+            }
+            fn port_names(&self) -> ::std::vec::Vec<(::std::string::String, vidmod_node::PortDirection)> {
+                self.__node_node.port_names()
+            }
+            fn inbuf_shape_changed(&mut self, name: &str) -> Option<vidmod_node::ShapeChange> {
+                self.__node_node.inbuf_shape_changed(name)
+            }
+            fn set_lenient(&mut self, lenient: bool) {
+                self.__node_node.set_lenient(lenient)
+            }
+            fn take_error(&mut self) -> Option<vidmod_node::VidmodError> {
+                self.__node_node.take_error()
+            }
+            fn is_port_empty(&self, name: &str) -> bool {
+                self.__node_node.is_port_empty(name)
+            }
+            fn is_port_full(&self, name: &str) -> bool {
+                self.__node_node.is_port_full(name)
+            }
+            fn set_tick_quota(&mut self, quota: Option<usize>) {
+                self.__node_node.set_tick_quota(quota)
+            }
+            fn tick_budget(&self) -> Option<usize> {
+                self.__node_node.tick_budget()
+            }
+            fn reset_budget(&mut self) {
+                self.__node_node.reset_budget()
+            }
+            fn budget_remaining(&self) -> usize {
+                self.__node_node.budget_remaining()
+            }
+            fn consume_budget(&mut self, count: usize) {
+                self.__node_node.consume_budget(count)
+            }
+            fn budget_exhausted(&self) -> bool {
+                self.__node_node.budget_exhausted()
+            }
+            fn assert_shape(&self, name: &str, expected: &[usize]) -> ::std::result::Result<(), vidmod_node::VidmodError> {
+                self.__node_node.assert_shape(name, expected)
+            }
+            fn set_clock(&mut self, tick: u64) {
+                self.__node_node.set_clock(tick)
+            }
+            fn clock(&self) -> u64 {
+                self.__node_node.clock()
+            }
+            fn idle_for(&self, name: &str) -> u64 {
+                self.__node_node.idle_for(name)
+            }
+            fn last_progress(&self) -> u64 {
+                self.__node_node.last_progress()
+            }
+            fn inbuf_origins(&self, name: &str) -> Vec<u64> {
+                self.__node_node.inbuf_origins(name)
+            }
+            fn pulled_origins(&self) -> &[u64] {
+                self.__node_node.pulled_origins()
+            }
+            fn set_pushed_origins(&mut self, port: &vidmod_node::PushPort, origins: &[u64]) {
+                self.__node_node.set_pushed_origins(port, origins)
+            }
+            fn request(&mut self, name: &str, count: usize) {
+                self.__node_node.request(name, count)
+            }
+            fn requested(&self, name: &str) -> usize {
+                self.__node_node.requested(name)
+            }
+            fn fulfil_request(&mut self, port: &vidmod_node::PushPort, count: usize) {
+                self.__node_node.fulfil_request(port, count)
+            }
         }
 
-        //Compile-time check to ensure our node implements Node2T
+        //Compile-time check to ensure our node implements NodeImpl
         const _: () = {
-            fn assert_Node2T<T: vidmod_node::Node2T>() {}
+            fn assert_NodeImpl<T: vidmod_node::NodeImpl>() {}
             fn assert_all() {
-                assert_Node2T::<#ident>();
+                assert_NodeImpl::<#ident>();
             }
         };
     };
-    output.into()
+    output
 }
 
 #[proc_macro_attribute]
@@ -96,7 +302,7 @@ pub fn node_new(_: TokenStream, item: TokenStream) -> TokenStream {
     let struct_stmt = &mut stmts[stmts_len - 1];
     if let syn::Stmt::Expr(syn::Expr::Struct(s)) = struct_stmt {
         s.fields
-            .push(syn::parse_quote!(__node_node: vidmod_node::Node2::new()));
+            .push(syn::parse_quote!(__node_node: vidmod_node::NodeCore::new()));
     }
     let output = quote! {
         #input_fn
@@ -205,3 +411,94 @@ pub fn unwrap_impl_frame_single(item: TokenStream) -> TokenStream {
     };
     output.into()
 }
+
+struct TestSourceArgs {
+    name:      Ident,
+    _comma1:   syn::token::Comma,
+    kind:      Ident,
+    _comma2:   syn::token::Comma,
+    generator: syn::Expr,
+}
+
+impl syn::parse::Parse for TestSourceArgs {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(TestSourceArgs {
+            name:      input.parse()?,
+            _comma1:   input.parse()?,
+            kind:      input.parse()?,
+            _comma2:   input.parse()?,
+            generator: input.parse()?,
+        })
+    }
+}
+
+/// Declare a source node emitting frames of the given kind on "out"
+///
+/// `test_source!(Name, Kind, generator)` where the generator is called with the index of each
+/// frame and returns `Some(value)` to emit `FrameSingle::Kind(value)`, or `None` to end the stream.
+#[proc_macro]
+pub fn test_source(item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(item as TestSourceArgs);
+    let name = args.name;
+    let kind = args.kind;
+    let generator = args.generator;
+    let decl = node_decl_impl(syn::parse_quote! {
+        struct #name {
+            next: usize,
+            done: bool,
+        }
+    });
+    let output = quote! {
+        #decl
+
+        impl #name {
+            pub fn new() -> Self {
+                Self {
+                    next: 0,
+                    done: false,
+                    __node_node: vidmod_node::NodeCore::new(),
+                }
+            }
+        }
+
+        impl vidmod_node::NodeImpl for #name {
+            fn init(&mut self) {
+                vidmod_node::NodePorts::register_pullport(
+                    self,
+                    "out",
+                    vidmod_node::frame::FrameKind::#kind,
+                    16,
+                );
+            }
+
+            fn tick(&mut self) -> bool {
+                let generator = #generator;
+                let mut res = false;
+                while !self.done
+                    && vidmod_node::NodePorts::outbuf_avail(self, "out") > 0
+                    && vidmod_node::NodePorts::budget_remaining(self) > 0
+                {
+                    match generator(self.next) {
+                        Some(value) => {
+                            vidmod_node::NodePorts::outbuf_put_single(
+                                self,
+                                "out",
+                                vidmod_node::frame::FrameSingle::#kind(value),
+                            );
+                            self.next += 1;
+                            vidmod_node::NodePorts::consume_budget(self, 1);
+                            res = true;
+                        }
+                        None => self.done = true,
+                    }
+                }
+                res
+            }
+
+            fn finish(&mut self) -> bool {
+                self.done
+            }
+        }
+    };
+    output.into()
+}
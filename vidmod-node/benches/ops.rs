@@ -0,0 +1,47 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ndarray::{ArcArray2, Array2};
+use vidmod_node::frame::{ops, RGBA8};
+
+const SIZES: &[(usize, usize)] = &[(64, 64), (720, 576), (1920, 1080)];
+
+fn image(size: (usize, usize)) -> ArcArray2<RGBA8> {
+    Array2::from_shape_fn(size, |(y, x)| {
+        RGBA8::new(x as u8, y as u8, (x ^ y) as u8, 255)
+    })
+    .into_shared()
+}
+
+fn bench_ops(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ops");
+    for &size in SIZES {
+        let img = image(size);
+        let id = format!("{}x{}", size.0, size.1);
+        group.throughput(Throughput::Elements((size.0 * size.1) as u64));
+        group.bench_with_input(BenchmarkId::new("to_luma_u8", &id), &img, |b, img| {
+            b.iter(|| ops::to_luma_u8(img))
+        });
+        group.bench_with_input(BenchmarkId::new("to_luma_u16", &id), &img, |b, img| {
+            b.iter(|| ops::to_luma_u16(img))
+        });
+        group.bench_with_input(BenchmarkId::new("apply_levels", &id), &img, |b, img| {
+            b.iter(|| ops::apply_levels(img, 16, 235))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("apply_gain_offset", &id),
+            &img,
+            |b, img| {
+                b.iter(|| ops::apply_gain_offset(img, [1.1, 1.0, 0.9, 1.0], [-4.0, 0.0, 4.0, 0.0]))
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("split_merge", &id), &img, |b, img| {
+            b.iter(|| {
+                let [r, g, b, a] = ops::split_channels(img);
+                ops::merge_channels(&r, &g, &b, &a)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ops);
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static HELD: AtomicUsize = AtomicUsize::new(0);
+
+/// The process-wide count of bytes held by frames waiting in port buffers
+///
+/// Every `NodeCore` acquires the bytes of the frames put into its buffers, and releases them as
+/// the frames are taken out, dropped on overflow, or the node itself is dropped. Each update is a
+/// single atomic add or subtract, so accounting is always on.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameAccounting;
+
+impl FrameAccounting {
+    /// Get the bytes held in port buffers across the process
+    pub fn bytes() -> usize {
+        HELD.load(Ordering::Relaxed)
+    }
+    /// Count bytes as held
+    pub fn acquire(bytes: usize) {
+        HELD.fetch_add(bytes, Ordering::Relaxed);
+    }
+    /// Count bytes as no longer held
+    pub fn release(bytes: usize) {
+        HELD.fetch_sub(bytes, Ordering::Relaxed);
+    }
+    /// Acquire or release the difference as a buffer grows or shrinks from `before` to `after`
+    pub fn resize(before: usize, after: usize) {
+        if after > before {
+            Self::acquire(after - before)
+        } else {
+            Self::release(before - after)
+        }
+    }
+}
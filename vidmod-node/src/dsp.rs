@@ -0,0 +1,243 @@
+use std::{collections::VecDeque, f64::consts::PI};
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// A Blackman-windowed sinc, `half` input samples either side of zero
+fn kernel(d: f64, cutoff: f64, half: usize) -> f64 {
+    let x = d / half as f64;
+    if x.abs() >= 1.0 {
+        return 0.0;
+    }
+    let window = 0.42 + 0.5 * (PI * x).cos() + 0.08 * (2.0 * PI * x).cos();
+    let t = PI * cutoff * d;
+    let sinc = if t == 0.0 { 1.0 } else { t.sin() / t };
+    cutoff * sinc * window
+}
+
+/// A streaming windowed-sinc polyphase resampler
+///
+/// The ratio is reduced to `up / down`, and a table of filter taps is kept for each of the `up`
+/// phases, so ratios with a large reduced numerator need a large table. Input is taken in chunks
+/// of any size, and the filter history is carried between them.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    up:       usize,
+    down:     usize,
+    half:     usize,
+    taps:     Vec<Vec<f32>>,
+    history:  VecDeque<f32>,
+    // Absolute input index of the first sample in `history`
+    start:    i64,
+    received: u64,
+    produced: u64,
+    flushed:  bool,
+}
+
+impl Resampler {
+    /// Create a resampler from `from_rate` to `to_rate`, with `half` taps either side of each output
+    ///
+    /// Panics if either rate or `half` is zero.
+    pub fn new(from_rate: usize, to_rate: usize, half: usize) -> Self {
+        assert!(
+            from_rate > 0 && to_rate > 0 && half > 0,
+            "Invalid resampler: {} to {} with {} taps",
+            from_rate,
+            to_rate,
+            half
+        );
+        let div = gcd(from_rate, to_rate);
+        let (up, down) = (to_rate / div, from_rate / div);
+        // Filter below the lower of the two Nyquist frequencies, with a little margin
+        let cutoff = 0.95 * f64::min(1.0, up as f64 / down as f64);
+        let taps = (0..up)
+            .map(|phase| {
+                let taps: Vec<f64> = (0..2 * half)
+                    .map(|k| {
+                        let d = (half - 1) as f64 - k as f64 + phase as f64 / up as f64;
+                        kernel(d, cutoff, half)
+                    })
+                    .collect();
+                // Normalise each phase to unity gain at DC
+                let sum: f64 = taps.iter().sum();
+                taps.iter().map(|t| (t / sum) as f32).collect()
+            })
+            .collect();
+        let history = std::iter::repeat(0.0).take(half - 1).collect();
+        Self {
+            up,
+            down,
+            half,
+            taps,
+            history,
+            start: 1 - half as i64,
+            received: 0,
+            produced: 0,
+            flushed: false,
+        }
+    }
+
+    /// Get the reduced ratio as `(up, down)`
+    pub fn ratio(&self) -> (usize, usize) {
+        (self.up, self.down)
+    }
+
+    /// Get the number of output samples the input so far should produce, rounding up
+    pub fn expected_len(&self) -> u64 {
+        (self.received * self.up as u64 + self.down as u64 - 1) / self.down as u64
+    }
+
+    /// Feed input samples, appending any output samples that are now ready to `out`
+    ///
+    /// Panics if called after `flush`.
+    pub fn process(&mut self, input: &[f32], out: &mut Vec<f32>) {
+        assert!(!self.flushed, "Resampler already flushed");
+        self.history.extend(input);
+        self.received += input.len() as u64;
+        self.drain(out);
+    }
+
+    /// Pad the input with silence and append the remaining output samples to `out`
+    ///
+    /// The total output is then `expected_len` samples long.
+    pub fn flush(&mut self, out: &mut Vec<f32>) {
+        if self.flushed {
+            return;
+        }
+        self.flushed = true;
+        self.history
+            .extend(std::iter::repeat(0.0).take(self.half + 1));
+        self.drain(out);
+    }
+
+    fn drain(&mut self, out: &mut Vec<f32>) {
+        let end = self.start + self.history.len() as i64;
+        let limit = self.expected_len();
+        loop {
+            if self.flushed && self.produced >= limit {
+                break;
+            }
+            let pos = self.produced * self.down as u64;
+            let centre = (pos / self.up as u64) as i64;
+            if centre + self.half as i64 >= end {
+                break;
+            }
+            let taps = &self.taps[(pos % self.up as u64) as usize];
+            let first = (centre + 1 - self.half as i64 - self.start) as usize;
+            let sample = self
+                .history
+                .iter()
+                .skip(first)
+                .take(2 * self.half)
+                .zip(taps)
+                .map(|(x, t)| x * t)
+                .sum();
+            out.push(sample);
+            self.produced += 1;
+        }
+        // Drop history no longer needed by the next output
+        let centre = (self.produced * self.down as u64 / self.up as u64) as i64;
+        let keep_from = centre + 1 - self.half as i64;
+        while self.start < keep_from && !self.history.is_empty() {
+            self.history.pop_front();
+            self.start += 1;
+        }
+    }
+}
+
+/// Decides how many times each input frame is output when converting between frame rates
+///
+/// The ratio of output to input rate is kept as a reduced fraction with a fractional accumulator,
+/// so after `n` inputs exactly `floor(n * output / input)` frames have been output, for any ratio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateController {
+    output: u64,
+    input:  u64,
+    acc:    u64,
+}
+
+impl RateController {
+    /// Create a controller converting `input` frames into `output` frames
+    ///
+    /// Panics if either is zero.
+    pub fn new(output: u64, input: u64) -> Self {
+        assert!(
+            output > 0 && input > 0,
+            "Invalid rate ratio: {}/{}",
+            output,
+            input
+        );
+        let div = gcd(output as usize, input as usize) as u64;
+        Self {
+            output: output / div,
+            input:  input / div,
+            acc:    0,
+        }
+    }
+    /// Get the reduced ratio as `(output, input)`
+    pub fn ratio(&self) -> (u64, u64) {
+        (self.output, self.input)
+    }
+    /// Take the next input frame, returning how many times to output it
+    pub fn next_count(&mut self) -> usize {
+        self.acc += self.output;
+        let count = self.acc / self.input;
+        self.acc %= self.input;
+        count as usize
+    }
+}
+
+/// A seeded pseudo-random generator, xoshiro256** with its state expanded from the seed by
+/// SplitMix64
+///
+/// The sequence for a seed depends only on integer arithmetic, so it is identical across runs and
+/// platforms. It is not cryptographic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prng {
+    state: [u64; 4],
+}
+
+impl Prng {
+    /// Create a generator whose sequence is fixed by `seed`
+    pub fn new(seed: u64) -> Self {
+        let mut x = seed;
+        let mut state = [0; 4];
+        for s in &mut state {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            *s = z ^ (z >> 31);
+        }
+        Self { state }
+    }
+    /// Get the next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let res = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        res
+    }
+    /// Get a uniform value in `[0, 1)`, with 24 bits of precision
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u32 << 24) as f32
+    }
+    /// Get an approximately normal value with mean 0 and standard deviation 1
+    ///
+    /// This is the sum of 12 uniform values less 6, so it lies in `[-6, 6]`. Unlike Box-Muller it
+    /// needs no transcendental functions, keeping the sequence identical across platforms.
+    pub fn next_gaussian(&mut self) -> f32 {
+        (0..12).map(|_| self.next_f32()).sum::<f32>() - 6.0
+    }
+}
@@ -0,0 +1,524 @@
+use std::{error::Error, fmt, path::PathBuf, time::Duration};
+
+use crate::{frame::FrameKind, BatchHint, NodeId};
+
+/// Which side of a node a port is on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PortDirection {
+    /// An output, which frames are pulled from
+    Pull,
+    /// An input, which frames are pushed to
+    Push,
+}
+
+impl fmt::Display for PortDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pull => write!(f, "pull"),
+            Self::Push => write!(f, "push"),
+        }
+    }
+}
+
+/// Errors returned by port and link operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum VidmodError {
+    /// The named port does not exist, on the node with the given ID if known
+    PortNotFound {
+        /// The node's ID
+        node:      Option<usize>,
+        /// The port's name
+        port:      String,
+        /// The side of the node it was looked for on, if only one side was searched
+        direction: Option<PortDirection>,
+    },
+    /// The two ends of a link have different frame kinds
+    KindMismatch {
+        /// The port being attached to, if the kinds belong to ports
+        port:      Option<String>,
+        /// The side of its node the port being attached to is on, if the kinds belong to ports
+        direction: Option<PortDirection>,
+        /// The kind of the port being attached to
+        expected:  FrameKind,
+        /// The kind of the port being attached
+        got:       FrameKind,
+    },
+    /// A multi-kind port does not accept the kind it is being linked to
+    KindNotAccepted {
+        /// The kinds the port accepts
+        accepted: Vec<FrameKind>,
+        /// The kind of the port being attached
+        got:      FrameKind,
+    },
+    /// A batch hint is zero or does not fit in the port's buffer
+    InvalidBatch {
+        /// The port's name
+        port:     String,
+        /// The rejected hint
+        hint:     BatchHint,
+        /// The capacity of the port's buffer
+        capacity: usize,
+    },
+    /// Watermarks are out of order or do not fit in the port's buffer
+    InvalidWatermarks {
+        /// The port's name
+        port:     String,
+        /// The rejected low watermark
+        low:      usize,
+        /// The rejected high watermark
+        high:     usize,
+        /// The capacity of the port's buffer
+        capacity: usize,
+    },
+    /// A port was registered with a buffer that can never hold a frame
+    ZeroCapacity {
+        /// The port's name
+        port: String,
+    },
+    /// A frame of the given kind cannot be made with the given shape
+    InvalidShape {
+        /// The kind of frame
+        kind:  FrameKind,
+        /// The rejected shape
+        shape: Option<(usize, usize)>,
+    },
+    /// A bit depth is outside 1 to 16, or was given for a kind other than U16, U16x1 or U16x2
+    InvalidBitDepth {
+        /// The port's name, if the depth was given for a port
+        port:  Option<String>,
+        /// The kind of frame
+        kind:  FrameKind,
+        /// The rejected depth
+        depth: u8,
+    },
+    /// Two linked ports declare different bit depths, which is only a warning
+    BitDepthMismatch {
+        /// The producing node and port
+        from:       (String, String),
+        /// The consuming node and port
+        to:         (String, String),
+        /// The producing port's depth
+        from_depth: u8,
+        /// The consuming port's depth
+        to_depth:   u8,
+    },
+    /// Text could not be parsed as a frame of the given kind
+    InvalidValue {
+        /// The kind of frame
+        kind:  FrameKind,
+        /// The rejected text
+        value: String,
+    },
+    /// The wrong number of tiles were given to reassemble an image
+    TileCountMismatch {
+        /// The number of tiles the image needs
+        expected: usize,
+        /// The number of tiles given
+        got:      usize,
+    },
+    /// The arrays in a frame cannot be stacked as they do not all have the same shape
+    RaggedStack {
+        /// The position in the queue of the first array with a different shape
+        index:    usize,
+        /// The shape of the first array in the queue
+        expected: (usize, usize),
+        /// The shape of the array at `index`
+        got:      (usize, usize),
+    },
+    /// A frame's bytes do not divide into whole elements of the kind it is reinterpreted as
+    ReinterpretMismatch {
+        /// The kind of the frame
+        from:  FrameKind,
+        /// The kind it is reinterpreted as
+        to:    FrameKind,
+        /// The number of bytes that must divide into whole elements
+        bytes: usize,
+    },
+    /// Ordered or error-diffusion dithering was asked of a kind that is not 2D
+    InvalidDither {
+        /// The kind of frame
+        kind: FrameKind,
+    },
+    /// A slice is too short to copy a frame's bytes into
+    SliceTooSmall {
+        /// The number of bytes in the frame
+        needed: usize,
+        /// The length of the slice
+        len:    usize,
+    },
+    /// Two frames combined elementwise differ in shape
+    ShapesDiffer {
+        /// The shape of the first frame
+        a: Vec<usize>,
+        /// The shape of the second frame
+        b: Vec<usize>,
+    },
+    /// A frame waiting on a port does not have the shape the node expects
+    ShapeMismatch {
+        /// The port's name
+        port:     String,
+        /// The shape the node expects
+        expected: Vec<usize>,
+        /// The shape of the frame
+        got:      Vec<usize>,
+    },
+    /// A port's buffer has no room for more frames
+    BufferFull {
+        /// The port's name
+        port:     String,
+        /// The capacity of the port's buffer
+        capacity: usize,
+    },
+    /// More frames were taken from a port than it holds
+    NotEnoughFrames {
+        /// The port's name
+        port:      String,
+        /// The number of frames asked for
+        wanted:    usize,
+        /// The number of frames in the buffer
+        available: usize,
+    },
+    /// A lenient node misused the buffer API
+    NodeFailed {
+        /// The node's name
+        node:  String,
+        /// The first misuse
+        error: Box<VidmodError>,
+    },
+    /// There is no link between the two named ports
+    LinkNotFound {
+        /// The producing node and port
+        from: (String, String),
+        /// The consuming node and port
+        to:   (String, String),
+    },
+    /// The node does not support seeking
+    SeekUnsupported,
+    /// The node does not accept control messages with this key
+    MessageUnsupported {
+        /// The message's key
+        key: String,
+    },
+    /// A node rejected a control message sent to it while running
+    MessageFailed {
+        /// The node's name
+        node:    String,
+        /// The message's key
+        key:     String,
+        /// The error the node returned
+        message: String,
+    },
+    /// Too many control messages are waiting to be delivered to a node
+    MessageQueueFull {
+        /// The node's name
+        node:     String,
+        /// The most messages that can wait
+        capacity: usize,
+    },
+    /// The graph has no node with this name
+    NodeNameNotFound {
+        /// The name looked up
+        node: String,
+    },
+    /// A node panicked while being ticked or finished
+    NodePanicked {
+        /// The node's name
+        node:    String,
+        /// The panic message
+        message: String,
+    },
+    /// A node could not acquire its resources at the start of a run
+    StartFailed {
+        /// The node's name
+        node:    String,
+        /// The error the node returned
+        message: String,
+    },
+    /// A node could not release its resources at the end of a run
+    StopFailed {
+        /// The node's name
+        node:    String,
+        /// The error the node returned
+        message: String,
+    },
+    /// A node's ticks repeatedly ran over its hard time limit
+    TickLimitExceeded {
+        /// The node's name
+        node:    String,
+        /// How long the last offending tick took
+        elapsed: Duration,
+        /// The node's hard limit
+        limit:   Duration,
+        /// How many ticks ran over the limit
+        count:   usize,
+    },
+    /// Frames waiting in port buffers hold more bytes than the graph allows
+    FrameBytesExceeded {
+        /// The most bytes allowed
+        limit:   usize,
+        /// The bytes held across the process
+        held:    usize,
+        /// The graph's largest port buffers as `node.port` and bytes, largest first
+        largest: Vec<(String, usize)>,
+    },
+    /// A port or node handle refers to a node that has since been removed from its graph
+    StaleHandle {
+        /// The node the handle was made for
+        node: NodeId,
+        /// The port's name, if the handle is a port
+        port: Option<String>,
+    },
+    /// A graph already has a node with this name
+    DuplicateNodeName {
+        /// The name in use
+        node: String,
+    },
+    /// A node was not given an argument it needs
+    MissingArg {
+        /// The argument's name
+        key: String,
+    },
+    /// The file named by an argument does not exist
+    PathNotFound {
+        /// The argument's name
+        key:  String,
+        /// The path the argument resolved to
+        path: PathBuf,
+    },
+    /// A manifest sets an argument reserved for vidmod that users may not set
+    ReservedArg {
+        /// The node's name
+        node: String,
+        /// The argument's name
+        key:  String,
+    },
+    /// A node has no links, which is only a warning
+    UnlinkedNode {
+        /// The node's name
+        node: String,
+    },
+    /// A port of a linked node has no link, which is only a warning
+    UnlinkedPort {
+        /// The node's name
+        node:      String,
+        /// The port's name
+        port:      String,
+        /// Whether frames are never taken from the port, or never given to it
+        direction: PortDirection,
+    },
+}
+
+impl fmt::Display for VidmodError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PortNotFound {
+                node,
+                port,
+                direction,
+            } => {
+                write!(f, "No ")?;
+                if let Some(direction) = direction {
+                    write!(f, "{} ", direction)?;
+                }
+                match node {
+                    Some(node) => write!(f, "port {} on node {}", port, node),
+                    None => write!(f, "port: {}", port),
+                }
+            }
+            Self::KindMismatch {
+                port: Some(port),
+                expected,
+                got,
+                ..
+            } => write!(
+                f,
+                "Port kind mismatch: {:?},{:?} on {}",
+                got, expected, port
+            ),
+            Self::KindMismatch {
+                port: None,
+                expected,
+                got,
+                ..
+            } => write!(f, "Port kind mismatch: {:?},{:?}", got, expected),
+            Self::KindNotAccepted { accepted, got } => {
+                write!(f, "Port kind mismatch: {:?},{:?}", got, accepted)
+            }
+            Self::InvalidBatch {
+                port,
+                hint,
+                capacity,
+            } => write!(
+                f,
+                "Invalid batch hint {:?} for buffer of {}: {}",
+                hint, capacity, port
+            ),
+            Self::InvalidWatermarks {
+                port,
+                low,
+                high,
+                capacity,
+            } => write!(
+                f,
+                "Invalid watermarks {},{} for buffer of {}: {}",
+                low, high, capacity, port
+            ),
+            Self::ZeroCapacity { port } => write!(f, "Zero capacity buffer: {}", port),
+            Self::InvalidShape { kind, shape } => {
+                write!(f, "Invalid shape {:?} for {:?}", shape, kind)
+            }
+            Self::InvalidBitDepth { port, kind, depth } => {
+                write!(f, "Invalid bit depth {} for {:?}", depth, kind)?;
+                if let Some(port) = port {
+                    write!(f, ": {}", port)?;
+                }
+                Ok(())
+            }
+            Self::BitDepthMismatch {
+                from,
+                to,
+                from_depth,
+                to_depth,
+            } => write!(
+                f,
+                "Bit depth mismatch: {}.{} is {}-bit, {}.{} is {}-bit",
+                from.0, from.1, from_depth, to.0, to.1, to_depth
+            ),
+            Self::TileCountMismatch { expected, got } => {
+                write!(f, "Tile count mismatch: expected {}, got {}", expected, got)
+            }
+            Self::RaggedStack {
+                index,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Cannot stack arrays: expected {:?}, got {:?} at {}",
+                expected, got, index
+            ),
+            Self::ReinterpretMismatch { from, to, bytes } => write!(
+                f,
+                "Cannot reinterpret {} bytes of {:?} as {:?}",
+                bytes, from, to
+            ),
+            Self::InvalidDither { kind } => {
+                write!(f, "Spatial dithering needs a 2D kind, got {:?}", kind)
+            }
+            Self::SliceTooSmall { needed, len } => {
+                write!(f, "Slice too small: needs {} bytes, has {}", needed, len)
+            }
+            Self::ShapesDiffer { a, b } => write!(f, "Shapes differ: {:?} and {:?}", a, b),
+            Self::InvalidValue { kind, value } => {
+                write!(f, "Invalid {:?} value: {:?}", kind, value)
+            }
+            Self::ShapeMismatch {
+                port,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Shape mismatch: {} expected {:?}, got {:?}",
+                port, expected, got
+            ),
+            Self::BufferFull { port, capacity } => {
+                write!(f, "Buffer full: {} holds {}", port, capacity)
+            }
+            Self::NotEnoughFrames {
+                port,
+                wanted,
+                available,
+            } => write!(
+                f,
+                "Not enough frames: {} wanted {}, has {}",
+                port, wanted, available
+            ),
+            Self::NodeFailed { node, error } => write!(f, "Node {} failed: {}", node, error),
+            Self::LinkNotFound { from, to } => {
+                write!(f, "No link: {}.{} -> {}.{}", from.0, from.1, to.0, to.1)
+            }
+            Self::SeekUnsupported => write!(f, "Seek not supported"),
+            Self::MessageUnsupported { key } => write!(f, "Message {} not supported", key),
+            Self::MessageFailed { node, key, message } => {
+                write!(f, "Node {} rejected message {}: {}", node, key, message)
+            }
+            Self::MessageQueueFull { node, capacity } => write!(
+                f,
+                "Message queue full: node {} already has {} waiting",
+                node, capacity
+            ),
+            Self::NodeNameNotFound { node } => write!(f, "No node named {}", node),
+            Self::NodePanicked { node, message } => {
+                write!(f, "Node {} panicked: {}", node, message)
+            }
+            Self::StartFailed { node, message } => {
+                write!(f, "Node {} failed to start: {}", node, message)
+            }
+            Self::StopFailed { node, message } => {
+                write!(f, "Node {} failed to stop: {}", node, message)
+            }
+            Self::TickLimitExceeded {
+                node,
+                elapsed,
+                limit,
+                count,
+            } => write!(
+                f,
+                "Node {} exceeded its tick limit of {}ms {} times, last taking {}ms",
+                node,
+                limit.as_millis(),
+                count,
+                elapsed.as_millis()
+            ),
+            Self::FrameBytesExceeded {
+                limit,
+                held,
+                largest,
+            } => {
+                write!(
+                    f,
+                    "Frames hold {} bytes, over the limit of {}. Largest buffers:",
+                    held, limit
+                )?;
+                for (port, bytes) in largest {
+                    write!(f, " {} ({} bytes)", port, bytes)?;
+                }
+                Ok(())
+            }
+            Self::StaleHandle { node, port } => {
+                write!(f, "Stale ")?;
+                if let Some(port) = port {
+                    write!(f, "port handle {} on ", port)?;
+                } else {
+                    write!(f, "handle to ")?;
+                }
+                write!(
+                    f,
+                    "node {} (generation {}), which has been removed",
+                    node.index(),
+                    node.generation()
+                )
+            }
+            Self::DuplicateNodeName { node } => write!(f, "Duplicate node name {}", node),
+            Self::MissingArg { key } => write!(f, "Missing argument {}", key),
+            Self::PathNotFound { key, path } => {
+                write!(f, "No file at {:?} for argument {}", path, key)
+            }
+            Self::ReservedArg { node, key } => {
+                write!(f, "Node {} sets reserved argument {}", node, key)
+            }
+            Self::UnlinkedNode { node } => write!(f, "Node {} has no links", node),
+            Self::UnlinkedPort {
+                node,
+                port,
+                direction: PortDirection::Pull,
+            } => write!(f, "Nothing consumes {}.{}", node, port),
+            Self::UnlinkedPort {
+                node,
+                port,
+                direction: PortDirection::Push,
+            } => write!(f, "Nothing produces into {}.{}", node, port),
+        }
+    }
+}
+
+impl Error for VidmodError {}
@@ -1,12 +1,61 @@
-use std::iter::FromIterator;
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    mem::size_of,
+    str::FromStr,
+};
 
-use ndarray::{ArcArray1, ArcArray2};
-use vidmod_macros::{unwrap_impl_frame, unwrap_impl_frame_single};
+#[cfg(feature = "macros")]
+use anyhow::Result;
+use ndarray::{ArcArray, ArcArray1, ArcArray2, Array1, Array2, Array3, Dimension, Ix3};
 
-use crate::limvecdeque::LimVecDeque;
+use crate::{limvecdeque::LimVecDeque, VidmodError};
 
-#[derive(Debug, Clone)]
-#[repr(packed)]
+/// Colour conversion and levels operations on image arrays
+pub mod ops;
+
+// Elementwise arithmetic between two frames
+mod arith;
+
+// Reinterpreting the raw bytes of a frame as another kind
+mod reinterpret;
+
+/// A shared 3D array, as returned by the `stack_*` methods of `Frame`. ndarray only names the 1D
+/// and 2D shared arrays
+pub type ArcArray3<A> = ArcArray<A, Ix3>;
+
+// How many emptied queues each thread keeps for `Frame::remove` to reuse
+const SPARE_QUEUES: usize = 16;
+
+thread_local! {
+    static SPARE: RefCell<Vec<Frame>> = RefCell::new(Vec::with_capacity(SPARE_QUEUES));
+}
+
+// The `unwrap_*` accessors of `Frame` and `FrameSingle`, panicking on any other kind. Declared
+// here rather than in vidmod-macros so frames build without the `macros` feature.
+macro_rules! unwrap_fns {
+    ($($name:ident: $variant:ident($ret:ty), $label:literal;)*) => {$(
+        /// Unwrap the frame into its contents
+        pub fn $name(self) -> $ret {
+            match self {
+                Self::$variant(v) => v,
+                _ => panic!(concat!("Tried to unwrap {:?} as ", $label), FrameKind::from(&self)),
+            }
+        }
+    )*};
+}
+
+/// The byte order of multi-byte samples in raw data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    /// Least significant byte first
+    Little,
+    /// Most significant byte first
+    Big,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(C)]
 #[allow(missing_docs)]
 pub struct RGBA8 {
     pub r: u8,
@@ -15,7 +64,77 @@ pub struct RGBA8 {
     pub a: u8,
 }
 
+impl RGBA8 {
+    /// Opaque black
+    pub const BLACK: Self = Self::new(0, 0, 0, 255);
+    /// Opaque white
+    pub const WHITE: Self = Self::new(255, 255, 255, 255);
+    /// Fully transparent black
+    pub const TRANSPARENT: Self = Self::new(0, 0, 0, 0);
+
+    /// Create a pixel from its four channels
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Create an opaque pixel from its colour channels
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::new(r, g, b, 255)
+    }
+
+    /// Create a pixel from its channels as bytes, in RGBA order
+    pub const fn from_bytes(bytes: [u8; 4]) -> Self {
+        Self::new(bytes[0], bytes[1], bytes[2], bytes[3])
+    }
+
+    /// Get the pixel's channels as bytes, in RGBA order
+    pub const fn to_bytes(self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    /// View pixels as their bytes, in RGBA order, without copying
+    #[cfg(feature = "bytemuck")]
+    pub fn slice_as_bytes(pixels: &[RGBA8]) -> &[u8] {
+        bytemuck::cast_slice(pixels)
+    }
+
+    /// View bytes in RGBA order as pixels, without copying
+    ///
+    /// Panics if the length of `bytes` is not a multiple of 4.
+    #[cfg(feature = "bytemuck")]
+    pub fn slice_from_bytes(bytes: &[u8]) -> &[RGBA8] {
+        bytemuck::cast_slice(bytes)
+    }
+}
+
+// Safety: four u8 fields under repr(C) leave no padding, and any bytes are a valid pixel
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for RGBA8 {}
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for RGBA8 {}
+
+impl From<(u8, u8, u8, u8)> for RGBA8 {
+    fn from((r, g, b, a): (u8, u8, u8, u8)) -> Self {
+        Self::new(r, g, b, a)
+    }
+}
+
 /// A frame is a single point of data to pass between nodes
+///
+/// A frame is a first-in, first-out queue: every `add` variant appends at the back and every
+/// `remove` variant takes from the front, so elements leave in the order they were added.
+///
+/// # Allocation
+///
+/// A queue allocates its storage once, in `with_capacity`. After that `add_single`,
+/// `remove_single`, `add_partial`, `peek_at` and `peek_single` never allocate, and nor do `add`
+/// and `add_overwrite`, though they free the storage of the frame they consume. Array kinds only
+/// bump a reference count when their arrays are moved or peeked. `remove` and `split_at` reuse
+/// the storage of queues handed back by `recycle` where the thread has one of the same kind,
+/// which `NodeCore` does with every frame it empties into a port buffer, so moving frames over a
+/// link between nodes doesn't allocate once the first transfer has. Otherwise anything returning
+/// a new queue allocates one: `remove`, `peek`, `split_at`, `remove_all` and conversions, except
+/// that a count of zero gives an empty queue without allocating.
 #[derive(Debug, Clone)]
 pub enum Frame {
     /// A buffer of single u8s
@@ -41,6 +160,7 @@ pub enum Frame {
 }
 
 /// A frame is a single point of data to pass between nodes
+#[derive(Debug, Clone)]
 pub enum FrameSingle {
     /// A buffer of single u8s
     U8(u8),
@@ -89,20 +209,217 @@ pub enum FrameKind {
     RGBA8x2,
 }
 
+impl FrameKind {
+    /// Get the neutral frame of this kind, for nodes that must emit something before they have data
+    ///
+    /// Numbers are zero and pixels transparent black. Array kinds give a single element, so a 1D
+    /// array of length 1 or a 1x1 2D array, as they have no shape of their own.
+    pub fn default_scalar(&self) -> FrameSingle {
+        let shape = match ops::dims(*self) {
+            0 => None,
+            _ => Some((1, 1)),
+        };
+        FrameSingle::try_splat(*self, shape, 0.0).unwrap()
+    }
+}
+
+/// An element type of 2D frames, see `FrameSingle::from_array2`
+pub trait ArrayElement: Sized {
+    /// Wrap a 2D array in the matching frame
+    fn frame2(a: ArcArray2<Self>) -> FrameSingle;
+}
+
+/// An element type of scalar and 1D frames as well as 2D ones, see `FrameSingle::from_scalar`
+pub trait ScalarElement: ArrayElement {
+    /// Wrap a single value in the matching frame
+    fn frame0(v: Self) -> FrameSingle;
+    /// Wrap a 1D array in the matching frame
+    fn frame1(a: ArcArray1<Self>) -> FrameSingle;
+}
+
+macro_rules! element {
+    ($t:ty, $scalar:ident, $x1:ident, $x2:ident) => {
+        impl ArrayElement for $t {
+            fn frame2(a: ArcArray2<Self>) -> FrameSingle {
+                FrameSingle::$x2(a)
+            }
+        }
+        impl ScalarElement for $t {
+            fn frame0(v: Self) -> FrameSingle {
+                FrameSingle::$scalar(v)
+            }
+            fn frame1(a: ArcArray1<Self>) -> FrameSingle {
+                FrameSingle::$x1(a)
+            }
+        }
+    };
+}
+
+element!(u8, U8, U8x1, U8x2);
+element!(u16, U16, U16x1, U16x2);
+element!(f32, F32, F32x1, F32x2);
+
+impl ArrayElement for RGBA8 {
+    fn frame2(a: ArcArray2<Self>) -> FrameSingle {
+        FrameSingle::RGBA8x2(a)
+    }
+}
+
 impl FrameSingle {
-    unwrap_impl_frame_single!(u8, 0);
-    unwrap_impl_frame_single!(u8, 1);
-    unwrap_impl_frame_single!(u8, 2);
-    unwrap_impl_frame_single!(u16, 0);
-    unwrap_impl_frame_single!(u16, 1);
-    unwrap_impl_frame_single!(u16, 2);
-    unwrap_impl_frame_single!(f32, 0);
-    unwrap_impl_frame_single!(f32, 1);
-    unwrap_impl_frame_single!(f32, 2);
-    unwrap_impl_frame_single!(RGBA8, 2);
+    /// Make a scalar frame, whose kind follows from the value's type
+    pub fn from_scalar<T: ScalarElement>(v: T) -> FrameSingle {
+        T::frame0(v)
+    }
+    /// Make a 1D frame, taking over the array's storage without copying it
+    pub fn from_array1<T: ScalarElement>(a: Array1<T>) -> FrameSingle {
+        T::frame1(a.into_shared())
+    }
+    /// Make a 2D frame, taking over the array's storage without copying it
+    pub fn from_array2<T: ArrayElement>(a: Array2<T>) -> FrameSingle {
+        T::frame2(a.into_shared())
+    }
+    /// Create a frame of the given kind with every value zero
+    ///
+    /// See `splat` for how `shape` is used.
+    #[cfg(feature = "macros")]
+    pub fn zero(kind: FrameKind, shape: Option<(usize, usize)>) -> Result<FrameSingle> {
+        Self::splat(kind, shape, 0.0)
+    }
+    /// Create a frame of the given kind with every value, or every RGBA channel, set to `value`
+    ///
+    /// Integer kinds round and saturate the value. Scalar kinds ignore `shape`, 2D kinds require it,
+    /// and 1D kinds require it to be `(len, 1)`.
+    #[cfg(feature = "macros")]
+    pub fn splat(
+        kind: FrameKind,
+        shape: Option<(usize, usize)>,
+        value: f64,
+    ) -> Result<FrameSingle> {
+        Ok(Self::try_splat(kind, shape, value)?)
+    }
+    // `splat` without anyhow, for the port bookkeeping
+    pub(crate) fn try_splat(
+        kind: FrameKind,
+        shape: Option<(usize, usize)>,
+        value: f64,
+    ) -> Result<FrameSingle, VidmodError> {
+        let invalid = || VidmodError::InvalidShape { kind, shape };
+        let dim2 = || shape.ok_or_else(invalid);
+        let dim1 = || match shape {
+            Some((len, 1)) => Ok(len),
+            _ => Err(invalid()),
+        };
+        let (v8, v16, v32) = (value.round() as u8, value.round() as u16, value as f32);
+        Ok(match kind {
+            FrameKind::U8 => Self::U8(v8),
+            FrameKind::U8x1 => Self::U8x1(ArcArray1::from_elem(dim1()?, v8)),
+            FrameKind::U8x2 => Self::U8x2(ArcArray2::from_elem(dim2()?, v8)),
+            FrameKind::U16 => Self::U16(v16),
+            FrameKind::U16x1 => Self::U16x1(ArcArray1::from_elem(dim1()?, v16)),
+            FrameKind::U16x2 => Self::U16x2(ArcArray2::from_elem(dim2()?, v16)),
+            FrameKind::F32 => Self::F32(v32),
+            FrameKind::F32x1 => Self::F32x1(ArcArray1::from_elem(dim1()?, v32)),
+            FrameKind::F32x2 => Self::F32x2(ArcArray2::from_elem(dim2()?, v32)),
+            FrameKind::RGBA8x2 => {
+                Self::RGBA8x2(ArcArray2::from_elem(dim2()?, RGBA8::new(v8, v8, v8, v8)))
+            }
+        })
+    }
+    /// Get the kind of the frame
+    pub fn kind(&self) -> FrameKind {
+        self.into()
+    }
+    /// Get the number of bytes taken by the frame's elements
+    pub fn bytes(&self) -> usize {
+        match self {
+            Self::U8(_) => size_of::<u8>(),
+            Self::U8x1(a) => array_bytes(a),
+            Self::U8x2(a) => array_bytes(a),
+            Self::U16(_) => size_of::<u16>(),
+            Self::U16x1(a) => array_bytes(a),
+            Self::U16x2(a) => array_bytes(a),
+            Self::F32(_) => size_of::<f32>(),
+            Self::F32x1(a) => array_bytes(a),
+            Self::F32x2(a) => array_bytes(a),
+            Self::RGBA8x2(a) => array_bytes(a),
+        }
+    }
+    /// Parse a frame of the given kind from text, as written in a manifest
+    ///
+    /// Scalar kinds take a single value, 1D kinds comma-separated values, and 2D kinds rows of
+    /// comma-separated values separated by semicolons. RGBA8 pixels are written `r:g:b:a`.
+    pub fn parse(kind: FrameKind, text: &str) -> Result<FrameSingle, VidmodError> {
+        let invalid = || VidmodError::InvalidValue {
+            kind,
+            value: text.to_owned(),
+        };
+        fn value<T: FromStr>(text: &str) -> Option<T> {
+            text.trim().parse().ok()
+        }
+        fn pixel(text: &str) -> Option<RGBA8> {
+            let c: Vec<u8> = text.split(':').map(value).collect::<Option<_>>()?;
+            match c.as_slice() {
+                [r, g, b, a] => Some(RGBA8::new(*r, *g, *b, *a)),
+                _ => None,
+            }
+        }
+        fn row<T>(text: &str, f: fn(&str) -> Option<T>) -> Option<Vec<T>> {
+            text.split(',').map(f).collect()
+        }
+        fn rows<T>(text: &str, f: fn(&str) -> Option<T>) -> Option<ArcArray2<T>> {
+            let rows: Vec<Vec<T>> = text.split(';').map(|r| row(r, f)).collect::<Option<_>>()?;
+            let cols = rows[0].len();
+            let values = rows.into_iter().flatten().collect::<Vec<T>>();
+            ArcArray2::from_shape_vec((values.len() / cols, cols), values).ok()
+        }
+        let frame = match kind {
+            FrameKind::U8 => value(text).map(Self::U8),
+            FrameKind::U8x1 => row(text, value).map(|v| Self::U8x1(ArcArray1::from_vec(v))),
+            FrameKind::U8x2 => rows(text, value).map(Self::U8x2),
+            FrameKind::U16 => value(text).map(Self::U16),
+            FrameKind::U16x1 => row(text, value).map(|v| Self::U16x1(ArcArray1::from_vec(v))),
+            FrameKind::U16x2 => rows(text, value).map(Self::U16x2),
+            FrameKind::F32 => value(text).map(Self::F32),
+            FrameKind::F32x1 => row(text, value).map(|v| Self::F32x1(ArcArray1::from_vec(v))),
+            FrameKind::F32x2 => rows(text, value).map(Self::F32x2),
+            FrameKind::RGBA8x2 => rows(text, pixel).map(Self::RGBA8x2),
+        };
+        frame.ok_or_else(invalid)
+    }
+    unwrap_fns! {
+        unwrap_u8: U8(u8), "U8";
+        unwrap_u8x1: U8x1(ArcArray1<u8>), "U8";
+        unwrap_u8x2: U8x2(ArcArray2<u8>), "U8";
+        unwrap_u16: U16(u16), "U16";
+        unwrap_u16x1: U16x1(ArcArray1<u16>), "U16";
+        unwrap_u16x2: U16x2(ArcArray2<u16>), "U16";
+        unwrap_f32: F32(f32), "F32";
+        unwrap_f32x1: F32x1(ArcArray1<f32>), "F32";
+        unwrap_f32x2: F32x2(ArcArray2<f32>), "F32";
+        unwrap_rgba8x2: RGBA8x2(ArcArray2<RGBA8>), "RGBA8";
+    }
 }
 
 impl Frame {
+    /// Create a queue of `count` frames, each made by `FrameSingle::splat`
+    #[cfg(feature = "macros")]
+    pub fn filled(
+        kind: FrameKind,
+        shape: Option<(usize, usize)>,
+        value: f64,
+        count: usize,
+    ) -> Result<Frame> {
+        let single = FrameSingle::splat(kind, shape, value)?;
+        let mut frame = Frame::with_capacity(kind, count);
+        for _ in 0..count {
+            frame.add_single(single.clone()).unwrap();
+        }
+        Ok(frame)
+    }
+    /// Get the kind of the frames in the queue
+    pub fn kind(&self) -> FrameKind {
+        self.into()
+    }
     /// Get the number of frames in the queue
     pub fn size(&self) -> usize {
         match self {
@@ -133,6 +450,10 @@ impl Frame {
             Self::RGBA8x2(v) => v.capacity(),
         }
     }
+    /// Check whether the queue holds no frames
+    pub fn is_empty(&self) -> bool {
+        self.size() == 0
+    }
     /// Add a number of frames to the queue
     pub fn add(&mut self, data: Frame) -> Option<()> {
         if self.capacity() >= self.size() + data.size() {
@@ -153,6 +474,62 @@ impl Frame {
             None
         }
     }
+    /// Move as many frames from `data` as will fit into the queue, returning how many were moved
+    pub fn add_partial(&mut self, data: &mut Frame) -> usize {
+        let count = usize::min(data.size(), self.capacity() - self.size());
+        if count == 0 {
+            return 0;
+        }
+        match (self, data) {
+            (Self::U8(v), Self::U8(d)) => v.transfer_from(d, count),
+            (Self::U8x1(v), Self::U8x1(d)) => v.transfer_from(d, count),
+            (Self::U8x2(v), Self::U8x2(d)) => v.transfer_from(d, count),
+            (Self::U16(v), Self::U16(d)) => v.transfer_from(d, count),
+            (Self::U16x1(v), Self::U16x1(d)) => v.transfer_from(d, count),
+            (Self::U16x2(v), Self::U16x2(d)) => v.transfer_from(d, count),
+            (Self::F32(v), Self::F32(d)) => v.transfer_from(d, count),
+            (Self::F32x1(v), Self::F32x1(d)) => v.transfer_from(d, count),
+            (Self::F32x2(v), Self::F32x2(d)) => v.transfer_from(d, count),
+            (Self::RGBA8x2(v), Self::RGBA8x2(d)) => v.transfer_from(d, count),
+            (this, data) => panic!(
+                "Tried to unwrap {:?} as {:?}",
+                FrameKind::from(&*data),
+                FrameKind::from(&*this)
+            ),
+        }
+    }
+    /// Add a number of frames to the queue, dropping the oldest frames to make room
+    /// Returns how many frames were dropped
+    pub fn add_overwrite(&mut self, data: Frame) -> usize {
+        match self {
+            Self::U8(v) => v.append_overwrite(&mut data.unwrap_u8()),
+            Self::U8x1(v) => v.append_overwrite(&mut data.unwrap_u8x1()),
+            Self::U8x2(v) => v.append_overwrite(&mut data.unwrap_u8x2()),
+            Self::U16(v) => v.append_overwrite(&mut data.unwrap_u16()),
+            Self::U16x1(v) => v.append_overwrite(&mut data.unwrap_u16x1()),
+            Self::U16x2(v) => v.append_overwrite(&mut data.unwrap_u16x2()),
+            Self::F32(v) => v.append_overwrite(&mut data.unwrap_f32()),
+            Self::F32x1(v) => v.append_overwrite(&mut data.unwrap_f32x1()),
+            Self::F32x2(v) => v.append_overwrite(&mut data.unwrap_f32x2()),
+            Self::RGBA8x2(v) => v.append_overwrite(&mut data.unwrap_rgba8x2()),
+        }
+    }
+    /// Add a single frame to the queue, dropping the oldest frame if full
+    /// Returns true if a frame was dropped
+    pub fn add_single_overwrite(&mut self, data: FrameSingle) -> bool {
+        match self {
+            Self::U8(v) => v.push_back_overwrite(data.unwrap_u8()).is_some(),
+            Self::U8x1(v) => v.push_back_overwrite(data.unwrap_u8x1()).is_some(),
+            Self::U8x2(v) => v.push_back_overwrite(data.unwrap_u8x2()).is_some(),
+            Self::U16(v) => v.push_back_overwrite(data.unwrap_u16()).is_some(),
+            Self::U16x1(v) => v.push_back_overwrite(data.unwrap_u16x1()).is_some(),
+            Self::U16x2(v) => v.push_back_overwrite(data.unwrap_u16x2()).is_some(),
+            Self::F32(v) => v.push_back_overwrite(data.unwrap_f32()).is_some(),
+            Self::F32x1(v) => v.push_back_overwrite(data.unwrap_f32x1()).is_some(),
+            Self::F32x2(v) => v.push_back_overwrite(data.unwrap_f32x2()).is_some(),
+            Self::RGBA8x2(v) => v.push_back_overwrite(data.unwrap_rgba8x2()).is_some(),
+        }
+    }
     /// Add a single frame to the queue
     pub fn add_single(&mut self, data: FrameSingle) -> Option<()> {
         if self.capacity() > self.size() {
@@ -174,61 +551,71 @@ impl Frame {
         }
     }
     /// Look a number of frames from the queue without removing
-    pub fn peek(&mut self, count: usize) -> Option<Frame> {
-        if self.size() >= count {
+    /// A count of zero returns an empty frame without allocating
+    pub fn peek(&self, count: usize) -> Option<Frame> {
+        if count == 0 {
+            Some(Frame::with_capacity(self.into(), 0))
+        } else if self.size() >= count {
             Some(match self {
-                Self::U8(v) => Frame::U8(LimVecDeque::from(v.make_contiguous()[..count].to_vec())),
-                Self::U8x1(v) => {
-                    Frame::U8x1(LimVecDeque::from(v.make_contiguous()[..count].to_vec()))
-                }
-                Self::U8x2(v) => {
-                    Frame::U8x2(LimVecDeque::from(v.make_contiguous()[..count].to_vec()))
-                }
-                Self::U16(v) => {
-                    Frame::U16(LimVecDeque::from(v.make_contiguous()[..count].to_vec()))
-                }
-                Self::U16x1(v) => {
-                    Frame::U16x1(LimVecDeque::from(v.make_contiguous()[..count].to_vec()))
-                }
-                Self::U16x2(v) => {
-                    Frame::U16x2(LimVecDeque::from(v.make_contiguous()[..count].to_vec()))
-                }
-                Self::F32(v) => {
-                    Frame::F32(LimVecDeque::from(v.make_contiguous()[..count].to_vec()))
-                }
-                Self::F32x1(v) => {
-                    Frame::F32x1(LimVecDeque::from(v.make_contiguous()[..count].to_vec()))
-                }
-                Self::F32x2(v) => {
-                    Frame::F32x2(LimVecDeque::from(v.make_contiguous()[..count].to_vec()))
-                }
-                Self::RGBA8x2(v) => {
-                    Frame::RGBA8x2(LimVecDeque::from(v.make_contiguous()[..count].to_vec()))
-                }
+                Self::U8(v) => Frame::U8(v.iter().take(count).cloned().collect()),
+                Self::U8x1(v) => Frame::U8x1(v.iter().take(count).cloned().collect()),
+                Self::U8x2(v) => Frame::U8x2(v.iter().take(count).cloned().collect()),
+                Self::U16(v) => Frame::U16(v.iter().take(count).cloned().collect()),
+                Self::U16x1(v) => Frame::U16x1(v.iter().take(count).cloned().collect()),
+                Self::U16x2(v) => Frame::U16x2(v.iter().take(count).cloned().collect()),
+                Self::F32(v) => Frame::F32(v.iter().take(count).cloned().collect()),
+                Self::F32x1(v) => Frame::F32x1(v.iter().take(count).cloned().collect()),
+                Self::F32x2(v) => Frame::F32x2(v.iter().take(count).cloned().collect()),
+                Self::RGBA8x2(v) => Frame::RGBA8x2(v.iter().take(count).cloned().collect()),
             })
         } else {
             None
         }
     }
+    /// Look at the frame at a position in the queue without removing
+    pub fn peek_at(&self, index: usize) -> Option<FrameSingle> {
+        match self {
+            Self::U8(v) => v.iter().nth(index).cloned().map(FrameSingle::U8),
+            Self::U8x1(v) => v.iter().nth(index).cloned().map(FrameSingle::U8x1),
+            Self::U8x2(v) => v.iter().nth(index).cloned().map(FrameSingle::U8x2),
+            Self::U16(v) => v.iter().nth(index).cloned().map(FrameSingle::U16),
+            Self::U16x1(v) => v.iter().nth(index).cloned().map(FrameSingle::U16x1),
+            Self::U16x2(v) => v.iter().nth(index).cloned().map(FrameSingle::U16x2),
+            Self::F32(v) => v.iter().nth(index).cloned().map(FrameSingle::F32),
+            Self::F32x1(v) => v.iter().nth(index).cloned().map(FrameSingle::F32x1),
+            Self::F32x2(v) => v.iter().nth(index).cloned().map(FrameSingle::F32x2),
+            Self::RGBA8x2(v) => v.iter().nth(index).cloned().map(FrameSingle::RGBA8x2),
+        }
+    }
+    /// Look at the first frame in the queue without removing
+    pub fn peek_single(&self) -> Option<FrameSingle> {
+        self.peek_at(0)
+    }
     /// Remove a number of frames from the queue
+    /// A count of zero returns an empty frame without allocating
     pub fn remove(&mut self, count: usize) -> Option<Frame> {
-        if self.size() >= count {
-            Some(match self {
-                Self::U8(v) => Frame::U8(LimVecDeque::from_iter(v.drain(..count))),
-                Self::U8x1(v) => Frame::U8x1(LimVecDeque::from_iter(v.drain(..count))),
-                Self::U8x2(v) => Frame::U8x2(LimVecDeque::from_iter(v.drain(..count))),
-                Self::U16(v) => Frame::U16(LimVecDeque::from_iter(v.drain(..count))),
-                Self::U16x1(v) => Frame::U16x1(LimVecDeque::from_iter(v.drain(..count))),
-                Self::U16x2(v) => Frame::U16x2(LimVecDeque::from_iter(v.drain(..count))),
-                Self::F32(v) => Frame::F32(LimVecDeque::from_iter(v.drain(..count))),
-                Self::F32x1(v) => Frame::F32x1(LimVecDeque::from_iter(v.drain(..count))),
-                Self::F32x2(v) => Frame::F32x2(LimVecDeque::from_iter(v.drain(..count))),
-                Self::RGBA8x2(v) => Frame::RGBA8x2(LimVecDeque::from_iter(v.drain(..count))),
-            })
+        if count == 0 {
+            Some(Frame::with_capacity(FrameKind::from(self as &Frame), 0))
+        } else if self.size() >= count {
+            let mut res = Frame::spare(self.kind(), count);
+            res.add_partial(self);
+            Some(res)
         } else {
             None
         }
     }
+    /// Split all frames in the queue into those before `index` and the rest, leaving it empty
+    ///
+    /// Panics if `index` is greater than the number of frames.
+    pub fn split_at(&mut self, index: usize) -> (Frame, Frame) {
+        let size = self.size();
+        if index > size {
+            panic!("Split index {} out of range: {}", index, size);
+        }
+        let first = self.remove(index).unwrap();
+        let second = self.remove(size - index).unwrap();
+        (first, second)
+    }
     /// Remove all frames from the queue
     pub fn remove_all(&mut self) -> Frame {
         let mut new = Frame::with_capacity(FrameKind::from(self as &Frame), self.capacity());
@@ -254,7 +641,7 @@ impl Frame {
     pub fn with_capacity(kind: FrameKind, capacity: usize) -> Self {
         match kind {
             FrameKind::U8 => Self::U8(LimVecDeque::with_capacity(capacity)),
-            FrameKind::U8x1 => Self::U8x2(LimVecDeque::with_capacity(capacity)),
+            FrameKind::U8x1 => Self::U8x1(LimVecDeque::with_capacity(capacity)),
             FrameKind::U8x2 => Self::U8x2(LimVecDeque::with_capacity(capacity)),
             FrameKind::U16 => Self::U16(LimVecDeque::with_capacity(capacity)),
             FrameKind::U16x1 => Self::U16x1(LimVecDeque::with_capacity(capacity)),
@@ -265,18 +652,335 @@ impl Frame {
             FrameKind::RGBA8x2 => Self::RGBA8x2(LimVecDeque::with_capacity(capacity)),
         }
     }
-    unwrap_impl_frame!(u8, 0);
-    unwrap_impl_frame!(u8, 1);
-    unwrap_impl_frame!(u8, 2);
-    unwrap_impl_frame!(u16, 0);
-    unwrap_impl_frame!(u16, 1);
-    unwrap_impl_frame!(u16, 2);
-    unwrap_impl_frame!(f32, 0);
-    unwrap_impl_frame!(f32, 1);
-    unwrap_impl_frame!(f32, 2);
-    unwrap_impl_frame!(RGBA8, 2);
+    /// Hand an emptied queue back, for `remove` on this thread to reuse its storage
+    ///
+    /// Queues still holding frames are just dropped.
+    pub fn recycle(self) {
+        if self.is_empty() {
+            // The spares are gone if the thread is exiting, and then there's nothing to reuse
+            let _ = SPARE.try_with(|spare| {
+                let mut spare = spare.borrow_mut();
+                if spare.len() < SPARE_QUEUES {
+                    spare.push(self);
+                }
+            });
+        }
+    }
+    // An empty queue of `capacity`, on recycled storage if there is a spare of the same kind
+    fn spare(kind: FrameKind, capacity: usize) -> Frame {
+        let spare = SPARE
+            .try_with(|spare| {
+                let mut spare = spare.borrow_mut();
+                let idx = spare.iter().position(|frame| frame.kind() == kind)?;
+                Some(spare.swap_remove(idx))
+            })
+            .ok()
+            .flatten();
+        match spare {
+            Some(mut frame) => {
+                match &mut frame {
+                    Self::U8(v) => v.reset(capacity),
+                    Self::U8x1(v) => v.reset(capacity),
+                    Self::U8x2(v) => v.reset(capacity),
+                    Self::U16(v) => v.reset(capacity),
+                    Self::U16x1(v) => v.reset(capacity),
+                    Self::U16x2(v) => v.reset(capacity),
+                    Self::F32(v) => v.reset(capacity),
+                    Self::F32x1(v) => v.reset(capacity),
+                    Self::F32x2(v) => v.reset(capacity),
+                    Self::RGBA8x2(v) => v.reset(capacity),
+                }
+                frame
+            }
+            None => Frame::with_capacity(kind, capacity),
+        }
+    }
+    /// Create a queue holding just `frame`
+    pub fn single(frame: FrameSingle) -> Frame {
+        let mut res = Frame::with_capacity(frame.kind(), 1);
+        res.add_single(frame).unwrap();
+        res
+    }
+    /// Create a queue of the frames `singles` yields, collected straight into its buffer
+    ///
+    /// The queue has room for exactly those frames. Errors if any frame is not of `kind`.
+    pub fn from_singles<I: IntoIterator<Item = FrameSingle>>(
+        kind: FrameKind,
+        singles: I,
+    ) -> Result<Frame, VidmodError> {
+        let singles = singles.into_iter();
+        macro_rules! collect {
+            ($variant:ident) => {
+                singles
+                    .map(|single| match single {
+                        FrameSingle::$variant(v) => Ok(v),
+                        other => Err(VidmodError::KindMismatch {
+                            port:      None,
+                            direction: None,
+                            expected:  kind,
+                            got:       other.kind(),
+                        }),
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Frame::$variant)
+            };
+        }
+        match kind {
+            FrameKind::U8 => collect!(U8),
+            FrameKind::U8x1 => collect!(U8x1),
+            FrameKind::U8x2 => collect!(U8x2),
+            FrameKind::U16 => collect!(U16),
+            FrameKind::U16x1 => collect!(U16x1),
+            FrameKind::U16x2 => collect!(U16x2),
+            FrameKind::F32 => collect!(F32),
+            FrameKind::F32x1 => collect!(F32x1),
+            FrameKind::F32x2 => collect!(F32x2),
+            FrameKind::RGBA8x2 => collect!(RGBA8x2),
+        }
+    }
+    /// Get the number of bytes taken by the elements of every frame in the queue
+    pub fn bytes(&self) -> usize {
+        match self {
+            Self::U8(v) => v.len() * size_of::<u8>(),
+            Self::U8x1(v) => v.iter().map(array_bytes).sum(),
+            Self::U8x2(v) => v.iter().map(array_bytes).sum(),
+            Self::U16(v) => v.len() * size_of::<u16>(),
+            Self::U16x1(v) => v.iter().map(array_bytes).sum(),
+            Self::U16x2(v) => v.iter().map(array_bytes).sum(),
+            Self::F32(v) => v.len() * size_of::<f32>(),
+            Self::F32x1(v) => v.iter().map(array_bytes).sum(),
+            Self::F32x2(v) => v.iter().map(array_bytes).sum(),
+            Self::RGBA8x2(v) => v.iter().map(array_bytes).sum(),
+        }
+    }
+    /// Get the shape of each frame in the queue, for array kinds, or nothing for scalar kinds
+    pub fn shapes(&self) -> Vec<Vec<usize>> {
+        match self {
+            Self::U8(_) | Self::U16(_) | Self::F32(_) => Vec::new(),
+            Self::U8x1(v) => v.iter().map(|a| a.shape().to_vec()).collect(),
+            Self::U8x2(v) => v.iter().map(|a| a.shape().to_vec()).collect(),
+            Self::U16x1(v) => v.iter().map(|a| a.shape().to_vec()).collect(),
+            Self::U16x2(v) => v.iter().map(|a| a.shape().to_vec()).collect(),
+            Self::F32x1(v) => v.iter().map(|a| a.shape().to_vec()).collect(),
+            Self::F32x2(v) => v.iter().map(|a| a.shape().to_vec()).collect(),
+            Self::RGBA8x2(v) => v.iter().map(|a| a.shape().to_vec()).collect(),
+        }
+    }
+    /// Get the shape of each frame in the queue, for 2D kinds, or nothing for other kinds
+    pub fn shapes_2d(&self) -> Vec<(usize, usize)> {
+        match self {
+            Self::U8x2(v) => v.iter().map(|a| a.dim()).collect(),
+            Self::U16x2(v) => v.iter().map(|a| a.dim()).collect(),
+            Self::F32x2(v) => v.iter().map(|a| a.dim()).collect(),
+            Self::RGBA8x2(v) => v.iter().map(|a| a.dim()).collect(),
+            _ => Vec::new(),
+        }
+    }
+    /// Stack the queued arrays of a U8x2 frame into one array, indexed by position in the queue
+    ///
+    /// The arrays are copied into a single contiguous array, so a batch operation can work on
+    /// the whole queue at once. Every array must have the same shape.
+    pub fn stack_u8x2(&self) -> Result<ArcArray3<u8>, VidmodError> {
+        match self {
+            Self::U8x2(v) => stack_2d(v),
+            _ => Err(self.stack_kind_mismatch(FrameKind::U8x2)),
+        }
+    }
+    /// Stack the queued arrays of a U16x2 frame into one array, see [`Frame::stack_u8x2`]
+    pub fn stack_u16x2(&self) -> Result<ArcArray3<u16>, VidmodError> {
+        match self {
+            Self::U16x2(v) => stack_2d(v),
+            _ => Err(self.stack_kind_mismatch(FrameKind::U16x2)),
+        }
+    }
+    /// Stack the queued arrays of a F32x2 frame into one array, see [`Frame::stack_u8x2`]
+    pub fn stack_f32x2(&self) -> Result<ArcArray3<f32>, VidmodError> {
+        match self {
+            Self::F32x2(v) => stack_2d(v),
+            _ => Err(self.stack_kind_mismatch(FrameKind::F32x2)),
+        }
+    }
+    /// Stack the queued arrays of a RGBA8x2 frame into one array, see [`Frame::stack_u8x2`]
+    pub fn stack_rgba8x2(&self) -> Result<ArcArray3<RGBA8>, VidmodError> {
+        match self {
+            Self::RGBA8x2(v) => stack_2d(v),
+            _ => Err(self.stack_kind_mismatch(FrameKind::RGBA8x2)),
+        }
+    }
+    fn stack_kind_mismatch(&self, expected: FrameKind) -> VidmodError {
+        VidmodError::KindMismatch {
+            port: None,
+            direction: None,
+            expected,
+            got: self.kind(),
+        }
+    }
+    /// Copy any arrays not in standard (row-major, contiguous) layout into it
+    ///
+    /// Arrays already in standard layout are shared rather than copied.
+    pub fn as_standard_layout(&self) -> Frame {
+        match self {
+            Self::U8x1(v) => Self::U8x1(v.iter().map(standard_layout).collect()),
+            Self::U8x2(v) => Self::U8x2(v.iter().map(standard_layout).collect()),
+            Self::U16x1(v) => Self::U16x1(v.iter().map(standard_layout).collect()),
+            Self::U16x2(v) => Self::U16x2(v.iter().map(standard_layout).collect()),
+            Self::F32x1(v) => Self::F32x1(v.iter().map(standard_layout).collect()),
+            Self::F32x2(v) => Self::F32x2(v.iter().map(standard_layout).collect()),
+            Self::RGBA8x2(v) => Self::RGBA8x2(v.iter().map(standard_layout).collect()),
+            Self::U8(_) | Self::U16(_) | Self::F32(_) => self.clone(),
+        }
+    }
+    /// Write the raw little-endian contents of every frame in the queue
+    pub fn write_bytes<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            Self::U8(v) => {
+                let (a, b) = v.as_slices();
+                w.write_all(a)?;
+                w.write_all(b)?;
+            }
+            Self::U8x1(v) => {
+                for a in v.iter() {
+                    w.write_all(&a.iter().copied().collect::<Vec<u8>>())?;
+                }
+            }
+            Self::U8x2(v) => {
+                for a in v.iter() {
+                    w.write_all(&a.iter().copied().collect::<Vec<u8>>())?;
+                }
+            }
+            Self::U16(v) => {
+                for x in v.iter() {
+                    w.write_all(&x.to_le_bytes())?;
+                }
+            }
+            Self::U16x1(v) => {
+                for x in v.iter().flat_map(|a| a.iter()) {
+                    w.write_all(&x.to_le_bytes())?;
+                }
+            }
+            Self::U16x2(v) => {
+                for x in v.iter().flat_map(|a| a.iter()) {
+                    w.write_all(&x.to_le_bytes())?;
+                }
+            }
+            Self::F32(v) => {
+                for x in v.iter() {
+                    w.write_all(&x.to_le_bytes())?;
+                }
+            }
+            Self::F32x1(v) => {
+                for x in v.iter().flat_map(|a| a.iter()) {
+                    w.write_all(&x.to_le_bytes())?;
+                }
+            }
+            Self::F32x2(v) => {
+                for x in v.iter().flat_map(|a| a.iter()) {
+                    w.write_all(&x.to_le_bytes())?;
+                }
+            }
+            Self::RGBA8x2(v) => {
+                for a in v.iter() {
+                    write_rgba8(w, a)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Copy the raw little-endian contents of every frame in the queue to the start of `dst`,
+    /// returning the number of bytes written, see [`Frame::write_bytes`]
+    ///
+    /// Fails without writing anything if `dst` is shorter than [`Frame::bytes`].
+    pub fn copy_bytes_into(&self, dst: &mut [u8]) -> Result<usize, VidmodError> {
+        let needed = self.bytes();
+        if dst.len() < needed {
+            return Err(VidmodError::SliceTooSmall {
+                needed,
+                len: dst.len(),
+            });
+        }
+        // The slice has room for everything, so writing to it cannot fail
+        self.write_bytes(&mut &mut dst[..needed]).unwrap();
+        Ok(needed)
+    }
+    unwrap_fns! {
+        unwrap_u8: U8(LimVecDeque<u8>), "U8";
+        unwrap_u8x1: U8x1(LimVecDeque<ArcArray1<u8>>), "U8";
+        unwrap_u8x2: U8x2(LimVecDeque<ArcArray2<u8>>), "U8";
+        unwrap_u16: U16(LimVecDeque<u16>), "U16";
+        unwrap_u16x1: U16x1(LimVecDeque<ArcArray1<u16>>), "U16";
+        unwrap_u16x2: U16x2(LimVecDeque<ArcArray2<u16>>), "U16";
+        unwrap_f32: F32(LimVecDeque<f32>), "F32";
+        unwrap_f32x1: F32x1(LimVecDeque<ArcArray1<f32>>), "F32";
+        unwrap_f32x2: F32x2(LimVecDeque<ArcArray2<f32>>), "F32";
+        unwrap_rgba8x2: RGBA8x2(LimVecDeque<ArcArray2<RGBA8>>), "RGBA8";
+    }
+}
+
+fn write_rgba8<W: Write>(w: &mut W, a: &ArcArray2<RGBA8>) -> io::Result<()> {
+    #[cfg(feature = "bytemuck")]
+    {
+        if let Some(pixels) = a.as_slice() {
+            return w.write_all(RGBA8::slice_as_bytes(pixels));
+        }
+    }
+    for px in a.iter() {
+        w.write_all(&px.to_bytes())?;
+    }
+    Ok(())
+}
+
+fn array_bytes<A, D: Dimension>(a: &ArcArray<A, D>) -> usize {
+    a.len() * size_of::<A>()
 }
 
+fn standard_layout<A: Clone, D: Dimension>(a: &ArcArray<A, D>) -> ArcArray<A, D> {
+    if a.is_standard_layout() {
+        a.clone()
+    } else {
+        a.as_standard_layout().into_owned().into_shared()
+    }
+}
+
+fn stack_2d<A: Clone>(v: &LimVecDeque<ArcArray2<A>>) -> Result<ArcArray3<A>, VidmodError> {
+    let (rows, cols) = v.iter().next().map_or((0, 0), |a| a.dim());
+    if let Some((index, a)) = v.iter().enumerate().find(|(_, a)| a.dim() != (rows, cols)) {
+        return Err(VidmodError::RaggedStack {
+            index,
+            expected: (rows, cols),
+            got: a.dim(),
+        });
+    }
+    let data = v.iter().flat_map(|a| a.iter().cloned()).collect();
+    Ok(Array3::from_shape_vec((v.len(), rows, cols), data)
+        .unwrap()
+        .into_shared())
+}
+
+macro_rules! impl_from_single {
+    ($($t:ty => $variant:ident),*) => {
+        $(
+            impl From<$t> for FrameSingle {
+                fn from(data: $t) -> Self {
+                    FrameSingle::$variant(data)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_single!(
+    u8 => U8,
+    ArcArray1<u8> => U8x1,
+    ArcArray2<u8> => U8x2,
+    u16 => U16,
+    ArcArray1<u16> => U16x1,
+    ArcArray2<u16> => U16x2,
+    f32 => F32,
+    ArcArray1<f32> => F32x1,
+    ArcArray2<f32> => F32x2,
+    ArcArray2<RGBA8> => RGBA8x2
+);
+
 impl From<ArcArray2<u8>> for Frame {
     fn from(data: ArcArray2<u8>) -> Self {
         Frame::U8x2(LimVecDeque::from(vec![data]))
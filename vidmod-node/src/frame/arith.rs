@@ -0,0 +1,201 @@
+use ndarray::{ArcArray, Dimension, Zip};
+
+use super::{Frame, FrameSingle, RGBA8};
+use crate::VidmodError;
+
+// An elementwise operation between two frames. All but `Map` work on integers directly
+enum BinOp<'a> {
+    AddSaturating,
+    SubAbs,
+    Mul,
+    Min,
+    Max,
+    Map(&'a dyn Fn(f64, f64) -> f64),
+}
+
+trait Elem: Clone {
+    fn apply(op: &BinOp, a: Self, b: Self) -> Self;
+}
+
+macro_rules! elem_int {
+    ($t:ty) => {
+        impl Elem for $t {
+            fn apply(op: &BinOp, a: Self, b: Self) -> Self {
+                match op {
+                    BinOp::AddSaturating => a.saturating_add(b),
+                    BinOp::SubAbs => a.max(b) - a.min(b),
+                    BinOp::Mul => {
+                        let max = <$t>::MAX as u32;
+                        ((a as u32 * b as u32 + max / 2) / max) as $t
+                    }
+                    BinOp::Min => a.min(b),
+                    BinOp::Max => a.max(b),
+                    // Float to int casts saturate, and map NaN to zero
+                    BinOp::Map(f) => f(a as f64, b as f64).round() as $t,
+                }
+            }
+        }
+    };
+}
+
+elem_int!(u8);
+elem_int!(u16);
+
+impl Elem for f32 {
+    fn apply(op: &BinOp, a: Self, b: Self) -> Self {
+        match op {
+            BinOp::AddSaturating => a + b,
+            BinOp::SubAbs => (a - b).abs(),
+            BinOp::Mul => a * b,
+            BinOp::Min => a.min(b),
+            BinOp::Max => a.max(b),
+            BinOp::Map(f) => f(a as f64, b as f64) as f32,
+        }
+    }
+}
+
+impl Elem for RGBA8 {
+    fn apply(op: &BinOp, a: Self, b: Self) -> Self {
+        RGBA8::new(
+            u8::apply(op, a.r, b.r),
+            u8::apply(op, a.g, b.g),
+            u8::apply(op, a.b, b.b),
+            u8::apply(op, a.a, b.a),
+        )
+    }
+}
+
+fn zip<T: Elem, D: Dimension>(
+    op: &BinOp,
+    a: &ArcArray<T, D>,
+    b: &ArcArray<T, D>,
+) -> Result<ArcArray<T, D>, VidmodError> {
+    if a.shape() != b.shape() {
+        return Err(VidmodError::ShapesDiffer {
+            a: a.shape().to_vec(),
+            b: b.shape().to_vec(),
+        });
+    }
+    Ok(Zip::from(a)
+        .and(b)
+        .map_collect(|a, b| T::apply(op, a.clone(), b.clone()))
+        .into_shared())
+}
+
+impl FrameSingle {
+    fn combine(&self, other: &FrameSingle, op: &BinOp) -> Result<FrameSingle, VidmodError> {
+        match (self, other) {
+            (Self::U8(a), Self::U8(b)) => Ok(Self::U8(u8::apply(op, *a, *b))),
+            (Self::U8x1(a), Self::U8x1(b)) => zip(op, a, b).map(Self::U8x1),
+            (Self::U8x2(a), Self::U8x2(b)) => zip(op, a, b).map(Self::U8x2),
+            (Self::U16(a), Self::U16(b)) => Ok(Self::U16(u16::apply(op, *a, *b))),
+            (Self::U16x1(a), Self::U16x1(b)) => zip(op, a, b).map(Self::U16x1),
+            (Self::U16x2(a), Self::U16x2(b)) => zip(op, a, b).map(Self::U16x2),
+            (Self::F32(a), Self::F32(b)) => Ok(Self::F32(f32::apply(op, *a, *b))),
+            (Self::F32x1(a), Self::F32x1(b)) => zip(op, a, b).map(Self::F32x1),
+            (Self::F32x2(a), Self::F32x2(b)) => zip(op, a, b).map(Self::F32x2),
+            (Self::RGBA8x2(a), Self::RGBA8x2(b)) => zip(op, a, b).map(Self::RGBA8x2),
+            (a, b) => Err(VidmodError::KindMismatch {
+                port:      None,
+                direction: None,
+                expected:  a.kind(),
+                got:       b.kind(),
+            }),
+        }
+    }
+
+    /// Combine with a frame of the same kind and shape elementwise through `f`
+    ///
+    /// Integer results are rounded and clipped to their type's range. RGBA8 pixels are combined
+    /// channel by channel, alpha included.
+    pub fn zip_map<F: Fn(f64, f64) -> f64>(
+        self,
+        other: FrameSingle,
+        f: F,
+    ) -> Result<FrameSingle, VidmodError> {
+        self.combine(&other, &BinOp::Map(&f))
+    }
+
+    /// Add elementwise, saturating integers at their maximum
+    pub fn add_saturating(self, other: FrameSingle) -> Result<FrameSingle, VidmodError> {
+        self.combine(&other, &BinOp::AddSaturating)
+    }
+
+    /// Take the absolute difference elementwise
+    pub fn sub_abs(self, other: FrameSingle) -> Result<FrameSingle, VidmodError> {
+        self.combine(&other, &BinOp::SubAbs)
+    }
+
+    /// Multiply elementwise, treating integers as fractions of their maximum, so multiplying by
+    /// the maximum leaves a value unchanged
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(self, other: FrameSingle) -> Result<FrameSingle, VidmodError> {
+        self.combine(&other, &BinOp::Mul)
+    }
+
+    /// Take the smaller of each pair of elements
+    pub fn min(self, other: FrameSingle) -> Result<FrameSingle, VidmodError> {
+        self.combine(&other, &BinOp::Min)
+    }
+
+    /// Take the larger of each pair of elements
+    pub fn max(self, other: FrameSingle) -> Result<FrameSingle, VidmodError> {
+        self.combine(&other, &BinOp::Max)
+    }
+}
+
+impl Frame {
+    fn combine(&self, other: &Frame, op: &BinOp) -> Result<Frame, VidmodError> {
+        let (kind, got) = (self.kind(), other.kind());
+        if kind != got {
+            return Err(VidmodError::KindMismatch {
+                port: None,
+                direction: None,
+                expected: kind,
+                got,
+            });
+        }
+        let (mut a, mut b) = (self.clone(), other.clone());
+        let mut res = Frame::with_capacity(kind, usize::min(a.size(), b.size()));
+        while let (Some(a), Some(b)) = (a.remove_single(), b.remove_single()) {
+            res.add_single(a.combine(&b, op)?);
+        }
+        Ok(res)
+    }
+
+    /// Combine two frames of the same kind pairwise through `f`, see `FrameSingle::zip_map`
+    ///
+    /// The frames are combined up to the length of the shorter.
+    pub fn zip_map<F: Fn(f64, f64) -> f64>(
+        &self,
+        other: &Frame,
+        f: F,
+    ) -> Result<Frame, VidmodError> {
+        self.combine(other, &BinOp::Map(&f))
+    }
+
+    /// Add two frames pairwise, see `FrameSingle::add_saturating`
+    pub fn add_saturating(&self, other: &Frame) -> Result<Frame, VidmodError> {
+        self.combine(other, &BinOp::AddSaturating)
+    }
+
+    /// Take the absolute difference of two frames pairwise, see `FrameSingle::sub_abs`
+    pub fn sub_abs(&self, other: &Frame) -> Result<Frame, VidmodError> {
+        self.combine(other, &BinOp::SubAbs)
+    }
+
+    /// Multiply two frames pairwise, see `FrameSingle::mul`
+    pub fn mul(&self, other: &Frame) -> Result<Frame, VidmodError> {
+        self.combine(other, &BinOp::Mul)
+    }
+
+    /// Take the pairwise minimum of two frames, see `FrameSingle::min`
+    pub fn min(&self, other: &Frame) -> Result<Frame, VidmodError> {
+        self.combine(other, &BinOp::Min)
+    }
+
+    /// Take the pairwise maximum of two frames, see `FrameSingle::max`
+    pub fn max(&self, other: &Frame) -> Result<Frame, VidmodError> {
+        self.combine(other, &BinOp::Max)
+    }
+}
@@ -0,0 +1,827 @@
+use ndarray::{s, ArcArray1, ArcArray2, Array, Array1, Array2, Dimension, Zip};
+
+use super::{Frame, FrameKind, RGBA8};
+use crate::{dsp::Prng, limvecdeque::LimVecDeque, VidmodError};
+
+// Rec. 601 luma weights, in thousandths
+const LUMA_R: u32 = 299;
+const LUMA_G: u32 = 587;
+const LUMA_B: u32 = 114;
+
+fn luma(p: &RGBA8) -> u32 {
+    LUMA_R * p.r as u32 + LUMA_G * p.g as u32 + LUMA_B * p.b as u32
+}
+
+fn levels(v: u8, black: u8, span: u32) -> u8 {
+    let v = (v.saturating_sub(black) as u32 * 255 + span / 2) / span;
+    u32::min(v, 255) as u8
+}
+
+fn gain_offset(v: u8, gain: f32, offset: f32) -> u8 {
+    // Float to int casts saturate, so this clips to 0..=255
+    (v as f32 * gain + offset).round() as u8
+}
+
+/// Convert an image to 8-bit Rec. 601 luma, ignoring alpha
+pub fn to_luma_u8(img: &ArcArray2<RGBA8>) -> ArcArray2<u8> {
+    img.map(|p| ((luma(p) + 500) / 1000) as u8).into_shared()
+}
+
+/// Convert an image to 16-bit Rec. 601 luma, ignoring alpha
+pub fn to_luma_u16(img: &ArcArray2<RGBA8>) -> ArcArray2<u16> {
+    img.map(|p| ((luma(p) * 257 + 500) / 1000) as u16)
+        .into_shared()
+}
+
+/// Remap the colour channels so `black` becomes 0 and `white` becomes 255, clipping outside them
+///
+/// Panics if `white` is not above `black`.
+pub fn apply_levels(img: &ArcArray2<RGBA8>, black: u8, white: u8) -> ArcArray2<RGBA8> {
+    assert!(white > black, "Invalid levels: {},{}", black, white);
+    let span = (white - black) as u32;
+    img.map(|p| {
+        RGBA8::new(
+            levels(p.r, black, span),
+            levels(p.g, black, span),
+            levels(p.b, black, span),
+            p.a,
+        )
+    })
+    .into_shared()
+}
+
+/// Remap a 16-bit greyscale image so `black` becomes 0 and `white` becomes 65535, clipping outside them
+///
+/// Panics if `white` is not above `black`.
+pub fn apply_levels_u16(img: &ArcArray2<u16>, black: u16, white: u16) -> ArcArray2<u16> {
+    assert!(white > black, "Invalid levels: {},{}", black, white);
+    let span = (white - black) as u64;
+    img.map(|&v| {
+        let v = (v.saturating_sub(black) as u64 * 65535 + span / 2) / span;
+        u64::min(v, 65535) as u16
+    })
+    .into_shared()
+}
+
+fn depth_max(depth: u8) -> u32 {
+    assert!((1..=16).contains(&depth), "Invalid bit depth: {}", depth);
+    (1 << depth) - 1
+}
+
+/// Scale a sample with `depth` significant bits up to the full 16-bit range, rounding
+///
+/// The largest `depth`-bit value becomes 65535, and values above it are clipped to it first.
+/// Panics if `depth` is not 1 to 16.
+pub fn promote_to_full_scale(v: u16, depth: u8) -> u16 {
+    rescale_depth(v, depth, 16)
+}
+
+/// Scale a full-range 16-bit sample down to `depth` significant bits, rounding
+///
+/// Undoes `promote_to_full_scale` exactly. Panics if `depth` is not 1 to 16.
+pub fn demote_to_depth(v: u16, depth: u8) -> u16 {
+    rescale_depth(v, 16, depth)
+}
+
+/// Scale a sample with `from` significant bits to `to` significant bits, rounding
+///
+/// The largest `from`-bit value becomes the largest `to`-bit value, and values above it are
+/// clipped to it first. Panics if either depth is not 1 to 16.
+pub fn rescale_depth(v: u16, from: u8, to: u8) -> u16 {
+    let (from, to) = (depth_max(from), depth_max(to));
+    let v = u32::min(v as u32, from);
+    ((v * to + from / 2) / from) as u16
+}
+
+/// Scale then offset each channel, in RGBA order, rounding and clipping to 0..=255
+pub fn apply_gain_offset(
+    img: &ArcArray2<RGBA8>,
+    gain: [f32; 4],
+    offset: [f32; 4],
+) -> ArcArray2<RGBA8> {
+    img.map(|p| {
+        RGBA8::new(
+            gain_offset(p.r, gain[0], offset[0]),
+            gain_offset(p.g, gain[1], offset[1]),
+            gain_offset(p.b, gain[2], offset[2]),
+            gain_offset(p.a, gain[3], offset[3]),
+        )
+    })
+    .into_shared()
+}
+
+fn over(fg: &RGBA8, bg: &RGBA8) -> RGBA8 {
+    let (fa, ba) = (fg.a as f32 / 255.0, bg.a as f32 / 255.0);
+    let alpha = fa + ba * (1.0 - fa);
+    if alpha == 0.0 {
+        return RGBA8::TRANSPARENT;
+    }
+    let mix = |f: u8, b: u8| ((f as f32 * fa + b as f32 * ba * (1.0 - fa)) / alpha).round() as u8;
+    RGBA8::new(
+        mix(fg.r, bg.r),
+        mix(fg.g, bg.g),
+        mix(fg.b, bg.b),
+        (alpha * 255.0).round() as u8,
+    )
+}
+
+/// Composite `fg` over `bg` with straight, not premultiplied, alpha
+///
+/// Panics if the images differ in shape.
+pub fn composite_over(fg: &ArcArray2<RGBA8>, bg: &ArcArray2<RGBA8>) -> ArcArray2<RGBA8> {
+    Zip::from(fg)
+        .and(bg)
+        .map_collect(over)
+        .into_shared()
+}
+
+/// Split an image into its R, G, B and A planes
+pub fn split_channels(img: &ArcArray2<RGBA8>) -> [ArcArray2<u8>; 4] {
+    [
+        img.map(|p| p.r).into_shared(),
+        img.map(|p| p.g).into_shared(),
+        img.map(|p| p.b).into_shared(),
+        img.map(|p| p.a).into_shared(),
+    ]
+}
+
+/// Merge R, G, B and A planes into an image
+///
+/// Panics if the planes differ in shape.
+pub fn merge_channels(
+    r: &ArcArray2<u8>,
+    g: &ArcArray2<u8>,
+    b: &ArcArray2<u8>,
+    a: &ArcArray2<u8>,
+) -> ArcArray2<RGBA8> {
+    Zip::from(r)
+        .and(g)
+        .and(b)
+        .and(a)
+        .map_collect(|&r, &g, &b, &a| RGBA8::new(r, g, b, a))
+        .into_shared()
+}
+
+/// Interpolation used when resizing an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeFilter {
+    /// Take the nearest source pixel
+    Nearest,
+    /// Blend the four nearest source pixels
+    Bilinear,
+}
+
+impl ResizeFilter {
+    /// Parse a filter name, `nearest` or `bilinear`
+    pub fn parse(f: &str) -> Option<Self> {
+        Some(match f {
+            "nearest" => ResizeFilter::Nearest,
+            "bilinear" => ResizeFilter::Bilinear,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&str> for ResizeFilter {
+    fn from(f: &str) -> Self {
+        ResizeFilter::parse(f).unwrap_or_else(|| unimplemented!("Resize filter {}", f))
+    }
+}
+
+/// How `quantize` spreads the error of rounding to fewer levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DitherMode {
+    /// Round each value to the nearest level
+    None,
+    /// Offset each pixel by a 4x4 Bayer threshold before truncating, 2D kinds only
+    Ordered4x4,
+    /// Diffuse each pixel's error onto the neighbours right of and below it, 2D kinds only
+    ///
+    /// The error carries across the rows of one image, but never on to the next image or frame.
+    FloydSteinberg,
+    /// Add triangular noise of up to one level either way, from a `dsp::Prng` with this seed
+    Tpdf(u64),
+}
+
+impl DitherMode {
+    /// Parse a mode name, `none`, `ordered`, `floyd_steinberg` or `tpdf`, the last seeded with 0
+    pub fn parse(f: &str) -> Option<Self> {
+        Some(match f {
+            "none" => DitherMode::None,
+            "ordered" => DitherMode::Ordered4x4,
+            "floyd_steinberg" => DitherMode::FloydSteinberg,
+            "tpdf" => DitherMode::Tpdf(0),
+            _ => return None,
+        })
+    }
+}
+
+impl From<&str> for DitherMode {
+    fn from(f: &str) -> Self {
+        DitherMode::parse(f).unwrap_or_else(|| unimplemented!("Dither mode {}", f))
+    }
+}
+
+// Thresholds for ordered dithering, in sixteenths of a level
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// Round values in units of the target to whole levels in 0..=max, after any noise
+fn dither_levels<D: Dimension>(
+    levels: &mut Array<f32, D>,
+    mode: DitherMode,
+    max: f32,
+    rng: &mut Prng,
+) {
+    if let DitherMode::Tpdf(_) = mode {
+        levels.mapv_inplace(|v| v + rng.next_f32() - rng.next_f32());
+    }
+    levels.mapv_inplace(|v| v.round().max(0.0).min(max));
+}
+
+fn dither_image(img: &mut Array2<f32>, mode: DitherMode, max: f32, rng: &mut Prng) {
+    match mode {
+        DitherMode::Ordered4x4 => {
+            for ((r, c), v) in img.indexed_iter_mut() {
+                let threshold = (f32::from(BAYER_4X4[r % 4][c % 4]) + 0.5) / 16.0;
+                *v = (*v + threshold).floor().max(0.0).min(max);
+            }
+        }
+        DitherMode::FloydSteinberg => {
+            let (rows, cols) = img.dim();
+            for r in 0..rows {
+                for c in 0..cols {
+                    let old = img[[r, c]];
+                    let new = old.round().max(0.0).min(max);
+                    img[[r, c]] = new;
+                    let err = old - new;
+                    if c + 1 < cols {
+                        img[[r, c + 1]] += err * 7.0 / 16.0;
+                    }
+                    if r + 1 < rows {
+                        if c > 0 {
+                            img[[r + 1, c - 1]] += err * 3.0 / 16.0;
+                        }
+                        img[[r + 1, c]] += err * 5.0 / 16.0;
+                        if c + 1 < cols {
+                            img[[r + 1, c + 1]] += err / 16.0;
+                        }
+                    }
+                }
+            }
+        }
+        _ => dither_levels(img, mode, max, rng),
+    }
+}
+
+/// Reduce a numeric frame to the U8 or U16 `kind` of the same dimensions, dithering as it rounds
+///
+/// Integer frames are rescaled so their full range maps onto the target's, e.g. 65535 to 255,
+/// while F32 values are taken to be in units of the target already, as `Frame::convert_to` does.
+/// Ordered and Floyd-Steinberg dithering need 2D kinds. TPDF suits audio and other 1D kinds, and
+/// leaves every value within one level of plain rounding.
+pub fn quantize(frame: &Frame, kind: FrameKind, mode: DitherMode) -> Result<Frame, VidmodError> {
+    let got = FrameKind::from(frame);
+    let mismatch = VidmodError::KindMismatch {
+        port: None,
+        direction: None,
+        expected: kind,
+        got,
+    };
+    let max = match kind {
+        FrameKind::U8 | FrameKind::U8x1 | FrameKind::U8x2 => 255.0,
+        FrameKind::U16 | FrameKind::U16x1 | FrameKind::U16x2 => 65535.0,
+        _ => return Err(mismatch),
+    };
+    if got == FrameKind::RGBA8x2 || dims(got) != dims(kind) {
+        return Err(mismatch);
+    }
+    let spatial = matches!(mode, DitherMode::Ordered4x4 | DitherMode::FloydSteinberg);
+    if spatial && dims(got) != 2 {
+        return Err(VidmodError::InvalidDither { kind: got });
+    }
+    let scale = match got {
+        FrameKind::U8 | FrameKind::U8x1 | FrameKind::U8x2 => max / 255.0,
+        FrameKind::U16 | FrameKind::U16x1 | FrameKind::U16x2 => max / 65535.0,
+        _ => 1.0,
+    };
+    let mut rng = Prng::new(match mode {
+        DitherMode::Tpdf(seed) => seed,
+        _ => 0,
+    });
+    let float_kind = [FrameKind::F32, FrameKind::F32x1, FrameKind::F32x2][dims(got)];
+    Ok(match frame.convert_to(float_kind)? {
+        Frame::F32(v) => {
+            let mut levels = Array1::from_iter(v.iter().map(|&x| x * scale));
+            dither_levels(&mut levels, mode, max, &mut rng);
+            let v: LimVecDeque<f32> = levels.iter().copied().collect();
+            cast_scalars(&v, kind)
+        }
+        Frame::F32x1(v) => {
+            let v: LimVecDeque<ArcArray1<f32>> = v
+                .iter()
+                .map(|a| {
+                    let mut levels = a.mapv(|x| x * scale);
+                    dither_levels(&mut levels, mode, max, &mut rng);
+                    levels.into_shared()
+                })
+                .collect();
+            cast_arrays1(&v, kind)
+        }
+        Frame::F32x2(v) => {
+            let v: LimVecDeque<ArcArray2<f32>> = v
+                .iter()
+                .map(|a| {
+                    let mut levels = a.mapv(|x| x * scale);
+                    dither_image(&mut levels, mode, max, &mut rng);
+                    levels.into_shared()
+                })
+                .collect();
+            cast_arrays2(&v, kind)
+        }
+        _ => unreachable!(),
+    })
+}
+
+/// A pixel type that can be blended for bilinear resizing
+pub trait Lerp: Clone {
+    /// Blend `a` and `b`, taking `t` of `b`
+    fn lerp(a: &Self, b: &Self, t: f32) -> Self;
+}
+
+fn lerp_f32(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+impl Lerp for u8 {
+    fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+        lerp_f32(*a as f32, *b as f32, t).round() as u8
+    }
+}
+
+impl Lerp for u16 {
+    fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+        lerp_f32(*a as f32, *b as f32, t).round() as u16
+    }
+}
+
+impl Lerp for f32 {
+    fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+        lerp_f32(*a, *b, t)
+    }
+}
+
+impl Lerp for RGBA8 {
+    fn lerp(a: &Self, b: &Self, t: f32) -> Self {
+        RGBA8::new(
+            u8::lerp(&a.r, &b.r, t),
+            u8::lerp(&a.g, &b.g, t),
+            u8::lerp(&a.b, &b.b, t),
+            u8::lerp(&a.a, &b.a, t),
+        )
+    }
+}
+
+// Map an output index to a source coordinate, aligning pixel centres
+fn source_coord(dst: usize, src_len: usize, dst_len: usize) -> f32 {
+    let pos = (dst as f32 + 0.5) * src_len as f32 / dst_len as f32 - 0.5;
+    pos.max(0.0).min((src_len - 1) as f32)
+}
+
+// The source index whose pixel contains the centre of output pixel `dst`
+fn nearest_index(dst: usize, src_len: usize, dst_len: usize) -> usize {
+    usize::min((2 * dst + 1) * src_len / (2 * dst_len), src_len - 1)
+}
+
+fn check_resize<T>(img: &ArcArray2<T>) {
+    assert!(
+        img.nrows() > 0 && img.ncols() > 0,
+        "Cannot resize an empty image"
+    );
+}
+
+/// Resize an image to `(rows, cols)`, taking the nearest source pixel
+///
+/// Panics if the image is empty.
+pub fn nearest_resize<T: Clone>(img: &ArcArray2<T>, shape: (usize, usize)) -> ArcArray2<T> {
+    check_resize(img);
+    let (rows, cols) = img.dim();
+    ArcArray2::from_shape_fn(shape, |(y, x)| {
+        img[(
+            nearest_index(y, rows, shape.0),
+            nearest_index(x, cols, shape.1),
+        )]
+            .clone()
+    })
+}
+
+/// Resize an image to `(rows, cols)`, blending the four nearest source pixels
+///
+/// Pixel centres are aligned, so the edges of the output repeat the edges of the input.
+/// Panics if the image is empty.
+pub fn bilinear_resize<T: Lerp>(img: &ArcArray2<T>, shape: (usize, usize)) -> ArcArray2<T> {
+    check_resize(img);
+    let (rows, cols) = img.dim();
+    ArcArray2::from_shape_fn(shape, |(y, x)| {
+        let sy = source_coord(y, rows, shape.0);
+        let sx = source_coord(x, cols, shape.1);
+        let (y0, x0) = (sy as usize, sx as usize);
+        let (y1, x1) = (usize::min(y0 + 1, rows - 1), usize::min(x0 + 1, cols - 1));
+        let (ty, tx) = (sy - y0 as f32, sx - x0 as f32);
+        let top = T::lerp(&img[(y0, x0)], &img[(y0, x1)], tx);
+        let bottom = T::lerp(&img[(y1, x0)], &img[(y1, x1)], tx);
+        T::lerp(&top, &bottom, ty)
+    })
+}
+
+/// Split an image into tiles of `tile_h` rows by `tile_w` columns, in row-major order
+///
+/// Tiles on the bottom and right edges are cut short when the image is not a multiple of the tile
+/// size. Panics if either tile dimension is zero.
+pub fn tile<T: Clone>(arr: &ArcArray2<T>, tile_w: usize, tile_h: usize) -> Vec<ArcArray2<T>> {
+    assert!(
+        tile_w > 0 && tile_h > 0,
+        "Invalid tile size {}x{}",
+        tile_w,
+        tile_h
+    );
+    let (height, width) = arr.dim();
+    let mut tiles = Vec::new();
+    for row in (0..height).step_by(tile_h) {
+        for col in (0..width).step_by(tile_w) {
+            let tile = arr.slice(s![
+                row..usize::min(row + tile_h, height),
+                col..usize::min(col + tile_w, width)
+            ]);
+            tiles.push(tile.to_owned().into_shared());
+        }
+    }
+    tiles
+}
+
+/// Reassemble an image of `original_shape` from `rows` by `cols` tiles made by `tile`
+///
+/// The tile size is taken from the first tile. Errors if the number of tiles does not match, or a
+/// tile does not have the size its position in the image needs.
+pub fn untile<T: Clone>(
+    tiles: &[ArcArray2<T>],
+    cols: usize,
+    rows: usize,
+    original_shape: (usize, usize),
+) -> Result<ArcArray2<T>, VidmodError> {
+    let (height, width) = original_shape;
+    // Tiles are never empty, so an empty first tile can only fit an empty image
+    let (tile_h, tile_w) = tiles
+        .first()
+        .map_or((1, 1), |tile| (tile.nrows().max(1), tile.ncols().max(1)));
+    let grid = (
+        (height + tile_h - 1) / tile_h,
+        (width + tile_w - 1) / tile_w,
+    );
+    if tiles.len() != cols * rows || grid != (rows, cols) {
+        return Err(VidmodError::TileCountMismatch {
+            expected: grid.0 * grid.1,
+            got:      tiles.len(),
+        });
+    }
+    let mut values = Vec::with_capacity(height * width);
+    for row in 0..height {
+        for col in 0..width {
+            let idx = (row / tile_h) * cols + col / tile_w;
+            let tile = &tiles[idx];
+            let expected = [
+                usize::min(tile_h, height - row / tile_h * tile_h),
+                usize::min(tile_w, width - col / tile_w * tile_w),
+            ];
+            if tile.shape() != expected {
+                return Err(VidmodError::ShapeMismatch {
+                    port:     format!("tile {}", idx),
+                    expected: expected.to_vec(),
+                    got:      tile.shape().to_vec(),
+                });
+            }
+            values.push(tile[[row % tile_h, col % tile_w]].clone());
+        }
+    }
+    Ok(ArcArray2::from_shape_vec(original_shape, values).unwrap())
+}
+
+/// A numeric element type that `Frame::convert_to` can cast between
+trait Sample: Copy {
+    /// The value as an f32, exact for every integer sample
+    fn to_f32(self) -> f32;
+    /// The nearest value to `v`, saturating at the type's bounds
+    fn from_f32(v: f32) -> Self;
+}
+
+impl Sample for u8 {
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn from_f32(v: f32) -> Self {
+        // Float to int casts saturate, and map NaN to zero
+        v.round() as u8
+    }
+}
+
+impl Sample for u16 {
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+    fn from_f32(v: f32) -> Self {
+        v.round() as u16
+    }
+}
+
+impl Sample for f32 {
+    fn to_f32(self) -> f32 {
+        self
+    }
+    fn from_f32(v: f32) -> Self {
+        v
+    }
+}
+
+fn cast<A: Sample, B: Sample>(v: A) -> B {
+    B::from_f32(v.to_f32())
+}
+
+fn cast_scalars<A: Sample>(v: &LimVecDeque<A>, kind: FrameKind) -> Frame {
+    match kind {
+        FrameKind::U8 => Frame::U8(v.iter().map(|&x| cast(x)).collect()),
+        FrameKind::U16 => Frame::U16(v.iter().map(|&x| cast(x)).collect()),
+        FrameKind::F32 => Frame::F32(v.iter().map(|&x| cast(x)).collect()),
+        _ => unreachable!(),
+    }
+}
+
+fn cast_arrays1<A: Sample>(v: &LimVecDeque<ArcArray1<A>>, kind: FrameKind) -> Frame {
+    fn map<A: Sample, B: Sample>(v: &LimVecDeque<ArcArray1<A>>) -> LimVecDeque<ArcArray1<B>> {
+        v.iter()
+            .map(|a| a.map(|&x| cast(x)).into_shared())
+            .collect()
+    }
+    match kind {
+        FrameKind::U8x1 => Frame::U8x1(map(v)),
+        FrameKind::U16x1 => Frame::U16x1(map(v)),
+        FrameKind::F32x1 => Frame::F32x1(map(v)),
+        _ => unreachable!(),
+    }
+}
+
+fn cast_arrays2<A: Sample>(v: &LimVecDeque<ArcArray2<A>>, kind: FrameKind) -> Frame {
+    fn map<A: Sample, B: Sample>(v: &LimVecDeque<ArcArray2<A>>) -> LimVecDeque<ArcArray2<B>> {
+        v.iter()
+            .map(|a| a.map(|&x| cast(x)).into_shared())
+            .collect()
+    }
+    match kind {
+        FrameKind::U8x2 => Frame::U8x2(map(v)),
+        FrameKind::U16x2 => Frame::U16x2(map(v)),
+        FrameKind::F32x2 => Frame::F32x2(map(v)),
+        _ => unreachable!(),
+    }
+}
+
+// 0 for scalar kinds, 1 for x1 kinds, 2 for x2 kinds
+pub(super) fn dims(kind: FrameKind) -> usize {
+    match kind {
+        FrameKind::U8 | FrameKind::U16 | FrameKind::F32 => 0,
+        FrameKind::U8x1 | FrameKind::U16x1 | FrameKind::F32x1 => 1,
+        FrameKind::U8x2 | FrameKind::U16x2 | FrameKind::F32x2 | FrameKind::RGBA8x2 => 2,
+    }
+}
+
+impl FrameKind {
+    /// Check whether `Frame::convert_to` can turn frames of this kind into `other`
+    pub fn can_convert_to(&self, other: FrameKind) -> bool {
+        match (*self, other) {
+            (from, to) if from == to => true,
+            (FrameKind::RGBA8x2, FrameKind::U8x2) | (FrameKind::RGBA8x2, FrameKind::U16x2) => true,
+            (FrameKind::RGBA8x2, _) | (_, FrameKind::RGBA8x2) => false,
+            (from, to) => dims(from) == dims(to),
+        }
+    }
+}
+
+fn expect_kind(frame: &Frame, expected: FrameKind) -> Result<(), VidmodError> {
+    let got = FrameKind::from(frame);
+    if got == expected {
+        Ok(())
+    } else {
+        Err(VidmodError::KindMismatch {
+            port: None,
+            direction: None,
+            expected,
+            got,
+        })
+    }
+}
+
+impl Frame {
+    fn map_rgba8<T, F: Fn(&ArcArray2<RGBA8>) -> T>(
+        &self,
+        f: F,
+    ) -> Result<LimVecDeque<T>, VidmodError> {
+        expect_kind(self, FrameKind::RGBA8x2)?;
+        match self {
+            Frame::RGBA8x2(v) => Ok(v.iter().map(f).collect()),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Convert to another kind, see `FrameKind::can_convert_to` for the supported pairs
+    ///
+    /// Numeric kinds of the same dimensions cast each value, rounding and saturating into
+    /// integer types, and RGBA8x2 converts to U8x2 or U16x2 luma.
+    pub fn convert_to(&self, kind: FrameKind) -> Result<Frame, VidmodError> {
+        let got = FrameKind::from(self);
+        if !got.can_convert_to(kind) {
+            return Err(VidmodError::KindMismatch {
+                port: None,
+                direction: None,
+                expected: kind,
+                got,
+            });
+        }
+        if got == kind {
+            return Ok(self.clone());
+        }
+        match self {
+            Frame::U8(v) => Ok(cast_scalars(v, kind)),
+            Frame::U16(v) => Ok(cast_scalars(v, kind)),
+            Frame::F32(v) => Ok(cast_scalars(v, kind)),
+            Frame::U8x1(v) => Ok(cast_arrays1(v, kind)),
+            Frame::U16x1(v) => Ok(cast_arrays1(v, kind)),
+            Frame::F32x1(v) => Ok(cast_arrays1(v, kind)),
+            Frame::U8x2(v) => Ok(cast_arrays2(v, kind)),
+            Frame::U16x2(v) => Ok(cast_arrays2(v, kind)),
+            Frame::F32x2(v) => Ok(cast_arrays2(v, kind)),
+            Frame::RGBA8x2(_) if kind == FrameKind::U8x2 => self.to_luma_u8(),
+            Frame::RGBA8x2(_) => self.to_luma_u16(),
+        }
+    }
+
+    /// Convert an RGBA8x2 frame to U8x2 luma, see `ops::to_luma_u8`
+    pub fn to_luma_u8(&self) -> Result<Frame, VidmodError> {
+        self.map_rgba8(to_luma_u8).map(Frame::U8x2)
+    }
+
+    /// Convert an RGBA8x2 frame to U16x2 luma, see `ops::to_luma_u16`
+    pub fn to_luma_u16(&self) -> Result<Frame, VidmodError> {
+        self.map_rgba8(to_luma_u16).map(Frame::U16x2)
+    }
+
+    /// Apply levels to an RGBA8x2 or U16x2 frame, see `ops::apply_levels`
+    ///
+    /// For U16x2 frames the levels are scaled up from 8 bits.
+    pub fn apply_levels(&self, black: u8, white: u8) -> Result<Frame, VidmodError> {
+        match self {
+            Frame::U16x2(v) => {
+                let (black, white) = (black as u16 * 257, white as u16 * 257);
+                Ok(Frame::U16x2(
+                    v.iter()
+                        .map(|img| apply_levels_u16(img, black, white))
+                        .collect(),
+                ))
+            }
+            _ => self
+                .map_rgba8(|img| apply_levels(img, black, white))
+                .map(Frame::RGBA8x2),
+        }
+    }
+
+    fn map_u16<F: Fn(u16) -> u16>(&self, depths: &[u8], f: F) -> Result<Frame, VidmodError> {
+        let invalid = |depth| VidmodError::InvalidBitDepth {
+            port: None,
+            kind: self.into(),
+            depth,
+        };
+        if let Some(&depth) = depths.iter().find(|depth| !(1..=16).contains(*depth)) {
+            return Err(invalid(depth));
+        }
+        match self {
+            Frame::U16(v) => Ok(Frame::U16(v.iter().map(|&x| f(x)).collect())),
+            Frame::U16x1(v) => Ok(Frame::U16x1(
+                v.iter().map(|a| a.mapv(&f).into_shared()).collect(),
+            )),
+            Frame::U16x2(v) => Ok(Frame::U16x2(
+                v.iter().map(|a| a.mapv(&f).into_shared()).collect(),
+            )),
+            _ => Err(invalid(depths[0])),
+        }
+    }
+
+    /// Scale a U16, U16x1 or U16x2 frame of `depth`-bit samples to full scale, see `ops::promote_to_full_scale`
+    pub fn promote_to_full_scale(&self, depth: u8) -> Result<Frame, VidmodError> {
+        self.map_u16(&[depth], |v| promote_to_full_scale(v, depth))
+    }
+
+    /// Scale a full-scale U16, U16x1 or U16x2 frame down to `depth` bits, see `ops::demote_to_depth`
+    pub fn demote_to_depth(&self, depth: u8) -> Result<Frame, VidmodError> {
+        self.map_u16(&[depth], |v| demote_to_depth(v, depth))
+    }
+
+    /// Scale a U16, U16x1 or U16x2 frame from `from`-bit to `to`-bit samples, see `ops::rescale_depth`
+    pub fn rescale_depth(&self, from: u8, to: u8) -> Result<Frame, VidmodError> {
+        self.map_u16(&[from, to], |v| rescale_depth(v, from, to))
+    }
+
+    /// Apply per-channel gain and offset to an RGBA8x2 frame, see `ops::apply_gain_offset`
+    pub fn apply_gain_offset(
+        &self,
+        gain: [f32; 4],
+        offset: [f32; 4],
+    ) -> Result<Frame, VidmodError> {
+        self.map_rgba8(|img| apply_gain_offset(img, gain, offset))
+            .map(Frame::RGBA8x2)
+    }
+
+    /// Resize every image in a 2D frame to `(rows, cols)`, see `ops::nearest_resize` and `ops::bilinear_resize`
+    pub fn resize(
+        &self,
+        shape: (usize, usize),
+        filter: ResizeFilter,
+    ) -> Result<Frame, VidmodError> {
+        if shape.0 == 0 || shape.1 == 0 || self.shapes_2d().iter().any(|&(r, c)| r == 0 || c == 0) {
+            return Err(VidmodError::InvalidShape {
+                kind:  self.into(),
+                shape: Some(shape),
+            });
+        }
+        fn resize_all<T: Lerp>(
+            v: &LimVecDeque<ArcArray2<T>>,
+            shape: (usize, usize),
+            filter: ResizeFilter,
+        ) -> LimVecDeque<ArcArray2<T>> {
+            v.iter()
+                .map(|img| match filter {
+                    ResizeFilter::Nearest => nearest_resize(img, shape),
+                    ResizeFilter::Bilinear => bilinear_resize(img, shape),
+                })
+                .collect()
+        }
+        match self {
+            Frame::U8x2(v) => Ok(Frame::U8x2(resize_all(v, shape, filter))),
+            Frame::U16x2(v) => Ok(Frame::U16x2(resize_all(v, shape, filter))),
+            Frame::F32x2(v) => Ok(Frame::F32x2(resize_all(v, shape, filter))),
+            Frame::RGBA8x2(v) => Ok(Frame::RGBA8x2(resize_all(v, shape, filter))),
+            _ => Err(VidmodError::InvalidShape {
+                kind:  self.into(),
+                shape: Some(shape),
+            }),
+        }
+    }
+
+    /// Composite an RGBA8x2 frame over another pairwise, see `ops::composite_over`
+    ///
+    /// The frames are composited up to the length of the shorter.
+    pub fn composite_over(&self, bg: &Frame) -> Result<Frame, VidmodError> {
+        expect_kind(self, FrameKind::RGBA8x2)?;
+        expect_kind(bg, FrameKind::RGBA8x2)?;
+        match (self, bg) {
+            (Frame::RGBA8x2(fg), Frame::RGBA8x2(bg)) => fg
+                .iter()
+                .zip(bg.iter())
+                .map(|(fg, bg)| {
+                    if fg.shape() == bg.shape() {
+                        Ok(composite_over(fg, bg))
+                    } else {
+                        Err(VidmodError::ShapesDiffer {
+                            a: fg.shape().to_vec(),
+                            b: bg.shape().to_vec(),
+                        })
+                    }
+                })
+                .collect::<Result<_, _>>()
+                .map(Frame::RGBA8x2),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Split an RGBA8x2 frame into four U8x2 frames, in RGBA order
+    pub fn split_channels(&self) -> Result<[Frame; 4], VidmodError> {
+        let planes = self.map_rgba8(split_channels)?;
+        let plane = |idx: usize| Frame::U8x2(planes.iter().map(|p| p[idx].clone()).collect());
+        Ok([plane(0), plane(1), plane(2), plane(3)])
+    }
+
+    /// Merge four U8x2 frames, in RGBA order, into an RGBA8x2 frame
+    ///
+    /// The frames are merged pairwise up to the length of the shortest.
+    pub fn merge_channels(channels: [&Frame; 4]) -> Result<Frame, VidmodError> {
+        for channel in &channels {
+            expect_kind(channel, FrameKind::U8x2)?;
+        }
+        match channels {
+            [Frame::U8x2(r), Frame::U8x2(g), Frame::U8x2(b), Frame::U8x2(a)] => Ok(Frame::RGBA8x2(
+                r.iter()
+                    .zip(g.iter())
+                    .zip(b.iter())
+                    .zip(a.iter())
+                    .map(|(((r, g), b), a)| merge_channels(r, g, b, a))
+                    .collect(),
+            )),
+            _ => unreachable!(),
+        }
+    }
+}
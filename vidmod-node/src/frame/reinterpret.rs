@@ -0,0 +1,180 @@
+use std::convert::TryInto;
+
+use ndarray::{ArcArray1, ArcArray2, Array1, Array2};
+
+use super::{ops::dims, Endian, Frame, FrameKind, RGBA8};
+use crate::{limvecdeque::LimVecDeque, VidmodError};
+
+// A sample with a fixed size in raw bytes
+trait RawSample: Sized {
+    const SIZE: usize;
+    fn write_raw(self, endian: Endian, out: &mut Vec<u8>);
+    fn read_raw(bytes: &[u8], endian: Endian) -> Self;
+}
+
+macro_rules! raw_sample {
+    ($t:ty) => {
+        impl RawSample for $t {
+            const SIZE: usize = std::mem::size_of::<$t>();
+            fn write_raw(self, endian: Endian, out: &mut Vec<u8>) {
+                match endian {
+                    Endian::Little => out.extend_from_slice(&self.to_le_bytes()),
+                    Endian::Big => out.extend_from_slice(&self.to_be_bytes()),
+                }
+            }
+            fn read_raw(bytes: &[u8], endian: Endian) -> Self {
+                let bytes = bytes.try_into().unwrap();
+                match endian {
+                    Endian::Little => <$t>::from_le_bytes(bytes),
+                    Endian::Big => <$t>::from_be_bytes(bytes),
+                }
+            }
+        }
+    };
+}
+
+raw_sample!(u8);
+raw_sample!(u16);
+raw_sample!(f32);
+
+// Pixels are always laid out in RGBA order, whatever the byte order
+impl RawSample for RGBA8 {
+    const SIZE: usize = 4;
+    fn write_raw(self, _endian: Endian, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_bytes());
+    }
+    fn read_raw(bytes: &[u8], _endian: Endian) -> Self {
+        RGBA8::from_bytes(bytes.try_into().unwrap())
+    }
+}
+
+fn sample_size(kind: FrameKind) -> usize {
+    match kind {
+        FrameKind::U8 | FrameKind::U8x1 | FrameKind::U8x2 => u8::SIZE,
+        FrameKind::U16 | FrameKind::U16x1 | FrameKind::U16x2 => u16::SIZE,
+        FrameKind::F32 | FrameKind::F32x1 | FrameKind::F32x2 => f32::SIZE,
+        FrameKind::RGBA8x2 => RGBA8::SIZE,
+    }
+}
+
+// The bytes of one unit of a frame, reinterpreted separately from the others, split into rows
+struct Unit {
+    bytes:     Vec<u8>,
+    rows:      usize,
+    row_bytes: usize,
+}
+
+impl Unit {
+    fn new<'a, T: RawSample + Copy + 'a>(
+        samples: impl Iterator<Item = &'a T>,
+        rows: usize,
+        cols: usize,
+        endian: Endian,
+    ) -> Self {
+        let mut bytes = Vec::with_capacity(rows * cols * T::SIZE);
+        for &sample in samples {
+            sample.write_raw(endian, &mut bytes);
+        }
+        Self {
+            bytes,
+            rows,
+            row_bytes: cols * T::SIZE,
+        }
+    }
+
+    fn line<'a, T: RawSample + Copy + 'a>(
+        samples: impl ExactSizeIterator<Item = &'a T>,
+        endian: Endian,
+    ) -> Self {
+        let len = samples.len();
+        Self::new(samples, 1, len, endian)
+    }
+
+    fn read<T: RawSample>(&self, endian: Endian) -> Vec<T> {
+        self.bytes
+            .chunks_exact(T::SIZE)
+            .map(|b| T::read_raw(b, endian))
+            .collect()
+    }
+
+    fn read_1d<T: RawSample>(&self, endian: Endian) -> ArcArray1<T> {
+        Array1::from(self.read(endian)).into_shared()
+    }
+
+    fn read_2d<T: RawSample>(&self, endian: Endian) -> ArcArray2<T> {
+        let shape = (self.rows, self.row_bytes / T::SIZE);
+        Array2::from_shape_vec(shape, self.read(endian))
+            .unwrap()
+            .into_shared()
+    }
+}
+
+fn read_all<T, F: Fn(&Unit) -> T>(units: &[Unit], f: F) -> LimVecDeque<T> {
+    units.iter().map(f).collect()
+}
+
+impl Frame {
+    /// Reinterpret the raw bytes of the frame as another kind with as many dimensions, without
+    /// converting values
+    ///
+    /// Scalar frames are reinterpreted as one run of bytes, 1D arrays one array at a time and 2D
+    /// arrays one row at a time, so two U8 samples become one U16 sample and a 2x8 U8x2 array
+    /// becomes a 2x2 RGBA8x2 array. `endian` gives the byte order of multi-byte samples on both
+    /// sides. Fails if the kinds have different dimensions, or if the bytes do not divide into
+    /// whole samples of the new kind.
+    pub fn reinterpret_as(&self, kind: FrameKind, endian: Endian) -> Result<Frame, VidmodError> {
+        let from = self.kind();
+        if dims(from) != dims(kind) {
+            return Err(VidmodError::KindMismatch {
+                port:      None,
+                direction: None,
+                expected:  kind,
+                got:       from,
+            });
+        }
+        let units = match self {
+            Self::U8(v) => vec![Unit::line(v.iter(), endian)],
+            Self::U16(v) => vec![Unit::line(v.iter(), endian)],
+            Self::F32(v) => vec![Unit::line(v.iter(), endian)],
+            Self::U8x1(v) => v.iter().map(|a| Unit::line(a.iter(), endian)).collect(),
+            Self::U16x1(v) => v.iter().map(|a| Unit::line(a.iter(), endian)).collect(),
+            Self::F32x1(v) => v.iter().map(|a| Unit::line(a.iter(), endian)).collect(),
+            Self::U8x2(v) => v
+                .iter()
+                .map(|a| Unit::new(a.iter(), a.nrows(), a.ncols(), endian))
+                .collect(),
+            Self::U16x2(v) => v
+                .iter()
+                .map(|a| Unit::new(a.iter(), a.nrows(), a.ncols(), endian))
+                .collect(),
+            Self::F32x2(v) => v
+                .iter()
+                .map(|a| Unit::new(a.iter(), a.nrows(), a.ncols(), endian))
+                .collect(),
+            Self::RGBA8x2(v) => v
+                .iter()
+                .map(|a| Unit::new(a.iter(), a.nrows(), a.ncols(), endian))
+                .collect(),
+        };
+        let size = sample_size(kind);
+        if let Some(unit) = units.iter().find(|u| u.row_bytes % size != 0) {
+            return Err(VidmodError::ReinterpretMismatch {
+                from,
+                to: kind,
+                bytes: unit.row_bytes,
+            });
+        }
+        Ok(match kind {
+            FrameKind::U8 => Self::U8(units[0].read(endian).into_iter().collect()),
+            FrameKind::U16 => Self::U16(units[0].read(endian).into_iter().collect()),
+            FrameKind::F32 => Self::F32(units[0].read(endian).into_iter().collect()),
+            FrameKind::U8x1 => Self::U8x1(read_all(&units, |u| u.read_1d(endian))),
+            FrameKind::U16x1 => Self::U16x1(read_all(&units, |u| u.read_1d(endian))),
+            FrameKind::F32x1 => Self::F32x1(read_all(&units, |u| u.read_1d(endian))),
+            FrameKind::U8x2 => Self::U8x2(read_all(&units, |u| u.read_2d(endian))),
+            FrameKind::U16x2 => Self::U16x2(read_all(&units, |u| u.read_2d(endian))),
+            FrameKind::F32x2 => Self::F32x2(read_all(&units, |u| u.read_2d(endian))),
+            FrameKind::RGBA8x2 => Self::RGBA8x2(read_all(&units, |u| u.read_2d(endian))),
+        })
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{
+    frame::{Frame, FrameSingle},
+    NodePorts,
+};
+
+/// A handle to one of a node's outputs, which the node puts the frames it produces into
+///
+/// Outputs are the node's pull ports: downstream nodes pull from them. The handle only offers
+/// output operations, so it cannot be mixed up with an [`InputPort`]. It names the port rather
+/// than owning it, so it is `Copy` and takes the node it belongs to as an argument, e.g.
+/// `self.out().put(self, frame)` for a handle generated from an `#[output] out` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputPort {
+    name: &'static str,
+}
+
+impl OutputPort {
+    /// Create a handle to the output with the given name
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+    /// Get the port's name, for the string-based API
+    pub const fn name(self) -> &'static str {
+        self.name
+    }
+    /// Put frames into the output, see [`NodePorts::outbuf_put`]
+    pub fn put<N: NodePorts + ?Sized>(self, node: &mut N, frame: Frame) {
+        node.outbuf_put(self.name, frame)
+    }
+    /// Put a single frame into the output, see [`NodePorts::outbuf_put_single`]
+    pub fn put_single<N: NodePorts + ?Sized>(self, node: &mut N, frame: FrameSingle) {
+        node.outbuf_put_single(self.name, frame)
+    }
+    /// Check how many spaces are free in the output, see [`NodePorts::outbuf_avail`]
+    pub fn avail<N: NodePorts + ?Sized>(self, node: &N) -> usize {
+        node.outbuf_avail(self.name)
+    }
+}
+
+/// A handle to one of a node's inputs, which the node gets the frames it consumes from
+///
+/// Inputs are the node's push ports: upstream nodes push into them. The handle only offers
+/// input operations, so it cannot be mixed up with an [`OutputPort`]. Like an output handle it
+/// takes the node it belongs to as an argument, e.g. `self.input().get(self, count)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputPort {
+    name: &'static str,
+}
+
+impl InputPort {
+    /// Create a handle to the input with the given name
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+    /// Get the port's name, for the string-based API
+    pub const fn name(self) -> &'static str {
+        self.name
+    }
+    /// Get frames from the input, see [`NodePorts::inbuf_get`]
+    pub fn get<N: NodePorts + ?Sized>(self, node: &mut N, count: usize) -> Frame {
+        node.inbuf_get(self.name, count)
+    }
+    /// Get a single frame from the input, see [`NodePorts::inbuf_get_single`]
+    pub fn get_single<N: NodePorts + ?Sized>(self, node: &mut N) -> FrameSingle {
+        node.inbuf_get_single(self.name)
+    }
+    /// Get frames from the input without consuming them, see [`NodePorts::inbuf_peek`]
+    pub fn peek<N: NodePorts + ?Sized>(self, node: &N, count: usize) -> Frame {
+        node.inbuf_peek(self.name, count)
+    }
+    /// Check how many frames are waiting in the input, see [`NodePorts::inbuf_avail`]
+    pub fn avail<N: NodePorts + ?Sized>(self, node: &N) -> usize {
+        node.inbuf_avail(self.name)
+    }
+    /// Check whether the input's upstream has finished, see [`NodePorts::inbuf_eos`]
+    pub fn eos<N: NodePorts + ?Sized>(self, node: &N) -> bool {
+        node.inbuf_eos(self.name)
+    }
+}
@@ -0,0 +1,163 @@
+//! Stable content hashes of frames
+//!
+//! A hash is XXH64 with seed 0 over:
+//! - the frame kind's name (as printed by `Debug`) followed by a zero byte
+//! - the number of elements, as a little-endian u64
+//! - for each element: if it is an array, each dimension of its shape as a little-endian u64,
+//!   then every value in logical (row-major) order as little-endian bytes. f32 values use their
+//!   IEEE 754 bit pattern, except that every NaN is written as the quiet NaN `0x7fc00000`, and
+//!   RGBA8 pixels are written as r, g, b, a.
+//!
+//! The layout is fixed, so hashes are stable across runs and platforms and can key caches or
+//! detect duplicate frames. XXH64 is not cryptographic: do not use these hashes where an
+//! adversary could choose frame contents.
+
+use ndarray::{ArcArray, Dimension};
+use xxhash_rust::xxh64::{xxh64, Xxh64};
+
+use crate::frame::{Frame, FrameKind, FrameSingle, RGBA8};
+
+trait HashBytes {
+    fn hash_into(&self, h: &mut Xxh64);
+}
+
+impl HashBytes for u8 {
+    fn hash_into(&self, h: &mut Xxh64) {
+        h.update(&[*self]);
+    }
+}
+
+impl HashBytes for u16 {
+    fn hash_into(&self, h: &mut Xxh64) {
+        h.update(&self.to_le_bytes());
+    }
+}
+
+// NaN payloads and signs depend on the platform and the operations that produced them
+const CANONICAL_NAN: u32 = 0x7fc0_0000;
+
+impl HashBytes for f32 {
+    fn hash_into(&self, h: &mut Xxh64) {
+        let bits = if self.is_nan() {
+            CANONICAL_NAN
+        } else {
+            self.to_bits()
+        };
+        h.update(&bits.to_le_bytes());
+    }
+}
+
+impl HashBytes for RGBA8 {
+    fn hash_into(&self, h: &mut Xxh64) {
+        h.update(&self.to_bytes());
+    }
+}
+
+impl<T: HashBytes, D: Dimension> HashBytes for ArcArray<T, D> {
+    fn hash_into(&self, h: &mut Xxh64) {
+        for dim in self.shape() {
+            h.update(&(*dim as u64).to_le_bytes());
+        }
+        for x in self.iter() {
+            x.hash_into(h);
+        }
+    }
+}
+
+fn header(kind: FrameKind, count: usize) -> Xxh64 {
+    let mut h = Xxh64::new(0);
+    h.update(format!("{:?}", kind).as_bytes());
+    h.update(&[0]);
+    h.update(&(count as u64).to_le_bytes());
+    h
+}
+
+fn hash_all<'a, T: HashBytes + 'a, I: ExactSizeIterator<Item = &'a T>>(
+    kind: FrameKind,
+    iter: I,
+) -> u64 {
+    let mut h = header(kind, iter.len());
+    for x in iter {
+        x.hash_into(&mut h);
+    }
+    h.digest()
+}
+
+fn roll_all<'a, T: HashBytes + 'a, I: Iterator<Item = &'a T>>(
+    kind: FrameKind,
+    iter: I,
+    mut state: u64,
+) -> u64 {
+    for x in iter {
+        let mut h = header(kind, 1);
+        x.hash_into(&mut h);
+        state = roll(state, h.digest());
+    }
+    state
+}
+
+fn roll(state: u64, hash: u64) -> u64 {
+    let mut buf = [0; 16];
+    buf[..8].copy_from_slice(&state.to_le_bytes());
+    buf[8..].copy_from_slice(&hash.to_le_bytes());
+    xxh64(&buf, 0)
+}
+
+impl Frame {
+    /// A stable hash of the frame's kind, shape and contents
+    pub fn content_hash(&self) -> u64 {
+        let kind = FrameKind::from(self);
+        match self {
+            Self::U8(v) => hash_all(kind, v.iter()),
+            Self::U8x1(v) => hash_all(kind, v.iter()),
+            Self::U8x2(v) => hash_all(kind, v.iter()),
+            Self::U16(v) => hash_all(kind, v.iter()),
+            Self::U16x1(v) => hash_all(kind, v.iter()),
+            Self::U16x2(v) => hash_all(kind, v.iter()),
+            Self::F32(v) => hash_all(kind, v.iter()),
+            Self::F32x1(v) => hash_all(kind, v.iter()),
+            Self::F32x2(v) => hash_all(kind, v.iter()),
+            Self::RGBA8x2(v) => hash_all(kind, v.iter()),
+        }
+    }
+    /// Fold every frame in the queue into a running hash, one at a time
+    ///
+    /// The result only depends on the sequence of frames, not on how they were split into
+    /// queues, so it can be accumulated across ticks.
+    pub fn rolling_hash(&self, state: u64) -> u64 {
+        let kind = FrameKind::from(self);
+        match self {
+            Self::U8(v) => roll_all(kind, v.iter(), state),
+            Self::U8x1(v) => roll_all(kind, v.iter(), state),
+            Self::U8x2(v) => roll_all(kind, v.iter(), state),
+            Self::U16(v) => roll_all(kind, v.iter(), state),
+            Self::U16x1(v) => roll_all(kind, v.iter(), state),
+            Self::U16x2(v) => roll_all(kind, v.iter(), state),
+            Self::F32(v) => roll_all(kind, v.iter(), state),
+            Self::F32x1(v) => roll_all(kind, v.iter(), state),
+            Self::F32x2(v) => roll_all(kind, v.iter(), state),
+            Self::RGBA8x2(v) => roll_all(kind, v.iter(), state),
+        }
+    }
+}
+
+impl FrameSingle {
+    /// A stable hash of the frame, equal to that of a queue holding only this frame
+    pub fn content_hash(&self) -> u64 {
+        let kind = FrameKind::from(self);
+        let mut h = header(kind, 1);
+        match self {
+            Self::U8(v) => v.hash_into(&mut h),
+            Self::U8x1(v) => v.hash_into(&mut h),
+            Self::U8x2(v) => v.hash_into(&mut h),
+            Self::U16(v) => v.hash_into(&mut h),
+            Self::U16x1(v) => v.hash_into(&mut h),
+            Self::U16x2(v) => v.hash_into(&mut h),
+            Self::F32(v) => v.hash_into(&mut h),
+            Self::F32x1(v) => v.hash_into(&mut h),
+            Self::F32x2(v) => v.hash_into(&mut h),
+            Self::RGBA8x2(v) => v.hash_into(&mut h),
+        }
+        h.digest()
+    }
+}
@@ -2,10 +2,20 @@
 #![allow(clippy::new_without_default)]
 
 //! API for declaring vidmod  processing nodes
+//!
+//! The default `macros` feature builds the node-writing API: `#[node_decl]` and `#[node_new]`, the
+//! prelude, and the [`Node`]/[`NodeImpl`] lifecycle whose errors are [`anyhow`] errors. Without it,
+//! only frames, their buffers and the port bookkeeping of [`NodeCore`] are built, with no
+//! dependency on either. See [`frame::Frame`] for which buffer operations allocate.
 
-use std::{collections::BTreeMap, fmt::Debug};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::Debug,
+};
 
-use anyhow::{Error, Result};
+#[cfg(feature = "macros")]
+use anyhow::Result;
 use frame::{Frame, FrameKind, FrameSingle};
 
 /// Types, traits, and methods for handling frames
@@ -14,42 +24,223 @@ pub mod frame;
 /// A VecDeque with a maximum capacity limit
 pub mod limvecdeque;
 
+/// Errors returned by port and link operations
+pub mod error;
+
+mod hash;
+
+mod accounting;
+
+mod handle;
+
+/// Signal processing shared between nodes
+pub mod dsp;
+
+/// Streaming statistics shared between nodes
+pub mod stats;
+
+/// Helpers for reading node arguments
+pub mod params;
+
+/// Everything needed to write a node
+#[cfg(feature = "macros")]
+pub mod prelude;
+
+pub use accounting::FrameAccounting;
+#[cfg(feature = "macros")]
+pub use anyhow;
+pub use error::{PortDirection, VidmodError};
+pub use handle::{InputPort, OutputPort};
+
+/// The error type of fallible port and link operations
+pub type Error = VidmodError;
+
+/// A node's identity within a graph
+///
+/// The graph hands these out as nodes are inserted. The generation tells apart nodes that have
+/// occupied the same slot, so a handle to a removed node is not mistaken for its replacement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId {
+    index:      usize,
+    generation: u32,
+}
+
+impl NodeId {
+    /// Create an ID for the node in slot `index`, the `generation`th to occupy it
+    pub fn new(index: usize, generation: u32) -> Self {
+        Self { index, generation }
+    }
+    /// Get the node's slot in its graph
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// Get how many nodes occupied the slot before this one
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// The first node to occupy a slot, for nodes used outside of a graph
+impl From<usize> for NodeId {
+    fn from(index: usize) -> Self {
+        Self::new(index, 0)
+    }
+}
+
 /// A node's port to pull frames out
 #[derive(Debug, Clone)]
 pub struct PullPort {
-    id:   usize,
-    name: String,
-    kind: FrameKind,
+    id:    NodeId,
+    name:  String,
+    kind:  FrameKind,
+    depth: Option<u8>,
 }
 
 impl PullPort {
     /// Get the node's ID
-    pub fn id(&self) -> usize {
+    pub fn id(&self) -> NodeId {
         self.id
     }
     /// Get the port's name
     pub fn name(&self) -> &str {
         &self.name
     }
+    /// Get the port's frame kind
+    pub fn kind(&self) -> FrameKind {
+        self.kind
+    }
+    /// Get the number of significant bits in the port's U16 samples, if declared
+    pub fn bit_depth(&self) -> Option<u8> {
+        self.depth
+    }
 }
 
 /// A node's port to push frames in
 #[derive(Debug, Clone)]
 pub struct PushPort {
-    id:   usize,
-    name: String,
-    kind: FrameKind,
+    id:      NodeId,
+    name:    String,
+    kind:    FrameKind,
+    accepts: Vec<FrameKind>,
+    batch:   Option<BatchHint>,
+    depth:   Option<u8>,
 }
 
 impl PushPort {
     /// Get the node's ID
-    pub fn id(&self) -> usize {
+    pub fn id(&self) -> NodeId {
         self.id
     }
     /// Get the port's name
     pub fn name(&self) -> &str {
         &self.name
     }
+    /// Get the port's frame kind
+    pub fn kind(&self) -> FrameKind {
+        self.kind
+    }
+    /// Check whether the port accepts frames of the given kind
+    pub fn accepts(&self, kind: FrameKind) -> bool {
+        if self.accepts.is_empty() {
+            self.kind == kind
+        } else {
+            self.accepts.contains(&kind)
+        }
+    }
+    /// Get the port's batching hint, if any
+    pub fn batch_hint(&self) -> Option<BatchHint> {
+        self.batch
+    }
+    /// Get the number of significant bits in the port's U16 samples, if declared or negotiated
+    pub fn bit_depth(&self) -> Option<u8> {
+        self.depth
+    }
+}
+
+/// A hint that a push port wants frames delivered in batches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchHint {
+    /// Frames are only transferred in multiples of this count
+    pub multiple: usize,
+    /// Frames are only transferred when at least this many can be moved
+    pub min:      usize,
+}
+
+impl BatchHint {
+    /// Round a transfer count down to one this hint allows
+    pub fn round(&self, count: usize) -> usize {
+        let count = count - count % self.multiple;
+        if count < self.min {
+            0
+        } else {
+            count
+        }
+    }
+}
+
+/// How full a pull port's buffer is relative to its watermarks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pressure {
+    /// At or below the low watermark
+    Low,
+    /// Between the watermarks
+    Normal,
+    /// At or above the high watermark
+    High,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Watermarks {
+    low:      usize,
+    high:     usize,
+    pressure: Pressure,
+}
+
+impl Watermarks {
+    fn pressure(&self, size: usize) -> Pressure {
+        if size >= self.high {
+            Pressure::High
+        } else if size <= self.low {
+            Pressure::Low
+        } else {
+            Pressure::Normal
+        }
+    }
+}
+
+/// Statistics collected on a port while running
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PortStats {
+    /// Number of times the port's pressure changed level
+    pub pressure_transitions: usize,
+    /// Number of times the port entered high pressure
+    pub high_pressure_count:  usize,
+    /// Number of frames dropped by the port's overflow policy
+    pub dropped:              usize,
+}
+
+/// What a port does when more frames arrive than its buffer can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Never overfill; the sender must wait for space
+    Block,
+    /// Discard the incoming frames that do not fit
+    DropNewest,
+    /// Discard the oldest buffered frames to make room
+    DropOldest,
+}
+
+impl Default for OverflowPolicy {
+    fn default() -> Self {
+        OverflowPolicy::Block
+    }
+}
+
+/// The result of seeking a node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeekOutcome {
+    /// The position actually reached, which may be before the one requested
+    pub position: u64,
 }
 
 /// All nodes must be able to be ticked
@@ -69,202 +260,1207 @@ pub trait FinishNode {
 }
 
 /// A processing node
+#[cfg(feature = "macros")]
 #[derive(Debug)]
-pub struct Node(pub Box<dyn Node2TA>);
+pub struct Node(Box<dyn NodeObject>);
 
+#[cfg(feature = "macros")]
 impl Node {
+    /// Wrap a node
+    pub fn new<T: NodeObject + 'static>(node: T) -> Self {
+        Self(Box::new(node))
+    }
+    /// Wrap an already boxed node
+    pub fn from_box(node: Box<dyn NodeObject>) -> Self {
+        Self(node)
+    }
     /// Initialize the node
     pub fn init(&mut self) {
         self.0.init()
     }
+    /// Seek the node to a position
+    pub fn seek(&mut self, position: u64) -> Result<SeekOutcome> {
+        self.0.seek(position)
+    }
+    /// Start the node before a run
+    pub fn start(&mut self) -> Result<()> {
+        self.0.start()
+    }
+    /// Stop the node after a run
+    pub fn stop(&mut self) -> Result<()> {
+        self.0.stop()
+    }
+    /// Deliver a control message to the node
+    pub fn on_message(&mut self, key: &str, value: &str) -> Result<()> {
+        self.0.on_message(key, value)
+    }
+    /// Get a pull port, given the node's ID
+    pub fn get_pull_port(&self, id: NodeId, name: &str) -> Result<PullPort, VidmodError> {
+        self.0.get_pull_port(id, name)
+    }
+    /// Get a push port, given the node's ID
+    pub fn get_push_port(&self, id: NodeId, name: &str) -> Result<PushPort, VidmodError> {
+        self.0.get_push_port(id, name)
+    }
+    /// Attach a pull port of another node to one of this node's push ports
+    pub fn attach_pull_port(&mut self, name: &str, port: PullPort) -> Result<(), VidmodError> {
+        self.0.attach_pull_port(name, port)
+    }
+    /// Attach a push port of another node to one of this node's pull ports
+    pub fn attach_push_port(&mut self, name: &str, port: PushPort) -> Result<(), VidmodError> {
+        self.0.attach_push_port(name, port)
+    }
+    /// Get the number of frames ready to be pulled from a port
+    pub fn ready_to_pull(&self, port: &PullPort) -> usize {
+        self.0.ready_to_pull(port)
+    }
+    /// Get the number of frames that can be pushed to a port
+    pub fn ready_to_push(&self, port: &PushPort) -> usize {
+        self.0.ready_to_push(port)
+    }
+    /// Get the room left in a port's buffer, ignoring its batch hint
+    pub fn free_to_push(&self, port: &PushPort) -> usize {
+        self.0.free_to_push(port)
+    }
+    /// Pull frames from a port
+    pub fn pull_frame(&mut self, port: &PullPort, count: usize) -> Frame {
+        self.0.pull_frame(port, count)
+    }
+    /// Push frames to a port
+    pub fn push_frame(&mut self, port: &PushPort, frame: Frame) {
+        self.0.push_frame(port, frame)
+    }
+    /// Signal that no more frames will be pushed to a port
+    pub fn signal_eos(&mut self, port: &PushPort) {
+        self.0.signal_eos(port)
+    }
+    /// Check whether a push port has reached end of stream
+    pub fn inbuf_eos(&self, name: &str) -> bool {
+        self.0.inbuf_eos(name)
+    }
+    /// Get the frame kind of a push port, if it has been negotiated
+    pub fn inbuf_kind(&self, name: &str) -> Option<FrameKind> {
+        self.0.inbuf_kind(name)
+    }
+    /// Get the bit depth of a push port, if declared or negotiated
+    pub fn inbuf_bit_depth(&self, name: &str) -> Option<u8> {
+        self.0.inbuf_bit_depth(name)
+    }
+    /// Get the batch hint of a push port
+    pub fn batch_hint(&self, name: &str) -> Option<BatchHint> {
+        self.0.batch_hint(name)
+    }
+    /// Get the traffic counters of a port
+    pub fn port_stats(&self, name: &str) -> PortStats {
+        self.0.port_stats(name)
+    }
+    /// Get the name and direction of each of the node's ports
+    pub fn port_names(&self) -> Vec<(String, PortDirection)> {
+        self.0.port_names()
+    }
+    /// Get the bytes held in each of the node's port buffers
+    pub fn buffer_bytes(&self) -> Vec<(String, usize)> {
+        self.0.buffer_bytes()
+    }
+    /// Record misuse of the buffer API as an error instead of panicking
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.0.set_lenient(lenient)
+    }
+    /// Take the first error recorded in lenient mode
+    pub fn take_error(&mut self) -> Option<VidmodError> {
+        self.0.take_error()
+    }
+    /// Set the number of elements the node should process per tick, or `None` for no limit
+    pub fn set_tick_quota(&mut self, quota: Option<usize>) {
+        self.0.set_tick_quota(quota)
+    }
+    /// Restore the full quota before a tick
+    pub fn reset_budget(&mut self) {
+        self.0.reset_budget()
+    }
+    /// Check whether the node used up its whole quota in the last tick
+    pub fn budget_exhausted(&self) -> bool {
+        self.0.budget_exhausted()
+    }
+    /// Set the graph tick the node is being ticked in
+    pub fn set_clock(&mut self, tick: u64) {
+        self.0.set_clock(tick)
+    }
+    /// Get the number of graph ticks since the node last put or got frames on a port
+    pub fn idle_for(&self, name: &str) -> u64 {
+        self.0.idle_for(name)
+    }
+    /// Get the graph tick the node last put or got frames on any port in
+    pub fn last_progress(&self) -> u64 {
+        self.0.last_progress()
+    }
+    /// Get the origin ticks of the frames removed by the last `pull_frame`
+    pub fn pulled_origins(&self) -> &[u64] {
+        self.0.pulled_origins()
+    }
+    /// Set the origin ticks of the frames added by the last `push_frame`
+    pub fn set_pushed_origins(&mut self, port: &PushPort, origins: &[u64]) {
+        self.0.set_pushed_origins(port, origins)
+    }
+    /// Get the number of requested frames on a push port not yet delivered
+    pub fn requested(&self, name: &str) -> usize {
+        self.0.requested(name)
+    }
+    /// Count frames delivered over a lazy link against the port's requests
+    pub fn fulfil_request(&mut self, port: &PushPort, count: usize) {
+        self.0.fulfil_request(port, count)
+    }
 }
 
+#[cfg(feature = "macros")]
 impl TickNode for Node {
     fn tick(&mut self) -> bool {
         self.0.tick()
     }
 }
 
+#[cfg(feature = "macros")]
 impl FinishNode for Node {
     fn finish(&mut self) -> bool {
         self.0.finish()
     }
 }
 
-/// Rev2 node- TODO rename
+/// The port buffers and bookkeeping embedded in every node by `#[node_decl]`
+///
+/// Port buffers are FIFO: elements leave a port in the order they were put, whichever mix of
+/// put, get and link transfer methods moved them.
 #[derive(Debug)]
-pub struct Node2 {
-    pullports: BTreeMap<String, Frame>,
-    pushports: BTreeMap<String, Frame>,
+pub struct NodeCore {
+    // Output buffers, which the node puts frames into and downstream pull ports drain
+    outbufs:     BTreeMap<String, Frame>,
+    // Input buffers, which upstream push ports fill and the node gets frames from
+    inbufs:      BTreeMap<String, Frame>,
+    batch_hints: BTreeMap<String, BatchHint>,
+    eos:         BTreeSet<String>,
+    watermarks:  BTreeMap<String, Watermarks>,
+    stats:       BTreeMap<String, PortStats>,
+    negotiable:  BTreeMap<String, (Vec<FrameKind>, usize)>,
+    pull_policy: BTreeMap<String, OverflowPolicy>,
+    push_policy: BTreeMap<String, OverflowPolicy>,
+    pull_depth:  BTreeMap<String, u8>,
+    push_depth:  BTreeMap<String, u8>,
+    lenient:     bool,
+    error:       RefCell<Option<VidmodError>>,
+    shapes:      BTreeMap<String, ShapeTracker>,
+    quota:       TickQuota,
+    origins:     Origins,
+    requests:    BTreeMap<String, usize>,
+    activity:    Activity,
+}
+
+// The elements a node may still process this tick, out of the quota set by the graph
+#[derive(Debug, Clone, Copy)]
+struct TickQuota {
+    quota:     Option<usize>,
+    remaining: usize,
+    exhausted: bool,
+}
+
+impl TickQuota {
+    fn unlimited() -> Self {
+        Self {
+            quota:     None,
+            remaining: usize::MAX,
+            exhausted: false,
+        }
+    }
+}
+
+/// A change in a 2D frame's shape, from the old shape to the new
+pub type ShapeChange = ((usize, usize), (usize, usize));
+
+// The last shape pushed to a port, and the changes not yet reported to the node
+#[derive(Debug, Default)]
+struct ShapeTracker {
+    last:    Option<(usize, usize)>,
+    changes: VecDeque<ShapeChange>,
+}
+
+impl ShapeTracker {
+    fn observe(&mut self, shapes: &[(usize, usize)]) {
+        for &shape in shapes {
+            if let Some(last) = self.last {
+                if last != shape {
+                    self.changes.push_back((last, shape));
+                }
+            }
+            self.last = Some(shape);
+        }
+    }
+}
+
+// The graph tick each buffered element was first produced in, kept in step with the port buffers
+//
+// A node's output is stamped with the oldest origin among the inputs it consumed since its last
+// output, or with the current tick if it consumed none, so origins pass through any node.
+#[derive(Debug, Default)]
+struct Origins {
+    clock:   u64,
+    pull:    BTreeMap<String, VecDeque<u64>>,
+    push:    BTreeMap<String, VecDeque<u64>>,
+    carried: Option<u64>,
+    // Set by an output, so the next input starts a new carried origin
+    stale:   bool,
+    pulled:  Vec<u64>,
+    pushed:  usize,
+}
+
+// The graph tick each port last had frames put into or got from it by the node, for telling an
+// idle node from a busy one. Ports are entered when registered, so marking them doesn't allocate
+#[derive(Debug, Default)]
+struct Activity {
+    ports: BTreeMap<String, u64>,
+    last:  u64,
+}
+
+impl Activity {
+    fn register(&mut self, name: &str, clock: u64) {
+        self.ports.insert(name.to_owned(), clock);
+    }
+    fn mark(&mut self, name: &str, clock: u64) {
+        if let Some(tick) = self.ports.get_mut(name) {
+            *tick = clock;
+        }
+        self.last = clock;
+    }
+}
+
+// Drop the oldest stamps to make room, then append `added` so the queue matches its buffer. The
+// queue never outgrows its buffer, so once sized to it this doesn't allocate
+fn stamp(queue: &mut VecDeque<u64>, origin: u64, added: usize, size: usize) {
+    let added = usize::min(added, size);
+    while queue.len() + added > size {
+        queue.pop_front();
+    }
+    queue.extend(std::iter::repeat(origin).take(added));
+}
+
+impl Origins {
+    // Queues are entered and sized when their port is registered, so that stamping and taking
+    // origins on the frame path doesn't allocate
+    fn register_pull(&mut self, name: &str, buf_size: usize) {
+        self.pull
+            .insert(name.to_owned(), VecDeque::with_capacity(buf_size));
+        self.pulled.reserve(buf_size);
+    }
+    fn register_push(&mut self, name: &str, buf_size: usize) {
+        self.push
+            .insert(name.to_owned(), VecDeque::with_capacity(buf_size));
+    }
+    fn consume(&mut self, name: &str, count: usize) {
+        let queue = match self.push.get_mut(name) {
+            Some(queue) => queue,
+            None => return,
+        };
+        for _ in 0..count {
+            let origin = match queue.pop_front() {
+                Some(origin) => origin,
+                None => break,
+            };
+            self.carried = match self.carried {
+                Some(carried) if !self.stale => Some(u64::min(carried, origin)),
+                _ => Some(origin),
+            };
+            self.stale = false;
+        }
+    }
+    fn produce(&mut self, name: &str, added: usize, size: usize) {
+        let origin = self.carried.unwrap_or(self.clock);
+        if let Some(queue) = self.pull.get_mut(name) {
+            stamp(queue, origin, added, size);
+        }
+        self.stale = true;
+    }
+}
+
+// Add frames to a buffer according to its overflow policy, returning how many were dropped, or
+// None if a blocking buffer had no room. A frame emptied into the buffer is recycled.
+fn put(buf: &mut Frame, mut frame: Frame, policy: OverflowPolicy) -> Option<usize> {
+    match policy {
+        OverflowPolicy::Block => {
+            if buf.size() + frame.size() > buf.capacity() {
+                return None;
+            }
+            let bytes = frame.bytes();
+            buf.add_partial(&mut frame);
+            FrameAccounting::acquire(bytes);
+            frame.recycle();
+            Some(0)
+        }
+        OverflowPolicy::DropNewest => {
+            let bytes = frame.bytes();
+            buf.add_partial(&mut frame);
+            FrameAccounting::acquire(bytes - frame.bytes());
+            let dropped = frame.size();
+            frame.recycle();
+            Some(dropped)
+        }
+        OverflowPolicy::DropOldest => {
+            let before = buf.bytes();
+            let dropped = buf.add_overwrite(frame);
+            FrameAccounting::resize(before, buf.bytes());
+            Some(dropped)
+        }
+    }
+}
+
+// A stand-in returned by lenient nodes when the real frame cannot be produced
+fn empty_frame(kind: Option<FrameKind>) -> Frame {
+    Frame::with_capacity(kind.unwrap_or(FrameKind::U8), 0)
+}
+
+fn check_capacity(name: &str, buf_size: usize) -> Result<(), VidmodError> {
+    if buf_size == 0 {
+        Err(VidmodError::ZeroCapacity {
+            port: name.to_owned(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn check_depth(name: &str, kind: FrameKind, depth: u8) -> Result<(), VidmodError> {
+    let u16_kind = matches!(kind, FrameKind::U16 | FrameKind::U16x1 | FrameKind::U16x2);
+    if u16_kind && (1..=16).contains(&depth) {
+        Ok(())
+    } else {
+        Err(VidmodError::InvalidBitDepth {
+            port: Some(name.to_owned()),
+            kind,
+            depth,
+        })
+    }
+}
+
+fn port_not_found(
+    node: Option<usize>,
+    port: &str,
+    direction: Option<PortDirection>,
+) -> VidmodError {
+    VidmodError::PortNotFound {
+        node,
+        port: port.to_owned(),
+        direction,
+    }
+}
+
+// Frames still waiting in the buffers are no longer held once the node is gone
+impl Drop for NodeCore {
+    fn drop(&mut self) {
+        let held = self.outbufs.values().chain(self.inbufs.values());
+        FrameAccounting::release(held.map(Frame::bytes).sum());
+    }
 }
 
 #[allow(missing_docs)]
-impl Node2 {
+impl NodeCore {
     pub fn new() -> Self {
         Self {
-            pullports: BTreeMap::new(),
-            pushports: BTreeMap::new(),
+            outbufs:     BTreeMap::new(),
+            inbufs:      BTreeMap::new(),
+            batch_hints: BTreeMap::new(),
+            eos:         BTreeSet::new(),
+            watermarks:  BTreeMap::new(),
+            stats:       BTreeMap::new(),
+            negotiable:  BTreeMap::new(),
+            pull_policy: BTreeMap::new(),
+            push_policy: BTreeMap::new(),
+            pull_depth:  BTreeMap::new(),
+            push_depth:  BTreeMap::new(),
+            lenient:     false,
+            error:       RefCell::new(None),
+            shapes:      BTreeMap::new(),
+            quota:       TickQuota::unlimited(),
+            origins:     Origins::default(),
+            requests:    BTreeMap::new(),
+            activity:    Activity::default(),
+        }
+    }
+
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+    pub fn take_error(&mut self) -> Option<VidmodError> {
+        self.error.get_mut().take()
+    }
+    pub fn set_tick_quota(&mut self, quota: Option<usize>) {
+        self.quota.quota = quota;
+        self.reset_budget();
+    }
+    pub fn tick_budget(&self) -> Option<usize> {
+        self.quota.quota
+    }
+    pub fn reset_budget(&mut self) {
+        self.quota.remaining = self.quota.quota.unwrap_or(usize::MAX);
+        self.quota.exhausted = false;
+    }
+    pub fn budget_remaining(&self) -> usize {
+        self.quota.remaining
+    }
+    pub fn consume_budget(&mut self, count: usize) {
+        self.quota.remaining = self.quota.remaining.saturating_sub(count);
+        if self.quota.quota.is_some() && self.quota.remaining == 0 {
+            self.quota.exhausted = true;
         }
     }
+    pub fn budget_exhausted(&self) -> bool {
+        self.quota.exhausted
+    }
+    // Misuse of the buffer API panics with `msg`, or in lenient mode records the first error and
+    // carries on with `fallback`
+    fn misuse<T>(&self, err: VidmodError, msg: &str, fallback: T) -> T {
+        if !self.lenient {
+            panic!("{}", msg);
+        }
+        self.error.borrow_mut().get_or_insert(err);
+        fallback
+    }
+    fn missing_port<T>(&self, direction: Option<PortDirection>, name: &str, fallback: T) -> T {
+        let err = port_not_found(None, name, direction);
+        let msg = err.to_string();
+        self.misuse(err, &msg, fallback)
+    }
+    fn too_few(&self, name: &str, wanted: usize, frame: &Frame) -> Frame {
+        let err = VidmodError::NotEnoughFrames {
+            port: name.to_owned(),
+            wanted,
+            available: frame.size(),
+        };
+        let msg = err.to_string();
+        self.misuse(err, &msg, empty_frame(Some(frame.into())))
+    }
+    fn buffer_full(&self, name: &str, capacity: usize) {
+        let err = VidmodError::BufferFull {
+            port: name.to_owned(),
+            capacity,
+        };
+        let msg = err.to_string();
+        self.misuse(err, &msg, ())
+    }
 
     pub fn register_pullport(&mut self, name: &str, kind: FrameKind, buf_size: usize) {
-        self.pullports
-            .insert(name.to_owned(), Frame::with_capacity(kind, buf_size));
+        self.try_register_pullport(name, kind, buf_size)
+            .unwrap_or_else(|e| panic!("{}", e))
     }
     pub fn register_pushport(&mut self, name: &str, kind: FrameKind, buf_size: usize) {
-        self.pushports
+        self.try_register_pushport(name, kind, buf_size)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    pub fn try_register_pullport(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+    ) -> Result<(), VidmodError> {
+        check_capacity(name, buf_size)?;
+        let old = self
+            .outbufs
+            .insert(name.to_owned(), Frame::with_capacity(kind, buf_size));
+        FrameAccounting::release(old.map_or(0, |old| old.bytes()));
+        self.activity.register(name, self.origins.clock);
+        self.origins.register_pull(name, buf_size);
+        Ok(())
+    }
+    pub fn try_register_pushport(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+    ) -> Result<(), VidmodError> {
+        check_capacity(name, buf_size)?;
+        let old = self
+            .inbufs
             .insert(name.to_owned(), Frame::with_capacity(kind, buf_size));
+        FrameAccounting::release(old.map_or(0, |old| old.bytes()));
+        self.activity.register(name, self.origins.clock);
+        self.origins.register_push(name, buf_size);
+        Ok(())
+    }
+    pub fn register_pullport_with_policy(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+        policy: OverflowPolicy,
+    ) {
+        self.register_pullport(name, kind, buf_size);
+        self.pull_policy.insert(name.to_owned(), policy);
+    }
+    pub fn register_pushport_with_policy(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+        policy: OverflowPolicy,
+    ) {
+        self.register_pushport(name, kind, buf_size);
+        self.push_policy.insert(name.to_owned(), policy);
+    }
+    pub fn register_pullport_with_depth(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+        depth: u8,
+    ) {
+        check_depth(name, kind, depth).unwrap_or_else(|e| panic!("{}", e));
+        self.register_pullport(name, kind, buf_size);
+        self.pull_depth.insert(name.to_owned(), depth);
+    }
+    pub fn register_pushport_with_depth(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+        depth: u8,
+    ) {
+        check_depth(name, kind, depth).unwrap_or_else(|e| panic!("{}", e));
+        self.register_pushport(name, kind, buf_size);
+        self.push_depth.insert(name.to_owned(), depth);
+    }
+    pub fn register_pushport_any(&mut self, name: &str, kinds: &[FrameKind], buf_size: usize) {
+        check_capacity(name, buf_size).unwrap_or_else(|e| panic!("{}", e));
+        self.negotiable
+            .insert(name.to_owned(), (kinds.to_vec(), buf_size));
+        self.activity.register(name, self.origins.clock);
+        self.origins.register_push(name, buf_size);
+    }
+    pub fn inbuf_kind(&self, name: &str) -> Option<FrameKind> {
+        if let Some(frame) = self.inbufs.get(name) {
+            Some(frame.into())
+        } else if self.negotiable.contains_key(name) {
+            None
+        } else {
+            self.missing_port(Some(PortDirection::Push), name, None)
+        }
+    }
+    pub fn inbuf_bit_depth(&self, name: &str) -> Option<u8> {
+        if self.inbufs.contains_key(name) || self.negotiable.contains_key(name) {
+            self.push_depth.get(name).copied()
+        } else {
+            self.missing_port(Some(PortDirection::Push), name, None)
+        }
+    }
+    pub fn set_batch(&mut self, name: &str, hint: BatchHint) -> Result<(), VidmodError> {
+        if let Some(frame) = self.inbufs.get(name) {
+            if hint.multiple == 0 || hint.multiple > frame.capacity() || hint.min > frame.capacity()
+            {
+                Err(VidmodError::InvalidBatch {
+                    port: name.to_owned(),
+                    hint,
+                    capacity: frame.capacity(),
+                })
+            } else {
+                self.batch_hints.insert(name.to_owned(), hint);
+                Ok(())
+            }
+        } else {
+            Err(port_not_found(None, name, Some(PortDirection::Push)))
+        }
+    }
+    pub fn batch_hint(&self, name: &str) -> Option<BatchHint> {
+        self.batch_hints.get(name).copied()
+    }
+    pub fn set_watermarks(
+        &mut self,
+        name: &str,
+        low: usize,
+        high: usize,
+    ) -> Result<(), VidmodError> {
+        if let Some(frame) = self.outbufs.get(name) {
+            if low >= high || high > frame.capacity() {
+                Err(VidmodError::InvalidWatermarks {
+                    port: name.to_owned(),
+                    low,
+                    high,
+                    capacity: frame.capacity(),
+                })
+            } else {
+                let mut marks = Watermarks {
+                    low,
+                    high,
+                    pressure: Pressure::Normal,
+                };
+                marks.pressure = marks.pressure(frame.size());
+                self.watermarks.insert(name.to_owned(), marks);
+                Ok(())
+            }
+        } else {
+            Err(port_not_found(None, name, Some(PortDirection::Pull)))
+        }
+    }
+    pub fn outbuf_pressure(&self, name: &str) -> Pressure {
+        if self.outbufs.contains_key(name) {
+            self.watermarks
+                .get(name)
+                .map_or(Pressure::Normal, |marks| marks.pressure)
+        } else {
+            self.missing_port(Some(PortDirection::Pull), name, Pressure::Normal)
+        }
+    }
+    pub fn port_stats(&self, name: &str) -> PortStats {
+        self.stats.get(name).cloned().unwrap_or_default()
+    }
+    pub fn port_names(&self) -> Vec<(String, PortDirection)> {
+        let pull = self.outbufs.keys().map(|name| (name, PortDirection::Pull));
+        let push = self.inbufs.keys().chain(self.negotiable.keys());
+        pull.chain(push.map(|name| (name, PortDirection::Push)))
+            .map(|(name, direction)| (name.clone(), direction))
+            .collect()
+    }
+    pub fn buffer_bytes(&self) -> Vec<(String, usize)> {
+        self.outbufs
+            .iter()
+            .chain(&self.inbufs)
+            .map(|(name, frame)| (name.clone(), frame.bytes()))
+            .collect()
+    }
+    fn record_drops(&mut self, name: &str, dropped: usize) {
+        if dropped > 0 {
+            self.stats.entry(name.to_owned()).or_default().dropped += dropped;
+        }
+    }
+    fn update_pressure(&mut self, name: &str) {
+        if let Some(marks) = self.watermarks.get_mut(name) {
+            let pressure = marks.pressure(self.outbufs[name].size());
+            if pressure != marks.pressure {
+                marks.pressure = pressure;
+                let stats = self.stats.entry(name.to_owned()).or_default();
+                stats.pressure_transitions += 1;
+                if pressure == Pressure::High {
+                    stats.high_pressure_count += 1;
+                }
+            }
+        }
     }
 
-    pub fn get_pull_port(&self, id: usize, name: &str) -> anyhow::Result<PullPort> {
-        if let Some(frame) = self.pullports.get(name) {
+    pub fn get_pull_port(&self, id: NodeId, name: &str) -> Result<PullPort, VidmodError> {
+        if let Some(frame) = self.outbufs.get(name) {
             Ok(PullPort {
                 id,
                 name: name.to_owned(),
                 kind: frame.into(),
+                depth: self.pull_depth.get(name).copied(),
             })
         } else {
-            Err(Error::msg(format!("No pull port: {}", name)))
+            Err(port_not_found(
+                Some(id.index()),
+                name,
+                Some(PortDirection::Pull),
+            ))
         }
     }
-    pub fn get_push_port(&self, id: usize, name: &str) -> anyhow::Result<PushPort> {
-        if let Some(frame) = self.pushports.get(name) {
+    pub fn get_push_port(&self, id: NodeId, name: &str) -> Result<PushPort, VidmodError> {
+        if let Some(frame) = self.inbufs.get(name) {
             Ok(PushPort {
                 id,
                 name: name.to_owned(),
                 kind: frame.into(),
+                accepts: Vec::new(),
+                batch: self.batch_hint(name),
+                depth: self.push_depth.get(name).copied(),
+            })
+        } else if let Some((kinds, _)) = self.negotiable.get(name) {
+            Ok(PushPort {
+                id,
+                name: name.to_owned(),
+                kind: kinds[0],
+                accepts: kinds.clone(),
+                batch: self.batch_hint(name),
+                depth: self.push_depth.get(name).copied(),
             })
         } else {
-            Err(Error::msg(format!("No push port: {}", name)))
+            Err(port_not_found(
+                Some(id.index()),
+                name,
+                Some(PortDirection::Push),
+            ))
         }
     }
 
-    pub fn attach_push_port(&self, name: &str, port: PushPort) -> Result<()> {
-        if let Some(frame) = self.pullports.get(name) {
-            if port.kind == frame.into() {
+    pub fn attach_push_port(&mut self, name: &str, port: PushPort) -> Result<(), VidmodError> {
+        if let Some(frame) = self.outbufs.get(name) {
+            if port.accepts(frame.into()) {
                 Ok(())
+            } else if port.accepts.is_empty() {
+                Err(VidmodError::KindMismatch {
+                    port:      Some(name.to_owned()),
+                    direction: Some(PortDirection::Pull),
+                    expected:  frame.into(),
+                    got:       port.kind,
+                })
             } else {
-                Err(Error::msg(format!(
-                    "Port kind mismatch: {:?},{:?}",
-                    port.kind,
-                    FrameKind::from(frame)
-                )))
+                Err(VidmodError::KindNotAccepted {
+                    accepted: port.accepts,
+                    got:      frame.into(),
+                })
             }
         } else {
-            Err(Error::msg(format!("No push port: {}", name)))
+            Err(port_not_found(None, name, Some(PortDirection::Pull)))
         }
     }
 
-    pub fn attach_pull_port(&self, name: &str, port: PullPort) -> Result<()> {
-        if let Some(frame) = self.pushports.get(name) {
+    pub fn attach_pull_port(&mut self, name: &str, port: PullPort) -> Result<(), VidmodError> {
+        if let Some(frame) = self.inbufs.get(name) {
             if port.kind == frame.into() {
+                self.adopt_depth(name, &port);
                 Ok(())
             } else {
-                Err(Error::msg(format!(
-                    "Port kind mismatch: {:?},{:?}",
-                    port.kind,
-                    FrameKind::from(frame)
-                )))
+                Err(VidmodError::KindMismatch {
+                    port:      Some(name.to_owned()),
+                    direction: Some(PortDirection::Push),
+                    expected:  frame.into(),
+                    got:       port.kind,
+                })
+            }
+        } else if let Some((kinds, buf_size)) = self.negotiable.get(name) {
+            if kinds.contains(&port.kind) {
+                let frame = Frame::with_capacity(port.kind, *buf_size);
+                self.inbufs.insert(name.to_owned(), frame);
+                self.negotiable.remove(name);
+                self.adopt_depth(name, &port);
+                Ok(())
+            } else {
+                Err(VidmodError::KindNotAccepted {
+                    accepted: kinds.clone(),
+                    got:      port.kind,
+                })
             }
         } else {
-            Err(Error::msg(format!("No pull port: {}", name)))
+            Err(port_not_found(None, name, Some(PortDirection::Push)))
+        }
+    }
+
+    // A push port without a depth of its own takes the depth of the pull port linked to it
+    fn adopt_depth(&mut self, name: &str, port: &PullPort) {
+        if let Some(depth) = port.depth {
+            self.push_depth.entry(name.to_owned()).or_insert(depth);
         }
     }
 
     pub fn outbuf_avail(&self, name: &str) -> usize {
-        if let Some(frame) = self.pullports.get(name) {
+        if let Some(frame) = self.outbufs.get(name) {
             frame.capacity() - frame.size()
         } else {
-            panic!("No pull port: {}", name)
+            self.missing_port(Some(PortDirection::Pull), name, 0)
         }
     }
     pub fn inbuf_avail(&self, name: &str) -> usize {
-        if let Some(frame) = self.pushports.get(name) {
+        if let Some(frame) = self.inbufs.get(name) {
             frame.size()
+        } else if self.negotiable.contains_key(name) {
+            0
+        } else {
+            self.missing_port(Some(PortDirection::Push), name, 0)
+        }
+    }
+    // Ports not yet given a kind by negotiation have an empty buffer with no capacity
+    fn port_fill(&self, name: &str) -> Option<(usize, usize)> {
+        if let Some(frame) = self.outbufs.get(name).or_else(|| self.inbufs.get(name)) {
+            Some((frame.size(), frame.capacity()))
+        } else if let Some((_, buf_size)) = self.negotiable.get(name) {
+            Some((0, *buf_size))
         } else {
-            panic!("No push port: {}", name)
+            self.missing_port(None, name, None)
         }
     }
+    pub fn is_port_empty(&self, name: &str) -> bool {
+        self.port_fill(name).map_or(true, |(size, _)| size == 0)
+    }
+    pub fn is_port_full(&self, name: &str) -> bool {
+        self.port_fill(name)
+            .map_or(false, |(size, capacity)| size >= capacity)
+    }
     pub fn outbuf_put(&mut self, name: &str, frame: Frame) {
-        if let Some(f) = self.pullports.get_mut(name) {
-            f.add(frame).unwrap();
+        if let Some(f) = self.outbufs.get_mut(name) {
+            let policy = self.pull_policy.get(name).copied().unwrap_or_default();
+            let offered = frame.size();
+            match put(f, frame, policy) {
+                Some(dropped) => {
+                    let added = match policy {
+                        OverflowPolicy::DropNewest => offered - dropped,
+                        _ => offered,
+                    };
+                    let size = f.size();
+                    self.origins.produce(name, added, size);
+                    if added > 0 {
+                        self.activity.mark(name, self.origins.clock);
+                    }
+                    self.record_drops(name, dropped)
+                }
+                None => {
+                    let capacity = f.capacity();
+                    self.buffer_full(name, capacity)
+                }
+            }
+            self.update_pressure(name);
         } else {
-            panic!("No pull port: {}", name)
+            self.missing_port(Some(PortDirection::Pull), name, ())
         }
     }
     pub fn outbuf_put_single(&mut self, name: &str, frame: FrameSingle) {
-        if let Some(f) = self.pullports.get_mut(name) {
-            f.add_single(frame).unwrap();
+        if let Some(f) = self.outbufs.get_mut(name) {
+            let policy = self.pull_policy.get(name).copied().unwrap_or_default();
+            let (added, dropped) = match policy {
+                OverflowPolicy::Block | OverflowPolicy::DropNewest => {
+                    let bytes = frame.bytes();
+                    let added = f.add_single(frame).is_some();
+                    if added {
+                        FrameAccounting::acquire(bytes);
+                    }
+                    (added, !added && policy == OverflowPolicy::DropNewest)
+                }
+                OverflowPolicy::DropOldest => {
+                    let before = f.bytes();
+                    let dropped = f.add_single_overwrite(frame);
+                    FrameAccounting::resize(before, f.bytes());
+                    (true, dropped)
+                }
+            };
+            let (size, capacity) = (f.size(), f.capacity());
+            self.origins.produce(name, added as usize, size);
+            if added {
+                self.activity.mark(name, self.origins.clock);
+            }
+            if !added && policy == OverflowPolicy::Block {
+                self.buffer_full(name, capacity);
+            }
+            self.record_drops(name, dropped as usize);
+            self.update_pressure(name);
+        } else {
+            self.missing_port(Some(PortDirection::Pull), name, ())
+        }
+    }
+    pub fn inbuf_peek(&self, name: &str, count: usize) -> Frame {
+        if let Some(frame) = self.inbufs.get(name) {
+            match frame.peek(count) {
+                Some(res) => res,
+                None => self.too_few(name, count, frame),
+            }
         } else {
-            panic!("No pull port: {}", name)
+            self.missing_port(Some(PortDirection::Push), name, empty_frame(None))
         }
     }
-    pub fn inbuf_peek(&mut self, name: &str, count: usize) -> Frame {
-        if let Some(frame) = self.pushports.get_mut(name) {
-            frame.peek(count).unwrap()
+    #[deprecated(note = "inbuf_peek no longer needs &mut self")]
+    pub fn inbuf_peek_mut(&mut self, name: &str, count: usize) -> Frame {
+        self.inbuf_peek(name, count)
+    }
+    pub fn inbuf_peek_single(&self, name: &str) -> Option<FrameSingle> {
+        if let Some(frame) = self.inbufs.get(name) {
+            frame.peek_single()
         } else {
-            panic!("No pull port: {}", name)
+            self.missing_port(Some(PortDirection::Push), name, None)
         }
     }
     pub fn inbuf_get(&mut self, name: &str, count: usize) -> Frame {
-        if let Some(frame) = self.pushports.get_mut(name) {
-            frame.remove(count).unwrap()
+        if let Some(frame) = self.inbufs.get_mut(name) {
+            match frame.remove(count) {
+                Some(res) => {
+                    FrameAccounting::release(res.bytes());
+                    self.origins.consume(name, res.size());
+                    if res.size() > 0 {
+                        self.activity.mark(name, self.origins.clock);
+                    }
+                    res
+                }
+                None => self.too_few(name, count, &self.inbufs[name]),
+            }
         } else {
-            panic!("No pull port: {}", name)
+            self.missing_port(Some(PortDirection::Push), name, empty_frame(None))
+        }
+    }
+    pub fn inbuf_min_avail(&self, names: &[&str]) -> usize {
+        names
+            .iter()
+            .map(|name| self.inbuf_avail(name))
+            .min()
+            .unwrap_or(0)
+    }
+    pub fn inbuf_get_zipped(&mut self, names: &[&str], count: usize) -> Option<Vec<Frame>> {
+        if self.inbuf_min_avail(names) >= count {
+            Some(
+                names
+                    .iter()
+                    .map(|name| self.inbuf_get(name, count))
+                    .collect(),
+            )
+        } else {
+            None
         }
     }
     pub fn inbuf_get_all(&mut self, name: &str) -> Frame {
-        if let Some(frame) = self.pushports.get_mut(name) {
-            frame.remove_all()
+        if let Some(frame) = self.inbufs.get_mut(name) {
+            let res = frame.remove_all();
+            FrameAccounting::release(res.bytes());
+            self.origins.consume(name, res.size());
+            if res.size() > 0 {
+                self.activity.mark(name, self.origins.clock);
+            }
+            res
         } else {
-            panic!("No pull port: {}", name)
+            self.missing_port(Some(PortDirection::Push), name, empty_frame(None))
         }
     }
     pub fn inbuf_get_single(&mut self, name: &str) -> FrameSingle {
-        if let Some(frame) = self.pushports.get_mut(name) {
-            frame.remove_single().unwrap()
+        if let Some(frame) = self.inbuf_try_get_single(name) {
+            return frame;
+        }
+        let kind = self.inbufs.get(name).map(FrameKind::from);
+        let err = VidmodError::NotEnoughFrames {
+            port:      name.to_owned(),
+            wanted:    1,
+            available: 0,
+        };
+        let fallback =
+            FrameSingle::try_splat(kind.unwrap_or(FrameKind::U8), Some((0, 1)), 0.0).unwrap();
+        self.misuse(err, &format!("Empty push port: {}", name), fallback)
+    }
+    pub fn inbuf_try_get_single(&mut self, name: &str) -> Option<FrameSingle> {
+        if let Some(frame) = self.inbufs.get_mut(name) {
+            let res = frame.remove_single();
+            if let Some(single) = &res {
+                FrameAccounting::release(single.bytes());
+                self.origins.consume(name, 1);
+                self.activity.mark(name, self.origins.clock);
+            }
+            res
+        } else {
+            self.missing_port(Some(PortDirection::Push), name, None)
+        }
+    }
+    pub fn inbuf_shape_changed(&mut self, name: &str) -> Option<ShapeChange> {
+        if self.inbufs.contains_key(name) || self.negotiable.contains_key(name) {
+            self.shapes.get_mut(name)?.changes.pop_front()
+        } else {
+            self.missing_port(Some(PortDirection::Push), name, None)
+        }
+    }
+    pub fn assert_shape(&self, name: &str, expected: &[usize]) -> Result<(), VidmodError> {
+        let frame = self
+            .inbufs
+            .get(name)
+            .ok_or_else(|| port_not_found(None, name, Some(PortDirection::Push)))?;
+        match frame.shapes().into_iter().find(|shape| shape != expected) {
+            Some(got) => Err(VidmodError::ShapeMismatch {
+                port: name.to_owned(),
+                expected: expected.to_vec(),
+                got,
+            }),
+            None => Ok(()),
+        }
+    }
+    pub fn inbuf_eos(&self, name: &str) -> bool {
+        if self.inbufs.contains_key(name) || self.negotiable.contains_key(name) {
+            self.eos.contains(name)
         } else {
-            panic!("No pull port: {}", name)
+            self.missing_port(Some(PortDirection::Push), name, true)
         }
     }
 
     pub fn ready_to_pull(&self, port: &PullPort) -> usize {
-        if let Some(frame) = self.pullports.get(&port.name) {
+        if let Some(frame) = self.outbufs.get(&port.name) {
             frame.size()
         } else {
-            panic!("No pull port: {}", &port.name)
+            self.missing_port(Some(PortDirection::Pull), &port.name, 0)
         }
     }
     pub fn ready_to_push(&self, port: &PushPort) -> usize {
-        if let Some(frame) = self.pushports.get(&port.name) {
-            frame.capacity() - frame.size()
+        let free = self.free_to_push(port);
+        match self.batch_hints.get(&port.name) {
+            Some(hint) => hint.round(free),
+            None => free,
+        }
+    }
+    pub fn free_to_push(&self, port: &PushPort) -> usize {
+        if let Some(frame) = self.inbufs.get(&port.name) {
+            // Ports that drop on overflow always accept a full buffer's worth
+            match self.push_policy.get(&port.name) {
+                Some(OverflowPolicy::DropNewest) | Some(OverflowPolicy::DropOldest) => {
+                    frame.capacity()
+                }
+                _ => frame.capacity() - frame.size(),
+            }
         } else {
-            panic!("No push port: {}", &port.name)
+            self.missing_port(Some(PortDirection::Push), &port.name, 0)
         }
     }
+    // Neither this nor `push_frame` allocates once the first frame has crossed: the returned queue
+    // reuses one recycled by an earlier push, and origin queues are sized when ports register
     pub fn pull_frame(&mut self, port: &PullPort, count: usize) -> Frame {
-        if let Some(frame) = self.pullports.get_mut(&port.name) {
-            frame.remove(count).unwrap()
+        if let Some(frame) = self.outbufs.get_mut(&port.name) {
+            let res = match frame.remove(count) {
+                Some(res) => {
+                    FrameAccounting::release(res.bytes());
+                    res
+                }
+                None => self.too_few(&port.name, count, &self.outbufs[&port.name]),
+            };
+            let pulled = &mut self.origins.pulled;
+            pulled.clear();
+            if let Some(queue) = self.origins.pull.get_mut(&port.name) {
+                let taken = usize::min(res.size(), queue.len());
+                pulled.extend(queue.drain(..taken));
+            }
+            self.update_pressure(&port.name);
+            res
         } else {
-            panic!("No pull port: {}", port.name)
+            self.missing_port(Some(PortDirection::Pull), &port.name, empty_frame(None))
         }
     }
     pub fn push_frame(&mut self, port: &PushPort, frame: Frame) {
-        if let Some(f) = self.pushports.get_mut(&port.name) {
-            f.add(frame).unwrap();
+        if let Some(f) = self.inbufs.get_mut(&port.name) {
+            let shapes = frame.shapes_2d();
+            if !shapes.is_empty() {
+                match self.shapes.get_mut(&port.name) {
+                    Some(tracker) => tracker.observe(&shapes),
+                    None => {
+                        let mut tracker = ShapeTracker::default();
+                        tracker.observe(&shapes);
+                        self.shapes.insert(port.name.clone(), tracker);
+                    }
+                }
+            }
+            let policy = self
+                .push_policy
+                .get(&port.name)
+                .copied()
+                .unwrap_or_default();
+            let offered = frame.size();
+            let added = match put(f, frame, policy) {
+                Some(dropped) => {
+                    self.record_drops(&port.name, dropped);
+                    match policy {
+                        OverflowPolicy::DropNewest => offered - dropped,
+                        _ => offered,
+                    }
+                }
+                None => {
+                    let capacity = f.capacity();
+                    self.buffer_full(&port.name, capacity);
+                    0
+                }
+            };
+            // Stamped as arriving now, until the graph passes on the origins they came with
+            let size = self.inbufs[&port.name].size();
+            if let Some(queue) = self.origins.push.get_mut(&port.name) {
+                stamp(queue, self.origins.clock, added, size);
+            }
+            self.origins.pushed = usize::min(added, size);
         } else {
-            panic!("No pull port: {}", port.name)
+            self.missing_port(Some(PortDirection::Push), &port.name, ())
+        }
+    }
+    pub fn set_clock(&mut self, tick: u64) {
+        self.origins.clock = tick;
+    }
+    pub fn clock(&self) -> u64 {
+        self.origins.clock
+    }
+    pub fn idle_for(&self, name: &str) -> u64 {
+        match self.activity.ports.get(name) {
+            Some(tick) => self.origins.clock.saturating_sub(*tick),
+            None => self.missing_port(None, name, 0),
+        }
+    }
+    pub fn last_progress(&self) -> u64 {
+        self.activity.last
+    }
+    pub fn inbuf_origins(&self, name: &str) -> Vec<u64> {
+        if self.inbufs.contains_key(name) || self.negotiable.contains_key(name) {
+            self.origins
+                .push
+                .get(name)
+                .map_or_else(Vec::new, |queue| queue.iter().copied().collect())
+        } else {
+            self.missing_port(Some(PortDirection::Push), name, Vec::new())
+        }
+    }
+    pub fn pulled_origins(&self) -> &[u64] {
+        &self.origins.pulled
+    }
+    pub fn set_pushed_origins(&mut self, port: &PushPort, origins: &[u64]) {
+        let queue = match self.origins.push.get_mut(&port.name) {
+            Some(queue) => queue,
+            None => return,
+        };
+        let count = usize::min(self.origins.pushed, origins.len());
+        let start = queue.len() - self.origins.pushed;
+        for (idx, origin) in origins.iter().take(count).enumerate() {
+            queue[start + idx] = *origin;
+        }
+    }
+    pub fn signal_eos(&mut self, port: &PushPort) {
+        if self.inbufs.contains_key(&port.name) {
+            self.eos.insert(port.name.clone());
+        } else {
+            self.missing_port(Some(PortDirection::Push), &port.name, ())
+        }
+    }
+    pub fn request(&mut self, name: &str, count: usize) {
+        if self.inbufs.contains_key(name) || self.negotiable.contains_key(name) {
+            *self.requests.entry(name.to_owned()).or_default() += count;
+        } else {
+            self.missing_port(Some(PortDirection::Push), name, ())
+        }
+    }
+    pub fn requested(&self, name: &str) -> usize {
+        if self.inbufs.contains_key(name) || self.negotiable.contains_key(name) {
+            self.requests.get(name).copied().unwrap_or(0)
+        } else {
+            self.missing_port(Some(PortDirection::Push), name, 0)
+        }
+    }
+    pub fn fulfil_request(&mut self, port: &PushPort, count: usize) {
+        if let Some(requested) = self.requests.get_mut(&port.name) {
+            *requested = requested.saturating_sub(count);
         }
     }
 }
 
+/// Deprecated name of [`NodeCore`]
+#[deprecated(note = "renamed to NodeCore")]
+pub type Node2 = NodeCore;
+/// Deprecated name of [`NodeImpl`], implemented for every type that implements it
+#[cfg(feature = "macros")]
+#[deprecated(note = "renamed to NodeImpl")]
+pub trait Node2T: NodeImpl {}
+
+#[cfg(feature = "macros")]
+#[allow(deprecated)]
+impl<T: NodeImpl + ?Sized> Node2T for T {}
+
+/// Deprecated name of [`NodeObject`], implemented for every type that implements it
+#[cfg(feature = "macros")]
+#[deprecated(note = "renamed to NodeObject")]
+pub trait Node2TA: NodeObject {}
+
+#[cfg(feature = "macros")]
+#[allow(deprecated)]
+impl<T: NodeObject + ?Sized> Node2TA for T {}
+
+/// Deprecated name of [`NodePorts`], implemented for every type that implements it
+#[deprecated(note = "renamed to NodePorts")]
+pub trait Node2MT: NodePorts {}
+
+#[allow(deprecated)]
+impl<T: NodePorts + ?Sized> Node2MT for T {}
+
 /// All trait functions for a node
-pub trait Node2TA: Node2T + Node2MT {}
+#[cfg(feature = "macros")]
+pub trait NodeObject: NodeImpl + NodePorts {}
 
-impl<T> Node2TA for T where T: Node2T + Node2MT {}
+#[cfg(feature = "macros")]
+impl<T> NodeObject for T where T: NodeImpl + NodePorts {}
 
 /// User-implemented functions for a node
-pub trait Node2T: Debug {
+#[cfg(feature = "macros")]
+pub trait NodeImpl: Debug {
     /// Setup for the node - register all ports here
     fn init(&mut self);
     /// Tick function for the node - signals the node to process data
@@ -273,30 +1469,143 @@ pub trait Node2T: Debug {
     /// Finish function for the node- signals the node to wrap up
     /// Returns true if we cannot possibly ever have more work to do
     fn finish(&mut self) -> bool;
+    /// Skip ahead so the next frame emitted is the one at `position`
+    fn seek(&mut self, _position: u64) -> Result<SeekOutcome> {
+        Err(VidmodError::SeekUnsupported.into())
+    }
+    /// Acquire external resources, such as file handles or devices, before the first tick
+    fn start(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Release the resources acquired by `start` once the run is over
+    fn stop(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Change a setting while running, such as a rate or mode, given as text like a parameter
+    ///
+    /// Only ever called between ticks.
+    fn on_message(&mut self, key: &str, _value: &str) -> Result<()> {
+        Err(VidmodError::MessageUnsupported {
+            key: key.to_owned(),
+        }
+        .into())
+    }
 }
 
 /// Macro-generated functions for a node
-pub trait Node2MT {
+///
+/// A node's outputs are its pull ports, which the node puts frames into for downstream nodes to
+/// pull, and its inputs are its push ports, which upstream nodes push frames into for the node to
+/// get. Besides the methods taking port names, `register_output` and `register_input` return
+/// [`OutputPort`] and [`InputPort`] handles that only offer the operations for their direction.
+/// `#[node_decl]` also turns fields annotated `#[output]` or `#[input]` into methods returning
+/// such handles, named after the field or given as in `#[input("in")]`; leave them out of the
+/// struct built in `#[node_new]`.
+pub trait NodePorts {
     /// Register a pull port
     fn register_pullport(&mut self, name: &str, kind: FrameKind, buf_size: usize);
     /// Register a push port
     fn register_pushport(&mut self, name: &str, kind: FrameKind, buf_size: usize);
+    /// Register a pull port, failing if the buffer size is zero
+    fn try_register_pullport(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+    ) -> Result<(), VidmodError>;
+    /// Register a push port, failing if the buffer size is zero
+    fn try_register_pushport(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+    ) -> Result<(), VidmodError>;
+    /// Register a pull port with an overflow policy
+    fn register_pullport_with_policy(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+        policy: OverflowPolicy,
+    );
+    /// Register a push port with an overflow policy
+    fn register_pushport_with_policy(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+        policy: OverflowPolicy,
+    );
+    /// Register a U16, U16x1 or U16x2 pull port whose samples have `depth` significant bits
+    fn register_pullport_with_depth(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+        depth: u8,
+    );
+    /// Register a U16, U16x1 or U16x2 push port expecting samples with `depth` significant bits
+    fn register_pushport_with_depth(
+        &mut self,
+        name: &str,
+        kind: FrameKind,
+        buf_size: usize,
+        depth: u8,
+    );
+    /// Register a push port whose kind is negotiated when it is linked
+    fn register_pushport_any(&mut self, name: &str, kinds: &[FrameKind], buf_size: usize);
+    /// Register an output, which is a pull port, and get a handle to it
+    fn register_output(
+        &mut self,
+        name: &'static str,
+        kind: FrameKind,
+        buf_size: usize,
+    ) -> OutputPort {
+        self.register_pullport(name, kind, buf_size);
+        OutputPort::new(name)
+    }
+    /// Register an input, which is a push port, and get a handle to it
+    fn register_input(
+        &mut self,
+        name: &'static str,
+        kind: FrameKind,
+        buf_size: usize,
+    ) -> InputPort {
+        self.register_pushport(name, kind, buf_size);
+        InputPort::new(name)
+    }
+    /// Get the kind of a push port, or None if it has not been negotiated yet
+    fn inbuf_kind(&self, name: &str) -> Option<FrameKind>;
+    /// Get the number of significant bits in a push port's U16 samples
+    ///
+    /// A port registered without a depth takes the depth of the pull port linked to it, if any.
+    fn inbuf_bit_depth(&self, name: &str) -> Option<u8>;
+    /// Request that a push port only receives frames in batches
+    fn set_batch(&mut self, name: &str, hint: BatchHint) -> Result<(), VidmodError>;
+    /// Get a push port's batching hint
+    fn batch_hint(&self, name: &str) -> Option<BatchHint>;
+    /// Set the low and high watermarks of a pull port
+    fn set_watermarks(&mut self, name: &str, low: usize, high: usize) -> Result<(), VidmodError>;
     /// Get a named pull port
-    fn get_pull_port(&self, id: usize, name: &str) -> Result<PullPort>;
+    fn get_pull_port(&self, id: NodeId, name: &str) -> Result<PullPort, VidmodError>;
     /// Get a named push port
-    fn get_push_port(&self, id: usize, name: &str) -> Result<PushPort>;
+    fn get_push_port(&self, id: NodeId, name: &str) -> Result<PushPort, VidmodError>;
     /// Attach a pull port to a named push port
-    fn attach_pull_port(&self, name: &str, port: PullPort) -> Result<()>;
+    fn attach_pull_port(&mut self, name: &str, port: PullPort) -> Result<(), VidmodError>;
     /// Attach a push port to a named pull port
-    fn attach_push_port(&self, name: &str, port: PushPort) -> Result<()>;
+    fn attach_push_port(&mut self, name: &str, port: PushPort) -> Result<(), VidmodError>;
     /// Check how many frames can be pulled before the output buffer is empty
     fn ready_to_pull(&self, port: &PullPort) -> usize;
     /// Check how many frames can be pushed before the input buffer is full
     fn ready_to_push(&self, port: &PushPort) -> usize;
+    /// Check how much room the input buffer has left, before rounding to any batch hint
+    fn free_to_push(&self, port: &PushPort) -> usize;
     /// Pull a frame from the output buffer
     fn pull_frame(&mut self, port: &PullPort, count: usize) -> Frame;
     /// Push a frame into the input buffer
     fn push_frame(&mut self, port: &PushPort, frame: Frame);
+    /// Signal that a push port will never receive more frames
+    fn signal_eos(&mut self, port: &PushPort);
 
     /// Check how many frames are available in the input buffer
     fn inbuf_avail(&self, name: &str) -> usize;
@@ -309,9 +1618,78 @@ pub trait Node2MT {
     /// Get frames from the input buffer
     fn inbuf_get(&mut self, name: &str, count: usize) -> Frame;
     /// Get frames from the input buffer without consuming
-    fn inbuf_peek(&mut self, name: &str, count: usize) -> Frame;
+    fn inbuf_peek(&self, name: &str, count: usize) -> Frame;
+    /// Get frames from the input buffer without consuming
+    #[deprecated(note = "inbuf_peek no longer needs &mut self")]
+    fn inbuf_peek_mut(&mut self, name: &str, count: usize) -> Frame {
+        self.inbuf_peek(name, count)
+    }
+    /// Get a frame from the input buffer without consuming
+    fn inbuf_peek_single(&self, name: &str) -> Option<FrameSingle>;
     /// Get a frame from the input buffer
     fn inbuf_get_single(&mut self, name: &str) -> FrameSingle;
+    /// Get a frame from the input buffer, or None if it is empty
+    fn inbuf_try_get_single(&mut self, name: &str) -> Option<FrameSingle>;
     /// Get a frame from the input buffer
     fn inbuf_get_all(&mut self, name: &str) -> Frame;
+    /// Check how many frames are available in all of several input buffers
+    fn inbuf_min_avail(&self, names: &[&str]) -> usize;
+    /// Get the same number of frames from each of several input buffers, or none if any is short
+    fn inbuf_get_zipped(&mut self, names: &[&str], count: usize) -> Option<Vec<Frame>>;
+    /// Check whether the input buffer's upstream has finished
+    fn inbuf_eos(&self, name: &str) -> bool;
+    /// Check how full the output buffer is relative to its watermarks
+    fn outbuf_pressure(&self, name: &str) -> Pressure;
+    /// Get the statistics collected on a port
+    fn port_stats(&self, name: &str) -> PortStats;
+    /// Get the bytes held in each port buffer
+    fn buffer_bytes(&self) -> Vec<(String, usize)>;
+    /// Get the name and direction of each port, pull ports first, including push ports whose
+    /// kind is yet to be negotiated
+    fn port_names(&self) -> Vec<(String, PortDirection)>;
+    /// Take the next change in shape between consecutive 2D frames pushed to the input buffer
+    fn inbuf_shape_changed(&mut self, name: &str) -> Option<ShapeChange>;
+    /// In lenient mode, misuse of the buffer API records an error instead of panicking
+    fn set_lenient(&mut self, lenient: bool);
+    /// Take the first error recorded in lenient mode
+    fn take_error(&mut self) -> Option<VidmodError>;
+    /// Check whether a port's buffer holds no frames
+    fn is_port_empty(&self, name: &str) -> bool;
+    /// Check whether a port's buffer has no room for more frames
+    fn is_port_full(&self, name: &str) -> bool;
+    /// Set the number of elements the node should process per tick, or `None` for no limit
+    fn set_tick_quota(&mut self, quota: Option<usize>);
+    /// Get the per-tick element quota set by the graph, if any
+    fn tick_budget(&self) -> Option<usize>;
+    /// Restore the full quota, which the graph does before every tick
+    fn reset_budget(&mut self);
+    /// Get the number of elements the node may still process this tick
+    fn budget_remaining(&self) -> usize;
+    /// Count elements processed against this tick's quota
+    fn consume_budget(&mut self, count: usize);
+    /// Check whether the node used up its whole quota in the last tick
+    fn budget_exhausted(&self) -> bool;
+    /// Check that every array frame waiting on a push port has the expected shape
+    fn assert_shape(&self, name: &str, expected: &[usize]) -> Result<(), VidmodError>;
+    /// Set the graph tick the node is being ticked in
+    fn set_clock(&mut self, tick: u64);
+    /// Get the graph tick the node is being ticked in
+    fn clock(&self) -> u64;
+    /// Get the number of graph ticks since the node last put frames into or got frames from a
+    /// port, counting from its registration if it never has
+    fn idle_for(&self, name: &str) -> u64;
+    /// Get the graph tick the node last put or got frames on any port in, or 0 if it never has
+    fn last_progress(&self) -> u64;
+    /// Get the tick each frame waiting on a push port was first produced in, oldest first
+    fn inbuf_origins(&self, name: &str) -> Vec<u64>;
+    /// Get the origin ticks of the frames removed by the last `pull_frame`
+    fn pulled_origins(&self) -> &[u64];
+    /// Set the origin ticks of the frames added by the last `push_frame`
+    fn set_pushed_origins(&mut self, port: &PushPort, origins: &[u64]);
+    /// Ask for `count` more frames on a push port whose link is lazy
+    fn request(&mut self, name: &str, count: usize);
+    /// Get the number of requested frames on a push port not yet delivered
+    fn requested(&self, name: &str) -> usize;
+    /// Count frames delivered over a lazy link against the port's requests
+    fn fulfil_request(&mut self, port: &PushPort, count: usize);
 }
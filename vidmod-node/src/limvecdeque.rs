@@ -1,12 +1,20 @@
-use std::{collections::VecDeque, ops::RangeBounds};
+use std::{
+    collections::VecDeque,
+    ops::{Bound, RangeBounds},
+};
 
 use all_asserts::assert_le;
 
+use self::order::OrderCheck;
+
+mod order;
+
 /// A VecDeque wrapper that enforces a limited capacity
 #[derive(Debug, Clone)]
 pub struct LimVecDeque<T> {
     queue:    VecDeque<T>,
     capacity: usize,
+    order:    OrderCheck,
 }
 
 impl<T> LimVecDeque<T> {
@@ -15,21 +23,67 @@ impl<T> LimVecDeque<T> {
         Self {
             queue: VecDeque::with_capacity(capacity),
             capacity,
+            order: OrderCheck::fresh(),
         }
     }
     /// Removed the first element and returns it, or `None` if empty.
     pub fn pop_front(&mut self) -> Option<T> {
-        self.queue.pop_front()
+        let val = self.queue.pop_front()?;
+        self.order.exit();
+        Some(val)
     }
     /// Appends an element to the back of the deque.
     pub fn push_back(&mut self, val: T) {
         assert_le!(self.queue.len() + 1, self.capacity);
+        self.order.arrive(1);
         self.queue.push_back(val)
     }
     /// Moves all elements of `other` into `self`, leaving `other` empty.
     pub fn append(&mut self, other: &mut LimVecDeque<T>) {
         assert_le!(self.queue.len() + other.len(), self.capacity);
-        self.queue.append(&mut other.queue)
+        self.order.admit(&mut other.order);
+        self.queue.append(&mut other.queue);
+    }
+    /// Moves up to `count` elements from the front of `other` to the back of `self`, as many as
+    /// `other` holds and `self` has room for, without allocating. Returns the number moved.
+    pub fn transfer_from(&mut self, other: &mut LimVecDeque<T>, count: usize) -> usize {
+        let count = usize::min(count, other.len());
+        let count = usize::min(count, self.capacity.saturating_sub(self.queue.len()));
+        for _ in 0..count {
+            let val = other.pop_front().unwrap();
+            self.push_back(val);
+        }
+        count
+    }
+    /// Appends an element, removing the front element first if the deque is full.
+    /// Returns the element that was dropped, if any.
+    pub fn push_back_overwrite(&mut self, val: T) -> Option<T> {
+        if self.capacity == 0 {
+            Some(val)
+        } else if self.queue.len() >= self.capacity {
+            let res = self.pop_front();
+            self.push_back(val);
+            res
+        } else {
+            self.push_back(val);
+            None
+        }
+    }
+    /// Moves all elements of `other` into `self`, dropping elements from the front to stay within
+    /// capacity. Returns the number of elements dropped.
+    pub fn append_overwrite(&mut self, other: &mut LimVecDeque<T>) -> usize {
+        self.order.admit(&mut other.order);
+        self.queue.append(&mut other.queue);
+        let excess = self.queue.len().saturating_sub(self.capacity);
+        self.drain(..excess).for_each(drop);
+        excess
+    }
+    /// Removes every element and sets a new capacity, keeping the storage if it is large enough.
+    pub fn reset(&mut self, capacity: usize) {
+        self.queue.clear();
+        self.queue.reserve(capacity);
+        self.capacity = capacity;
+        self.order = OrderCheck::fresh();
     }
     /// Returns the number of elements in the deque.
     pub fn len(&self) -> usize {
@@ -44,8 +98,24 @@ impl<T> LimVecDeque<T> {
     where
         R: RangeBounds<usize>,
     {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.queue.len(),
+        };
+        self.order.drain(start..end);
         self.queue.drain(range)
     }
+    /// Swaps the elements at indices `i` and `j`.
+    pub fn swap(&mut self, i: usize, j: usize) {
+        self.order.swap(i, j);
+        self.queue.swap(i, j)
+    }
     /// Rearranges the internal storage of this deque so it is one contiguous slice, which is then returned.
     pub fn make_contiguous(&mut self) -> &mut [T] {
         self.queue.make_contiguous()
@@ -62,13 +132,20 @@ impl<T> LimVecDeque<T> {
     pub fn iter(&self) -> std::collections::vec_deque::Iter<T> {
         self.queue.iter()
     }
+    /// Returns a front-to-back iterator that returns mutable references.
+    pub fn iter_mut(&mut self) -> std::collections::vec_deque::IterMut<'_, T> {
+        self.queue.iter_mut()
+    }
 }
 
 impl<T> From<Vec<T>> for LimVecDeque<T> {
     fn from(v: Vec<T>) -> Self {
+        let mut order = OrderCheck::fresh();
+        order.arrive(v.len());
         Self {
             capacity: v.len(),
-            queue:    VecDeque::from(v),
+            queue: v.into(),
+            order,
         }
     }
 }
@@ -78,9 +155,15 @@ impl<T> std::iter::FromIterator<T> for LimVecDeque<T> {
     where
         I: IntoIterator<Item = T>,
     {
-        let queue = VecDeque::from_iter(iter);
+        let queue: VecDeque<T> = iter.into_iter().collect();
         let capacity = queue.len();
-        Self { queue, capacity }
+        let mut order = OrderCheck::fresh();
+        order.arrive(capacity);
+        Self {
+            queue,
+            capacity,
+            order,
+        }
     }
 }
 
@@ -93,6 +176,6 @@ where
     type IntoIter = std::collections::vec_deque::IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.queue.clone().into_iter()
+        self.iter().cloned().collect::<VecDeque<T>>().into_iter()
     }
 }
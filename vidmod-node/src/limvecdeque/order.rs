@@ -0,0 +1,102 @@
+// Sequence numbers tagging each element of a LimVecDeque, to check they leave in the order they
+// arrived. The numbers are kept at the same indices as their elements in a queue of their own, so
+// the elements stay contiguous, and every LimVecDeque method that moves an element moves its
+// number with it. Only compiled in with the `debug-order-check` feature; otherwise every check is
+// a no-op.
+
+#[cfg(feature = "debug-order-check")]
+use std::collections::VecDeque;
+use std::ops::Range;
+
+#[cfg(feature = "debug-order-check")]
+#[derive(Debug, Clone, Default)]
+pub(super) struct OrderCheck {
+    seqs:     VecDeque<u64>,
+    next:     u64,
+    last_out: Option<u64>,
+}
+
+#[cfg(feature = "debug-order-check")]
+impl OrderCheck {
+    pub(super) fn fresh() -> Self {
+        Self::default()
+    }
+
+    // Number `count` elements arriving at the back
+    pub(super) fn arrive(&mut self, count: usize) {
+        for _ in 0..count {
+            self.seqs.push_back(self.next);
+            self.next += 1;
+        }
+    }
+
+    // The element leaving from the front must have arrived after every element that left before it
+    pub(super) fn exit(&mut self) {
+        if let Some(seq) = self.seqs.pop_front() {
+            check_after(seq, self.last_out);
+            self.last_out = Some(seq);
+        }
+    }
+
+    // Elements moved in from another deque leave it in order, and are then numbered as arrivals
+    pub(super) fn admit(&mut self, from: &mut OrderCheck) {
+        let count = from.seqs.len();
+        for _ in 0..count {
+            from.exit();
+        }
+        self.arrive(count);
+    }
+
+    // Only removals from the front are exits; a middle range is just discarded, but must itself
+    // still be in order
+    pub(super) fn drain(&mut self, range: Range<usize>) {
+        if range.start == 0 {
+            for _ in range {
+                self.exit();
+            }
+        } else {
+            let mut last = None;
+            for seq in self.seqs.drain(range) {
+                check_after(seq, last);
+                last = Some(seq);
+            }
+        }
+    }
+
+    pub(super) fn swap(&mut self, i: usize, j: usize) {
+        self.seqs.swap(i, j);
+    }
+}
+
+#[cfg(feature = "debug-order-check")]
+fn check_after(seq: u64, last: Option<u64>) {
+    if let Some(last) = last {
+        assert!(
+            seq > last,
+            "Frame order violated: element {} left after element {}",
+            seq,
+            last
+        );
+    }
+}
+
+#[cfg(not(feature = "debug-order-check"))]
+#[derive(Debug, Clone, Default)]
+pub(super) struct OrderCheck;
+
+#[cfg(not(feature = "debug-order-check"))]
+impl OrderCheck {
+    pub(super) fn fresh() -> Self {
+        Self
+    }
+
+    pub(super) fn arrive(&mut self, _count: usize) {}
+
+    pub(super) fn exit(&mut self) {}
+
+    pub(super) fn admit(&mut self, _from: &mut OrderCheck) {}
+
+    pub(super) fn drain(&mut self, _range: Range<usize>) {}
+
+    pub(super) fn swap(&mut self, _i: usize, _j: usize) {}
+}
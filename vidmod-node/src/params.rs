@@ -0,0 +1,139 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::VidmodError;
+
+/// Prefix of argument names reserved for vidmod itself
+pub const RESERVED_PREFIX: &str = "vidmod.";
+/// Argument holding the path of the project directory
+pub const PATH_ARG: &str = "vidmod.path";
+/// Argument holding the node's name in the manifest
+pub const NODE_NAME_ARG: &str = "vidmod.node_name";
+/// Argument holding the node's index in the graph
+pub const NODE_INDEX_ARG: &str = "vidmod.node_index";
+/// Argument holding the directory where the node may keep state between runs
+pub const STATE_DIR_ARG: &str = "vidmod.state_dir";
+
+/// Argument setting the time in milliseconds a single tick may take before a warning is logged
+pub const TICK_BUDGET_ARG: &str = "vidmod.tick_budget_ms";
+/// Argument setting the time in milliseconds a single tick may take before it counts against the node
+pub const TICK_LIMIT_ARG: &str = "vidmod.tick_limit_ms";
+/// Argument setting the number of elements a node should process per tick
+pub const TICK_QUOTA_ARG: &str = "vidmod.tick_quota";
+/// Argument that, when "true", makes misuse of the buffer API a node error instead of a panic
+pub const LENIENT_ARG: &str = "vidmod.lenient";
+
+/// Arguments injected into every node by the project loader, which manifests may not set
+pub const INJECTED_ARGS: &[&str] = &[PATH_ARG, NODE_NAME_ARG, NODE_INDEX_ARG, STATE_DIR_ARG];
+/// The only arguments starting with [`RESERVED_PREFIX`] that manifests may set
+pub const USER_ARGS: &[&str] = &[TICK_BUDGET_ARG, TICK_LIMIT_ARG, TICK_QUOTA_ARG, LENIENT_ARG];
+
+/// Check that a manifest may set an argument, i.e. it is not reserved or is one of [`USER_ARGS`]
+pub fn check_user_arg(node: &str, key: &str) -> Result<(), VidmodError> {
+    if key.starts_with(RESERVED_PREFIX) && !USER_ARGS.contains(&key) {
+        return Err(VidmodError::ReservedArg {
+            node: node.to_owned(),
+            key:  key.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// A node's arguments, as given in the manifest plus those injected by vidmod
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    args: BTreeMap<String, String>,
+}
+
+impl Params {
+    /// Wrap a node's argument map
+    pub fn new(args: BTreeMap<String, String>) -> Self {
+        Self { args }
+    }
+    /// Get an argument by name
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.args.get(key).map(String::as_str)
+    }
+    /// Get the path of the project directory
+    pub fn path(&self) -> Option<&str> {
+        self.get(PATH_ARG)
+    }
+    /// Get the project directory, which is absolute for nodes loaded from a manifest
+    pub fn project_root(&self) -> Option<&Path> {
+        self.path().map(Path::new)
+    }
+    /// Resolve the existing file named by an argument, see [`Params::output_path`]
+    ///
+    /// The result is canonical, and an error names both the argument and the path tried.
+    pub fn resolve_path(&self, key: &str) -> Result<PathBuf, VidmodError> {
+        let path = self.output_path(key)?;
+        fs::canonicalize(&path).map_err(|_| VidmodError::PathNotFound {
+            key: key.to_owned(),
+            path,
+        })
+    }
+    /// Resolve the file named by an argument, which need not exist yet
+    ///
+    /// Absolute paths are returned unchanged. Relative paths are joined to the project directory,
+    /// or left relative to the working directory outside a project.
+    pub fn output_path(&self, key: &str) -> Result<PathBuf, VidmodError> {
+        let file = self.get(key).ok_or_else(|| VidmodError::MissingArg {
+            key: key.to_owned(),
+        })?;
+        Ok(match self.project_root() {
+            Some(root) => root.join(file),
+            None => PathBuf::from(file),
+        })
+    }
+    /// Get the node's name in the manifest
+    pub fn node_name(&self) -> Option<&str> {
+        self.get(NODE_NAME_ARG)
+    }
+    /// Get the node's index in the graph
+    pub fn node_index(&self) -> Option<usize> {
+        self.get(NODE_INDEX_ARG).and_then(|v| v.parse().ok())
+    }
+    /// Get the directory where the node may keep state between runs
+    ///
+    /// The directory is only created by the first `write_state`, so it may not exist yet.
+    pub fn state_dir(&self) -> Option<&Path> {
+        self.get(STATE_DIR_ARG).map(Path::new)
+    }
+    /// Read a file from the state directory, or None if it has not been written yet
+    pub fn read_state(&self, file: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.state_file(file)?) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    /// Replace a file in the state directory, so readers see either the old or new contents
+    pub fn write_state(&self, file: &str, data: &[u8]) -> io::Result<()> {
+        let path = self.state_file(file)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_file_name(format!("{}.tmp", file));
+        fs::write(&tmp, data)?;
+        fs::rename(tmp, path)
+    }
+    fn state_file(&self, file: &str) -> io::Result<PathBuf> {
+        let dir = self
+            .state_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No state directory"))?;
+        Ok(dir.join(file))
+    }
+    /// Get the underlying argument map
+    pub fn args(&self) -> &BTreeMap<String, String> {
+        &self.args
+    }
+}
+
+impl From<BTreeMap<String, String>> for Params {
+    fn from(args: BTreeMap<String, String>) -> Self {
+        Self::new(args)
+    }
+}
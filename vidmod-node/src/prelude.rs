@@ -0,0 +1,10 @@
+pub use vidmod_macros::{node_decl, node_new};
+
+pub use crate::{
+    frame::{Frame, FrameKind, FrameSingle, RGBA8},
+    limvecdeque::LimVecDeque,
+    params::Params,
+    BatchHint, FinishNode, InputPort, Node, NodeCore, NodeId, NodeImpl, NodeObject, NodePorts,
+    OutputPort, OverflowPolicy, PortStats, Pressure, PullPort, PushPort, SeekOutcome, ShapeChange,
+    TickNode,
+};
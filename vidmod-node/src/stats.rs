@@ -0,0 +1,158 @@
+/// Running count, minimum, maximum, mean and variance of a stream of values
+///
+/// The mean and variance are updated by Welford's method, which stays accurate over long streams
+/// where summing squares would not. NaNs are counted on their own and leave every other
+/// statistic untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnlineStats {
+    count: u64,
+    nan:   u64,
+    min:   f64,
+    max:   f64,
+    mean:  f64,
+    // Sum of squared differences from the current mean
+    m2:    f64,
+}
+
+impl Default for OnlineStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OnlineStats {
+    /// Create an accumulator that has seen no values
+    pub fn new() -> Self {
+        Self {
+            count: 0,
+            nan:   0,
+            min:   f64::INFINITY,
+            max:   f64::NEG_INFINITY,
+            mean:  0.0,
+            m2:    0.0,
+        }
+    }
+    /// Add a value
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            self.nan += 1;
+            return;
+        }
+        self.count += 1;
+        self.min = f64::min(self.min, x);
+        self.max = f64::max(self.max, x);
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+    /// Get the number of values seen, not counting NaNs
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+    /// Get the number of NaNs seen
+    pub fn nan_count(&self) -> u64 {
+        self.nan
+    }
+    /// Get the smallest value seen, if any
+    pub fn min(&self) -> Option<f64> {
+        self.seen(self.min)
+    }
+    /// Get the largest value seen, if any
+    pub fn max(&self) -> Option<f64> {
+        self.seen(self.max)
+    }
+    /// Get the mean of the values seen, if any
+    pub fn mean(&self) -> Option<f64> {
+        self.seen(self.mean)
+    }
+    /// Get the population variance of the values seen, if any
+    pub fn variance(&self) -> Option<f64> {
+        self.seen(self.m2 / self.count as f64)
+    }
+    /// Get the population standard deviation of the values seen, if any
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+    fn seen(&self, stat: f64) -> Option<f64> {
+        if self.count > 0 {
+            Some(stat)
+        } else {
+            None
+        }
+    }
+}
+
+/// Counts of values falling in equal-width bins spanning `[lo, hi)`
+///
+/// Values below `lo` are counted as underflow and values at or above `hi` as overflow. NaNs are
+/// counted on their own, in no bin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    lo:        f64,
+    hi:        f64,
+    bins:      Vec<u64>,
+    underflow: u64,
+    overflow:  u64,
+    nan:       u64,
+}
+
+impl Histogram {
+    /// Create an empty histogram of `bins` bins over `[lo, hi)`
+    ///
+    /// Panics unless `lo < hi` and there is at least one bin.
+    pub fn new(lo: f64, hi: f64, bins: usize) -> Self {
+        assert!(lo < hi, "Histogram range {}..{} is empty", lo, hi);
+        assert!(bins > 0, "Histogram needs at least one bin");
+        Self {
+            lo,
+            hi,
+            bins: vec![0; bins],
+            underflow: 0,
+            overflow: 0,
+            nan: 0,
+        }
+    }
+    /// Add a value
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            self.nan += 1;
+        } else if x < self.lo {
+            self.underflow += 1;
+        } else if x >= self.hi {
+            self.overflow += 1;
+        } else {
+            let len = self.bins.len();
+            // Rounding can put a value just below `hi` one past the last bin
+            let idx = ((x - self.lo) / (self.hi - self.lo) * len as f64) as usize;
+            self.bins[usize::min(idx, len - 1)] += 1;
+        }
+    }
+    /// Get the count in each bin, lowest first
+    pub fn bins(&self) -> &[u64] {
+        &self.bins
+    }
+    /// Get the range of values counted in a bin
+    pub fn bin_range(&self, idx: usize) -> (f64, f64) {
+        let width = (self.hi - self.lo) / self.bins.len() as f64;
+        (
+            self.lo + width * idx as f64,
+            self.lo + width * (idx + 1) as f64,
+        )
+    }
+    /// Get the range the bins span
+    pub fn range(&self) -> (f64, f64) {
+        (self.lo, self.hi)
+    }
+    /// Get the number of values below the range
+    pub fn underflow(&self) -> u64 {
+        self.underflow
+    }
+    /// Get the number of values at or above the top of the range
+    pub fn overflow(&self) -> u64 {
+        self.overflow
+    }
+    /// Get the number of NaNs seen
+    pub fn nan_count(&self) -> u64 {
+        self.nan
+    }
+}
@@ -0,0 +1,70 @@
+//! Allocations on the steady-state frame path, counted by a wrapping global allocator
+//!
+//! The counter is shared by every thread of this test binary, so keep a single test here.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use vidmod_node::{
+    frame::{Frame, FrameSingle},
+    NodeCore,
+};
+
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::SeqCst);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[test]
+fn scalar_transfer_does_not_allocate() {
+    let singles = [
+        FrameSingle::U8(7),
+        FrameSingle::U16(700),
+        FrameSingle::F32(0.5),
+    ];
+    for single in &singles {
+        let mut src = Frame::with_capacity(single.kind(), 4);
+        let mut dst = Frame::with_capacity(single.kind(), 4);
+        let mut node = NodeCore::new();
+        node.register_pullport("out", single.kind(), 4);
+        node.register_pushport("in", single.kind(), 4);
+        let pull = node.get_pull_port(0.into(), "out").unwrap();
+        let push = node.get_push_port(0.into(), "in").unwrap();
+        let mut step = || {
+            src.add_single(single.clone()).unwrap();
+            assert_eq!(dst.add_partial(&mut src), 1);
+            assert!(dst.remove_single().is_some());
+
+            // Across a link, as the graph moves frames between nodes
+            node.outbuf_put_single("out", single.clone());
+            let frame = node.pull_frame(&pull, 1);
+            node.push_frame(&push, frame);
+            assert!(node.inbuf_try_get_single("in").is_some());
+        };
+        // The first pass makes the queue that later transfers reuse
+        step();
+        let before = ALLOCS.load(Ordering::SeqCst);
+        for _ in 0..1000 {
+            step();
+        }
+        assert_eq!(ALLOCS.load(Ordering::SeqCst) - before, 0, "{:?}", single);
+    }
+}
@@ -0,0 +1,153 @@
+use vidmod_node::{
+    frame::{Frame, FrameKind, FrameSingle},
+    limvecdeque::LimVecDeque,
+    NodeCore, PortDirection, VidmodError,
+};
+
+fn node() -> NodeCore {
+    let mut node = NodeCore::new();
+    node.register_pushport("in", FrameKind::U16, 2);
+    node.register_pullport("out", FrameKind::U16, 2);
+    node
+}
+
+fn fill(node: &mut NodeCore) {
+    let port = node.get_push_port(0.into(), "in").unwrap();
+    node.push_frame(&port, Frame::U16(LimVecDeque::from(vec![1, 2])));
+    node.outbuf_put("out", Frame::U16(LimVecDeque::from(vec![3, 4])));
+}
+
+fn empty() -> Frame {
+    Frame::with_capacity(FrameKind::U16, 0)
+}
+
+#[test]
+fn zero_capacity_rejected() {
+    let mut node = NodeCore::new();
+    assert_eq!(
+        node.try_register_pullport("out", FrameKind::U8, 0),
+        Err(VidmodError::ZeroCapacity {
+            port: "out".to_owned(),
+        })
+    );
+    assert!(node.try_register_pushport("in", FrameKind::U8, 0).is_err());
+    assert!(node.get_pull_port(0.into(), "out").is_err());
+    assert!(node.get_push_port(0.into(), "in").is_err());
+}
+
+#[test]
+#[should_panic(expected = "Zero capacity buffer: out")]
+fn zero_capacity_register_panics() {
+    NodeCore::new().register_pullport("out", FrameKind::U8, 0);
+}
+
+#[test]
+fn frame_zero_count() {
+    let mut frame = Frame::U16(LimVecDeque::from(vec![1, 2]));
+    let peeked = frame.peek(0).unwrap();
+    assert_eq!((peeked.size(), peeked.capacity()), (0, 0));
+    let removed = frame.remove(0).unwrap();
+    assert_eq!((removed.size(), removed.capacity()), (0, 0));
+    assert_eq!(frame.size(), 2);
+
+    let mut none = empty();
+    assert_eq!(none.peek(0).unwrap().size(), 0);
+    assert_eq!(none.remove(0).unwrap().size(), 0);
+    assert!(none.peek(1).is_none());
+    assert!(none.remove(1).is_none());
+    assert!(none.remove_single().is_none());
+}
+
+#[test]
+fn empty_accessors() {
+    let mut node = node();
+    let pull = node.get_pull_port(0.into(), "out").unwrap();
+    let push = node.get_push_port(0.into(), "in").unwrap();
+
+    assert_eq!(node.inbuf_avail("in"), 0);
+    assert_eq!(node.outbuf_avail("out"), 2);
+    assert_eq!(node.ready_to_pull(&pull), 0);
+    assert_eq!(node.ready_to_push(&push), 2);
+    assert_eq!(node.inbuf_peek("in", 0).size(), 0);
+    assert!(node.inbuf_peek_single("in").is_none());
+    assert!(node.inbuf_try_get_single("in").is_none());
+    assert_eq!(node.inbuf_get("in", 0).size(), 0);
+    assert_eq!(node.inbuf_get_all("in").size(), 0);
+    assert_eq!(node.inbuf_get_zipped(&["in"], 0).unwrap()[0].size(), 0);
+    assert!(node.inbuf_get_zipped(&["in"], 1).is_none());
+    assert_eq!(node.pull_frame(&pull, 0).size(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Empty push port: in")]
+fn empty_get_single_panics() {
+    node().inbuf_get_single("in");
+}
+
+#[test]
+fn full_accessors() {
+    let mut node = node();
+    fill(&mut node);
+    let pull = node.get_pull_port(0.into(), "out").unwrap();
+    let push = node.get_push_port(0.into(), "in").unwrap();
+
+    assert_eq!(node.ready_to_push(&push), 0);
+    assert_eq!(node.outbuf_avail("out"), 0);
+    assert_eq!(node.ready_to_pull(&pull), 2);
+
+    // Putting nothing into a full buffer is a no-op
+    node.push_frame(&push, empty());
+    node.outbuf_put("out", empty());
+    assert_eq!(node.inbuf_avail("in"), 2);
+    assert_eq!(node.ready_to_pull(&pull), 2);
+
+    assert_eq!(node.inbuf_get("in", 0).size(), 0);
+    assert_eq!(node.pull_frame(&pull, 0).size(), 0);
+    assert_eq!(node.inbuf_peek("in", 2).size(), 2);
+    assert!(matches!(
+        node.inbuf_try_get_single("in"),
+        Some(FrameSingle::U16(1))
+    ));
+    assert_eq!(node.inbuf_get_single("in").unwrap_u16(), 2);
+    assert!(node.inbuf_try_get_single("in").is_none());
+}
+
+#[test]
+fn lenient_misuse_records_first_error() {
+    let mut node = node();
+    node.set_lenient(true);
+    fill(&mut node);
+
+    assert_eq!(node.inbuf_avail("nope"), 0);
+    node.outbuf_put("out", Frame::U16(LimVecDeque::from(vec![5])));
+    assert_eq!(node.inbuf_get("in", 3).size(), 0);
+    assert_eq!(node.inbuf_avail("in"), 2);
+    assert_eq!(
+        node.take_error(),
+        Some(VidmodError::PortNotFound {
+            node:      None,
+            port:      "nope".to_owned(),
+            direction: Some(PortDirection::Push),
+        })
+    );
+    assert_eq!(node.take_error(), None);
+
+    node.inbuf_get("in", 2);
+    assert_eq!(node.inbuf_get_single("in").unwrap_u16(), 0);
+    assert_eq!(
+        node.take_error(),
+        Some(VidmodError::NotEnoughFrames {
+            port:      "in".to_owned(),
+            wanted:    1,
+            available: 0,
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "Buffer full: out")]
+fn strict_overflow_panics() {
+    let mut node = node();
+    fill(&mut node);
+    node.outbuf_put("out", Frame::U16(LimVecDeque::from(vec![5])));
+}
@@ -0,0 +1,447 @@
+use ndarray::{arr1, arr2, ArcArray1, ArcArray2, Array2};
+use vidmod_node::{
+    frame::{Endian, Frame, FrameKind, FrameSingle, RGBA8},
+    limvecdeque::LimVecDeque,
+    VidmodError,
+};
+
+/// A U8 frame whose deque is split across the ring boundary
+fn wrapped_frame() -> Frame {
+    let mut deque = LimVecDeque::with_capacity(4);
+    for v in 0..4 {
+        deque.push_back(v);
+    }
+    let mut next = 4;
+    while deque.as_slices().1.is_empty() {
+        deque.pop_front();
+        deque.push_back(next);
+        next += 1;
+    }
+    Frame::U8(deque)
+}
+
+#[test]
+fn peek_across_ring_boundary() {
+    let frame = wrapped_frame();
+    let expected: Vec<u8> = match &frame {
+        Frame::U8(v) => v.iter().copied().collect(),
+        _ => unreachable!(),
+    };
+
+    let peeked: Vec<u8> = frame.peek(3).unwrap().unwrap_u8().iter().copied().collect();
+    assert_eq!(peeked, expected[..3]);
+    for (i, v) in expected.iter().enumerate() {
+        assert_eq!(frame.peek_at(i).unwrap().unwrap_u8(), *v);
+    }
+    assert!(frame.peek_at(expected.len()).is_none());
+    assert!(frame.peek(expected.len() + 1).is_none());
+    assert_eq!(frame.size(), expected.len());
+}
+
+#[test]
+fn add_partial_moves_what_fits() {
+    let mut dst = Frame::with_capacity(FrameKind::U8, 3);
+    dst.add(Frame::U8(LimVecDeque::from(vec![1]))).unwrap();
+    let mut src = Frame::U8(LimVecDeque::from(vec![2, 3, 4]));
+
+    assert_eq!(dst.add_partial(&mut src), 2);
+    assert_eq!(dst.size(), 3);
+    assert_eq!(src.size(), 1);
+    assert_eq!(src.remove_single().unwrap().unwrap_u8(), 4);
+    assert_eq!(dst.add_partial(&mut src), 0);
+}
+
+#[test]
+fn split_at_divides_frame() {
+    let mut frame = Frame::U8(LimVecDeque::from(vec![1, 2, 3, 4, 5, 6]));
+    let (first, second) = frame.split_at(2);
+
+    let first: Vec<u8> = first.unwrap_u8().iter().copied().collect();
+    let second: Vec<u8> = second.unwrap_u8().iter().copied().collect();
+    assert_eq!(first, vec![1, 2]);
+    assert_eq!(second, vec![3, 4, 5, 6]);
+    assert_eq!(frame.size(), 0);
+}
+
+#[test]
+fn split_at_ends() {
+    let (first, second) = Frame::U8(LimVecDeque::from(vec![1, 2])).split_at(0);
+    assert_eq!((first.size(), second.size()), (0, 2));
+    let (first, second) = Frame::U8(LimVecDeque::from(vec![1, 2])).split_at(2);
+    assert_eq!((first.size(), second.size()), (2, 0));
+}
+
+#[test]
+#[should_panic(expected = "Split index 3 out of range: 2")]
+fn split_at_out_of_range() {
+    Frame::U8(LimVecDeque::from(vec![1, 2])).split_at(3);
+}
+
+#[test]
+fn standard_layout_shares_contiguous_arrays() {
+    let array = ArcArray2::from_shape_vec((2, 3), vec![1u8, 2, 3, 4, 5, 6]).unwrap();
+    let frame = Frame::U8x2(LimVecDeque::from(vec![
+        array.clone(),
+        array.t().to_shared(),
+    ]));
+    let mut res = frame.as_standard_layout().unwrap_u8x2();
+
+    let same = res.pop_front().unwrap();
+    assert_eq!(same.as_ptr(), array.as_ptr());
+    let transposed = res.pop_front().unwrap();
+    assert!(transposed.is_standard_layout());
+    assert_eq!(transposed, array.t());
+}
+
+#[test]
+fn rgba8_from_tuples() {
+    let pixels = ArcArray2::from_shape_vec(
+        (2, 2),
+        vec![
+            (255, 0, 0, 255).into(),
+            RGBA8::from_rgb(0, 255, 0),
+            RGBA8::new(0, 0, 255, 128),
+            RGBA8::TRANSPARENT,
+        ],
+    )
+    .unwrap();
+    let mut deque = LimVecDeque::with_capacity(1);
+    deque.push_back(pixels);
+    let frame = Frame::RGBA8x2(deque);
+    assert_eq!(FrameKind::from(&frame), FrameKind::RGBA8x2);
+
+    let pixels = frame.unwrap_rgba8x2().pop_front().unwrap();
+    let (r, g, b, a) = (
+        pixels[[0, 0]].r,
+        pixels[[0, 1]].g,
+        pixels[[1, 0]].a,
+        pixels[[1, 1]].a,
+    );
+    assert_eq!((r, g, b, a), (255, 255, 128, 0));
+    let white = RGBA8::WHITE;
+    assert_eq!((white.r, white.a), (255, 255));
+}
+
+#[test]
+fn rgba8_is_four_bytes() {
+    assert_eq!(std::mem::size_of::<RGBA8>(), 4);
+    assert_eq!(std::mem::align_of::<RGBA8>(), 1);
+    let px = RGBA8::new(1, 2, 3, 4);
+    assert_eq!(px.to_bytes(), [1, 2, 3, 4]);
+    assert_eq!(RGBA8::from_bytes(px.to_bytes()), px);
+    assert_eq!(RGBA8::default(), RGBA8::TRANSPARENT);
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+fn rgba8_cast_round_trip() {
+    let pixels = ArcArray2::from_shape_vec(
+        (1, 2),
+        vec![RGBA8::new(1, 2, 3, 4), RGBA8::from_rgb(5, 6, 7)],
+    )
+    .unwrap();
+    let bytes = RGBA8::slice_as_bytes(pixels.as_slice().unwrap());
+    assert_eq!(bytes, &[1, 2, 3, 4, 5, 6, 7, 255]);
+    assert_eq!(RGBA8::slice_from_bytes(bytes), pixels.as_slice().unwrap());
+
+    let mut written = Vec::new();
+    let mut deque = LimVecDeque::with_capacity(1);
+    deque.push_back(pixels.clone());
+    Frame::RGBA8x2(deque).write_bytes(&mut written).unwrap();
+    assert_eq!(written, bytes);
+}
+
+#[test]
+#[cfg(feature = "macros")]
+fn splat_scalars_ignore_shape() {
+    assert_eq!(
+        FrameSingle::splat(FrameKind::U8, None, 7.6)
+            .unwrap()
+            .unwrap_u8(),
+        8
+    );
+    assert_eq!(
+        FrameSingle::splat(FrameKind::U8, Some((2, 2)), 300.0)
+            .unwrap()
+            .unwrap_u8(),
+        255
+    );
+    assert_eq!(
+        FrameSingle::splat(FrameKind::U16, None, -3.0)
+            .unwrap()
+            .unwrap_u16(),
+        0
+    );
+    assert_eq!(
+        FrameSingle::splat(FrameKind::F32, None, 0.25)
+            .unwrap()
+            .unwrap_f32(),
+        0.25
+    );
+    assert_eq!(
+        FrameSingle::zero(FrameKind::U16, None)
+            .unwrap()
+            .unwrap_u16(),
+        0
+    );
+}
+
+#[test]
+#[cfg(feature = "macros")]
+fn splat_arrays() {
+    let a = FrameSingle::splat(FrameKind::U8x1, Some((3, 1)), 5.0).unwrap();
+    assert_eq!(a.unwrap_u8x1(), ArcArray1::from(vec![5, 5, 5]));
+    let a = FrameSingle::splat(FrameKind::U16x1, Some((2, 1)), 1000.0).unwrap();
+    assert_eq!(a.unwrap_u16x1(), ArcArray1::from(vec![1000, 1000]));
+    let a = FrameSingle::zero(FrameKind::F32x1, Some((1, 1))).unwrap();
+    assert_eq!(a.unwrap_f32x1(), ArcArray1::from(vec![0.0]));
+
+    let a = FrameSingle::splat(FrameKind::U8x2, Some((2, 3)), 9.0).unwrap();
+    assert_eq!(a.unwrap_u8x2(), ArcArray2::from_elem((2, 3), 9));
+    let a = FrameSingle::splat(FrameKind::U16x2, Some((3, 2)), 70000.0).unwrap();
+    assert_eq!(a.unwrap_u16x2(), ArcArray2::from_elem((3, 2), u16::MAX));
+    let a = FrameSingle::splat(FrameKind::F32x2, Some((1, 4)), 1.5).unwrap();
+    assert_eq!(a.unwrap_f32x2(), ArcArray2::from_elem((1, 4), 1.5));
+
+    let a = FrameSingle::splat(FrameKind::RGBA8x2, Some((2, 2)), 128.0)
+        .unwrap()
+        .unwrap_rgba8x2();
+    assert_eq!(a.dim(), (2, 2));
+    assert!(a
+        .iter()
+        .all(|p| (p.r, p.g, p.b, p.a) == (128, 128, 128, 128)));
+    let a = FrameSingle::zero(FrameKind::RGBA8x2, Some((1, 1)))
+        .unwrap()
+        .unwrap_rgba8x2();
+    assert_eq!((a[[0, 0]].r, a[[0, 0]].a), (0, 0));
+}
+
+#[test]
+#[cfg(feature = "macros")]
+fn splat_requires_shape() {
+    for kind in &[FrameKind::U8x2, FrameKind::F32x1, FrameKind::RGBA8x2] {
+        let err = FrameSingle::zero(*kind, None).unwrap_err();
+        assert_eq!(
+            err.downcast::<VidmodError>().unwrap(),
+            VidmodError::InvalidShape {
+                kind:  *kind,
+                shape: None,
+            }
+        );
+    }
+    assert!(FrameSingle::zero(FrameKind::U16x1, Some((4, 2))).is_err());
+}
+
+#[test]
+#[cfg(feature = "macros")]
+fn filled_frame() {
+    let frame = Frame::filled(FrameKind::U16x2, Some((2, 2)), 3.0, 4).unwrap();
+    assert_eq!(FrameKind::from(&frame), FrameKind::U16x2);
+    assert_eq!((frame.size(), frame.capacity()), (4, 4));
+    assert!(frame
+        .unwrap_u16x2()
+        .iter()
+        .all(|a| *a == ArcArray2::from_elem((2, 2), 3)));
+    assert_eq!(
+        Frame::filled(FrameKind::U8, None, 1.0, 0).unwrap().size(),
+        0
+    );
+    assert!(Frame::filled(FrameKind::U8x2, None, 1.0, 2).is_err());
+}
+
+#[test]
+fn is_empty_tracks_contents() {
+    let mut frame = Frame::with_capacity(FrameKind::U8, 2);
+    assert!(frame.is_empty());
+    frame.add_single(FrameSingle::U8(1)).unwrap();
+    assert!(!frame.is_empty());
+    frame.remove_single().unwrap();
+    assert!(frame.is_empty());
+}
+
+#[test]
+fn shapes_cover_every_array_kind() {
+    let frame = Frame::U8x1(LimVecDeque::from(vec![
+        ArcArray1::from_vec(vec![1, 2, 3]),
+        ArcArray1::from_vec(vec![4]),
+    ]));
+    assert_eq!(frame.shapes(), vec![vec![3], vec![1]]);
+    let frame = Frame::from(ArcArray2::<u8>::zeros((2, 5)));
+    assert_eq!(frame.shapes(), vec![vec![2, 5]]);
+    assert!(Frame::U16(LimVecDeque::from(vec![1, 2]))
+        .shapes()
+        .is_empty());
+}
+
+#[test]
+fn single_kind() {
+    let frame = FrameSingle::U16(7);
+    assert_eq!(frame.kind(), FrameKind::U16);
+    assert_eq!(FrameSingle::from(7u16).unwrap_u16(), 7);
+    assert_eq!(
+        FrameSingle::from(ArcArray1::from_vec(vec![1.0f32])).kind(),
+        FrameKind::F32x1
+    );
+    assert_eq!(Frame::U8(LimVecDeque::from(vec![1])).kind(), FrameKind::U8);
+}
+
+#[test]
+fn single_parse() {
+    let parse = |kind, text| FrameSingle::parse(kind, text).unwrap();
+    assert_eq!(parse(FrameKind::U16, " 42 ").unwrap_u16(), 42);
+    assert_eq!(
+        parse(FrameKind::F32x1, "0.5,1").unwrap_f32x1(),
+        ArcArray1::from_vec(vec![0.5, 1.0])
+    );
+    assert_eq!(
+        parse(FrameKind::U8x2, "1,2;3,4").unwrap_u8x2(),
+        arr2(&[[1, 2], [3, 4]])
+    );
+    let pixels = parse(FrameKind::RGBA8x2, "1:2:3:4").unwrap_rgba8x2();
+    assert_eq!(pixels.dim(), (1, 1));
+    let [r, g, b, a] = [
+        pixels[[0, 0]].r,
+        pixels[[0, 0]].g,
+        pixels[[0, 0]].b,
+        pixels[[0, 0]].a,
+    ];
+    assert_eq!((r, g, b, a), (1, 2, 3, 4));
+    for (kind, text) in [
+        (FrameKind::U8, "300"),
+        (FrameKind::U16x2, "1,2;3"),
+        (FrameKind::RGBA8x2, "1:2:3"),
+    ] {
+        assert_eq!(
+            FrameSingle::parse(kind, text).unwrap_err(),
+            VidmodError::InvalidValue {
+                kind,
+                value: text.to_owned(),
+            }
+        );
+    }
+}
+
+#[test]
+fn stack_u8x2_frames() {
+    let arrays: Vec<ArcArray2<u8>> = (0..3)
+        .map(|i| arr2(&[[i, i + 10], [i + 20, i + 30]]).into_shared())
+        .collect();
+    let frame = Frame::U8x2(LimVecDeque::from(arrays));
+    let stacked = frame.stack_u8x2().unwrap();
+    assert_eq!(stacked.dim(), (3, 2, 2));
+    assert_eq!(stacked[[2, 1, 0]], 22);
+
+    let ragged = Frame::U8x2(LimVecDeque::from(vec![
+        ArcArray2::zeros((2, 2)),
+        ArcArray2::zeros((2, 3)),
+    ]));
+    assert_eq!(
+        ragged.stack_u8x2().unwrap_err(),
+        VidmodError::RaggedStack {
+            index:    1,
+            expected: (2, 2),
+            got:      (2, 3),
+        }
+    );
+    assert!(Frame::U8(LimVecDeque::from(vec![1])).stack_u8x2().is_err());
+}
+
+#[test]
+fn reinterpret_bytes_as_u16() {
+    let bytes = Frame::U8(LimVecDeque::from(vec![0x01, 0x02]));
+    let little = bytes
+        .reinterpret_as(FrameKind::U16, Endian::Little)
+        .unwrap();
+    assert_eq!(
+        little.unwrap_u16().iter().copied().collect::<Vec<_>>(),
+        [0x0201]
+    );
+    let big = bytes.reinterpret_as(FrameKind::U16, Endian::Big).unwrap();
+    assert_eq!(
+        big.unwrap_u16().iter().copied().collect::<Vec<_>>(),
+        [0x0102]
+    );
+
+    let rows = Frame::U8x2(LimVecDeque::from(vec![
+        arr2(&[[1, 2, 3, 4], [5, 6, 7, 8]]).into_shared()
+    ]));
+    let pixels = rows
+        .reinterpret_as(FrameKind::RGBA8x2, Endian::Little)
+        .unwrap();
+    assert_eq!(
+        pixels.unwrap_rgba8x2().pop_front().unwrap(),
+        arr2(&[[RGBA8::new(1, 2, 3, 4)], [RGBA8::new(5, 6, 7, 8)]])
+    );
+
+    let odd = Frame::U8(LimVecDeque::from(vec![1, 2, 3]));
+    assert_eq!(
+        odd.reinterpret_as(FrameKind::U16, Endian::Little)
+            .unwrap_err(),
+        VidmodError::ReinterpretMismatch {
+            from:  FrameKind::U8,
+            to:    FrameKind::U16,
+            bytes: 3,
+        }
+    );
+    assert!(bytes
+        .reinterpret_as(FrameKind::U16x1, Endian::Little)
+        .is_err());
+}
+
+#[test]
+fn copy_bytes_into_slice() {
+    let frame = Frame::U16(LimVecDeque::from(vec![0x0102, 0x0304]));
+    let mut dst = [0xff; 6];
+    assert_eq!(frame.copy_bytes_into(&mut dst), Ok(4));
+    assert_eq!(dst, [0x02, 0x01, 0x04, 0x03, 0xff, 0xff]);
+
+    let mut short = [0; 3];
+    assert_eq!(
+        frame.copy_bytes_into(&mut short),
+        Err(VidmodError::SliceTooSmall {
+            needed: 4,
+            len:    3,
+        })
+    );
+    assert_eq!(short, [0; 3]);
+}
+
+#[test]
+fn default_scalar_is_zero() {
+    assert_eq!(FrameKind::F32.default_scalar().unwrap_f32(), 0.0);
+    assert_eq!(
+        FrameKind::U16x1.default_scalar().unwrap_u16x1().to_vec(),
+        vec![0]
+    );
+    let pixels = FrameKind::RGBA8x2.default_scalar().unwrap_rgba8x2();
+    assert_eq!(pixels.dim(), (1, 1));
+    assert_eq!(pixels[[0, 0]], RGBA8::new(0, 0, 0, 0));
+}
+
+#[test]
+fn frames_from_singles() {
+    let singles = (0..3).map(|v| FrameSingle::from_array2(Array2::from_elem((2, 2), v as u16)));
+    let frame = Frame::from_singles(FrameKind::U16x2, singles).unwrap();
+    assert_eq!(frame.size(), 3);
+    assert_eq!(frame.capacity(), 3);
+    let corners: Vec<u16> = frame.unwrap_u16x2().iter().map(|a| a[[0, 0]]).collect();
+    assert_eq!(corners, vec![0, 1, 2]);
+
+    let frame = Frame::single(FrameSingle::from_array1(arr1(&[1.5_f32, 2.5])));
+    assert_eq!(FrameKind::from(&frame), FrameKind::F32x1);
+    assert_eq!(frame.capacity(), 1);
+
+    let mixed = vec![
+        FrameSingle::from_scalar(1_u16),
+        FrameSingle::from_scalar(2_u8),
+    ];
+    assert_eq!(
+        Frame::from_singles(FrameKind::U16, mixed).unwrap_err(),
+        VidmodError::KindMismatch {
+            port:      None,
+            direction: None,
+            expected:  FrameKind::U16,
+            got:       FrameKind::U8,
+        }
+    );
+}
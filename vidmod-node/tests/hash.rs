@@ -0,0 +1,118 @@
+use ndarray::{arr1, arr2};
+use vidmod_node::{
+    frame::{Frame, FrameSingle, RGBA8},
+    limvecdeque::LimVecDeque,
+};
+
+fn frame<T>(v: Vec<T>, wrap: fn(LimVecDeque<T>) -> Frame) -> Frame {
+    wrap(LimVecDeque::from(v))
+}
+
+// Expected values computed independently from the byte layout documented in hash.rs
+#[test]
+fn known_vectors() {
+    let cases = [
+        (frame(vec![1u8, 2, 3], Frame::U8), 0xb3b15edeec5f6c74),
+        (
+            frame(vec![arr1(&[1u8, 2, 3]).into_shared()], Frame::U8x1),
+            0x03f6b3231e14ac0e,
+        ),
+        (
+            frame(vec![arr2(&[[1u8, 2], [3, 4]]).into_shared()], Frame::U8x2),
+            0xd8641c99d6f8c809,
+        ),
+        (frame(vec![1u16, 0x0102], Frame::U16), 0x87702cdeb8333b27),
+        (
+            frame(vec![arr1(&[1u16, 0x0102]).into_shared()], Frame::U16x1),
+            0x3df43d1aac34afee,
+        ),
+        (
+            frame(vec![arr2(&[[1u16, 0x0102]]).into_shared()], Frame::U16x2),
+            0x085617ca2e3a93eb,
+        ),
+        (frame(vec![1.0f32, -0.5], Frame::F32), 0xa45f3edc91f02315),
+        (
+            frame(vec![arr1(&[1.0f32, -0.5]).into_shared()], Frame::F32x1),
+            0x468b1cd2af6f8f16,
+        ),
+        (
+            frame(vec![arr2(&[[1.0f32], [-0.5]]).into_shared()], Frame::F32x2),
+            0x204ad96f8508ed38,
+        ),
+        (
+            frame(
+                vec![arr2(&[[RGBA8::new(1, 2, 3, 4), RGBA8::new(5, 6, 7, 8)]]).into_shared()],
+                Frame::RGBA8x2,
+            ),
+            0x478fce7815a62d96,
+        ),
+    ];
+    for (frame, expected) in &cases {
+        assert_eq!(frame.content_hash(), *expected, "{:?}", frame);
+    }
+}
+
+#[test]
+fn equal_frames_hash_equal() {
+    let a = frame(
+        vec![arr2(&[[1.0f32, 2.0], [3.0, 4.0]]).into_shared()],
+        Frame::F32x2,
+    );
+    let b = frame(
+        vec![arr2(&[[1.0f32, 2.0], [3.0, 4.0]]).into_shared()],
+        Frame::F32x2,
+    );
+    assert_eq!(a.content_hash(), b.content_hash());
+
+    let c = frame(
+        vec![arr2(&[[1.0f32, 2.0], [3.0, 4.5]]).into_shared()],
+        Frame::F32x2,
+    );
+    assert_ne!(a.content_hash(), c.content_hash());
+    let d = frame(vec![10u16, 20, 30], Frame::U16);
+    let e = frame(vec![10u16, 21, 30], Frame::U16);
+    assert_ne!(d.content_hash(), e.content_hash());
+}
+
+#[test]
+fn shape_changes_hash() {
+    let a = frame(vec![arr2(&[[1u8, 2], [3, 4]]).into_shared()], Frame::U8x2);
+    let b = frame(vec![arr2(&[[1u8, 2, 3, 4]]).into_shared()], Frame::U8x2);
+    assert_ne!(a.content_hash(), b.content_hash());
+}
+
+#[test]
+fn kind_changes_hash() {
+    let a = frame(vec![1u8], Frame::U8);
+    let b = frame(vec![arr1(&[1u8]).into_shared()], Frame::U8x1);
+    assert_ne!(a.content_hash(), b.content_hash());
+}
+
+#[test]
+fn single_matches_frame_of_one() {
+    let single = FrameSingle::U16x1(arr1(&[4u16, 5]).into_shared());
+    let frame = frame(vec![arr1(&[4u16, 5]).into_shared()], Frame::U16x1);
+    assert_eq!(single.content_hash(), frame.content_hash());
+}
+
+#[test]
+fn rolling_hash_ignores_chunking() {
+    let whole = frame(vec![1u16, 0x0102], Frame::U16).rolling_hash(0);
+    assert_eq!(whole, 0x3e8c6c85bb0608c5);
+
+    let first = frame(vec![1u16], Frame::U16).rolling_hash(0);
+    let split = frame(vec![0x0102u16], Frame::U16).rolling_hash(first);
+    assert_eq!(split, whole);
+}
+
+#[test]
+fn nan_payloads_hash_equal() {
+    let quiet = frame(vec![f32::NAN, 1.0], Frame::F32);
+    let payload = frame(vec![f32::from_bits(0xffc0_1234), 1.0], Frame::F32);
+    assert_eq!(quiet.content_hash(), payload.content_hash());
+    assert_eq!(quiet.rolling_hash(0), payload.rolling_hash(0));
+
+    let zero = frame(vec![0.0f32, 1.0], Frame::F32);
+    let negative_zero = frame(vec![-0.0f32, 1.0], Frame::F32);
+    assert_ne!(zero.content_hash(), negative_zero.content_hash());
+}
@@ -0,0 +1,13 @@
+use vidmod_node::limvecdeque::LimVecDeque;
+
+#[test]
+fn iter_mut_updates_in_place() {
+    let mut deque = LimVecDeque::from(vec![1u8, 2, 3]);
+    for x in deque.iter_mut() {
+        *x += 1;
+    }
+    assert_eq!(deque.pop_front(), Some(2));
+    assert_eq!(deque.pop_front(), Some(3));
+    assert_eq!(deque.pop_front(), Some(4));
+    assert_eq!(deque.pop_front(), None);
+}
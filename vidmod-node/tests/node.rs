@@ -0,0 +1,221 @@
+use ndarray::ArcArray2;
+use vidmod_node::{
+    frame::{Frame, FrameKind, FrameSingle},
+    limvecdeque::LimVecDeque,
+    NodeCore, OverflowPolicy, VidmodError,
+};
+
+fn node_with_input(data: Vec<u16>) -> NodeCore {
+    let mut node = NodeCore::new();
+    node.register_pushport("in", FrameKind::U16, 8);
+    let port = node.get_push_port(0.into(), "in").unwrap();
+    node.push_frame(&port, Frame::U16(LimVecDeque::from(data)));
+    node
+}
+
+#[test]
+fn peek_single_does_not_consume() {
+    let mut node = node_with_input(vec![7, 8]);
+
+    let first = node.inbuf_peek_single("in").unwrap().unwrap_u16();
+    let second = node.inbuf_peek_single("in").unwrap().unwrap_u16();
+    assert_eq!(first, 7);
+    assert_eq!(first, second);
+    assert_eq!(node.inbuf_avail("in"), 2);
+
+    assert_eq!(node.inbuf_get_single("in").unwrap_u16(), 7);
+    assert_eq!(node.inbuf_peek_single("in").unwrap().unwrap_u16(), 8);
+}
+
+#[test]
+fn peek_single_empty() {
+    let node = node_with_input(vec![]);
+    assert!(node.inbuf_peek_single("in").is_none());
+}
+
+#[test]
+fn get_zipped_consumes_nothing_when_short() {
+    let mut node = NodeCore::new();
+    node.register_pushport("a", FrameKind::U16, 8);
+    node.register_pushport("b", FrameKind::U16, 8);
+    let a = node.get_push_port(0.into(), "a").unwrap();
+    let b = node.get_push_port(0.into(), "b").unwrap();
+    node.push_frame(&a, Frame::U16(LimVecDeque::from(vec![1, 2, 3])));
+    node.push_frame(&b, Frame::U16(LimVecDeque::from(vec![4])));
+
+    assert_eq!(node.inbuf_min_avail(&["a", "b"]), 1);
+    assert!(node.inbuf_get_zipped(&["a", "b"], 2).is_none());
+    assert_eq!(node.inbuf_avail("a"), 3);
+    assert_eq!(node.inbuf_avail("b"), 1);
+
+    let frames = node.inbuf_get_zipped(&["a", "b"], 1).unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(node.inbuf_avail("a"), 2);
+    assert_eq!(node.inbuf_avail("b"), 0);
+}
+
+fn overfill(policy: OverflowPolicy) -> NodeCore {
+    let mut node = NodeCore::new();
+    node.register_pushport_with_policy("in", FrameKind::U16, 4, policy);
+    let port = node.get_push_port(0.into(), "in").unwrap();
+    for chunk in [vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8, 9, 10, 11]] {
+        assert_eq!(node.ready_to_push(&port), 4);
+        let len = chunk.len();
+        let mut deque = LimVecDeque::with_capacity(len);
+        for v in chunk {
+            deque.push_back(v);
+        }
+        node.push_frame(&port, Frame::U16(deque));
+    }
+    node
+}
+
+#[test]
+fn drop_oldest_keeps_recent_frames() {
+    let mut node = overfill(OverflowPolicy::DropOldest);
+    let res: Vec<u16> = node
+        .inbuf_get("in", 4)
+        .unwrap_u16()
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(res, vec![8, 9, 10, 11]);
+    assert_eq!(node.port_stats("in").dropped, 8);
+}
+
+#[test]
+fn drop_newest_keeps_first_frames() {
+    let mut node = overfill(OverflowPolicy::DropNewest);
+    let res: Vec<u16> = node
+        .inbuf_get("in", 4)
+        .unwrap_u16()
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(res, vec![0, 1, 2, 3]);
+    assert_eq!(node.port_stats("in").dropped, 8);
+}
+
+#[test]
+fn drop_oldest_single_frames() {
+    let mut node = NodeCore::new();
+    node.register_pullport_with_policy("out", FrameKind::U16, 2, OverflowPolicy::DropOldest);
+    for v in 0..5 {
+        node.outbuf_put_single("out", FrameSingle::U16(v));
+    }
+    let port = node.get_pull_port(0.into(), "out").unwrap();
+    let res: Vec<u16> = node
+        .pull_frame(&port, 2)
+        .unwrap_u16()
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(res, vec![3, 4]);
+    assert_eq!(node.port_stats("out").dropped, 3);
+}
+
+#[test]
+fn shape_change_reported_once() {
+    let mut node = NodeCore::new();
+    node.register_pushport("in", FrameKind::U8x2, 8);
+    let port = node.get_push_port(0.into(), "in").unwrap();
+    let image = |rows, cols| Frame::from(ndarray::ArcArray2::<u8>::zeros((rows, cols)));
+    node.push_frame(&port, image(2, 2));
+    assert_eq!(node.inbuf_shape_changed("in"), None);
+    node.push_frame(&port, image(2, 2));
+    node.push_frame(&port, image(3, 4));
+    node.push_frame(&port, image(3, 4));
+    node.push_frame(&port, image(2, 2));
+    assert_eq!(node.inbuf_shape_changed("in"), Some(((2, 2), (3, 4))));
+    assert_eq!(node.inbuf_shape_changed("in"), Some(((3, 4), (2, 2))));
+    assert_eq!(node.inbuf_shape_changed("in"), None);
+}
+
+#[test]
+fn tick_quota_counts_down() {
+    let mut node = NodeCore::new();
+    assert_eq!(node.tick_budget(), None);
+    node.consume_budget(1_000_000);
+    assert!(!node.budget_exhausted());
+
+    node.set_tick_quota(Some(3));
+    assert_eq!(node.budget_remaining(), 3);
+    node.consume_budget(2);
+    assert_eq!(node.budget_remaining(), 1);
+    assert!(!node.budget_exhausted());
+    node.consume_budget(5);
+    assert_eq!(node.budget_remaining(), 0);
+    assert!(node.budget_exhausted());
+
+    node.reset_budget();
+    assert_eq!(node.budget_remaining(), 3);
+    assert!(!node.budget_exhausted());
+}
+
+#[test]
+fn port_empty_and_full() {
+    let mut node = node_with_input(vec![]);
+    node.register_pullport("out", FrameKind::U16, 2);
+    assert!(node.is_port_empty("in"));
+    assert!(node.is_port_empty("out"));
+    assert!(!node.is_port_full("out"));
+
+    node.outbuf_put_single("out", FrameSingle::U16(1));
+    assert!(!node.is_port_empty("out"));
+    assert!(!node.is_port_full("out"));
+    node.outbuf_put_single("out", FrameSingle::U16(2));
+    assert!(node.is_port_full("out"));
+
+    let node = node_with_input(vec![3]);
+    assert!(!node.is_port_empty("in"));
+    assert!(!node.is_port_full("in"));
+}
+
+#[test]
+fn origins_follow_frames_through_node() {
+    let mut node = NodeCore::new();
+    node.register_pushport("in", FrameKind::U16, 8);
+    node.register_pullport("out", FrameKind::U16, 8);
+    let port = node.get_push_port(0.into(), "in").unwrap();
+    node.set_clock(2);
+    node.push_frame(&port, Frame::U16(LimVecDeque::from(vec![1, 2])));
+    node.set_pushed_origins(&port, &[1]);
+    node.set_clock(5);
+    node.push_frame(&port, Frame::U16(LimVecDeque::from(vec![3])));
+    assert_eq!(node.inbuf_origins("in"), vec![1, 2, 5]);
+
+    node.set_clock(6);
+    let frame = node.inbuf_get("in", 2);
+    node.outbuf_put("out", frame);
+    let frame = node.inbuf_get("in", 1);
+    node.outbuf_put("out", frame);
+    node.outbuf_put_single("out", FrameSingle::U16(4));
+    let out = node.get_pull_port(0.into(), "out").unwrap();
+    assert_eq!(node.pull_frame(&out, 4).size(), 4);
+    assert_eq!(node.pulled_origins(), &[1, 1, 5, 5]);
+}
+
+#[test]
+fn assert_shape_reports_mismatch() {
+    let mut node = NodeCore::new();
+    node.register_pushport("in", FrameKind::U8x2, 4);
+    let port = node.get_push_port(0.into(), "in").unwrap();
+    node.push_frame(&port, Frame::from(ArcArray2::<u8>::zeros((2, 2))));
+    assert_eq!(node.assert_shape("in", &[2, 2]), Ok(()));
+
+    node.push_frame(&port, Frame::from(ArcArray2::<u8>::zeros((3, 3))));
+    let err = node.assert_shape("in", &[2, 2]).unwrap_err();
+    assert_eq!(
+        err,
+        VidmodError::ShapeMismatch {
+            port:     "in".to_owned(),
+            expected: vec![2, 2],
+            got:      vec![3, 3],
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Shape mismatch: in expected [2, 2], got [3, 3]"
+    );
+    assert!(node.assert_shape("missing", &[2, 2]).is_err());
+}
@@ -0,0 +1,491 @@
+use ndarray::{arr1, arr2, ArcArray2};
+use vidmod_node::{
+    frame::{
+        ops,
+        ops::{DitherMode, ResizeFilter},
+        Frame, FrameKind, FrameSingle, RGBA8,
+    },
+    limvecdeque::LimVecDeque,
+    VidmodError,
+};
+
+fn fixture() -> ArcArray2<RGBA8> {
+    arr2(&[
+        [
+            RGBA8::new(0, 0, 0, 255),
+            RGBA8::new(255, 255, 255, 128),
+            RGBA8::new(255, 0, 0, 0),
+        ],
+        [
+            RGBA8::new(0, 255, 0, 10),
+            RGBA8::new(0, 0, 255, 20),
+            RGBA8::new(16, 128, 240, 30),
+        ],
+    ])
+    .into_shared()
+}
+
+fn channels(img: &ArcArray2<RGBA8>) -> Vec<(u8, u8, u8, u8)> {
+    img.iter().map(|p| (p.r, p.g, p.b, p.a)).collect()
+}
+
+#[test]
+fn luma() {
+    let img = fixture();
+    assert_eq!(ops::to_luma_u8(&img), arr2(&[[0, 255, 76], [150, 29, 107]]));
+    assert_eq!(
+        ops::to_luma_u16(&img),
+        arr2(&[[0, 65535, 19595], [38469, 7471, 27571]])
+    );
+}
+
+#[test]
+fn levels_clip() {
+    let res = ops::apply_levels(&fixture(), 16, 235);
+    assert_eq!(
+        channels(&res),
+        vec![
+            (0, 0, 0, 255),
+            (255, 255, 255, 128),
+            (255, 0, 0, 0),
+            (0, 255, 0, 10),
+            (0, 0, 255, 20),
+            (0, 130, 255, 30),
+        ]
+    );
+
+    let grey = arr2(&[[0, 4096, 32768, 61440, 65535]]).into_shared();
+    assert_eq!(
+        ops::apply_levels_u16(&grey, 4096, 61440),
+        arr2(&[[0, 0, 32768, 65535, 65535]])
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid levels: 200,100")]
+fn levels_inverted() {
+    ops::apply_levels(&fixture(), 200, 100);
+}
+
+#[test]
+fn gain_offset_saturates() {
+    let res = ops::apply_gain_offset(&fixture(), [1.5, 1.0, 0.5, 1.0], [-10.0, 0.0, 20.0, 0.0]);
+    assert_eq!(
+        channels(&res),
+        vec![
+            (0, 0, 20, 255),
+            (255, 255, 148, 128),
+            (255, 0, 20, 0),
+            (0, 255, 20, 10),
+            (0, 0, 148, 20),
+            (14, 128, 140, 30),
+        ]
+    );
+}
+
+#[test]
+fn split_merge_round_trip() {
+    let img = fixture();
+    let [r, g, b, a] = ops::split_channels(&img);
+    assert_eq!(r, arr2(&[[0, 255, 255], [0, 0, 16]]));
+    assert_eq!(a, arr2(&[[255, 128, 0], [10, 20, 30]]));
+    let merged = ops::merge_channels(&r, &g, &b, &a);
+    assert_eq!(channels(&merged), channels(&img));
+}
+
+#[test]
+fn frame_wrappers() {
+    let frame = Frame::RGBA8x2(LimVecDeque::from(vec![fixture(), fixture()]));
+
+    let luma = frame.to_luma_u8().unwrap();
+    assert_eq!(FrameKind::from(&luma), FrameKind::U8x2);
+    assert_eq!(luma.size(), 2);
+
+    let planes = frame.split_channels().unwrap();
+    let merged = Frame::merge_channels([&planes[0], &planes[1], &planes[2], &planes[3]]).unwrap();
+    assert_eq!(
+        channels(&merged.unwrap_rgba8x2().pop_front().unwrap()),
+        channels(&fixture())
+    );
+
+    let grey = frame.to_luma_u16().unwrap().apply_levels(0, 128).unwrap();
+    assert_eq!(
+        grey.unwrap_u16x2().pop_front().unwrap(),
+        arr2(&[[0, 65535, 39037], [65535, 14884, 54927]])
+    );
+}
+
+#[test]
+fn frame_wrappers_check_kind() {
+    let frame = Frame::U8x2(LimVecDeque::from(vec![arr2(&[[1u8]]).into_shared()]));
+    assert_eq!(
+        frame.to_luma_u8().unwrap_err(),
+        VidmodError::KindMismatch {
+            port:      None,
+            direction: None,
+            expected:  FrameKind::RGBA8x2,
+            got:       FrameKind::U8x2,
+        }
+    );
+    assert!(frame.apply_levels(0, 255).is_err());
+    assert!(Frame::merge_channels([&frame, &frame, &frame, &frame]).is_ok());
+    let luma = Frame::U16x2(LimVecDeque::from(vec![]));
+    assert!(Frame::merge_channels([&frame, &frame, &frame, &luma]).is_err());
+}
+
+#[test]
+fn bilinear_upscale() {
+    let img = arr2(&[[0u16, 100], [200, 300]]).into_shared();
+    assert_eq!(
+        ops::bilinear_resize(&img, (4, 4)),
+        arr2(&[
+            [0, 25, 75, 100],
+            [50, 75, 125, 150],
+            [150, 175, 225, 250],
+            [200, 225, 275, 300]
+        ])
+    );
+}
+
+#[test]
+fn resize_non_integer_scale() {
+    let img = arr2(&[[0u8, 100, 200]]).into_shared();
+    assert_eq!(ops::bilinear_resize(&img, (1, 2)), arr2(&[[25, 175]]));
+    assert_eq!(ops::nearest_resize(&img, (1, 2)), arr2(&[[0, 200]]));
+    let img = arr2(&[[1u8, 2]]).into_shared();
+    assert_eq!(ops::nearest_resize(&img, (1, 3)), arr2(&[[1, 2, 2]]));
+}
+
+#[test]
+fn resize_single_pixel() {
+    let img = arr2(&[[RGBA8::new(1, 2, 3, 4)]]).into_shared();
+    let out = ops::bilinear_resize(&img, (3, 2));
+    assert_eq!(channels(&out), vec![(1, 2, 3, 4); 6]);
+    assert_eq!(
+        channels(&ops::bilinear_resize(&out, (1, 1))),
+        channels(&img)
+    );
+    assert_eq!(channels(&ops::nearest_resize(&out, (1, 1))), channels(&img));
+}
+
+#[test]
+fn frame_resize() {
+    let frame = Frame::U8x2(LimVecDeque::from(vec![
+        arr2(&[[1u8, 2], [3, 4]]).into_shared(),
+        arr2(&[[5u8]]).into_shared(),
+    ]));
+    let out = frame.resize((3, 3), ResizeFilter::Nearest).unwrap();
+    assert_eq!(out.shapes_2d(), vec![(3, 3), (3, 3)]);
+    assert_eq!(
+        frame.resize((0, 3), ResizeFilter::Bilinear).unwrap_err(),
+        VidmodError::InvalidShape {
+            kind:  FrameKind::U8x2,
+            shape: Some((0, 3)),
+        }
+    );
+    let frame = Frame::U8x1(LimVecDeque::from(vec![]));
+    assert!(frame.resize((2, 2), ResizeFilter::Nearest).is_err());
+    assert_eq!(frame.shapes_2d(), vec![]);
+}
+
+const KINDS: [FrameKind; 10] = [
+    FrameKind::U8,
+    FrameKind::U8x1,
+    FrameKind::U8x2,
+    FrameKind::U16,
+    FrameKind::U16x1,
+    FrameKind::U16x2,
+    FrameKind::F32,
+    FrameKind::F32x1,
+    FrameKind::F32x2,
+    FrameKind::RGBA8x2,
+];
+
+#[test]
+#[cfg(feature = "macros")]
+fn can_convert_to_matches_convert_to() {
+    assert!(FrameKind::U8x2.can_convert_to(FrameKind::F32x2));
+    assert!(!FrameKind::U8.can_convert_to(FrameKind::RGBA8x2));
+    for &from in &KINDS {
+        let mut frame = Frame::with_capacity(from, 1);
+        frame
+            .add_single(FrameSingle::zero(from, Some((2, 1))).unwrap())
+            .unwrap();
+        for &to in &KINDS {
+            match frame.convert_to(to) {
+                Ok(converted) => {
+                    assert!(from.can_convert_to(to), "{:?} -> {:?}", from, to);
+                    assert_eq!(FrameKind::from(&converted), to);
+                    assert_eq!(converted.size(), 1);
+                }
+                Err(e) => {
+                    assert!(!from.can_convert_to(to), "{:?} -> {:?}", from, to);
+                    assert_eq!(
+                        e,
+                        VidmodError::KindMismatch {
+                            port:      None,
+                            direction: None,
+                            expected:  to,
+                            got:       from,
+                        }
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn convert_to_casts_values() {
+    let frame = Frame::F32(LimVecDeque::from(vec![-1.0, 2.6, 300.0]));
+    match frame.convert_to(FrameKind::U8).unwrap() {
+        Frame::U8(v) => assert_eq!(v.iter().copied().collect::<Vec<_>>(), vec![0, 3, 255]),
+        _ => unreachable!(),
+    }
+    let frame = Frame::U16x2(LimVecDeque::from(vec![ArcArray2::from_elem((1, 2), 1000)]));
+    match frame.convert_to(FrameKind::F32x2).unwrap() {
+        Frame::F32x2(v) => assert_eq!(v.iter().next().unwrap()[[0, 1]], 1000.0),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn tile_round_trips_uneven_sizes() {
+    let u8s = ArcArray2::from_shape_fn((5, 7), |(y, x)| (y * 7 + x) as u8);
+    let tiles = ops::tile(&u8s, 3, 2);
+    assert_eq!(tiles.len(), 9);
+    assert_eq!(tiles[0].dim(), (2, 3));
+    assert_eq!(tiles[2].dim(), (2, 1));
+    assert_eq!(tiles[8].dim(), (1, 1));
+    assert_eq!(ops::untile(&tiles, 3, 3, (5, 7)).unwrap(), u8s);
+
+    let u16s = u8s.mapv(|v| v as u16 * 300).into_shared();
+    let tiles = ops::tile(&u16s, 3, 2);
+    assert_eq!(ops::untile(&tiles, 3, 3, (5, 7)).unwrap(), u16s);
+
+    let f32s = u8s.mapv(|v| v as f32 / 3.0).into_shared();
+    let tiles = ops::tile(&f32s, 4, 4);
+    assert_eq!(ops::untile(&tiles, 2, 2, (5, 7)).unwrap(), f32s);
+
+    let channels = |a: &ArcArray2<RGBA8>| a.map(|p| (p.r, p.g, p.b, p.a));
+    let tiles = ops::tile(&fixture(), 2, 1);
+    let res = ops::untile(&tiles, 2, 2, (2, 3)).unwrap();
+    assert_eq!(channels(&res), channels(&fixture()));
+}
+
+#[test]
+fn untile_rejects_bad_tiles() {
+    let array = ArcArray2::from_shape_fn((4, 4), |(y, x)| (y * 4 + x) as u16);
+    let mut tiles = ops::tile(&array, 2, 2);
+    assert_eq!(
+        ops::untile(&tiles[..3], 2, 2, (4, 4)),
+        Err(VidmodError::TileCountMismatch {
+            expected: 4,
+            got:      3,
+        })
+    );
+    tiles[3] = ArcArray2::zeros((1, 2));
+    assert!(matches!(
+        ops::untile(&tiles, 2, 2, (4, 4)),
+        Err(VidmodError::ShapeMismatch { .. })
+    ));
+}
+
+#[test]
+fn promote_10_bit_to_full_scale() {
+    let promoted: Vec<u16> = [0, 1, 512, 1023, 2000]
+        .iter()
+        .map(|&v| ops::promote_to_full_scale(v, 10))
+        .collect();
+    assert_eq!(promoted, vec![0, 64, 32800, 65535, 65535]);
+    assert_eq!(ops::promote_to_full_scale(4095, 12), 65535);
+    assert_eq!(ops::promote_to_full_scale(12345, 16), 12345);
+}
+
+#[test]
+fn demote_round_trips() {
+    for depth in [10, 12] {
+        for v in 0..1 << depth {
+            let promoted = ops::promote_to_full_scale(v, depth);
+            assert_eq!(ops::demote_to_depth(promoted, depth), v);
+        }
+    }
+
+    let scan = arr2(&[[0u16, 1], [1022, 1023]]).into_shared();
+    let frame = Frame::U16x2(LimVecDeque::from(vec![scan.clone()]));
+    let promoted = frame.promote_to_full_scale(10).unwrap();
+    assert_eq!(
+        promoted.clone().unwrap_u16x2().pop_front().unwrap(),
+        arr2(&[[0, 64], [65471, 65535]])
+    );
+    let demoted = promoted.demote_to_depth(10).unwrap();
+    assert_eq!(demoted.unwrap_u16x2().pop_front().unwrap(), scan);
+    assert!(matches!(
+        frame.demote_to_depth(17),
+        Err(VidmodError::InvalidBitDepth { depth: 17, .. })
+    ));
+    let frame = Frame::RGBA8x2(LimVecDeque::from(vec![fixture()]));
+    assert!(frame.promote_to_full_scale(10).is_err());
+}
+
+#[test]
+fn frame_single_arithmetic_saturates() {
+    let a = FrameSingle::U16(60000);
+    let b = FrameSingle::U16(10000);
+    assert_eq!(
+        a.clone().add_saturating(b.clone()).unwrap().unwrap_u16(),
+        65535
+    );
+    assert_eq!(b.clone().sub_abs(a.clone()).unwrap().unwrap_u16(), 50000);
+    assert_eq!(a.clone().mul(b.clone()).unwrap().unwrap_u16(), 9155);
+    assert_eq!(a.clone().min(b.clone()).unwrap().unwrap_u16(), 10000);
+    let sum = a.zip_map(b, |a, b| a * 2.0 - b).unwrap();
+    assert_eq!(sum.unwrap_u16(), 65535);
+
+    let a = FrameSingle::U8x1(arr1(&[250, 3, 128]).into_shared());
+    let b = FrameSingle::U8x1(arr1(&[10, 5, 255]).into_shared());
+    assert_eq!(
+        a.clone().add_saturating(b.clone()).unwrap().unwrap_u8x1(),
+        arr1(&[255, 8, 255])
+    );
+    assert_eq!(
+        a.clone()
+            .zip_map(b.clone(), |a, b| a - b)
+            .unwrap()
+            .unwrap_u8x1(),
+        arr1(&[240, 0, 0])
+    );
+    assert_eq!(a.max(b).unwrap().unwrap_u8x1(), arr1(&[250, 5, 255]));
+}
+
+#[test]
+fn frame_arithmetic_checks_operands() {
+    let a = FrameSingle::U8x1(arr1(&[1, 2]).into_shared());
+    let b = FrameSingle::U8x1(arr1(&[1, 2, 3]).into_shared());
+    assert_eq!(
+        a.clone().sub_abs(b).unwrap_err(),
+        VidmodError::ShapesDiffer {
+            a: vec![2],
+            b: vec![3],
+        }
+    );
+    assert!(matches!(
+        a.add_saturating(FrameSingle::U16(1)),
+        Err(VidmodError::KindMismatch { .. })
+    ));
+
+    // Frames combine pairwise up to the shorter
+    let a = Frame::U8(LimVecDeque::from(vec![1, 2, 3]));
+    let b = Frame::U8(LimVecDeque::from(vec![10, 20]));
+    let sum = a.add_saturating(&b).unwrap().unwrap_u8();
+    assert_eq!(sum.iter().copied().collect::<Vec<_>>(), vec![11, 22]);
+    let f = Frame::F32(LimVecDeque::from(vec![1.0]));
+    assert!(a.mul(&f).is_err());
+}
+
+#[test]
+fn composite_over_blends_alpha() {
+    let fg = arr2(&[[
+        RGBA8::new(200, 100, 0, 128),
+        RGBA8::new(10, 20, 30, 255),
+        RGBA8::new(10, 20, 30, 0),
+    ]])
+    .into_shared();
+    let bg = arr2(&[[
+        RGBA8::new(0, 100, 200, 128),
+        RGBA8::new(1, 2, 3, 4),
+        RGBA8::new(1, 2, 3, 4),
+    ]])
+    .into_shared();
+    assert_eq!(
+        channels(&ops::composite_over(&fg, &bg)),
+        vec![(134, 100, 66, 192), (10, 20, 30, 255), (1, 2, 3, 4)]
+    );
+}
+
+fn quantized_u8(frame: &Frame, mode: DitherMode) -> Vec<Vec<Vec<u8>>> {
+    ops::quantize(frame, FrameKind::U8x2, mode)
+        .unwrap()
+        .unwrap_u8x2()
+        .iter()
+        .map(|img| img.outer_iter().map(|row| row.to_vec()).collect())
+        .collect()
+}
+
+#[test]
+fn ordered_dither_matches_bayer_thresholds() {
+    // A gradient stepping a sixteenth of a level per pixel, from 10 to 10 + 15/16
+    let gradient = ArcArray2::from_shape_fn((4, 4), |(r, c)| 10.0 + (4 * r + c) as f32 / 16.0);
+    let frame = Frame::F32x2(LimVecDeque::from(vec![gradient]));
+    assert_eq!(
+        quantized_u8(&frame, DitherMode::Ordered4x4),
+        vec![vec![
+            vec![10, 10, 10, 10],
+            vec![11, 10, 11, 10],
+            vec![10, 11, 10, 11],
+            vec![11, 11, 11, 11],
+        ]]
+    );
+    assert_eq!(
+        quantized_u8(&frame, DitherMode::None),
+        vec![vec![
+            vec![10, 10, 10, 10],
+            vec![10, 10, 10, 10],
+            vec![11, 11, 11, 11],
+            vec![11, 11, 11, 11],
+        ]]
+    );
+}
+
+#[test]
+fn floyd_steinberg_error_stays_within_an_image() {
+    let half = arr2(&[[10.5_f32, 10.5, 10.5, 10.5]]).into_shared();
+    let frame = Frame::F32x2(LimVecDeque::from(vec![half.clone(), half]));
+    let row = vec![vec![11, 10, 11, 10]];
+    assert_eq!(
+        quantized_u8(&frame, DitherMode::FloydSteinberg),
+        vec![row.clone(), row]
+    );
+}
+
+#[test]
+fn tpdf_dither_stays_within_one_level() {
+    let samples: Vec<f32> = (0..256).map(|i| 1000.0 + i as f32 * 0.1).collect();
+    let frame = Frame::F32x1(LimVecDeque::from(vec![arr1(&samples).into_shared()]));
+    let out = ops::quantize(&frame, FrameKind::U16x1, DitherMode::Tpdf(7))
+        .unwrap()
+        .unwrap_u16x1()
+        .pop_front()
+        .unwrap();
+    for (&x, &y) in samples.iter().zip(out.iter()) {
+        let ideal = x.round() as i32;
+        assert!((i32::from(y) - ideal).abs() <= 1, "{} -> {}", x, y);
+    }
+}
+
+#[test]
+fn quantize_rescales_integers_and_rejects_bad_kinds() {
+    let frame = Frame::U16x2(LimVecDeque::from(vec![
+        arr2(&[[0_u16, 257, 65535]]).into_shared()
+    ]));
+    assert_eq!(
+        quantized_u8(&frame, DitherMode::None),
+        vec![vec![vec![0, 1, 255]]]
+    );
+    assert_eq!(
+        ops::quantize(&frame, FrameKind::F32x2, DitherMode::None).unwrap_err(),
+        VidmodError::KindMismatch {
+            port:      None,
+            direction: None,
+            expected:  FrameKind::F32x2,
+            got:       FrameKind::U16x2,
+        }
+    );
+    let audio = Frame::U16x1(LimVecDeque::from(vec![arr1(&[1_u16, 2]).into_shared()]));
+    assert_eq!(
+        ops::quantize(&audio, FrameKind::U8x1, DitherMode::Ordered4x4).unwrap_err(),
+        VidmodError::InvalidDither {
+            kind: FrameKind::U16x1,
+        }
+    );
+}
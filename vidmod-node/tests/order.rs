@@ -0,0 +1,121 @@
+use vidmod_node::{
+    frame::{Frame, FrameKind, FrameSingle},
+    limvecdeque::LimVecDeque,
+    NodeCore,
+};
+
+const TOTAL: u16 = 10_000;
+
+// A small deterministic generator so the mix of calls is reproducible
+struct Lcg(u32);
+
+impl Lcg {
+    fn next(&mut self, below: usize) -> usize {
+        self.0 = self.0.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (self.0 >> 16) as usize % below
+    }
+}
+
+fn values(frame: Frame) -> Vec<u16> {
+    frame.unwrap_u16().iter().copied().collect()
+}
+
+#[test]
+fn mixed_put_get_preserves_order() {
+    let mut node = NodeCore::new();
+    node.register_pullport("out", FrameKind::U16, 7);
+    node.register_pushport("in", FrameKind::U16, 11);
+    let pull = node.get_pull_port(0.into(), "out").unwrap();
+    let push = node.get_push_port(0.into(), "in").unwrap();
+    let mut rng = Lcg(1);
+    let mut next = 0;
+    let mut seen = Vec::new();
+
+    while seen.len() < TOTAL as usize {
+        // Put, mixing batch and single puts
+        let space = usize::min(node.outbuf_avail("out"), (TOTAL - next) as usize);
+        if space > 0 {
+            let count = 1 + rng.next(space);
+            if rng.next(2) == 0 {
+                let batch: Vec<u16> = (next..next + count as u16).collect();
+                node.outbuf_put("out", Frame::U16(LimVecDeque::from(batch)));
+            } else {
+                for v in next..next + count as u16 {
+                    node.outbuf_put_single("out", FrameSingle::U16(v));
+                }
+            }
+            next += count as u16;
+        }
+
+        // Transfer across the link, sometimes in several pieces
+        let ready = usize::min(node.ready_to_pull(&pull), node.ready_to_push(&push));
+        if ready > 0 {
+            let count = 1 + rng.next(ready);
+            let mut frame = node.pull_frame(&pull, count);
+            let (first, second) = frame.split_at(rng.next(count + 1));
+            node.push_frame(&push, first);
+            node.push_frame(&push, second);
+        }
+
+        // Get, mixing every get variant
+        let avail = node.inbuf_avail("in");
+        if avail == 0 {
+            continue;
+        }
+        match rng.next(6) {
+            0 => seen.extend(values(node.inbuf_get("in", 1 + rng.next(avail)))),
+            1 => seen.push(node.inbuf_get_single("in").unwrap_u16()),
+            2 => seen.push(node.inbuf_try_get_single("in").unwrap().unwrap_u16()),
+            3 => seen.extend(values(node.inbuf_get_all("in"))),
+            4 => {
+                let frames = node.inbuf_get_zipped(&["in"], 1 + rng.next(avail));
+                seen.extend(values(frames.unwrap().pop().unwrap()));
+            }
+            _ => {
+                // Peeking must not consume or disturb the order
+                let peeked = values(node.inbuf_peek("in", avail));
+                assert_eq!(peeked.len(), avail);
+                assert_eq!(
+                    node.inbuf_peek_single("in").unwrap().unwrap_u16(),
+                    peeked[0]
+                );
+            }
+        }
+    }
+
+    assert_eq!(seen, (0..TOTAL).collect::<Vec<_>>());
+}
+
+#[test]
+fn frame_add_variants_preserve_order() {
+    let mut frame = Frame::with_capacity(FrameKind::U16, 8);
+    frame
+        .add(Frame::U16(LimVecDeque::from(vec![0, 1])))
+        .unwrap();
+    frame.add_single(FrameSingle::U16(2)).unwrap();
+    let mut rest = Frame::U16(LimVecDeque::from(vec![3, 4, 5, 6, 7, 8, 9]));
+    assert_eq!(frame.add_partial(&mut rest), 5);
+    assert_eq!(frame.add_overwrite(rest), 2);
+    assert!(frame.add_single_overwrite(FrameSingle::U16(10)));
+
+    let mut seen = values(frame.remove(2).unwrap());
+    seen.push(frame.remove_single().unwrap().unwrap_u16());
+    seen.extend(values(frame.remove_all()));
+    assert_eq!(seen, (3..=10).collect::<Vec<_>>());
+}
+
+#[cfg(feature = "debug-order-check")]
+#[test]
+#[should_panic(expected = "Frame order violated")]
+fn reordered_deque_is_caught() {
+    let mut deque = LimVecDeque::with_capacity(4);
+    let mut other = LimVecDeque::with_capacity(4);
+    for v in 0..3u16 {
+        other.push_back(v);
+    }
+    deque.append(&mut other);
+    // Each element's sequence number moves with it, so the swap is seen when they leave
+    deque.swap(0, 1);
+    deque.pop_front();
+    deque.pop_front();
+}
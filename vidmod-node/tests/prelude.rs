@@ -0,0 +1,167 @@
+use vidmod_node::prelude::*;
+
+/// Doubles every U8 it receives, written against the prelude alone
+#[node_decl]
+struct Doubler {}
+
+impl Doubler {
+    #[node_new]
+    fn new() -> Self {
+        Self {}
+    }
+}
+
+impl NodeImpl for Doubler {
+    fn init(&mut self) {
+        self.register_pushport("in", FrameKind::U8, 4);
+        self.register_pullport("out", FrameKind::U8, 4);
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.inbuf_avail("in") > 0 && self.outbuf_avail("out") > 0 {
+            let v = self.inbuf_get_single("in").unwrap_u8();
+            self.outbuf_put_single("out", FrameSingle::U8(v * 2));
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+/// Halves every U8 it receives, through port handles instead of port names
+#[node_decl]
+struct Halver {
+    #[input("in")]
+    input: InputPort,
+    #[output]
+    out:   OutputPort,
+}
+
+impl Halver {
+    #[node_new]
+    fn new() -> Self {
+        Self {}
+    }
+}
+
+impl NodeImpl for Halver {
+    fn init(&mut self) {
+        let input = self.register_input("in", FrameKind::U8, 4);
+        let out = self.register_output("out", FrameKind::U8, 4);
+        assert_eq!((input, out), (self.input(), self.out()));
+    }
+
+    fn tick(&mut self) -> bool {
+        let mut res = false;
+        while self.input().avail(self) > 0 && self.out().avail(self) > 0 {
+            let v = self.input().get_single(self).unwrap_u8();
+            self.out().put_single(self, FrameSingle::U8(v / 2));
+            res = true;
+        }
+        res
+    }
+
+    fn finish(&mut self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn prelude_node_runs() {
+    let mut node = Node::new(Doubler::new());
+    node.init();
+    let push = node.get_push_port(0.into(), "in").unwrap();
+    let pull = node.get_pull_port(0.into(), "out").unwrap();
+
+    node.push_frame(&push, Frame::U8(LimVecDeque::from(vec![1, 2, 3])));
+    assert!(node.tick());
+    assert_eq!(node.ready_to_pull(&pull), 3);
+    let out: Vec<u8> = node
+        .pull_frame(&pull, 3)
+        .unwrap_u8()
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(out, vec![2, 4, 6]);
+}
+
+#[test]
+fn drive_two_nodes_without_graph() {
+    let mut first = Node::new(Doubler::new());
+    let mut second = Node::from_box(Box::new(Doubler::new()));
+    first.init();
+    second.init();
+    let first_in = first.get_push_port(0.into(), "in").unwrap();
+    let first_out = first.get_pull_port(0.into(), "out").unwrap();
+    let second_in = second.get_push_port(1.into(), "in").unwrap();
+    let second_out = second.get_pull_port(1.into(), "out").unwrap();
+    first.attach_push_port("out", second_in.clone()).unwrap();
+    second.attach_pull_port("in", first_out.clone()).unwrap();
+
+    first.push_frame(&first_in, Frame::U8(LimVecDeque::from(vec![1, 2, 3])));
+    first.signal_eos(&first_in);
+    assert!(first.inbuf_eos("in"));
+    assert!(first.tick());
+
+    let count = usize::min(
+        first.ready_to_pull(&first_out),
+        second.ready_to_push(&second_in),
+    );
+    assert_eq!(count, 3);
+    let frame = first.pull_frame(&first_out, count);
+    second.push_frame(&second_in, frame);
+    assert!(second.tick());
+
+    let out: Vec<u8> = second
+        .pull_frame(&second_out, second.ready_to_pull(&second_out))
+        .unwrap_u8()
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(out, vec![4, 8, 12]);
+    assert_eq!(second.port_stats("in").dropped, 0);
+}
+
+#[test]
+fn port_handles_match_names() {
+    let mut node = Halver::new();
+    node.init();
+    assert_eq!(node.input().name(), "in");
+    assert_eq!(node.out().name(), "out");
+
+    let mut node = Node::new(node);
+    let push = node.get_push_port(0.into(), "in").unwrap();
+    let pull = node.get_pull_port(0.into(), "out").unwrap();
+    node.push_frame(&push, Frame::U8(LimVecDeque::from(vec![2, 4, 7])));
+    assert!(node.tick());
+    let out: Vec<u8> = node
+        .pull_frame(&pull, 3)
+        .unwrap_u8()
+        .iter()
+        .copied()
+        .collect();
+    assert_eq!(out, vec![1, 2, 3]);
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_trait_names_still_bound_nodes() {
+    fn finish<T: vidmod_node::Node2TA>(mut node: T) -> bool {
+        node.finish()
+    }
+    fn ports<T: vidmod_node::Node2MT>(node: &T) -> usize {
+        node.outbuf_avail("out")
+    }
+    fn init<T: vidmod_node::Node2T>(node: &mut T) {
+        node.init()
+    }
+
+    let mut node = Doubler::new();
+    init(&mut node);
+    assert_eq!(ports(&node), 4);
+    assert!(finish(node));
+}
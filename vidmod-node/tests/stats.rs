@@ -0,0 +1,41 @@
+use vidmod_node::stats::{Histogram, OnlineStats};
+
+#[test]
+fn online_stats_moments() {
+    let mut stats = OnlineStats::new();
+    assert_eq!(stats.mean(), None);
+    for x in &[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+        stats.push(*x);
+    }
+    stats.push(f64::NAN);
+    assert_eq!(stats.count(), 8);
+    assert_eq!(stats.nan_count(), 1);
+    assert_eq!(stats.min(), Some(2.0));
+    assert_eq!(stats.max(), Some(9.0));
+    assert_eq!(stats.mean(), Some(5.0));
+    assert_eq!(stats.variance(), Some(4.0));
+    assert_eq!(stats.std_dev(), Some(2.0));
+}
+
+#[test]
+fn online_stats_stay_accurate_with_large_offset() {
+    let mut stats = OnlineStats::new();
+    for x in &[4.0, 7.0, 13.0, 16.0] {
+        stats.push(1e9 + x);
+    }
+    assert_eq!(stats.mean(), Some(1e9 + 10.0));
+    assert_eq!(stats.variance(), Some(22.5));
+}
+
+#[test]
+fn histogram_bins() {
+    let mut hist = Histogram::new(0.0, 10.0, 5);
+    for x in &[-1.0, 0.0, 1.9, 2.0, 9.99, 10.0, f64::NAN] {
+        hist.push(*x);
+    }
+    assert_eq!(hist.bins(), [2, 1, 0, 0, 1]);
+    assert_eq!(hist.underflow(), 1);
+    assert_eq!(hist.overflow(), 1);
+    assert_eq!(hist.nan_count(), 1);
+    assert_eq!(hist.bin_range(1), (2.0, 4.0));
+}
@@ -13,12 +13,96 @@ pub type PluginRegSymbol<'a> = libloading::Symbol<
 
 pub struct Plugin {
     pub make_node: fn(params: BTreeMap<String, String>) -> Node,
+    /// The args the node understands, if its plugin describes them
+    pub args:      Option<Vec<ArgSpec>>,
+}
+
+/// How a node arg's value is written in a manifest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgType {
+    String,
+    Integer,
+    Number,
+    Bool,
+    Enum(&'static [&'static str]),
+}
+
+/// A node arg, as described to manifest tooling
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgSpec {
+    pub name:     &'static str,
+    pub ty:       ArgType,
+    pub required: bool,
+}
+
+impl ArgSpec {
+    pub fn required(name: &'static str, ty: ArgType) -> Self {
+        Self {
+            name,
+            ty,
+            required: true,
+        }
+    }
+
+    pub fn optional(name: &'static str, ty: ArgType) -> Self {
+        Self {
+            name,
+            ty,
+            required: false,
+        }
+    }
+}
+
+/// Node constructors registered at runtime, falling back to the loaded plugins
+#[derive(Default)]
+pub struct PluginRegistry {
+    nodes: BTreeMap<String, Plugin>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(
+        &mut self,
+        name: &str,
+        make_node: fn(params: BTreeMap<String, String>) -> Node,
+    ) {
+        self.nodes.insert(
+            name.to_owned(),
+            Plugin {
+                make_node,
+                args: None,
+            },
+        );
+    }
+
+    /// Describe the args of a registered node, for manifest tooling
+    pub fn describe(&mut self, name: &str, args: Vec<ArgSpec>) {
+        match self.nodes.get_mut(name) {
+            Some(plugin) => plugin.args = Some(args),
+            None => panic!("Cannot describe unregistered node {}", name),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Plugin> {
+        self.nodes.get(name).or_else(|| PLUGINS.get(name))
+    }
+
+    /// Every node that `get` can find, registered nodes shadowing loaded plugins
+    pub fn plugins(&self) -> BTreeMap<&str, &Plugin> {
+        let mut res: BTreeMap<&str, &Plugin> =
+            PLUGINS.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        res.extend(self.nodes.iter().map(|(k, v)| (k.as_str(), v)));
+        res
+    }
 }
 
 lazy_static! {
     pub static ref PLUGIN_LIBRARIES: BTreeMap<String, libloading::Library> = {
         let mut res = BTreeMap::new();
-        println!("Searching for plugins in {}/debug/", OUT_DIR);
+        eprintln!("Searching for plugins in {}/debug/", OUT_DIR);
         for i in glob(&format!("{}/release/libvidmod_plugins_*.so", OUT_DIR)).unwrap() {
             let lib = unsafe { libloading::Library::new(i.unwrap()).unwrap() };
             let plugin_name: libloading::Symbol<extern "C" fn() -> String> =
@@ -37,7 +121,10 @@ lazy_static! {
             for (node_name, make_node) in register_plugin() {
                 res.insert(
                     format!("{}::{}", plugin_name, node_name),
-                    Plugin { make_node },
+                    Plugin {
+                        make_node,
+                        args: None,
+                    },
                 );
             }
         }